@@ -0,0 +1,190 @@
+//! Writers for animated images, built on top of [`VideoEncoder`]
+
+use super::{EncodeError, Frame, PixelFormat, VideoCodec, VideoEncoder};
+use std::path::Path;
+use std::time::Duration;
+
+/// An encoder where each frame is shown for its own delay
+struct AnimationWriter {
+    encoder: VideoEncoder,
+    /// The number of timestamp ticks per second
+    tick_rate: u32,
+}
+
+impl AnimationWriter {
+    /// `loop_option` is the muxer option (and its value) which makes the animation loop forever
+    fn new<P>(
+        path: P,
+        width: u32,
+        height: u32,
+        codec: VideoCodec,
+        format: &str,
+        tick_rate: u32,
+        loop_option: (&str, &str),
+    ) -> Result<Self, EncodeError>
+    where
+        P: AsRef<Path>,
+    {
+        let encoder = VideoEncoder::builder(path, width, height)
+            .codec(codec)
+            .format(format)
+            .framerate(tick_rate as f32)
+            .format_option(loop_option.0, loop_option.1)
+            .build()?;
+
+        Ok(AnimationWriter { encoder, tick_rate })
+    }
+
+    /// The number of ticks a delay lasts for, every frame lasts at least one tick
+    fn ticks(&self, delay: Duration) -> i64 {
+        ((delay.as_secs_f64() * self.tick_rate as f64).round() as i64).max(1)
+    }
+
+    fn write(&mut self, frame: &Frame, delay: Duration) -> Result<(), EncodeError> {
+        let ticks = self.ticks(delay);
+        self.encoder.encode_strided(
+            frame.data(),
+            frame.stride(),
+            frame.dimensions(),
+            frame.format(),
+            ticks,
+        )
+    }
+
+    fn write_data(
+        &mut self,
+        data: &[u8],
+        format: PixelFormat,
+        delay: Duration,
+    ) -> Result<(), EncodeError> {
+        let ticks = self.ticks(delay);
+        let dimensions = self.encoder.dimensions();
+        self.encoder.encode_strided(
+            data,
+            dimensions.width as usize * format.bytes_per_pixel(),
+            dimensions,
+            format,
+            ticks,
+        )
+    }
+}
+
+/// Writes frames to an animated GIF, which loops forever
+///
+/// GIFs are limited to a fixed palette of 256 colours, so colours are approximated. Delays are rounded to the nearest hundredth of a second.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::{DecoderBuilder, GifWriter};
+/// use std::time::Duration;
+///
+/// # let file = "test.mp4";
+/// # let dir = std::env::temp_dir().join("ffmpeg-video-decoder-gif-doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # let output = dir.join("preview.gif");
+/// let mut decoder = DecoderBuilder::new(file)
+///     .filter("scale=320:-1")
+///     .target_fps(10.0)
+///     .build()
+///     .unwrap();
+/// let dimensions = decoder.dimensions();
+///
+/// let mut gif = GifWriter::new(output, dimensions.width(), dimensions.height()).unwrap();
+/// while let Some(frame) = decoder.next_frame().unwrap() {
+///     # if frame.index() > 10 { break; }
+///     gif.write(&frame, Duration::from_millis(100)).unwrap();
+/// }
+/// gif.finish().unwrap();
+/// # }
+/// ```
+pub struct GifWriter {
+    writer: AnimationWriter,
+}
+
+impl GifWriter {
+    /// Create a new GIF of the given size, frames which are a different size are scaled to fit
+    pub fn new<P>(path: P, width: u32, height: u32) -> Result<Self, EncodeError>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(GifWriter {
+            writer: AnimationWriter::new(
+                path,
+                width,
+                height,
+                VideoCodec::Gif,
+                "gif",
+                100,
+                ("loop", "0"),
+            )?,
+        })
+    }
+
+    /// Add a frame which is shown for `delay` before the next one
+    pub fn write(&mut self, frame: &Frame, delay: Duration) -> Result<(), EncodeError> {
+        self.writer.write(frame, delay)
+    }
+
+    /// Add a frame from tightly packed pixel data which is the same size as the GIF
+    pub fn write_data(
+        &mut self,
+        data: &[u8],
+        format: PixelFormat,
+        delay: Duration,
+    ) -> Result<(), EncodeError> {
+        self.writer.write_data(data, format, delay)
+    }
+
+    /// Finish writing the GIF, see [`VideoEncoder::finish`]
+    pub fn finish(self) -> Result<(), EncodeError> {
+        self.writer.encoder.finish()
+    }
+}
+
+/// Writes frames to an animated PNG, which loops forever
+///
+/// Unlike [`GifWriter`], this is lossless and keeps the alpha channel of [`PixelFormat::Rgba`] data. Delays are rounded to the nearest millisecond.
+pub struct ApngWriter {
+    writer: AnimationWriter,
+}
+
+impl ApngWriter {
+    /// Create a new animated PNG of the given size, frames which are a different size are scaled to fit
+    pub fn new<P>(path: P, width: u32, height: u32) -> Result<Self, EncodeError>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(ApngWriter {
+            writer: AnimationWriter::new(
+                path,
+                width,
+                height,
+                VideoCodec::Apng,
+                "apng",
+                1000,
+                // The muxer defaults to playing the animation once
+                ("plays", "0"),
+            )?,
+        })
+    }
+
+    /// Add a frame which is shown for `delay` before the next one
+    pub fn write(&mut self, frame: &Frame, delay: Duration) -> Result<(), EncodeError> {
+        self.writer.write(frame, delay)
+    }
+
+    /// Add a frame from tightly packed pixel data which is the same size as the image
+    pub fn write_data(
+        &mut self,
+        data: &[u8],
+        format: PixelFormat,
+        delay: Duration,
+    ) -> Result<(), EncodeError> {
+        self.writer.write_data(data, format, delay)
+    }
+
+    /// Finish writing the image, see [`VideoEncoder::finish`]
+    pub fn finish(self) -> Result<(), EncodeError> {
+        self.writer.encoder.finish()
+    }
+}
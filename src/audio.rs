@@ -0,0 +1,505 @@
+use super::{DecodeError, EncodeError, TranscodeError, VideoSource};
+use crate::c::ts_to_duration;
+use crate::input::{Input, InputOptions};
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Duration;
+use std::{ffi, mem, ptr};
+
+/// The size of a WAV header, which comes before the samples
+const WAV_HEADER_SIZE: u32 = 44;
+
+/// A block of decoded audio, see [`DecoderBuilder::audio`](crate::DecoderBuilder::audio) and [`MediaDecoder`](crate::MediaDecoder)
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioFrame {
+    pub(crate) timestamp: Duration,
+    pub(crate) sample_rate: u32,
+    pub(crate) channels: u16,
+    pub(crate) samples: Vec<f32>,
+}
+
+impl AudioFrame {
+    /// The time (from the start of the video) at which the first sample should be played
+    #[inline]
+    pub fn timestamp(&self) -> Duration {
+        self.timestamp
+    }
+
+    /// How long the samples last for
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.sample_count() as f64 / self.sample_rate as f64)
+    }
+
+    /// Only keep the samples played from `start` up to `end`, `None` if there are none
+    pub(crate) fn trimmed(mut self, start: Duration, end: Duration) -> Option<Self> {
+        let sample_at = |time: Duration| {
+            let offset = time.saturating_sub(self.timestamp).as_secs_f64();
+            ((offset * self.sample_rate as f64).round() as usize).min(self.sample_count())
+        };
+        let (first, last) = (sample_at(start), sample_at(end));
+        if first >= last {
+            return None;
+        }
+
+        let channels = self.channels as usize;
+        self.samples.truncate(last * channels);
+        self.samples.drain(..first * channels);
+        self.timestamp += Duration::from_secs_f64(first as f64 / self.sample_rate as f64);
+        Some(self)
+    }
+
+    /// The number of samples per second (of each channel)
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// The number of channels, the samples of which are interleaved
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// The number of samples in each channel
+    #[inline]
+    pub fn sample_count(&self) -> usize {
+        self.samples.len() / self.channels as usize
+    }
+
+    /// Get a reference to the interleaved samples (from `-1.0` to `1.0`)
+    #[inline]
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Convert this frame into its interleaved samples (from `-1.0` to `1.0`)
+    #[inline]
+    pub fn into_samples(self) -> Vec<f32> {
+        self.samples
+    }
+}
+
+/// The layout of the samples written by [`extract_audio`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AudioSpec {
+    sample_rate: u32,
+    channels: u16,
+    sample_count: usize,
+}
+
+impl AudioSpec {
+    /// The number of samples per second (of each channel)
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// The number of channels, the samples of which are interleaved
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// The number of bits in each sample, samples are always signed little endian integers
+    #[inline]
+    pub fn bits_per_sample(&self) -> u16 {
+        16
+    }
+
+    /// The number of samples in each channel
+    #[inline]
+    pub fn sample_count(&self) -> usize {
+        self.sample_count
+    }
+
+    /// How long the audio lasts for
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.sample_count as f64 / self.sample_rate as f64)
+    }
+
+    /// The header of a WAV file containing the samples
+    fn wav_header(&self) -> [u8; WAV_HEADER_SIZE as usize] {
+        let block_align = self.channels * self.bits_per_sample() / 8;
+        let data_size = u32::try_from(self.sample_count * block_align as usize)
+            .unwrap_or(u32::MAX - WAV_HEADER_SIZE);
+
+        let mut header = [0; WAV_HEADER_SIZE as usize];
+        header[0..4].copy_from_slice(b"RIFF");
+        header[4..8].copy_from_slice(&(data_size + WAV_HEADER_SIZE - 8).to_le_bytes());
+        header[8..16].copy_from_slice(b"WAVEfmt ");
+        header[16..20].copy_from_slice(&16u32.to_le_bytes());
+        // PCM
+        header[20..22].copy_from_slice(&1u16.to_le_bytes());
+        header[22..24].copy_from_slice(&self.channels.to_le_bytes());
+        header[24..28].copy_from_slice(&self.sample_rate.to_le_bytes());
+        header[28..32].copy_from_slice(&(self.sample_rate * block_align as u32).to_le_bytes());
+        header[32..34].copy_from_slice(&block_align.to_le_bytes());
+        header[34..36].copy_from_slice(&self.bits_per_sample().to_le_bytes());
+        header[36..40].copy_from_slice(b"data");
+        header[40..44].copy_from_slice(&data_size.to_le_bytes());
+        header
+    }
+}
+
+/// A decoder for an audio stream, which converts the decoded samples to interleaved `f32` (resampled to a fixed rate and channel count).
+///
+/// This does not own the input, packets read from it are passed in with [`AudioStream::send`] so audio can be decoded alongside other streams.
+pub(crate) struct AudioStream {
+    codec_ctx: *mut ffmpeg::AVCodecContext,
+    frame: *mut ffmpeg::AVFrame,
+    /// Created once the first frame is decoded, as the sample format is not always known before then
+    swr: *mut ffmpeg::SwrContext,
+    stream_id: i32,
+    time_base: ffmpeg::AVRational,
+    start_time: i64,
+    sample_rate: u32,
+    channels: u16,
+    /// Whether the samples buffered in the resampler have been returned
+    drained: bool,
+}
+
+// SAFETY: See `VideoDecoder`, the contexts are only accessed through `&mut self`
+unsafe impl Send for AudioStream {}
+
+impl AudioStream {
+    /// Open a decoder for a stream of the input, `sample_rate` and `channels` default to those of the stream
+    pub unsafe fn open(
+        input: &Input,
+        stream_id: usize,
+        sample_rate: Option<u32>,
+        channels: Option<u16>,
+    ) -> Result<Self, DecodeError> {
+        let stream = input.stream(stream_id);
+        let codecpar = (*stream).codecpar;
+
+        let codec = ffmpeg::avcodec_find_decoder((*codecpar).codec_id);
+        if codec.is_null() {
+            return Err(DecodeError::UnsupportedCodec);
+        }
+
+        let mut codec_ctx = ffmpeg::avcodec_alloc_context3(codec);
+        if codec_ctx.is_null() {
+            return Err(DecodeError::AllocationFailed);
+        }
+        ffmpeg::avcodec_parameters_to_context(codec_ctx, codecpar);
+        (*codec_ctx).pkt_timebase = (*stream).time_base;
+
+        if ffmpeg::avcodec_open2(codec_ctx, codec, ptr::null_mut()) < 0 {
+            ffmpeg::avcodec_free_context(&mut codec_ctx);
+            return Err(DecodeError::UnsupportedCodec);
+        }
+
+        let sample_rate = sample_rate.unwrap_or((*codec_ctx).sample_rate.max(0) as u32);
+        let channels = channels.unwrap_or((*codec_ctx).ch_layout.nb_channels.max(0) as u16);
+        if sample_rate == 0 || channels == 0 {
+            ffmpeg::avcodec_free_context(&mut codec_ctx);
+            return Err(DecodeError::UnsupportedCodec);
+        }
+
+        let frame = ffmpeg::av_frame_alloc();
+        if frame.is_null() {
+            ffmpeg::avcodec_free_context(&mut codec_ctx);
+            return Err(DecodeError::AllocationFailed);
+        }
+
+        Ok(AudioStream {
+            codec_ctx,
+            frame,
+            swr: ptr::null_mut(),
+            stream_id: stream_id as i32,
+            time_base: (*stream).time_base,
+            start_time: match (*stream).start_time {
+                ffmpeg::AV_NOPTS_VALUE => 0,
+                start_time => start_time,
+            },
+            sample_rate,
+            channels,
+            drained: false,
+        })
+    }
+
+    /// The index of the stream being decoded
+    #[inline]
+    pub fn stream_id(&self) -> i32 {
+        self.stream_id
+    }
+
+    /// The sample rate of the output samples
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// The number of (interleaved) channels in the output samples
+    #[inline]
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Send a packet to the decoder, a null packet flushes the decoder at the end of the input.
+    ///
+    /// Packets which fail to decode are skipped, as a glitch in the audio is not worth failing over.
+    pub unsafe fn send(&mut self, packet: *const ffmpeg::AVPacket) {
+        ffmpeg::avcodec_send_packet(self.codec_ctx, packet);
+    }
+
+    /// Discard any frames and samples still held by the decoder, after seeking
+    pub unsafe fn flush(&mut self) {
+        ffmpeg::avcodec_flush_buffers(self.codec_ctx);
+        // Recreated from the next frame decoded
+        ffmpeg::swr_free(&mut self.swr);
+        self.drained = false;
+    }
+
+    /// The time base of the timestamps of the decoded frames
+    #[inline]
+    pub fn time_base(&self) -> ffmpeg::AVRational {
+        self.time_base
+    }
+
+    /// Convert a timestamp of a decoded frame to the time since the start of the stream
+    pub fn pts_to_time(&self, pts: i64) -> Option<Duration> {
+        match pts {
+            ffmpeg::AV_NOPTS_VALUE => None,
+            pts => ts_to_duration(pts - self.start_time, self.time_base),
+        }
+    }
+
+    /// Receive the next decoded frame as its timestamp (which may be `AV_NOPTS_VALUE`) and interleaved samples,
+    /// returns `Ok(None)` once another packet is needed.
+    pub unsafe fn receive(&mut self) -> Result<Option<(i64, Vec<f32>)>, DecodeError> {
+        let result = ffmpeg::avcodec_receive_frame(self.codec_ctx, self.frame);
+        if result == ffmpeg::AVERROR_EOF {
+            // The resampler holds back a few samples, which are only returned once it is flushed
+            if self.drained || self.swr.is_null() {
+                return Ok(None);
+            }
+            self.drained = true;
+            let samples = self.convert(ptr::null(), 0)?;
+            return Ok((!samples.is_empty()).then_some((ffmpeg::AV_NOPTS_VALUE, samples)));
+        }
+        if result < 0 {
+            return Ok(None);
+        }
+
+        if self.swr.is_null() {
+            self.init_resampler()?;
+        }
+
+        let pts = (*self.frame).best_effort_timestamp;
+        let samples = self.convert(
+            (*self.frame).extended_data as *const *const u8,
+            (*self.frame).nb_samples,
+        );
+        ffmpeg::av_frame_unref(self.frame);
+
+        Ok(Some((pts, samples?)))
+    }
+
+    /// Create the resampler from the format of the current frame
+    unsafe fn init_resampler(&mut self) -> Result<(), DecodeError> {
+        let mut in_layout: ffmpeg::AVChannelLayout = mem::zeroed();
+        let mut out_layout: ffmpeg::AVChannelLayout = mem::zeroed();
+        // Streams without a channel layout (e.g. some wav files) are assumed to use the default layout
+        if (*self.frame).ch_layout.order == ffmpeg::AVChannelOrder::AV_CHANNEL_ORDER_UNSPEC {
+            ffmpeg::av_channel_layout_default(&mut in_layout, (*self.frame).ch_layout.nb_channels);
+        } else {
+            ffmpeg::av_channel_layout_copy(&mut in_layout, &(*self.frame).ch_layout);
+        }
+        ffmpeg::av_channel_layout_default(&mut out_layout, self.channels as ffi::c_int);
+
+        let result = ffmpeg::swr_alloc_set_opts2(
+            &mut self.swr,
+            &out_layout,
+            ffmpeg::AVSampleFormat::AV_SAMPLE_FMT_FLT,
+            self.sample_rate as ffi::c_int,
+            &in_layout,
+            mem::transmute::<ffi::c_int, ffmpeg::AVSampleFormat>((*self.frame).format),
+            (*self.frame).sample_rate,
+            0,
+            ptr::null_mut(),
+        );
+        ffmpeg::av_channel_layout_uninit(&mut in_layout);
+        ffmpeg::av_channel_layout_uninit(&mut out_layout);
+
+        if result < 0 || ffmpeg::swr_init(self.swr) < 0 {
+            ffmpeg::swr_free(&mut self.swr);
+            return Err(DecodeError::ConverterInitFailed);
+        }
+        Ok(())
+    }
+
+    /// Resample `count` samples from `data` (in the format of the decoded frames), a null `data` flushes the resampler
+    unsafe fn convert(
+        &mut self,
+        data: *const *const u8,
+        count: ffi::c_int,
+    ) -> Result<Vec<f32>, DecodeError> {
+        let capacity = ffmpeg::swr_get_out_samples(self.swr, count);
+        if capacity < 0 {
+            return Err(DecodeError::UnableToReadFrameBuffer);
+        }
+
+        let channels = self.channels as usize;
+        let mut samples = vec![0.0f32; capacity as usize * channels];
+        let output = samples.as_mut_ptr() as *mut u8;
+        let converted = ffmpeg::swr_convert(self.swr, &output, capacity, data, count);
+        if converted < 0 {
+            return Err(DecodeError::UnableToReadFrameBuffer);
+        }
+
+        samples.truncate(converted as usize * channels);
+        Ok(samples)
+    }
+}
+
+impl Drop for AudioStream {
+    fn drop(&mut self) {
+        unsafe {
+            ffmpeg::swr_free(&mut self.swr);
+            ffmpeg::av_frame_free(&mut self.frame);
+            ffmpeg::avcodec_free_context(&mut self.codec_ctx);
+        }
+    }
+}
+
+/// Decode the first audio stream of the source, calling `callback` with each decoded frame (timestamped from the start of the audio).
+///
+/// Returns the sample rate and number of channels of the samples.
+pub(crate) fn decode_audio<F>(
+    source: VideoSource,
+    sample_rate: Option<u32>,
+    channels: Option<u16>,
+    mut callback: F,
+) -> Result<(u32, u16), DecodeError>
+where
+    F: FnMut(AudioFrame),
+{
+    let mut input = Input::open(source, &InputOptions::default())?;
+    let stream_id = input
+        .find_stream(ffmpeg::AVMediaType::AVMEDIA_TYPE_AUDIO)
+        .ok_or(DecodeError::UnableToFindAudioStream)?;
+
+    unsafe {
+        let mut audio = AudioStream::open(&input, stream_id, sample_rate, channels)?;
+        let mut packet = ffmpeg::av_packet_alloc();
+        if packet.is_null() {
+            return Err(DecodeError::AllocationFailed);
+        }
+
+        let result = (|| -> Result<(u32, u16), DecodeError> {
+            // When the most recently decoded audio ends, for frames without a timestamp
+            let mut end = Duration::ZERO;
+            loop {
+                let finished = input.read_frame(packet)? < 0;
+                if finished {
+                    audio.send(ptr::null());
+                } else if (*packet).stream_index == audio.stream_id() {
+                    audio.send(packet);
+                }
+                ffmpeg::av_packet_unref(packet);
+
+                while let Some((pts, samples)) = audio.receive()? {
+                    let frame = AudioFrame {
+                        timestamp: audio.pts_to_time(pts).unwrap_or(end),
+                        sample_rate: audio.sample_rate(),
+                        channels: audio.channels(),
+                        samples,
+                    };
+                    end = frame.timestamp + frame.duration();
+                    callback(frame);
+                }
+                if finished {
+                    return Ok((audio.sample_rate(), audio.channels()));
+                }
+            }
+        })();
+
+        ffmpeg::av_packet_free(&mut packet);
+        result
+    }
+}
+
+/// Decode the audio of a video and write it to a file as 16 bit PCM, returning the layout of the samples.
+///
+/// If the output path ends in `.wav` a WAV file is written, otherwise the file contains only the raw interleaved samples
+/// (signed 16 bit little endian), as described by the returned [`AudioSpec`]. The sample rate and channels of the source audio are kept.
+///
+/// Returns [`DecodeError::UnableToFindAudioStream`] if the source data does not contain audio.
+/// The output file is removed if the audio can not be extracted.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::extract_audio;
+///
+/// # let file = "test.mp4";
+/// # let output = std::env::temp_dir().join("extract_audio_doc.wav");
+/// let spec = extract_audio(file, &output).unwrap();
+/// println!("{}Hz, {} channels, {:?}", spec.sample_rate(), spec.channels(), spec.duration());
+/// # }
+/// ```
+pub fn extract_audio<S, P>(source: S, output: P) -> Result<AudioSpec, TranscodeError>
+where
+    S: Into<VideoSource>,
+    P: AsRef<Path>,
+{
+    let output = output.as_ref();
+    let result = write_audio(source.into(), output);
+    if result.is_err() {
+        // Do not leave a partly written (or empty) file behind
+        let _ = std::fs::remove_file(output);
+    }
+    result
+}
+
+/// See [`extract_audio`], the output file is left as it is on failure
+fn write_audio(source: VideoSource, output: &Path) -> Result<AudioSpec, TranscodeError> {
+    let wav = output
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("wav"));
+
+    let mut file =
+        BufWriter::new(File::create(output).map_err(|_| EncodeError::UnableToOpenOutput)?);
+    if wav {
+        // Filled in once the number of samples is known
+        file.write_all(&[0; WAV_HEADER_SIZE as usize])
+            .map_err(|_| EncodeError::UnableToWrite)?;
+    }
+
+    let mut written = 0;
+    let mut failed = false;
+    let (sample_rate, channels) = decode_audio(source, None, None, |frame| {
+        if failed {
+            return;
+        }
+
+        let bytes: Vec<u8> = frame
+            .samples
+            .iter()
+            .flat_map(|&sample| ((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes())
+            .collect();
+        failed = file.write_all(&bytes).is_err();
+        written += frame.samples.len();
+    })?;
+    if failed {
+        return Err(EncodeError::UnableToWrite.into());
+    }
+
+    let spec = AudioSpec {
+        sample_rate,
+        channels,
+        sample_count: written / channels as usize,
+    };
+    if wav {
+        file.seek(SeekFrom::Start(0))
+            .and_then(|_| file.write_all(&spec.wav_header()))
+            .map_err(|_| EncodeError::UnableToWrite)?;
+    }
+    file.flush().map_err(|_| EncodeError::UnableToWrite)?;
+
+    Ok(spec)
+}
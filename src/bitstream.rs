@@ -0,0 +1,207 @@
+//! Decoding raw elementary streams (with no container), e.g. H.264 NAL units from an RTP session
+
+use super::{DecodeError, Dimensions, Frame, PixelFormat, VideoCodec};
+use crate::convert::Converter;
+use crate::motion::read_motion_vectors;
+use std::collections::VecDeque;
+use std::ptr;
+
+/// A decoder for a raw video bitstream, such as Annex B H.264/H.265 or AV1 OBUs, which is pushed in chunks
+///
+/// Unlike [`VideoDecoder`](crate::VideoDecoder) there is no container, so the data can be split at any point
+/// (the chunks do not need to line up with frames or NAL units). Frames have no timestamps, as these come from the container.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::{BitstreamDecoder, PixelFormat, VideoCodec};
+///
+/// let mut decoder = BitstreamDecoder::new(VideoCodec::H264, PixelFormat::Rgb24).unwrap();
+/// # let chunks: Vec<Vec<u8>> = Vec::new();
+/// for chunk in chunks {
+///     decoder.push(&chunk).unwrap();
+///     while let Some(frame) = decoder.next_frame() {
+///         // do something with the frame
+///     }
+/// }
+/// # }
+/// ```
+pub struct BitstreamDecoder {
+    codec_ctx: *mut ffmpeg::AVCodecContext,
+    parser: *mut ffmpeg::AVCodecParserContext,
+    packet: *mut ffmpeg::AVPacket,
+    raw_frame: *mut ffmpeg::AVFrame,
+    /// Created once the size of the frames is known, and recreated if it changes
+    converter: Option<Converter>,
+    format: PixelFormat,
+    /// Frames which have been decoded but not yet returned
+    frames: VecDeque<Frame>,
+    /// The number of the next decoded frame
+    index: usize,
+}
+
+// SAFETY: See `VideoDecoder`, the same reasoning applies here
+unsafe impl Send for BitstreamDecoder {}
+
+impl BitstreamDecoder {
+    /// Create a decoder for a raw bitstream of the given codec, which outputs frames in the given format
+    pub fn new(codec: VideoCodec, format: PixelFormat) -> Result<Self, DecodeError> {
+        unsafe {
+            let mut decoder = BitstreamDecoder {
+                codec_ctx: ptr::null_mut(),
+                parser: ffmpeg::av_parser_init(codec.as_raw() as i32),
+                packet: ffmpeg::av_packet_alloc(),
+                raw_frame: ffmpeg::av_frame_alloc(),
+                converter: None,
+                format,
+                frames: VecDeque::new(),
+                index: 1, // first frame is frame 1
+            };
+            if decoder.packet.is_null() || decoder.raw_frame.is_null() {
+                return Err(DecodeError::AllocationFailed);
+            }
+            // The parser splits the data into packets, which is not possible without one
+            if decoder.parser.is_null() {
+                return Err(DecodeError::UnsupportedCodec);
+            }
+
+            let codec = ffmpeg::avcodec_find_decoder(codec.as_raw());
+            if codec.is_null() {
+                return Err(DecodeError::UnsupportedCodec);
+            }
+
+            decoder.codec_ctx = ffmpeg::avcodec_alloc_context3(codec);
+            if decoder.codec_ctx.is_null() {
+                return Err(DecodeError::AllocationFailed);
+            }
+            if ffmpeg::avcodec_open2(decoder.codec_ctx, codec, ptr::null_mut()) < 0 {
+                return Err(DecodeError::UnsupportedCodec);
+            }
+
+            Ok(decoder)
+        }
+    }
+
+    /// Add the next chunk of the bitstream, decoding any frames it completes (see [`BitstreamDecoder::next_frame`])
+    pub fn push(&mut self, data: &[u8]) -> Result<(), DecodeError> {
+        let mut data = data;
+        while !data.is_empty() {
+            let consumed = unsafe { self.parse(data.as_ptr(), data.len())? };
+            data = &data[consumed..];
+        }
+        Ok(())
+    }
+
+    /// Signal the end of the bitstream, so that the frames still held by the decoder are returned
+    ///
+    /// No more data can be pushed after this.
+    pub fn finish(&mut self) -> Result<(), DecodeError> {
+        unsafe {
+            // Flush the parser, which may be holding the last packet
+            self.parse(ptr::null(), 0)?;
+            ffmpeg::avcodec_send_packet(self.codec_ctx, ptr::null());
+            self.receive_frames()
+        }
+    }
+
+    /// Get the next decoded frame, `None` if more data needs to be pushed first
+    pub fn next_frame(&mut self) -> Option<Frame> {
+        self.frames.pop_front()
+    }
+
+    /// The dimensions of the decoded video, `None` if no frames have been decoded yet
+    pub fn dimensions(&self) -> Option<Dimensions> {
+        self.converter.as_ref().map(Converter::dimensions)
+    }
+
+    /// The layout of the pixels in the frames returned by the decoder
+    #[inline]
+    pub fn output_format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Pass data through the parser and send any packet it completes to the decoder, returning the number of bytes which were used
+    ///
+    /// # Safety
+    ///
+    /// `data` must point to `size` bytes, a null pointer flushes the parser.
+    unsafe fn parse(&mut self, data: *const u8, size: usize) -> Result<usize, DecodeError> {
+        let (mut packet_data, mut packet_size) = (ptr::null_mut(), 0);
+        let consumed = ffmpeg::av_parser_parse2(
+            self.parser,
+            self.codec_ctx,
+            &mut packet_data,
+            &mut packet_size,
+            data,
+            size as i32,
+            ffmpeg::AV_NOPTS_VALUE,
+            ffmpeg::AV_NOPTS_VALUE,
+            0,
+        );
+        if consumed < 0 {
+            return Err(DecodeError::UnableToSendPacketToDecoder);
+        }
+
+        if packet_size > 0 {
+            // The packet data is owned by the parser, the decoder copies it
+            (*self.packet).data = packet_data;
+            (*self.packet).size = packet_size;
+            let result = ffmpeg::avcodec_send_packet(self.codec_ctx, self.packet);
+            (*self.packet).data = ptr::null_mut();
+            (*self.packet).size = 0;
+            if result < 0 && result != ffmpeg::AVERROR_INVALIDDATA {
+                return Err(DecodeError::UnableToSendPacketToDecoder);
+            }
+            self.receive_frames()?;
+        }
+
+        Ok(consumed as usize)
+    }
+
+    /// Receive the frames currently available from the decoder, and convert them to the output format
+    unsafe fn receive_frames(&mut self) -> Result<(), DecodeError> {
+        while ffmpeg::avcodec_receive_frame(self.codec_ctx, self.raw_frame) >= 0 {
+            let dimensions = Dimensions {
+                width: (*self.raw_frame).width as u32,
+                height: (*self.raw_frame).height as u32,
+            };
+            let converter = match self.converter.take() {
+                Some(converter) if converter.dimensions() == dimensions => converter,
+                _ => Converter::new(dimensions, self.format, true, ffmpeg::SWS_BILINEAR)?,
+            };
+            let converter = self.converter.insert(converter);
+
+            let result = converter.convert(self.raw_frame);
+            let motion_vectors = read_motion_vectors(self.raw_frame);
+            let keyframe = (*self.raw_frame).flags & ffmpeg::AV_FRAME_FLAG_KEY as i32 != 0;
+            ffmpeg::av_frame_unref(self.raw_frame);
+            let (data, stride) = result?;
+
+            self.frames.push_back(Frame {
+                index: self.index,
+                timestamp: None,
+                duration: None,
+                data: data.into(),
+                dimensions,
+                format: self.format,
+                stride,
+                motion_vectors,
+                captions: Vec::new(),
+                timecode: None,
+                keyframe,
+            });
+            self.index += 1;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for BitstreamDecoder {
+    fn drop(&mut self) {
+        unsafe {
+            ffmpeg::av_parser_close(self.parser);
+            ffmpeg::av_packet_free(&mut self.packet);
+            ffmpeg::av_frame_free(&mut self.raw_frame);
+            ffmpeg::avcodec_free_context(&mut self.codec_ctx);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use super::{DecodeError, DecoderBuilder, Dimensions, Frame, PixelFormat, VideoSource};
+use std::time::Duration;
+
+/// The fraction of pixels which must be dark for a frame to count as black
+const BLACK_PIXEL_RATIO: f64 = 0.98;
+
+/// A run of black frames, see [`detect_black_frames`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlackSegment {
+    start: Duration,
+    end: Duration,
+    first_frame: usize,
+    last_frame: usize,
+}
+
+impl BlackSegment {
+    /// The timestamp of the first black frame
+    #[inline]
+    pub fn start(&self) -> Duration {
+        self.start
+    }
+
+    /// When the last black frame ends
+    #[inline]
+    pub fn end(&self) -> Duration {
+        self.end
+    }
+
+    /// How long the black frames are shown for
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// The index of the first black frame
+    #[inline]
+    pub fn first_frame(&self) -> usize {
+        self.first_frame
+    }
+
+    /// The index of the last black frame
+    #[inline]
+    pub fn last_frame(&self) -> usize {
+        self.last_frame
+    }
+}
+
+/// Find the parts of a video which are black (or nearly black) for at least `min_duration`, e.g. to find ad breaks or trim the start and end of a recording.
+///
+/// A pixel is dark if its brightness is at most `threshold` (between `0.0` and `1.0`, around `0.1` works well),
+/// and a frame is black if almost all of its pixels are dark. Frames are only decoded to grayscale, so this is much cheaper than decoding to RGB.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::detect_black_frames;
+/// use std::time::Duration;
+///
+/// # let file = "test.mp4";
+/// for segment in detect_black_frames(file, 0.1, Duration::from_millis(500)).unwrap() {
+///     println!("black from {:?} to {:?}", segment.start(), segment.end());
+/// }
+/// # }
+/// ```
+pub fn detect_black_frames<S>(
+    source: S,
+    threshold: f32,
+    min_duration: Duration,
+) -> Result<Vec<BlackSegment>, DecodeError>
+where
+    S: Into<VideoSource>,
+{
+    let mut decoder = DecoderBuilder::new(source)
+        .output_format(PixelFormat::Gray8)
+        .build()?;
+
+    let max = (threshold.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let mut segments = Vec::new();
+    let mut current: Option<BlackSegment> = None;
+    while let Some(frame) = decoder.next_frame()? {
+        let timestamp = frame.timestamp().unwrap_or_default();
+        let end = timestamp + frame.duration().unwrap_or_default();
+
+        match (is_black(&frame, max), current.as_mut()) {
+            (true, Some(segment)) => {
+                segment.end = end;
+                segment.last_frame = frame.index();
+            }
+            (true, None) => {
+                current = Some(BlackSegment {
+                    start: timestamp,
+                    end,
+                    first_frame: frame.index(),
+                    last_frame: frame.index(),
+                })
+            }
+            (false, _) => segments.extend(current.take()),
+        }
+    }
+    segments.extend(current);
+
+    segments.retain(|segment| segment.duration() >= min_duration);
+    Ok(segments)
+}
+
+/// Whether almost every pixel of a grayscale frame is at most `max`
+fn is_black(frame: &Frame, max: u8) -> bool {
+    let plane = frame.planes()[0];
+    let Dimensions { width, height } = plane.dimensions();
+
+    let dark: usize = (0..height as usize)
+        .map(|row| plane.row(row).iter().filter(|&&luma| luma <= max).count())
+        .sum();
+    dark as f64 >= width as f64 * height as f64 * BLACK_PIXEL_RATIO
+}
@@ -0,0 +1,1007 @@
+use super::{DecodeError, DecoderHandle, Dimensions, PixelFormat, VideoDecoder, VideoSource};
+use crate::input::InputOptions;
+use crate::overlay::Overlay;
+use std::ffi;
+use std::time::Duration;
+
+/// How ffmpeg should split decoding work between threads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThreadType {
+    /// Decode multiple frames at once, this adds a delay of one frame per thread
+    Frame,
+    /// Decode multiple parts of a single frame at once, this does not add any delay
+    Slice,
+    /// Let ffmpeg pick whichever method the codec supports (preferring frame threading)
+    #[default]
+    Any,
+}
+
+impl ThreadType {
+    pub(crate) fn as_raw(&self) -> std::ffi::c_int {
+        match self {
+            ThreadType::Frame => ffmpeg::FF_THREAD_FRAME,
+            ThreadType::Slice => ffmpeg::FF_THREAD_SLICE,
+            ThreadType::Any => ffmpeg::FF_THREAD_FRAME | ffmpeg::FF_THREAD_SLICE,
+        }
+    }
+}
+
+/// What the decoder should do once reaching the end of the source data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopMode {
+    /// Play the source data once, then stop
+    #[default]
+    Never,
+    /// Loop back to the start forever
+    Infinite,
+    /// Play the source data this many times in total, then stop (so `Count(3)` loops back to the start twice).
+    ///
+    /// `Count(0)` and `Count(1)` behave the same as [`LoopMode::Never`].
+    Count(u32),
+    /// Play forward to the end, then backward to the start, forever (a "boomerang" loop).
+    ///
+    /// The first and last frames are only shown once each time the direction changes.
+    /// The backward pass decodes one group of pictures at a time from the keyframe before it, so the source data must be seekable.
+    PingPong,
+}
+
+impl From<bool> for LoopMode {
+    fn from(should_loop: bool) -> Self {
+        match should_loop {
+            true => LoopMode::Infinite,
+            false => LoopMode::Never,
+        }
+    }
+}
+
+/// Which frames the decoder should return, see [`DecoderBuilder::sample`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sampling {
+    /// Every `n`th frame, starting from the first frame (so `EveryNth(10)` returns frames 1, 11, 21, ...).
+    ///
+    /// `EveryNth(0)` behaves the same as `EveryNth(1)`, returning every frame.
+    EveryNth(usize),
+    /// Roughly this many frames per second of video, the first frame at or after each interval is returned.
+    ///
+    /// Every frame is returned if this is at least the framerate of the video.
+    PerSecond(f32),
+}
+
+impl Sampling {
+    /// Whether the frame with the given index (starting at 1) should be returned, for video with the given framerate
+    pub(crate) fn includes(&self, index: usize, framerate: f32) -> bool {
+        match *self {
+            Sampling::EveryNth(n) => index.saturating_sub(1) % n.max(1) == 0,
+            Sampling::PerSecond(rate) => {
+                if rate <= 0.0 || framerate <= 0.0 || index <= 1 {
+                    return true;
+                }
+
+                // The interval each frame falls in, allowing for a small amount of rounding error
+                let interval = |index: usize| {
+                    ((index - 1) as f64 * rate as f64 / framerate as f64 + 1e-9).floor()
+                };
+                interval(index) != interval(index - 1)
+            }
+        }
+    }
+}
+
+/// The curve used to map HDR video to SDR, see [`DecoderBuilder::tonemap`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToneMapping {
+    /// Preserves detail in both dark and bright areas, at the cost of slightly darkening the image
+    #[default]
+    Hable,
+    /// A simple curve which keeps the overall brightness, but loses detail in bright areas
+    Reinhard,
+    /// Keeps in range colors exactly, only compressing the brightest highlights
+    Mobius,
+    /// Clip values which are out of range (this is what happens without tone mapping)
+    Clip,
+}
+
+impl ToneMapping {
+    /// The name of the curve in the ffmpeg `tonemap` filter
+    fn as_filter(&self) -> &'static str {
+        match self {
+            ToneMapping::Hable => "hable",
+            ToneMapping::Reinhard => "reinhard",
+            ToneMapping::Mobius => "mobius",
+            ToneMapping::Clip => "clip",
+        }
+    }
+}
+
+/// The algorithm used to scale and convert frames, see [`DecoderBuilder::scaling_quality`]
+///
+/// These are listed from fastest (and lowest quality) to slowest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalingQuality {
+    /// A less accurate version of bilinear scaling
+    FastBilinear,
+    /// Linear interpolation between the nearest pixels
+    #[default]
+    Bilinear,
+    /// Smoother than bilinear, especially when enlarging
+    Bicubic,
+    /// The sharpest result, at the cost of some ringing around hard edges
+    Lanczos,
+    /// Sharp without as much ringing as lanczos, but the slowest
+    Spline,
+}
+
+impl ScalingQuality {
+    /// The `SWS_*` flag for the algorithm
+    fn as_raw(&self) -> ffi::c_int {
+        match self {
+            ScalingQuality::FastBilinear => ffmpeg::SWS_FAST_BILINEAR,
+            ScalingQuality::Bilinear => ffmpeg::SWS_BILINEAR,
+            ScalingQuality::Bicubic => ffmpeg::SWS_BICUBIC,
+            ScalingQuality::Lanczos => ffmpeg::SWS_LANCZOS,
+            ScalingQuality::Spline => ffmpeg::SWS_SPLINE,
+        }
+    }
+
+    /// The name of the algorithm in the swscale `flags` option
+    fn as_filter(&self) -> &'static str {
+        match self {
+            ScalingQuality::FastBilinear => "fast_bilinear",
+            ScalingQuality::Bilinear => "bilinear",
+            ScalingQuality::Bicubic => "bicubic",
+            ScalingQuality::Lanczos => "lanczos",
+            ScalingQuality::Spline => "spline",
+        }
+    }
+}
+
+/// How frames are made to fit a size which has a different aspect ratio, see [`DecoderBuilder::fit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FitMode {
+    /// Scale the frame to fit inside the size, and fill the rest with black bars (letterboxing or pillarboxing)
+    #[default]
+    Contain,
+    /// Scale the frame to cover the size, and cut off the edges which do not fit (keeping the centre)
+    Cover,
+    /// Stretch the frame to the size, ignoring its aspect ratio
+    Stretch,
+}
+
+impl FitMode {
+    /// The filters which scale a frame to `width` by `height`
+    fn as_filter(&self, width: u32, height: u32) -> String {
+        match self {
+            FitMode::Contain => format!(
+                "scale={width}:{height}:force_original_aspect_ratio=decrease,pad={width}:{height}:(ow-iw)/2:(oh-ih)/2:color=black,setsar=1"
+            ),
+            FitMode::Cover => format!(
+                "scale={width}:{height}:force_original_aspect_ratio=increase,crop={width}:{height},setsar=1"
+            ),
+            FitMode::Stretch => format!("scale={width}:{height},setsar=1"),
+        }
+    }
+}
+
+/// What the decoder should do when the source data is damaged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Return [`DecodeError::UnableToSendPacketToDecoder`] if a packet is rejected by the decoder, damaged frames are still returned
+    #[default]
+    Fail,
+    /// Skip packets which are damaged or rejected by the decoder, damaged frames are still returned
+    SkipPacket,
+    /// Skip packets which are damaged or rejected by the decoder, and frames which were decoded with errors
+    SkipFrame,
+}
+
+/// Which variant of an adaptive stream (HLS or DASH) to decode, see [`DecoderBuilder::variant`]
+///
+/// Variants are compared by their bandwidth (falling back to the bit rate of the stream), then their height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Variant {
+    /// The best quality variant
+    #[default]
+    Highest,
+    /// The lowest quality variant
+    Lowest,
+    /// The best quality variant which needs at most this many bits per second, or the lowest quality variant if they all need more
+    MaxBandwidth(u64),
+    /// The best quality variant which is at most this many pixels tall, or the lowest quality variant if they are all taller
+    MaxHeight(u32),
+}
+
+/// Limits on the resources a [`VideoDecoder`] may use, see [`DecoderBuilder::max_dimensions`]
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Limits {
+    pub max_dimensions: Option<Dimensions>,
+    pub max_frame_count: Option<usize>,
+    pub max_memory_bytes: Option<u64>,
+}
+
+impl Limits {
+    /// Check the dimensions of the video
+    pub fn check_dimensions(&self, width: i32, height: i32) -> Result<(), DecodeError> {
+        match self.max_dimensions {
+            Some(max) if width as i64 > max.width as i64 || height as i64 > max.height as i64 => {
+                Err(DecodeError::LimitExceeded)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the number of frames in the video, or the index of the latest frame
+    pub fn check_frame_count(&self, frames: usize) -> Result<(), DecodeError> {
+        match self.max_frame_count {
+            Some(max) if frames > max => Err(DecodeError::LimitExceeded),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the memory held by the decoder
+    pub fn check_memory(&self, bytes: u64) -> Result<(), DecodeError> {
+        match self.max_memory_bytes {
+            Some(max) if bytes > max => Err(DecodeError::LimitExceeded),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Options used when opening a [`VideoDecoder`]
+#[derive(Debug, Clone)]
+pub(crate) struct DecoderOptions {
+    pub loop_mode: LoopMode,
+    /// `None` uses the ffmpeg default
+    pub threads: Option<usize>,
+    pub thread_type: ThreadType,
+    /// `None` uses the first video stream
+    pub video_stream: Option<usize>,
+    /// `None` does not restrict the streams to a program
+    pub program: Option<usize>,
+    /// `None` uses the first variant
+    pub variant: Option<Variant>,
+    pub live_edge: bool,
+    pub packed_rows: bool,
+    pub output_format: PixelFormat,
+    pub keep_alpha: bool,
+    pub range: Option<(Duration, Duration)>,
+    pub backward_cache: usize,
+    pub frame_cache: usize,
+    /// An ffmpeg filter graph description
+    pub filter: Option<String>,
+    pub deinterlace: bool,
+    pub square_pixels: bool,
+    /// The region of the frame to keep, as `(x, y, width, height)`
+    pub crop: Option<(u32, u32, u32, u32)>,
+    /// The exact size frames should be output at, as `(width, height, mode)`
+    pub fit: Option<(u32, u32, FitMode)>,
+    /// An image drawn over every frame after it is converted
+    pub overlay: Option<Overlay>,
+    pub scaling_quality: ScalingQuality,
+    pub full_chroma: bool,
+    pub dither: bool,
+    pub target_fps: Option<f32>,
+    /// How HDR video should be mapped to SDR, `None` leaves it as it is
+    pub tonemap: Option<ToneMapping>,
+    /// The name of the preferred decoder implementation, `None` uses the ffmpeg default
+    pub decoder_name: Option<String>,
+    /// Options passed to the decoder when it is opened
+    pub codec_options: Vec<(String, String)>,
+    /// The indices of the data streams to read packets from
+    pub data_streams: Vec<usize>,
+    pub telemetry: bool,
+    /// The maximum number of data packets held until they are taken
+    pub max_data_packets: usize,
+    pub motion_vectors: bool,
+    pub closed_captions: bool,
+    pub audio: bool,
+    /// Whether to output frames as soon as possible rather than buffering them, for live sources
+    pub low_latency: bool,
+    /// The maximum number of bytes read to find the streams, `None` uses the ffmpeg default
+    pub probe_size: Option<u64>,
+    /// The maximum duration of the input read to find the stream information, `None` uses the ffmpeg default
+    pub analyze_duration: Option<Duration>,
+    pub error_policy: ErrorPolicy,
+    pub max_buffered_frames: usize,
+    pub sampling: Option<Sampling>,
+    pub shared_data: bool,
+    pub limits: Limits,
+    pub input: InputOptions,
+}
+
+impl DecoderOptions {
+    /// The full filter graph description decoded frames should be passed through, `None` if no filtering is needed
+    ///
+    /// `hdr` is whether the video uses an HDR transfer function, which is the only case where tone mapping is applied,
+    /// and `anamorphic` is whether the video has non-square pixels, which is the only case where they are made square.
+    pub fn filter_graph(&self, hdr: bool, anamorphic: bool) -> Option<String> {
+        let mut filters = Vec::new();
+        if self.deinterlace {
+            // Only frames which are marked as interlaced are changed
+            filters.push("bwdif=mode=send_frame:deint=interlaced".to_string());
+        }
+        if let Some(tonemap) = self.tonemap.filter(|_| hdr) {
+            // The tonemap filter works on linear light, then the result is converted back to BT.709
+            filters.push(format!(
+                "zscale=t=linear:npl=100,format=gbrpf32le,zscale=p=bt709,tonemap=tonemap={}:desat=0,zscale=t=bt709:m=bt709:r=tv,format=yuv444p16le",
+                tonemap.as_filter()
+            ));
+        }
+        if self.square_pixels && anamorphic {
+            // Stretch the width, keeping it even for chroma subsampled formats
+            filters.push("scale=trunc(iw*sar/2)*2:ih,setsar=1".to_string());
+        }
+        if let Some((x, y, width, height)) = self.crop {
+            // Without `exact` the offsets are rounded to the chroma subsampling
+            filters.push(format!("crop={width}:{height}:{x}:{y}:exact=1"));
+        }
+        filters.extend(self.filter.clone());
+        if let Some((width, height, mode)) = self.fit {
+            filters.push(mode.as_filter(width, height));
+        }
+        if let Some(fps) = self.target_fps {
+            filters.push(format!("fps={fps}"));
+        }
+
+        (!filters.is_empty()).then(|| filters.join(","))
+    }
+
+    /// The swscale flags frames should be converted with
+    pub fn sws_flags(&self) -> ffi::c_int {
+        let mut flags = self.scaling_quality.as_raw();
+        if self.full_chroma {
+            flags |=
+                ffmpeg::SWS_FULL_CHR_H_INT | ffmpeg::SWS_FULL_CHR_H_INP | ffmpeg::SWS_ACCURATE_RND;
+        }
+        if self.dither {
+            flags |= ffmpeg::SWS_ERROR_DIFFUSION;
+        }
+        flags
+    }
+
+    /// The swscale options used by the scaling filters of the filter graph, equivalent to [`DecoderOptions::sws_flags`]
+    pub fn scale_options(&self) -> String {
+        let mut flags = self.scaling_quality.as_filter().to_string();
+        if self.full_chroma {
+            flags.push_str("+full_chroma_int+full_chroma_inp+accurate_rnd");
+        }
+        match self.dither {
+            true => format!("flags={flags}:sws_dither=ed"),
+            false => format!("flags={flags}"),
+        }
+    }
+
+    /// The options the input should be opened with
+    pub fn input_options(&self) -> InputOptions {
+        let mut input = self.input.clone();
+        // Added first so they can still be overridden with `DecoderBuilder::format_option`, later options replace earlier ones
+        let mut defaults = Vec::new();
+        if self.low_latency {
+            defaults.extend([
+                ("fflags", "+nobuffer".to_string()),
+                ("probesize", "32".to_string()),
+                ("analyzeduration", "0".to_string()),
+            ]);
+        }
+        if let Some(size) = self.probe_size {
+            // ffmpeg does not accept less than 32 bytes
+            defaults.push(("probesize", size.max(32).to_string()));
+        }
+        if let Some(duration) = self.analyze_duration {
+            defaults.push(("analyzeduration", duration.as_micros().to_string()));
+        }
+        input.format_options.splice(
+            0..0,
+            defaults
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value)),
+        );
+        if self.live_edge {
+            input
+                .format_options
+                .insert(0, ("live_start_index".to_string(), "-1".to_string()));
+        }
+
+        input
+    }
+
+    /// The maximum number of decoded frames to hold before they are returned
+    pub fn buffer_capacity(&self) -> usize {
+        match self.low_latency {
+            true => 1,
+            false => self.max_buffered_frames.max(1),
+        }
+    }
+
+    /// How ffmpeg should split decoding work between threads
+    pub fn thread_type(&self) -> ThreadType {
+        match self.low_latency {
+            // Frame threading delays each frame by one frame per thread
+            true => ThreadType::Slice,
+            false => self.thread_type,
+        }
+    }
+}
+
+impl Default for DecoderOptions {
+    fn default() -> Self {
+        DecoderOptions {
+            loop_mode: LoopMode::Never,
+            threads: None,
+            thread_type: ThreadType::default(),
+            video_stream: None,
+            program: None,
+            variant: None,
+            live_edge: false,
+            packed_rows: true,
+            output_format: PixelFormat::default(),
+            keep_alpha: false,
+            range: None,
+            backward_cache: 32,
+            frame_cache: 0,
+            filter: None,
+            deinterlace: false,
+            square_pixels: false,
+            crop: None,
+            fit: None,
+            overlay: None,
+            scaling_quality: ScalingQuality::default(),
+            full_chroma: false,
+            dither: false,
+            target_fps: None,
+            tonemap: None,
+            decoder_name: None,
+            codec_options: Vec::new(),
+            data_streams: Vec::new(),
+            telemetry: false,
+            max_data_packets: 256,
+            motion_vectors: false,
+            closed_captions: false,
+            audio: false,
+            low_latency: false,
+            probe_size: None,
+            analyze_duration: None,
+            error_policy: ErrorPolicy::default(),
+            max_buffered_frames: 16,
+            sampling: None,
+            shared_data: false,
+            limits: Limits::default(),
+            input: InputOptions::default(),
+        }
+    }
+}
+
+/// A builder for a [`VideoDecoder`], for when the defaults used by [`VideoDecoder::new`] are not enough
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::{DecoderBuilder, ThreadType};
+///
+/// # let file = "test.mp4";
+/// let mut decoder = DecoderBuilder::new(file)
+///     .threads(4)
+///     .thread_type(ThreadType::Frame)
+///     .build()
+///     .unwrap();
+/// let first_frame = decoder.next_frame().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DecoderBuilder {
+    source: VideoSource,
+    options: DecoderOptions,
+}
+
+impl DecoderBuilder {
+    /// Create a new builder for the given input data
+    pub fn new<S>(source: S) -> Self
+    where
+        S: Into<VideoSource>,
+    {
+        DecoderBuilder {
+            source: source.into(),
+            options: DecoderOptions::default(),
+        }
+    }
+
+    /// Whether the decoder should loop back to the start once reaching the end of the source data (defaults to `false`)
+    ///
+    /// This is a shorthand for setting [`DecoderBuilder::loop_mode`] to either [`LoopMode::Infinite`] or [`LoopMode::Never`].
+    pub fn should_loop(mut self, should_loop: bool) -> Self {
+        self.options.loop_mode = should_loop.into();
+        self
+    }
+
+    /// What the decoder should do once reaching the end of the source data (defaults to [`LoopMode::Never`])
+    pub fn loop_mode(mut self, loop_mode: LoopMode) -> Self {
+        self.options.loop_mode = loop_mode;
+        self
+    }
+
+    /// The number of threads ffmpeg should decode with, `0` lets ffmpeg pick based on the number of cpus.
+    ///
+    /// If this is not set, the ffmpeg default is used (which is currently a single thread).
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.options.threads = Some(threads);
+        self
+    }
+
+    /// How work should be split between threads, this has no effect unless more than one thread is used (see [`DecoderBuilder::threads`])
+    pub fn thread_type(mut self, thread_type: ThreadType) -> Self {
+        self.options.thread_type = thread_type;
+        self
+    }
+
+    /// Decode a specific video stream rather than the first one found, see [`VideoDecoder::streams`] for the available streams.
+    ///
+    /// Opening the decoder will fail with [`DecodeError::InvalidVideoStream`] if the stream does not exist or is not a video stream.
+    pub fn video_stream(mut self, index: usize) -> Self {
+        self.options.video_stream = Some(index);
+        self
+    }
+
+    /// Decode the video of a specific program (channel), for sources such as MPEG-TS which carry multiple programs, see [`VideoDecoder::programs`] for the available programs.
+    ///
+    /// The other programs (and any streams which are only part of them) are discarded by the demuxer. Opening the decoder will fail with [`DecodeError::InvalidProgram`] if the program does not exist,
+    /// or [`DecodeError::UnableToFindVideoStream`] if it has no video. This is ignored if [`DecoderBuilder::video_stream`] is set.
+    pub fn program(mut self, index: usize) -> Self {
+        self.options.program = Some(index);
+        self
+    }
+
+    /// Which variant (rendition) of an adaptive stream, such as an HLS (`.m3u8`) or DASH (`.mpd`) playlist, to decode.
+    ///
+    /// By default the first variant in the playlist is used. The other variants (including their audio) are discarded, so they are not downloaded.
+    /// This is ignored if [`DecoderBuilder::video_stream`] or [`DecoderBuilder::program`] is set.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::{DecoderBuilder, Variant};
+    ///
+    /// # let url = "test.mp4";
+    /// let mut decoder = DecoderBuilder::new(url)
+    ///     .variant(Variant::MaxHeight(720))
+    ///     .build()
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.options.variant = Some(variant);
+        self
+    }
+
+    /// Start a live HLS stream at the most recent segment, rather than a few segments behind it (defaults to `false`).
+    ///
+    /// This reduces the delay behind the live stream, but leaves less data buffered to absorb network stalls.
+    /// The exact segment can be set with the `live_start_index` option instead (see [`DecoderBuilder::format_option`]).
+    pub fn live_edge(mut self, live_edge: bool) -> Self {
+        self.options.live_edge = live_edge;
+        self
+    }
+
+    /// The maximum number of decoded frames to hold before they are returned by [`VideoDecoder::next_frame`] (defaults to `16`, the minimum is `1`).
+    ///
+    /// A single packet can produce many frames (especially when decoding with multiple threads or with [`DecoderBuilder::target_fps`]),
+    /// once this many are buffered no more are taken from the decoder until some have been returned. Frames are only converted to the output format when they are returned.
+    pub fn max_buffered_frames(mut self, frames: usize) -> Self {
+        self.options.max_buffered_frames = frames;
+        self
+    }
+
+    /// Only return some of the frames (defaults to returning every frame), e.g. to extract a sparse set of frames for a dataset.
+    ///
+    /// Every frame still has to be decoded, but frames which are not returned are dropped straight away without being converted to the output format.
+    /// Frames keep their original index (see [`Frame::index`](crate::Frame::index)), and [`VideoDecoder::skip`] counts the returned frames.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::{DecoderBuilder, Sampling};
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = DecoderBuilder::new(file).sample(Sampling::PerSecond(1.0)).build().unwrap();
+    /// let first = decoder.next_frame().unwrap().unwrap();
+    /// let second = decoder.next_frame().unwrap().unwrap();
+    /// assert_eq!(second.index() - first.index(), 30); // a 30 fps video
+    /// # }
+    /// ```
+    pub fn sample(mut self, sampling: Sampling) -> Self {
+        self.options.sampling = Some(sampling);
+        self
+    }
+
+    /// Store the data of each frame in a reference counted buffer (defaults to `false`).
+    ///
+    /// Cloning a frame then shares its data instead of copying it, so one decoded frame can be passed to several consumers
+    /// (e.g. a display, a recorder and an analyzer) cheaply. See [`Frame::into_shared_data`](crate::Frame::into_shared_data) to get the buffer itself.
+    /// Editing the data of a shared frame (with [`Frame::data_mut`](crate::Frame::data_mut)) copies it first.
+    pub fn shared_data(mut self, shared_data: bool) -> Self {
+        self.options.shared_data = shared_data;
+        self
+    }
+
+    /// Whether frame data should be tightly packed (defaults to `true`).
+    ///
+    /// ffmpeg aligns the start of each row in memory, so for widths which are not a multiple of the alignment there is padding at the end of each row.
+    /// When this is enabled the padding is stripped, so [`Frame::data`](crate::Frame::data) is exactly `width * height * bytes_per_pixel` bytes.
+    /// Disabling this avoids a copy, but callers must then account for the stride of each row.
+    pub fn packed_rows(mut self, packed_rows: bool) -> Self {
+        self.options.packed_rows = packed_rows;
+        self
+    }
+
+    /// The layout of the pixels in decoded frames (defaults to [`PixelFormat::Rgb24`])
+    pub fn output_format(mut self, format: PixelFormat) -> Self {
+        self.options.output_format = format;
+        self
+    }
+
+    /// Whether the alpha channel of video with transparency should be kept (defaults to `false`).
+    ///
+    /// When this is enabled and the decoded frames have an alpha channel (see [`VideoDecoder::has_alpha`]), [`PixelFormat::Rgb24`] is replaced with [`PixelFormat::Rgba`]
+    /// and [`PixelFormat::Rgb48`] is replaced with [`PixelFormat::Rgba64`], so [`VideoDecoder::output_format`] may not be the format passed to [`DecoderBuilder::output_format`].
+    ///
+    /// Note that the native ffmpeg VP9 decoder ignores the alpha channel of WebM files, the `libvpx-vp9` decoder must be used for those.
+    pub fn keep_alpha(mut self, keep_alpha: bool) -> Self {
+        self.options.keep_alpha = keep_alpha;
+        self
+    }
+
+    /// Only decode the frames between two timestamps (inclusive).
+    ///
+    /// The decoder seeks to `start` before the first frame, and stops once a frame is past `end`.
+    /// If the decoder loops, it loops back to `start` rather than the start of the video.
+    ///
+    /// Opening the decoder will fail with [`DecodeError::InvalidRange`] if `start` is after `end`.
+    pub fn range(mut self, start: Duration, end: Duration) -> Self {
+        self.options.range = Some((start, end));
+        self
+    }
+
+    /// The maximum number of decoded frames to keep for [`VideoDecoder::prev_frame`] (defaults to `32`).
+    ///
+    /// Stepping backward decodes forward from the nearest keyframe, keeping the frames in between so that the following steps do not need to decode them again.
+    /// Ideally this is at least the distance between keyframes in the source data, larger values use more memory (frames are kept in their decoded format, not converted to RGB).
+    pub fn backward_cache(mut self, frames: usize) -> Self {
+        self.options.backward_cache = frames;
+        self
+    }
+
+    /// The maximum number of frames to keep for [`VideoDecoder::get_frame`] and [`VideoDecoder::get_frame_at`] (defaults to `0`, which disables the cache).
+    ///
+    /// When the cache is full, the least recently used frame is evicted. Each frame is `width * height * bytes_per_pixel` bytes.
+    pub fn frame_cache(mut self, frames: usize) -> Self {
+        self.options.frame_cache = frames;
+        self
+    }
+
+    /// Pass decoded frames through an ffmpeg filter graph before they are converted to RGB, e.g. `scale=1280:-1,hue=s=0`.
+    ///
+    /// This uses the same syntax as the `-vf` option of the ffmpeg command line tool, see <https://ffmpeg.org/ffmpeg-filters.html> for the available filters.
+    /// Filters which change the size or framerate of the video are reflected in [`VideoDecoder::dimensions`] and [`VideoDecoder::framerate`].
+    ///
+    /// Opening the decoder will fail with [`DecodeError::InvalidFilter`] if the description could not be parsed.
+    pub fn filter(mut self, filter: &str) -> Self {
+        self.options.filter = Some(filter.to_string());
+        self
+    }
+
+    /// Whether interlaced frames should be deinterlaced before they are converted to RGB (defaults to `false`).
+    ///
+    /// Without this, interlaced video (common in broadcast captures) shows combing artifacts around moving objects.
+    /// Only frames which are marked as interlaced are changed, so this is safe to enable for progressive video. See also [`VideoDecoder::is_interlaced`].
+    ///
+    /// This is applied before any [`DecoderBuilder::filter`].
+    pub fn deinterlace(mut self, deinterlace: bool) -> Self {
+        self.options.deinterlace = deinterlace;
+        self
+    }
+
+    /// Whether frames with non-square pixels (e.g. anamorphic DVDs) should be stretched so their pixels are square (defaults to `false`).
+    ///
+    /// Without this, such frames look squashed or stretched unless they are displayed at [`VideoDecoder::display_dimensions`].
+    /// When enabled, [`VideoDecoder::dimensions`] is the display size. This is applied before any [`DecoderBuilder::filter`].
+    pub fn square_pixels(mut self, square_pixels: bool) -> Self {
+        self.options.square_pixels = square_pixels;
+        self
+    }
+
+    /// Only decode the region of each frame which is `width` by `height` pixels, with its top left corner at (`x`, `y`).
+    ///
+    /// The region is cut out before the frames are converted to RGB, so only the region is converted and copied.
+    /// This is applied after [`DecoderBuilder::square_pixels`] and before any [`DecoderBuilder::filter`], and is reflected in [`VideoDecoder::dimensions`].
+    ///
+    /// Opening the decoder will fail with [`DecodeError::InvalidFilter`] if the region is not inside the frame.
+    pub fn crop(mut self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        self.options.crop = Some((x, y, width, height));
+        self
+    }
+
+    /// Output frames at exactly `width` by `height` pixels, using `mode` to handle a different aspect ratio (e.g. letterboxing with [`FitMode::Contain`]).
+    ///
+    /// This is applied after any [`DecoderBuilder::filter`], and is reflected in [`VideoDecoder::dimensions`].
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::{DecoderBuilder, FitMode};
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = DecoderBuilder::new(file)
+    ///     .fit(640, 640, FitMode::Contain)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(decoder.dimensions().width(), 640);
+    /// assert_eq!(decoder.dimensions().height(), 640);
+    /// # }
+    /// ```
+    pub fn fit(mut self, width: u32, height: u32, mode: FitMode) -> Self {
+        self.options.fit = Some((width, height, mode));
+        self
+    }
+
+    /// Draw an RGBA image (such as a logo or watermark) over every frame, with its top left corner at (`x`, `y`).
+    ///
+    /// `data` is `width * height` RGBA pixels (8 bits per channel, not premultiplied), which are alpha blended onto the frame after it is converted to the [`DecoderBuilder::output_format`].
+    /// Any part of the image outside the frame is left out.
+    ///
+    /// Opening the decoder will fail with [`DecodeError::InvalidOverlay`] if `data` is the wrong size.
+    pub fn overlay(mut self, data: &[u8], width: u32, height: u32, x: u32, y: u32) -> Self {
+        self.options.overlay = Some(Overlay::new(data, width, height, x, y));
+        self
+    }
+
+    /// The algorithm used to scale frames, both when converting them to the output format and in any scaling filters
+    /// (such as [`DecoderBuilder::fit`] or a `scale` filter), defaults to [`ScalingQuality::Bilinear`].
+    pub fn scaling_quality(mut self, quality: ScalingQuality) -> Self {
+        self.options.scaling_quality = quality;
+        self
+    }
+
+    /// Interpolate the chroma (color) channels at full resolution and round accurately when converting frames (defaults to `false`).
+    ///
+    /// Most video stores color at a lower resolution than brightness, so this gives sharper color edges at the cost of slower conversion.
+    pub fn full_chroma(mut self, full_chroma: bool) -> Self {
+        self.options.full_chroma = full_chroma;
+        self
+    }
+
+    /// Dither with error diffusion rather than an ordered pattern when converting to fewer bits per channel,
+    /// e.g. in a `format=rgb8` filter (defaults to `false`).
+    ///
+    /// This avoids visible patterns in smooth gradients, at the cost of slower conversion.
+    pub fn dither(mut self, dither: bool) -> Self {
+        self.options.dither = dither;
+        self
+    }
+
+    /// Map HDR video (HDR10 or HLG) to SDR with the given curve, so it is not washed out or clipped when converted to RGB.
+    ///
+    /// This has no effect for SDR video (see [`VideoDecoder::is_hdr`]). It is applied after deinterlacing and before any [`DecoderBuilder::filter`].
+    /// To keep the bit depth of HDR video instead, use [`PixelFormat::Rgb48`] or [`PixelFormat::Rgba64`] (see [`DecoderBuilder::output_format`]) without tone mapping.
+    ///
+    /// This requires ffmpeg to be built with `zimg`, otherwise opening an HDR video fails with [`DecodeError::InvalidFilter`].
+    pub fn tonemap(mut self, tonemap: ToneMapping) -> Self {
+        self.options.tonemap = Some(tonemap);
+        self
+    }
+
+    /// Drop or duplicate frames so that the video is decoded at a fixed framerate (e.g. decoding a 60fps video at 10fps for sampling).
+    ///
+    /// Frame indices and timestamps are those of the output frames, so frame `n` is shown at `(n - 1) / fps` seconds,
+    /// and [`VideoDecoder::framerate`] returns `fps`. This is applied after any [`DecoderBuilder::filter`].
+    pub fn target_fps(mut self, fps: f32) -> Self {
+        self.options.target_fps = Some(fps);
+        self
+    }
+
+    /// A handle which can cancel the blocking operations of the decoder from another thread, see [`DecoderHandle`].
+    ///
+    /// If the handle is already cancelled, opening the decoder fails with [`DecodeError::Cancelled`].
+    pub fn handle(mut self, handle: DecoderHandle) -> Self {
+        self.options.input.handle = Some(handle);
+        self
+    }
+
+    /// The maximum time a single blocking operation (opening the input, or reading a packet) may take before it fails with [`DecodeError::Cancelled`].
+    ///
+    /// This is mostly useful for network sources, which can otherwise stall forever. The timeout applies to each operation separately, not the decoder as a whole.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.input.timeout = Some(timeout);
+        self
+    }
+
+    /// The container format of the source data (the short name of an ffmpeg demuxer), e.g. `h264` for a raw Annex B stream or `mpegts`.
+    ///
+    /// By default the format is detected by probing the data, which can fail for formats without a header.
+    /// Opening the decoder fails with [`DecodeError::UnknownFormat`](crate::DecodeError::UnknownFormat) if ffmpeg has no demuxer with this name,
+    /// see [`FfmpegInfo::demuxers`](crate::FfmpegInfo::demuxers) for the available formats.
+    pub fn format_hint(mut self, format: &str) -> Self {
+        self.options.input.format = Some(format.to_string());
+        self
+    }
+
+    /// Set an option of the demuxer (or protocol) used to open the input, e.g. `probesize`, `analyzeduration`, `rtsp_transport` or `fflags`.
+    ///
+    /// See <https://ffmpeg.org/ffmpeg-formats.html> and <https://ffmpeg.org/ffmpeg-protocols.html> for the available options.
+    /// Options which are not recognised by the demuxer are ignored. Setting the same key twice replaces the earlier value.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::DecoderBuilder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = DecoderBuilder::new(file)
+    ///     .format_option("probesize", "32")
+    ///     .format_option("fflags", "nobuffer")
+    ///     .build()
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn format_option(mut self, key: &str, value: &str) -> Self {
+        self.options
+            .input
+            .format_options
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// The maximum number of bytes of the source data read when opening it to find the streams and their parameters (defaults to 5MB).
+    ///
+    /// Opening large or slow sources is mostly spent reading the start of the data, so lowering this can make opening much faster,
+    /// at the cost of missing streams which start late or parameters which are only known once decoding has started. This is at least 32 bytes.
+    pub fn probe_size(mut self, bytes: u64) -> Self {
+        self.options.probe_size = Some(bytes);
+        self
+    }
+
+    /// The maximum duration of the source data read when opening it to find the stream parameters, such as the framerate (defaults to 5 seconds).
+    ///
+    /// Like [`DecoderBuilder::probe_size`], lowering this makes opening faster but the stream information may be less accurate.
+    pub fn analyze_duration(mut self, duration: Duration) -> Self {
+        self.options.analyze_duration = Some(duration);
+        self
+    }
+
+    /// Read as little of the source data as possible when opening it, trusting the codec parameters in the header of the container (defaults to `false`).
+    ///
+    /// This makes opening much faster for formats which store the codec parameters in their header (such as MP4, MKV and WebM).
+    /// If the header is missing the codec, dimensions or framerate of a stream (as for MPEG-TS or raw streams), the stream information is read as usual.
+    /// [`DecoderBuilder::probe_size`] and [`DecoderBuilder::analyze_duration`] take priority over this.
+    pub fn skip_stream_analysis(mut self, skip: bool) -> Self {
+        self.options.input.skip_stream_analysis = skip;
+        self
+    }
+
+    /// Skip reading the stream information when opening the source data, using the codec parameters in the header of the container instead (defaults to `false`).
+    ///
+    /// Unlike [`DecoderBuilder::skip_stream_analysis`] no packets are read until the first frame is decoded, which makes opening many files
+    /// (e.g. for thumbnails or indexing) much faster. This works for well formed MP4, MKV and WebM files; if the header is missing the codec,
+    /// dimensions or framerate of a stream, the stream information is read anyway. [`VideoDecoder::pixel_format`] may be `None` until the first frame is decoded.
+    pub fn fast_open(mut self, fast_open: bool) -> Self {
+        self.options.input.fast_open = fast_open;
+        self
+    }
+
+    /// Prefer a specific decoder implementation, e.g. `libdav1d` rather than ffmpeg's native AV1 decoder.
+    ///
+    /// If no decoder with this name is available, or it does not support the codec of the video stream, the ffmpeg default is used instead.
+    /// See [`available_decoders`](crate::available_decoders) for the decoders ffmpeg was built with, and [`VideoDecoder::decoder_name`] for the one which was picked.
+    pub fn decoder_name(mut self, name: &str) -> Self {
+        self.options.decoder_name = Some(name.to_string());
+        self
+    }
+
+    /// Set an option of the video decoder, e.g. `skip_loop_filter` or `lowres`.
+    ///
+    /// See <https://ffmpeg.org/ffmpeg-codecs.html> for the available options.
+    /// Options which are not recognised by the decoder are ignored. Setting the same key twice replaces the earlier value.
+    pub fn codec_option(mut self, key: &str, value: &str) -> Self {
+        self.options
+            .codec_options
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Read the packets of a data stream (such as the KLV metadata of drone video) alongside the video, see [`VideoDecoder::take_data_packets`].
+    ///
+    /// This can be called multiple times to read from multiple data streams, see [`VideoDecoder::streams`] for the available streams.
+    pub fn data_stream(mut self, index: usize) -> Self {
+        self.options.data_streams.push(index);
+        self
+    }
+
+    /// Read the packets of any GoPro telemetry (GPMF) streams alongside the video, see [`VideoDecoder::take_data_packets`] (defaults to `false`).
+    ///
+    /// With the `gpmf` feature enabled, these can be parsed with [`Telemetry::parse`](crate::Telemetry::parse) to get the GPS, accelerometer, and gyroscope readings.
+    pub fn telemetry(mut self, telemetry: bool) -> Self {
+        self.options.telemetry = telemetry;
+        self
+    }
+
+    /// The maximum number of data packets to hold until they are taken with [`VideoDecoder::take_data_packets`] (defaults to `256`, the minimum is `1`).
+    ///
+    /// Data packets are read alongside the video whether or not they are taken, so once this many are held the oldest is dropped for each new packet
+    /// (see [`DecoderStats::data_packets_dropped`](crate::DecoderStats::data_packets_dropped)).
+    pub fn max_data_packets(mut self, packets: usize) -> Self {
+        self.options.max_data_packets = packets;
+        self
+    }
+
+    /// Export the motion vectors used to predict each frame, see [`Frame::motion_vectors`](crate::Frame::motion_vectors) (defaults to `false`).
+    ///
+    /// This is useful for motion analysis, as a cheap approximation of optical flow. Only some decoders (such as H.264, HEVC and MPEG-4) support this.
+    pub fn motion_vectors(mut self, motion_vectors: bool) -> Self {
+        self.options.motion_vectors = motion_vectors;
+        self
+    }
+
+    /// Decode the closed captions (EIA-608 and CEA-708) embedded in the video stream, as is common for broadcast captures, see [`Frame::captions`](crate::Frame::captions) (defaults to `false`).
+    ///
+    /// These are carried alongside the frames (for example in H.264 SEI messages) rather than in a separate stream, so they can not be read with a [`SubtitleDecoder`](crate::SubtitleDecoder).
+    pub fn closed_captions(mut self, closed_captions: bool) -> Self {
+        self.options.closed_captions = closed_captions;
+        self
+    }
+
+    /// Decode the audio alongside the video, see [`VideoDecoder::take_audio_frames`] (defaults to `false`).
+    ///
+    /// The first audio stream is decoded (at its own sample rate and channel layout), if the source data does not contain audio
+    /// (or its codec is not supported) this does nothing. The audio is trimmed to the [`DecoderBuilder::range`], and the audio played
+    /// during frames which are left out by [`DecoderBuilder::sample`] is left out too.
+    /// This is enabled by [`MediaDecoder`](crate::MediaDecoder), which returns the audio and video in presentation order.
+    pub fn audio(mut self, audio: bool) -> Self {
+        self.options.audio = audio;
+        self
+    }
+
+    /// Minimise the delay between a frame arriving from the source and it being returned by the decoder, for live sources (defaults to `false`).
+    ///
+    /// This disables buffering in the demuxer, only probes the start of the input (so the stream information may be less accurate),
+    /// tells the decoder to output frames as soon as possible, only splits work between threads within a single frame (see [`ThreadType::Slice`]),
+    /// and only buffers a single decoded frame (see [`DecoderBuilder::max_buffered_frames`]).
+    pub fn low_latency(mut self, low_latency: bool) -> Self {
+        self.options.low_latency = low_latency;
+        self
+    }
+
+    /// Fail with [`DecodeError::LimitExceeded`] if the video is wider or taller than `max` (defaults to no limit).
+    ///
+    /// This is checked from the header of the source data before anything is allocated, and again for every decoded frame
+    /// (as the dimensions can change part way through a stream), so it is safe to use with untrusted source data.
+    pub fn max_dimensions(mut self, max: Dimensions) -> Self {
+        self.options.limits.max_dimensions = Some(max);
+        self
+    }
+
+    /// Fail with [`DecodeError::LimitExceeded`] if the video has more than `max` frames (defaults to no limit).
+    ///
+    /// This is checked from the header of the source data if it gives the number of frames, and otherwise once a frame with an index above `max` is decoded.
+    pub fn max_frame_count(mut self, max: usize) -> Self {
+        self.options.limits.max_frame_count = Some(max);
+        self
+    }
+
+    /// Fail with [`DecodeError::LimitExceeded`] if the frames held by the decoder would use more than `max` bytes (defaults to no limit).
+    ///
+    /// This counts the buffered and cached frames (see [`DecoderBuilder::max_buffered_frames`], [`DecoderBuilder::backward_cache`] and [`DecoderBuilder::frame_cache`])
+    /// at the size of the frames being decoded, and is checked when the decoder is opened and for every decoded frame.
+    /// Memory used internally by ffmpeg (such as the reference frames of the codec) is not counted.
+    pub fn max_memory_bytes(mut self, max: u64) -> Self {
+        self.options.limits.max_memory_bytes = Some(max);
+        self
+    }
+
+    /// What the decoder should do when the source data is damaged (defaults to [`ErrorPolicy::Fail`]).
+    ///
+    /// Skipped packets and frames are counted in [`VideoDecoder::stats`]. Skipped frames still take up a frame index, so the indices of later frames are not changed.
+    pub fn on_error(mut self, policy: ErrorPolicy) -> Self {
+        self.options.error_policy = policy;
+        self
+    }
+
+    /// Open the decoder
+    pub fn build(self) -> Result<VideoDecoder, DecodeError> {
+        if self.options.range.is_some_and(|(start, end)| start > end) {
+            return Err(DecodeError::InvalidRange);
+        }
+        VideoDecoder::open(self.source, self.options)
+    }
+}
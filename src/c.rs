@@ -1,6 +1,8 @@
 //! Internal helpers to interface with the c ffmpeg code
 
+use std::collections::HashMap;
 use std::path::Path;
+use std::time::Duration;
 use std::{cmp, ffi, ptr};
 
 #[repr(C)]
@@ -20,9 +22,111 @@ pub unsafe extern "C" fn read_stream(ptr: *mut ffi::c_void, buf: *mut u8, size:
     size as i32
 }
 
+/// Convert a path into a nul terminated string for ffmpeg, `None` if ffmpeg can not represent the path.
+///
+/// On unix ffmpeg takes the raw bytes of the path, elsewhere the path must be valid unicode.
 pub fn path_to_raw(path: &Path) -> Option<Vec<u8>> {
-    let mut buf = Vec::new();
-    buf.extend(path.to_str()?.as_bytes());
+    #[cfg(unix)]
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str());
+    #[cfg(not(unix))]
+    let bytes = path.to_str()?.as_bytes();
+
+    if bytes.contains(&0) {
+        return None;
+    }
+
+    let mut buf = Vec::with_capacity(bytes.len() + 1);
+    buf.extend(bytes);
     buf.push(0);
     Some(buf)
 }
+
+/// Copy a (nullable) c string into an owned string
+pub unsafe fn str_from_ptr(ptr: *const ffi::c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+
+    Some(ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned())
+}
+
+/// Whether a pixel format has an alpha channel
+pub fn has_alpha(format: ffmpeg::AVPixelFormat) -> bool {
+    unsafe {
+        let descriptor = ffmpeg::av_pix_fmt_desc_get(format);
+        !descriptor.is_null() && (*descriptor).flags & ffmpeg::AV_PIX_FMT_FLAG_ALPHA as u64 != 0
+    }
+}
+
+/// Get the value of a key from a (nullable) ffmpeg dictionary
+pub unsafe fn dict_get(dict: *const ffmpeg::AVDictionary, key: &ffi::CStr) -> Option<String> {
+    let entry = ffmpeg::av_dict_get(dict, key.as_ptr(), ptr::null(), 0);
+    if entry.is_null() {
+        return None;
+    }
+
+    str_from_ptr((*entry).value)
+}
+
+/// Copy every entry of a (nullable) ffmpeg dictionary
+pub unsafe fn dict_to_map(dict: *const ffmpeg::AVDictionary) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    let mut entry = ffmpeg::av_dict_iterate(dict, ptr::null());
+    while !entry.is_null() {
+        if let (Some(key), Some(value)) = (str_from_ptr((*entry).key), str_from_ptr((*entry).value))
+        {
+            map.insert(key, value);
+        }
+        entry = ffmpeg::av_dict_iterate(dict, entry);
+    }
+
+    map
+}
+
+/// An owned ffmpeg dictionary, used to pass options to ffmpeg
+pub struct Dictionary(pub *mut ffmpeg::AVDictionary);
+
+impl Dictionary {
+    /// Create a dictionary from key value pairs, `None` if a key or value contains a nul byte
+    pub fn new(entries: &[(String, String)]) -> Option<Self> {
+        let mut dict = Dictionary(ptr::null_mut());
+        for (key, value) in entries {
+            let key = ffi::CString::new(key.as_str()).ok()?;
+            let value = ffi::CString::new(value.as_str()).ok()?;
+            unsafe {
+                ffmpeg::av_dict_set(&mut dict.0, key.as_ptr(), value.as_ptr(), 0);
+            }
+        }
+
+        Some(dict)
+    }
+}
+
+impl Drop for Dictionary {
+    fn drop(&mut self) {
+        unsafe {
+            ffmpeg::av_dict_free(&mut self.0);
+        }
+    }
+}
+
+/// Convert a timestamp in the given time base to a duration, `None` if the timestamp is unset or negative
+pub fn ts_to_duration(ts: i64, time_base: ffmpeg::AVRational) -> Option<Duration> {
+    if ts == ffmpeg::AV_NOPTS_VALUE || ts < 0 || time_base.den <= 0 {
+        return None;
+    }
+
+    let nanos = ts as i128 * time_base.num as i128 * 1_000_000_000 / time_base.den as i128;
+    u64::try_from(nanos).ok().map(Duration::from_nanos)
+}
+
+/// Convert a duration to a timestamp in the given time base (rounding down)
+pub fn duration_to_ts(duration: Duration, time_base: ffmpeg::AVRational) -> i64 {
+    if time_base.num <= 0 {
+        return 0;
+    }
+
+    let ts = duration.as_nanos() as i128 * time_base.den as i128
+        / (time_base.num as i128 * 1_000_000_000);
+    ts.min(i64::MAX as i128) as i64
+}
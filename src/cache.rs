@@ -0,0 +1,53 @@
+use super::Frame;
+use std::collections::VecDeque;
+
+/// A least-recently-used cache of decoded frames, keyed by frame index
+///
+/// This is only intended to hold a small number of frames, so lookups are a linear search.
+pub(crate) struct FrameCache {
+    capacity: usize,
+    /// Least recently used first
+    frames: VecDeque<Frame>,
+}
+
+impl FrameCache {
+    pub fn new(capacity: usize) -> Self {
+        FrameCache {
+            capacity,
+            frames: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// The maximum number of frames held
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get a copy of a cached frame, marking it as the most recently used
+    pub fn get(&mut self, index: usize) -> Option<Frame> {
+        let position = self.frames.iter().position(|frame| frame.index == index)?;
+        let frame = self.frames.remove(position)?;
+        let copy = frame.clone();
+        self.frames.push_back(frame);
+        Some(copy)
+    }
+
+    /// Add a copy of a frame to the cache, evicting the least recently used frame if the cache is full
+    pub fn insert(&mut self, frame: &Frame) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if let Some(position) = self
+            .frames
+            .iter()
+            .position(|cached| cached.index == frame.index)
+        {
+            self.frames.remove(position);
+        } else if self.frames.len() >= self.capacity {
+            self.frames.pop_front();
+        }
+
+        self.frames.push_back(frame.clone());
+    }
+}
@@ -0,0 +1,105 @@
+use super::{DecodeError, Frame, SubtitleCue};
+use crate::subtitle::read_cue;
+use std::{mem, ptr};
+
+/// Decodes the closed captions (EIA-608 and CEA-708) carried in the side data of video frames, see [`DecoderBuilder::closed_captions`](crate::DecoderBuilder::closed_captions)
+pub(crate) struct CaptionDecoder {
+    codec_ctx: *mut ffmpeg::AVCodecContext,
+    packet: *mut ffmpeg::AVPacket,
+    time_base: ffmpeg::AVRational,
+}
+
+impl CaptionDecoder {
+    /// Create a caption decoder for frames with timestamps in `time_base`
+    pub fn new(time_base: ffmpeg::AVRational) -> Result<Self, DecodeError> {
+        unsafe {
+            let codec = ffmpeg::avcodec_find_decoder(ffmpeg::AVCodecID::AV_CODEC_ID_EIA_608);
+            if codec.is_null() {
+                return Err(DecodeError::UnsupportedCodec);
+            }
+
+            let mut decoder = CaptionDecoder {
+                codec_ctx: ffmpeg::avcodec_alloc_context3(codec),
+                packet: ffmpeg::av_packet_alloc(),
+                time_base,
+            };
+            if decoder.codec_ctx.is_null() || decoder.packet.is_null() {
+                return Err(DecodeError::AllocationFailed);
+            }
+
+            // Required for ffmpeg to convert the packet timestamps of the cues
+            (*decoder.codec_ctx).pkt_timebase = time_base;
+            if ffmpeg::avcodec_open2(decoder.codec_ctx, codec, ptr::null_mut()) < 0 {
+                return Err(DecodeError::UnsupportedCodec);
+            }
+
+            Ok(decoder)
+        }
+    }
+
+    /// Decode the captions attached to a frame, returning any cues which are now complete.
+    ///
+    /// Frames must be given in presentation order, `pts` is the timestamp of the frame relative to the start of the video.
+    pub unsafe fn decode(&mut self, frame: *const ffmpeg::AVFrame, pts: i64) -> Vec<SubtitleCue> {
+        let side_data = ffmpeg::av_frame_get_side_data(
+            frame,
+            ffmpeg::AVFrameSideDataType::AV_FRAME_DATA_A53_CC,
+        );
+        if side_data.is_null() || (*side_data).size == 0 {
+            return Vec::new();
+        }
+
+        if ffmpeg::av_new_packet(self.packet, (*side_data).size as i32) < 0 {
+            return Vec::new();
+        }
+        ptr::copy_nonoverlapping((*side_data).data, (*self.packet).data, (*side_data).size);
+        (*self.packet).pts = pts;
+        (*self.packet).dts = pts;
+
+        let mut cues = Vec::new();
+        let mut subtitle: ffmpeg::AVSubtitle = mem::zeroed();
+        let mut got_subtitle = 0;
+        let result = ffmpeg::avcodec_decode_subtitle2(
+            self.codec_ctx,
+            &mut subtitle,
+            &mut got_subtitle,
+            self.packet,
+        );
+        if got_subtitle != 0 {
+            if result >= 0 {
+                cues.push(read_cue(&subtitle, self.packet, self.time_base));
+            }
+            ffmpeg::avsubtitle_free(&mut subtitle);
+        }
+        ffmpeg::av_packet_unref(self.packet);
+
+        // The caption decoder also outputs a cue to clear the screen, which has nothing to show
+        cues.retain(|cue| !cue.content().is_empty());
+        cues
+    }
+
+    /// Discard any partially received captions, after seeking
+    pub fn flush(&mut self) {
+        unsafe { ffmpeg::avcodec_flush_buffers(self.codec_ctx) }
+    }
+}
+
+impl Drop for CaptionDecoder {
+    fn drop(&mut self) {
+        unsafe {
+            ffmpeg::av_packet_free(&mut self.packet);
+            ffmpeg::avcodec_free_context(&mut self.codec_ctx);
+        }
+    }
+}
+
+impl Frame {
+    /// The closed captions which finished with this frame, this is empty unless [`DecoderBuilder::closed_captions`](crate::DecoderBuilder::closed_captions) is enabled
+    ///
+    /// Captions are sent a few characters at a time alongside the frames, so a cue is only returned with the frame which completes it,
+    /// and its [`SubtitleCue::start`] is when it should be shown.
+    #[inline]
+    pub fn captions(&self) -> &[SubtitleCue] {
+        &self.captions
+    }
+}
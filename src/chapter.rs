@@ -0,0 +1,60 @@
+use crate::c::{dict_get, ts_to_duration};
+use std::time::Duration;
+
+/// A chapter of the source data, see [`VideoDecoder::chapters`](crate::VideoDecoder::chapters)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chapter {
+    start: Duration,
+    end: Duration,
+    title: Option<String>,
+}
+
+impl Chapter {
+    /// When the chapter starts, this can be passed to [`VideoDecoder::get_frame_at`](crate::VideoDecoder::get_frame_at) to seek to the chapter
+    #[inline]
+    pub fn start(&self) -> Duration {
+        self.start
+    }
+
+    /// When the chapter ends
+    #[inline]
+    pub fn end(&self) -> Duration {
+        self.end
+    }
+
+    /// The title of the chapter, if specified by the source data
+    #[inline]
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Read a chapter
+    ///
+    /// # Safety
+    ///
+    /// `chapter` must point to a valid chapter of an opened input context.
+    pub(crate) unsafe fn from_raw(chapter: *const ffmpeg::AVChapter) -> Self {
+        let time_base = (*chapter).time_base;
+        let start = ts_to_duration((*chapter).start, time_base).unwrap_or_default();
+
+        Chapter {
+            start,
+            end: ts_to_duration((*chapter).end, time_base)
+                .unwrap_or_default()
+                .max(start),
+            title: dict_get((*chapter).metadata, c"title"),
+        }
+    }
+}
+
+/// Read every chapter of an input context
+///
+/// # Safety
+///
+/// `input_ctx` must point to a valid opened input context.
+pub(crate) unsafe fn read_chapters(input_ctx: *const ffmpeg::AVFormatContext) -> Vec<Chapter> {
+    (0..(*input_ctx).nb_chapters as usize)
+        .map(|i| Chapter::from_raw(*(*input_ctx).chapters.add(i)))
+        .collect()
+}
@@ -0,0 +1,98 @@
+//! The decoders ffmpeg was built with
+
+use crate::c::str_from_ptr;
+use std::{ffi, iter, ptr};
+
+/// A video decoder available to ffmpeg, see [`available_decoders`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecoderInfo {
+    name: String,
+    description: String,
+    codec: String,
+    hardware: bool,
+}
+
+impl DecoderInfo {
+    /// The name of the decoder, this is what should be passed to [`DecoderBuilder::decoder_name`](crate::DecoderBuilder::decoder_name)
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// A human readable description of the decoder
+    #[inline]
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// The (short) name of the codec the decoder supports, e.g. `av1` for `libdav1d`
+    #[inline]
+    pub fn codec(&self) -> &str {
+        &self.codec
+    }
+
+    /// Whether the decoder runs on dedicated hardware (e.g. `h264_cuvid`)
+    #[inline]
+    pub fn is_hardware(&self) -> bool {
+        self.hardware
+    }
+
+    /// Read the description of a decoder
+    ///
+    /// # Safety
+    ///
+    /// `codec` must point to a valid codec.
+    unsafe fn from_raw(codec: *const ffmpeg::AVCodec) -> Self {
+        DecoderInfo {
+            name: str_from_ptr((*codec).name).unwrap_or_default(),
+            description: str_from_ptr((*codec).long_name).unwrap_or_default(),
+            codec: str_from_ptr(ffmpeg::avcodec_get_name((*codec).id)).unwrap_or_default(),
+            hardware: (*codec).capabilities & ffmpeg::AV_CODEC_CAP_HARDWARE as ffi::c_int != 0,
+        }
+    }
+}
+
+/// The video decoders ffmpeg was built with, in the order ffmpeg prefers them
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::available_decoders;
+///
+/// let av1 = available_decoders().into_iter().filter(|decoder| decoder.codec() == "av1");
+/// for decoder in av1 {
+///     println!("{}: {}", decoder.name(), decoder.description());
+/// }
+/// # }
+/// ```
+pub fn available_decoders() -> Vec<DecoderInfo> {
+    decoders().collect()
+}
+
+/// Iterate over the video decoders ffmpeg was built with
+pub(crate) fn decoders() -> impl Iterator<Item = DecoderInfo> {
+    let mut opaque = ptr::null_mut();
+    iter::from_fn(move || unsafe {
+        loop {
+            let codec = ffmpeg::av_codec_iterate(&mut opaque);
+            if codec.is_null() {
+                return None;
+            }
+            if ffmpeg::av_codec_is_decoder(codec) != 0
+                && (*codec).type_ == ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO
+            {
+                return Some(DecoderInfo::from_raw(codec));
+            }
+        }
+    })
+}
+
+/// Find a decoder by name, `None` if there is no decoder with this name which supports the codec
+pub(crate) unsafe fn find_decoder_by_name(
+    name: &str,
+    id: ffmpeg::AVCodecID,
+) -> Option<*const ffmpeg::AVCodec> {
+    let name = ffi::CString::new(name).ok()?;
+    let codec = ffmpeg::avcodec_find_decoder_by_name(name.as_ptr());
+    (!codec.is_null() && (*codec).id == id).then_some(codec)
+}
@@ -0,0 +1,183 @@
+use super::{DecodeError, DecoderBuilder, Frame, PixelFormat, VideoSource};
+
+/// The size of the windows SSIM is measured over
+const SSIM_WINDOW: usize = 8;
+/// How far apart the SSIM windows are (so they overlap)
+const SSIM_STEP: usize = 4;
+const SSIM_C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const SSIM_C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+/// The quality of a single frame, see [`compare`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameQuality {
+    index: usize,
+    psnr: f64,
+    ssim: f64,
+}
+
+impl FrameQuality {
+    /// The index of the frame
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The peak signal to noise ratio of the frame in decibels, this is infinite if the frames are identical
+    #[inline]
+    pub fn psnr(&self) -> f64 {
+        self.psnr
+    }
+
+    /// The structural similarity of the frame, between `-1.0` and `1.0` (where `1.0` means the frames are identical)
+    #[inline]
+    pub fn ssim(&self) -> f64 {
+        self.ssim
+    }
+}
+
+/// The result of comparing two videos, see [`compare`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Comparison {
+    frames: Vec<FrameQuality>,
+    psnr: f64,
+    ssim: f64,
+}
+
+impl Comparison {
+    /// The quality of each frame which was compared
+    #[inline]
+    pub fn frames(&self) -> &[FrameQuality] {
+        &self.frames
+    }
+
+    /// The peak signal to noise ratio of the whole video in decibels (worked out from the average error of every frame), this is infinite if the videos are identical
+    #[inline]
+    pub fn psnr(&self) -> f64 {
+        self.psnr
+    }
+
+    /// The average structural similarity of every frame
+    #[inline]
+    pub fn ssim(&self) -> f64 {
+        self.ssim
+    }
+}
+
+/// Measure how closely one video matches another (e.g. an encoded copy against the original), using PSNR and SSIM of the luma of each frame.
+///
+/// Both videos are decoded in lockstep, and frames are compared until either video ends.
+/// If the videos have different dimensions, `distorted` is scaled to the dimensions of `reference`.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::compare;
+///
+/// # let file = "test.mp4";
+/// let comparison = compare(file, file).unwrap();
+/// assert_eq!(comparison.ssim(), 1.0);
+/// # }
+/// ```
+pub fn compare<A, B>(reference: A, distorted: B) -> Result<Comparison, DecodeError>
+where
+    A: Into<VideoSource>,
+    B: Into<VideoSource>,
+{
+    let mut reference = DecoderBuilder::new(reference)
+        .output_format(PixelFormat::Gray8)
+        .build()?;
+    let dimensions = reference.dimensions();
+    // Scaling to the same dimensions leaves the frames untouched
+    let mut distorted = DecoderBuilder::new(distorted)
+        .output_format(PixelFormat::Gray8)
+        .filter(&format!("scale={}:{}", dimensions.width, dimensions.height))
+        .build()?;
+
+    let mut frames = Vec::new();
+    let mut total_error = 0.0;
+    while let (Some(a), Some(b)) = (reference.next_frame()?, distorted.next_frame()?) {
+        let error = mean_squared_error(&a, &b);
+        total_error += error;
+        frames.push(FrameQuality {
+            index: a.index(),
+            psnr: psnr(error),
+            ssim: ssim(&a, &b),
+        });
+    }
+
+    let count = frames.len().max(1) as f64;
+    Ok(Comparison {
+        psnr: psnr(total_error / count),
+        ssim: frames.iter().map(|frame| frame.ssim).sum::<f64>() / count,
+        frames,
+    })
+}
+
+/// The peak signal to noise ratio for a mean squared error of 8 bit values
+fn psnr(error: f64) -> f64 {
+    if error == 0.0 {
+        f64::INFINITY
+    } else {
+        10.0 * (255.0 * 255.0 / error).log10()
+    }
+}
+
+/// The mean squared error between two grayscale frames
+fn mean_squared_error(a: &Frame, b: &Frame) -> f64 {
+    let (a, b) = (a.planes()[0], b.planes()[0]);
+    let dimensions = a.dimensions();
+
+    let total: u64 = (0..dimensions.height as usize)
+        .map(|y| {
+            a.row(y)
+                .iter()
+                .zip(b.row(y))
+                .map(|(&a, &b)| (a as i64 - b as i64).pow(2) as u64)
+                .sum::<u64>()
+        })
+        .sum();
+    total as f64 / (dimensions.width as f64 * dimensions.height as f64).max(1.0)
+}
+
+/// The average structural similarity between two grayscale frames, over overlapping windows
+fn ssim(a: &Frame, b: &Frame) -> f64 {
+    let (a, b) = (a.planes()[0], b.planes()[0]);
+    let dimensions = a.dimensions();
+    let (width, height) = (dimensions.width as usize, dimensions.height as usize);
+    let window = SSIM_WINDOW.min(width).min(height).max(1);
+
+    let mut total = 0.0;
+    let mut windows = 0;
+    for top in (0..=height - window).step_by(SSIM_STEP) {
+        for left in (0..=width - window).step_by(SSIM_STEP) {
+            let (mut sum_a, mut sum_b, mut sum_aa, mut sum_bb, mut sum_ab) =
+                (0.0, 0.0, 0.0, 0.0, 0.0);
+            for y in top..top + window {
+                let rows = a.row(y)[left..left + window]
+                    .iter()
+                    .zip(&b.row(y)[left..left + window]);
+                for (&a, &b) in rows {
+                    let (a, b) = (a as f64, b as f64);
+                    sum_a += a;
+                    sum_b += b;
+                    sum_aa += a * a;
+                    sum_bb += b * b;
+                    sum_ab += a * b;
+                }
+            }
+
+            let n = (window * window) as f64;
+            let (mean_a, mean_b) = (sum_a / n, sum_b / n);
+            let variance_a = sum_aa / n - mean_a * mean_a;
+            let variance_b = sum_bb / n - mean_b * mean_b;
+            let covariance = sum_ab / n - mean_a * mean_b;
+            total += ((2.0 * mean_a * mean_b + SSIM_C1) * (2.0 * covariance + SSIM_C2))
+                / ((mean_a * mean_a + mean_b * mean_b + SSIM_C1)
+                    * (variance_a + variance_b + SSIM_C2));
+            windows += 1;
+        }
+    }
+
+    total / windows.max(1) as f64
+}
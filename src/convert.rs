@@ -0,0 +1,221 @@
+//! Conversion of decoded frames to the output pixel format
+
+use super::{DecodeError, Dimensions, PixelFormat};
+use crate::frame::pack_rows;
+use std::{ffi, mem, ptr};
+
+// ffmpeg buffer alignment
+const BUFFER_ALIGNMENT: ffi::c_int = 32; // 256 bits
+
+/// Converts decoded frames (of any pixel format and size) to a fixed pixel format and size
+pub(crate) struct Converter {
+    dimensions: Dimensions,
+    format: PixelFormat,
+    /// Whether to strip the padding from the end of each row
+    packed_rows: bool,
+    /// The `SWS_*` flags the converter context is created with
+    flags: ffi::c_int,
+    texture_data: Vec<u8>,
+    sws_context: *mut ffmpeg::SwsContext,
+    /// The color space and range the converter context was configured for, `None` if it has not been configured yet
+    color: Option<(ffmpeg::AVColorSpace, ffmpeg::AVColorRange)>,
+    rgb_frame: *mut ffmpeg::AVFrame,
+}
+
+// SAFETY: See `VideoDecoder`, the same reasoning applies here
+unsafe impl Send for Converter {}
+
+impl Converter {
+    /// A function which creates new converters with the same output as this one, e.g. to convert frames on several threads at once
+    #[cfg(feature = "rayon")]
+    pub fn factory(&self) -> impl Fn() -> Result<Converter, DecodeError> + Send + Sync {
+        let (dimensions, format, packed_rows, flags) =
+            (self.dimensions, self.format, self.packed_rows, self.flags);
+        move || Converter::new(dimensions, format, packed_rows, flags)
+    }
+
+    /// Create a converter which outputs frames with the given dimensions and format, using the given `SWS_*` flags
+    pub fn new(
+        dimensions: Dimensions,
+        format: PixelFormat,
+        packed_rows: bool,
+        flags: ffi::c_int,
+    ) -> Result<Self, DecodeError> {
+        unsafe {
+            let mut converter = Converter {
+                dimensions,
+                format,
+                packed_rows,
+                flags,
+                texture_data: Vec::new(),
+                sws_context: ptr::null_mut(),
+                color: None,
+                rgb_frame: ffmpeg::av_frame_alloc(),
+            };
+            if converter.rgb_frame.is_null() {
+                return Err(DecodeError::AllocationFailed);
+            }
+
+            let buffer_size = ffmpeg::av_image_get_buffer_size(
+                format.as_raw(),
+                dimensions.width as i32,
+                dimensions.height as i32,
+                BUFFER_ALIGNMENT,
+            );
+            if buffer_size <= 0 {
+                return Err(DecodeError::UnableToReadFrameBuffer);
+            }
+
+            converter.texture_data = vec![0; buffer_size as usize];
+
+            if ffmpeg::av_image_fill_arrays(
+                (*converter.rgb_frame).data.as_mut_ptr(),
+                (*converter.rgb_frame).linesize.as_mut_ptr(),
+                converter.texture_data.as_mut_ptr(),
+                format.as_raw(),
+                dimensions.width as i32,
+                dimensions.height as i32,
+                BUFFER_ALIGNMENT,
+            ) <= 0
+            {
+                return Err(DecodeError::UnableToReadFrameBuffer);
+            }
+
+            Ok(converter)
+        }
+    }
+
+    /// The dimensions of the converted frames
+    #[inline]
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    /// The pixel format of the converted frames
+    #[inline]
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Prepare the converter context for frames of the given size and format, returns `false` if the format is not supported.
+    ///
+    /// The context is only recreated if the source changes, so this is cheap to call for every frame.
+    pub fn prepare(&mut self, width: i32, height: i32, format: ffmpeg::AVPixelFormat) -> bool {
+        let previous = self.sws_context;
+        unsafe {
+            self.sws_context = ffmpeg::sws_getCachedContext(
+                self.sws_context,
+                width,                         // Source
+                height,                        // Source
+                format,                        // Source
+                self.dimensions.width as i32,  // Destination
+                self.dimensions.height as i32, // Destination
+                self.format.as_raw(),          // Destination
+                self.flags,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+        }
+
+        if self.sws_context != previous {
+            // A new context uses the default color space and range
+            self.color = None;
+        }
+
+        !self.sws_context.is_null()
+    }
+
+    /// Configure the converter context for the color space and range of the source frames
+    ///
+    /// # Safety
+    ///
+    /// The converter must have been prepared (see [`Converter::prepare`]).
+    unsafe fn set_color(&mut self, colorspace: ffmpeg::AVColorSpace, range: ffmpeg::AVColorRange) {
+        if self.color == Some((colorspace, range)) {
+            return;
+        }
+        self.color = Some((colorspace, range));
+
+        let (mut inv_table, mut table) = (ptr::null_mut(), ptr::null_mut());
+        let (mut src_range, mut dst_range) = (0, 0);
+        let (mut brightness, mut contrast, mut saturation) = (0, 0, 0);
+        if ffmpeg::sws_getColorspaceDetails(
+            self.sws_context,
+            &mut inv_table,
+            &mut src_range,
+            &mut table,
+            &mut dst_range,
+            &mut brightness,
+            &mut contrast,
+            &mut saturation,
+        ) < 0
+        {
+            // Only supported when converting to RGB
+            return;
+        }
+
+        // The `SWS_CS_*` constants match the ffmpeg color spaces, unknown values use the default (BT.601)
+        ffmpeg::sws_setColorspaceDetails(
+            self.sws_context,
+            ffmpeg::sws_getCoefficients(colorspace as ffi::c_int),
+            (range == ffmpeg::AVColorRange::AVCOL_RANGE_JPEG) as ffi::c_int,
+            table,
+            dst_range,
+            brightness,
+            contrast,
+            saturation,
+        );
+    }
+
+    /// Convert a decoded frame to the output format, returning the frame data and the stride of each row
+    ///
+    /// # Safety
+    ///
+    /// `frame` must point to a valid decoded video frame.
+    pub unsafe fn convert(
+        &mut self,
+        frame: *const ffmpeg::AVFrame,
+    ) -> Result<(Vec<u8>, usize), DecodeError> {
+        // The frame format always comes from ffmpeg, so it is a valid pixel format
+        let format = mem::transmute::<ffi::c_int, ffmpeg::AVPixelFormat>((*frame).format);
+        if !self.prepare((*frame).width, (*frame).height, format) {
+            return Err(DecodeError::ConverterInitFailed);
+        }
+        self.set_color((*frame).colorspace, (*frame).color_range);
+
+        ffmpeg::sws_scale(
+            self.sws_context,
+            (*frame).data.as_ptr() as *const *const _,
+            (*frame).linesize.as_ptr() as *mut _,
+            0,
+            (*frame).height,
+            (*self.rgb_frame).data.as_ptr(),
+            (*self.rgb_frame).linesize.as_ptr() as *mut _,
+        );
+
+        let row = self.dimensions.width as usize * self.format.bytes_per_pixel();
+        let linesize = (*self.rgb_frame).linesize[0] as usize;
+        Ok(match self.packed_rows && linesize != row {
+            true => (
+                pack_rows(
+                    &self.texture_data,
+                    linesize,
+                    row,
+                    self.dimensions.height as usize,
+                ),
+                row,
+            ),
+            false => (self.texture_data.clone(), linesize),
+        })
+    }
+}
+
+impl Drop for Converter {
+    fn drop(&mut self) {
+        unsafe {
+            ffmpeg::sws_freeContext(self.sws_context);
+            ffmpeg::av_frame_free(&mut self.rgb_frame);
+        }
+    }
+}
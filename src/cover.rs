@@ -0,0 +1,26 @@
+use super::{DecodeError, VideoSource};
+use crate::input::{Input, InputOptions};
+
+/// Read the encoded data of the cover art embedded in the source data (usually a JPEG or PNG image), `None` if there is no cover art.
+///
+/// Unlike [`VideoDecoder::cover_art`](crate::VideoDecoder::cover_art), this also works for audio files (such as an MP3 or FLAC file with album art),
+/// which can not be opened with a [`VideoDecoder`](crate::VideoDecoder) as they have no video to decode.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::cover_art;
+///
+/// let file = "song.flac";
+/// # let file = "test.mp4";
+/// if let Some(data) = cover_art(file).unwrap() {
+///     std::fs::write("cover.jpg", data).unwrap();
+/// }
+/// # }
+/// ```
+pub fn cover_art<S>(source: S) -> Result<Option<Vec<u8>>, DecodeError>
+where
+    S: Into<VideoSource>,
+{
+    let input = Input::open(source.into(), &InputOptions::default())?;
+    Ok(input.cover_art())
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+/// A packet of timed data (such as KLV metadata or telemetry) read alongside the video, see [`DecoderBuilder::data_stream`](crate::DecoderBuilder::data_stream)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataPacket {
+    pub(crate) stream_index: usize,
+    pub(crate) pts: Option<Duration>,
+    pub(crate) bytes: Vec<u8>,
+}
+
+impl DataPacket {
+    /// The index of the stream the packet was read from
+    #[inline]
+    pub fn stream_index(&self) -> usize {
+        self.stream_index
+    }
+
+    /// The presentation timestamp of the packet, relative to the start of the video (so it can be matched with [`Frame::timestamp`](crate::Frame::timestamp))
+    ///
+    /// This is `None` if the source data does not contain timing information for the packet.
+    #[inline]
+    pub fn pts(&self) -> Option<Duration> {
+        self.pts
+    }
+
+    /// Get a reference to the raw packet data
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Convert this packet into its raw data
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
@@ -1,60 +1,60 @@
-use super::{DecodeError, Dimensions, VideoSource};
-use crate::c::{path_to_raw, read_stream, Stream};
+use super::{
+    AudioFrame, DataPacket, DecodeError, DecoderBuilder, DecoderHandle, DecoderPosition,
+    Dimensions, Frame, FrameInfo, PixelFormat, SubtitleCue, Timecode, VideoSource,
+};
+use crate::audio::AudioStream;
+use crate::builder::{DecoderOptions, ErrorPolicy, Limits, LoopMode, Sampling};
+use crate::c::{
+    dict_get, dict_to_map, duration_to_ts, has_alpha, str_from_ptr, ts_to_duration, Dictionary,
+};
+use crate::cache::FrameCache;
+use crate::caption::CaptionDecoder;
+use crate::chapter::{read_chapters, Chapter};
+use crate::codec::find_decoder_by_name;
+use crate::convert::Converter;
+use crate::filter::FilterGraph;
+use crate::frame::FrameData;
+use crate::input::Input;
+use crate::motion::read_motion_vectors;
+use crate::overlay::Overlay;
+use crate::program::{read_programs, Program};
+use crate::stats::DecoderStats;
+use crate::stream::{read_streams, StreamInfo};
+use crate::timecode::read_timecode;
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 use std::{ffi, mem, ptr};
 
-/// ffmpeg buffer size
-const BUFFER_SIZE: usize = 8192;
+/// Skips of fewer frames than this decode every frame, as seeking would not save anything
+const FAST_SKIP_FRAMES: usize = 30;
 
-// ffmpeg buffer alignment (this is unrelated to the previous constant)
-const BUFFER_ALIGNMENT: std::ffi::c_int = 32; // 256 bits
-
-/// A single frame from a decoded video
-pub struct Frame {
+/// A decoded frame which has not yet been converted to the output format
+struct RawFrame {
+    frame: *mut ffmpeg::AVFrame,
     index: usize,
-    data: Vec<u8>,
-    dimensions: Dimensions,
+    /// The closed captions completed by this frame
+    captions: Vec<SubtitleCue>,
 }
 
-impl Frame {
-    /// The frame number in the source video (starts at 1)
-    #[inline]
-    pub fn index(&self) -> usize {
-        self.index
-    }
-
-    /// Get the dimensions of the frame data
-    #[inline]
-    pub fn dimensions(&self) -> Dimensions {
-        self.dimensions
-    }
-
-    /// Get a reference to the raw frame data
-    #[inline]
-    pub fn data(&self) -> &[u8] {
-        &self.data
-    }
-
-    /// Convert this frame into its raw data
-    #[inline]
-    pub fn into_data(self) -> Vec<u8> {
-        self.data
-    }
+// SAFETY: The frame is only ever accessed by the owner of the `RawFrame`
+unsafe impl Send for RawFrame {}
 
-    /// The raw data along with its dimensions
-    #[inline]
-    pub fn into_raw(self) -> (Vec<u8>, Dimensions) {
-        (self.data, self.dimensions)
-    }
+/// The state of a backward pass through the video, which decodes one group of pictures at a time
+struct Reverse {
+    /// The decoded frames of the current group of pictures which have not been returned yet, in order (so the next frame is at the end)
+    frames: Vec<RawFrame>,
+    /// The index of the first frame of the current group, the next group is the frames before this
+    end: usize,
+    /// The index of the most recently returned frame
+    last: usize,
+}
 
-    /// Convert this frame into a [image::DynamicImage](https://docs.rs/image/latest/image/enum.DynamicImage.html)
-    #[cfg(feature = "image")]
-    pub fn into_image(self) -> image::DynamicImage {
-        image::DynamicImage::ImageRgb8(
-            image::ImageBuffer::from_raw(self.dimensions.width, self.dimensions.height, self.data)
-                .unwrap(), // unwrap is safe as both data and dimensions are readonly to the caller
-        )
+impl Drop for RawFrame {
+    fn drop(&mut self) {
+        unsafe {
+            ffmpeg::av_frame_free(&mut self.frame);
+        }
     }
 }
 
@@ -94,33 +94,107 @@ impl Frame {
 /// # }
 /// ```
 pub struct VideoDecoder {
-    /// The framerate of the decoded video
+    /// The framerate of the decoded video, 0 if it is not known
     framerate: f32,
+    /// The average framerate of the decoded video, if known
+    avg_framerate: Option<f32>,
+    /// Whether the decoded video has a variable framerate
+    vfr: bool,
     /// The dimensions of the the decoded video
     dimensions: Dimensions,
+    /// The shape of each pixel of the decoded video, if known
+    sample_aspect_ratio: Option<ffmpeg::AVRational>,
     /// Internal frame buffer, as ffmpeg returns frames in chunks
-    buffer: VecDeque<Frame>,
-    /// Whether we should loop the frames when we reach the end of the input data
-    should_loop: bool,
+    buffer: VecDeque<RawFrame>,
+    /// The maximum number of frames to keep in `buffer`, any more are left in the decoder until there is room
+    buffer_capacity: usize,
+    /// How many more times we should loop the frames when we reach the end of the input data, `None` loops forever
+    loops_remaining: Option<u32>,
+    /// Whether we have reached the end of the input data (or range) and are flushing the remaining frames out of the decoder
+    draining: bool,
+    /// Whether to play backward after reaching the end, see [`LoopMode::PingPong`]
+    ping_pong: bool,
+    /// Set while frames are being returned in reverse order
+    reverse: Option<Reverse>,
     /// The next frame index
     index: usize,
-
-    /// The source data, we must store it so the pointer passed to ffmpeg is not dropped
-    _source: VideoSource,
+    /// Whether the next frame index must be worked out from the timestamp of the next frame (after seeking)
+    index_from_pts: bool,
+    /// The frame index we are seeking to, all frames before this are discarded
+    seek_target: Option<usize>,
+    /// Set during a fast skip, the decoder leaves out frames which are not referenced by other frames if they are before this stream timestamp
+    discard_before: Option<i64>,
+    /// The frames the decoder was opened to leave out (e.g. from a `skip_frame` codec option), restored once a fast skip is done
+    skip_frame: ffmpeg::AVDiscard,
+    /// The stream timestamp and index of the frame a restored position resumes from, all frames before the timestamp are discarded
+    restore_target: Option<(i64, usize)>,
+    /// The index and stream timestamp of the frame after the most recently returned frame, if known
+    next_pts: Option<(usize, i64)>,
+    /// Frames decoded before the most recent frame returned by [`VideoDecoder::prev_frame`], oldest first
+    backward: VecDeque<RawFrame>,
+    /// The maximum number of frames to keep in `backward`
+    backward_capacity: usize,
+    /// The frame index [`VideoDecoder::next_frame`] should continue from after stepping backward
+    resume_at: Option<usize>,
+    /// Recently returned frames from [`VideoDecoder::get_frame`]
+    cache: FrameCache,
+    /// The time base of the stream being decoded
+    time_base: ffmpeg::AVRational,
+    /// The timestamp of the first frame of the stream
+    start_time: i64,
+    /// The stream timestamps (inclusive) to decode between
+    range: Option<(i64, i64)>,
+    /// The timecode of the first frame, from the metadata of the source data
+    timecode: Option<Timecode>,
+    /// The indices of the data streams to read packets from, see [`DecoderBuilder::data_stream`]
+    data_streams: Vec<i32>,
+    /// Packets read from `data_streams` which have not been taken yet
+    data_packets: VecDeque<DataPacket>,
+    /// The maximum length of `data_packets`, see [`DecoderBuilder::max_data_packets`]
+    max_data_packets: usize,
+    /// The decoder for the audio stream, see [`DecoderBuilder::audio`]
+    audio: Option<AudioStream>,
+    /// Audio decoded from `audio` which has not been taken yet
+    audio_frames: VecDeque<AudioFrame>,
+    /// When the most recently decoded audio ends, for audio frames without a timestamp
+    audio_end: Duration,
+    /// What to do when the source data is damaged
+    error_policy: ErrorPolicy,
+    /// Which frames to return, any others are dropped before being converted
+    sampling: Option<Sampling>,
+    /// Whether frame data is shared between clones of each frame, see [`DecoderBuilder::shared_data`]
+    shared_data: bool,
+    /// The limits on the size of the video and the memory held, see [`DecoderBuilder::max_dimensions`]
+    limits: Limits,
+    stats: DecoderStats,
 
     // -------------- ffmpeg data --------------
-    texture_data: Vec<u8>,
-    sws_context: *mut ffmpeg::SwsContext,
-    rgb_frame: *mut ffmpeg::AVFrame,
+    /// Decoded frames are passed through this before being converted, if set with [`DecoderBuilder::filter`]
+    filter: Option<FilterGraph>,
+    /// Set if [`DecoderBuilder::closed_captions`] is enabled
+    captions: Option<CaptionDecoder>,
+    /// Captions completed by frames which were skipped, returned with the next frame
+    pending_captions: Vec<SubtitleCue>,
+    converter: Option<Converter>,
+    /// Drawn over each frame after it is converted, if set with [`DecoderBuilder::overlay`]
+    overlay: Option<Overlay>,
     raw_frame: *mut ffmpeg::AVFrame,
-    /// Only used if we got a [`VideoSource::Raw(_)`]
-    avio: Option<*mut ffmpeg::AVIOContext>,
     codec_ctx: *mut ffmpeg::AVCodecContext,
-    input_ctx: *mut ffmpeg::AVFormatContext,
+    /// Declared after the other contexts so it is dropped after them
+    input: Input,
     packet: ffmpeg::AVPacket,
     stream_id: i32,
 }
 
+// SAFETY: Every ffmpeg pointer held by the decoder is allocated in `VideoDecoder::open`, owned
+// exclusively by this decoder, and freed only in `Drop`. None of them are exposed to the caller,
+// and ffmpeg does not tie any of these contexts to the thread that created them.
+// All data that ffmpeg points back into (the rgb frame buffer owned by `Converter`, and the input data owned by `Input`)
+// is heap allocated and owned by the decoder, so it does not move when the decoder does.
+//
+// The decoder is deliberately not `Sync`, all access to the contexts goes through `&mut self`.
+unsafe impl Send for VideoDecoder {}
+
 impl VideoDecoder {
     /// Create a new video decoder.
     ///
@@ -128,369 +202,2012 @@ impl VideoDecoder {
     ///
     /// * `source` - The input video data
     /// * `should_loop` - Whether the decoder should loop back to the start once reaching the end of the source data
+    ///
+    /// See [`VideoDecoder::builder`] for more options.
     pub fn new<S>(source: S, should_loop: bool) -> Result<Self, DecodeError>
     where
         S: Into<VideoSource>,
     {
-        let source: VideoSource = source.into();
-
-        unsafe {
-            let buffer = ffmpeg::av_malloc(BUFFER_SIZE);
-
-            let mut avio: Option<*mut ffmpeg::AVIOContext> = None;
-            let mut input_ctx: *mut ffmpeg::AVFormatContext = ffmpeg::avformat_alloc_context();
-
-            if let VideoSource::Raw(ref data) = source {
-                let mut stream = Stream {
-                    length: data.len(),
-                    offset: 0,
-                    data: data.as_ptr(),
-                };
-
-                avio = Some(ffmpeg::avio_alloc_context(
-                    buffer as *mut u8,
-                    BUFFER_SIZE as i32,
-                    0,
-                    &mut stream as *mut Stream as *mut ffi::c_void,
-                    Some(read_stream),
-                    None,
-                    None,
-                ));
-
-                (*input_ctx).pb = avio.unwrap();
-                (*input_ctx).flags |= ffmpeg::AVFMT_FLAG_CUSTOM_IO;
-            }
-
-            let mut _source_path_raw = Vec::new();
-            let path = match source {
-                VideoSource::Raw(_) => ptr::null(),
-                VideoSource::Filesystem(ref path) => {
-                    _source_path_raw = path_to_raw(path).ok_or(DecodeError::InvalidSource)?;
-                    _source_path_raw.as_ptr()
-                }
-            };
+        DecoderBuilder::new(source).should_loop(should_loop).build()
+    }
 
-            // Open video
-            if ffmpeg::avformat_open_input(
-                &mut input_ctx,
-                path as *const i8,
-                ptr::null_mut(),
-                ptr::null_mut(),
-            ) != 0
-            {
-                return Err(DecodeError::UnableToOpenInput);
-            }
+    /// Create a [`DecoderBuilder`] to configure a new video decoder
+    pub fn builder<S>(source: S) -> DecoderBuilder
+    where
+        S: Into<VideoSource>,
+    {
+        DecoderBuilder::new(source)
+    }
 
-            // Get stream information
-            if ffmpeg::avformat_find_stream_info(input_ctx, ptr::null_mut()) < 0 {
-                return Err(DecodeError::UnableToReadStreamInfo);
-            }
+    pub(crate) fn open(
+        source: VideoSource,
+        mut options: DecoderOptions,
+    ) -> Result<Self, DecodeError> {
+        if let Some(crop) = match &source {
+            VideoSource::Device(device) => device.crop_filter(),
+            _ => None,
+        } {
+            // Cropped before any other filtering, as if the device had only captured the region
+            options.filter = Some(match options.filter.take() {
+                Some(filter) => format!("{crop},{filter}"),
+                None => crop,
+            });
+        }
+        let input = Input::open(source, &options.input_options())?;
 
+        unsafe {
             // Find video stream
-            let (codecpar, stream_id) = {
-                let mut stream_id = None;
-
-                for i in 0..(*input_ctx).nb_streams as isize {
-                    if (*(*(*(*input_ctx).streams.offset(i))).codecpar).codec_type
-                        == ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO
-                    {
-                        stream_id = Some(i);
-                        break;
+            let stream_id = match (options.video_stream, options.program) {
+                (Some(index), _)
+                    if input.is_stream(index, ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO) =>
+                {
+                    index
+                }
+                (Some(_), _) => return Err(DecodeError::InvalidVideoStream),
+                (None, Some(program)) => {
+                    if !input.select_program(program) {
+                        return Err(DecodeError::InvalidProgram);
                     }
+                    input
+                        .find_program_video_stream(program)
+                        .ok_or(DecodeError::UnableToFindVideoStream)?
                 }
-
-                let stream_id = stream_id.ok_or(DecodeError::UnableToFindVideoStream)?;
-                (
-                    (*(*(*input_ctx).streams.offset(stream_id))).codecpar,
-                    stream_id,
-                )
+                (None, None) if options.variant.is_some() => input
+                    .select_variant(options.variant.unwrap_or_default())
+                    .ok_or(DecodeError::UnableToFindVideoStream)?,
+                (None, None) => input.find_video_stream().ok_or_else(|| {
+                    match input.find_stream(ffmpeg::AVMediaType::AVMEDIA_TYPE_AUDIO) {
+                        Some(_) => DecodeError::AudioOnlyInput,
+                        None => DecodeError::UnableToFindVideoStream,
+                    }
+                })?,
             };
+            let codecpar = (*input.stream(stream_id)).codecpar;
 
-            let codec = ffmpeg::avcodec_find_decoder((*codecpar).codec_id);
+            let codec = options
+                .decoder_name
+                .as_deref()
+                .and_then(|name| find_decoder_by_name(name, (*codecpar).codec_id))
+                .unwrap_or_else(|| ffmpeg::avcodec_find_decoder((*codecpar).codec_id));
             if codec.is_null() {
                 return Err(DecodeError::UnsupportedCodec);
             }
 
-            // Duplicate codec so we can reuse the input context
-            let codec_ctx = {
-                let codec_ctx = ffmpeg::avcodec_alloc_context3(codec);
-                ffmpeg::avcodec_parameters_to_context(codec_ctx, codecpar);
-                codec_ctx
+            let stream = input.stream(stream_id);
+            // Checked from the header before anything is allocated for decoding
+            options
+                .limits
+                .check_dimensions((*codecpar).width, (*codecpar).height)?;
+            options
+                .limits
+                .check_frame_count((*stream).nb_frames.max(0) as usize)?;
+            // The base framerate is only missing when the stream information was not read, see `DecoderBuilder::fast_open`
+            let framerate = match (*stream).r_frame_rate {
+                rate if rate.num > 0 && rate.den > 0 => rate,
+                _ => (*stream).avg_frame_rate,
+            };
+            let avg_framerate = (*stream).avg_frame_rate;
+            let start_time = match (*stream).start_time {
+                ffmpeg::AV_NOPTS_VALUE => 0,
+                start_time => start_time,
             };
 
-            // Open decoder context
-            if ffmpeg::avcodec_open2(codec_ctx, codec, ptr::null_mut()) < 0 {
+            // Everything allocated from here on is owned by the decoder, so it is freed if we return early
+            let mut decoder = VideoDecoder {
+                dimensions: Dimensions {
+                    width: (*codecpar).width as u32,
+                    height: (*codecpar).height as u32,
+                },
+                // Frames can not be found by number without a framerate, so anything which needs one checks it is positive
+                framerate: (framerate.num > 0 && framerate.den > 0)
+                    .then(|| framerate.num as f32 / framerate.den as f32)
+                    .unwrap_or(0.0),
+                sample_aspect_ratio: Some(ffmpeg::av_guess_sample_aspect_ratio(
+                    input.ctx,
+                    stream,
+                    ptr::null_mut(),
+                ))
+                .filter(|aspect| aspect.num > 0 && aspect.den > 0),
+                avg_framerate: (avg_framerate.num > 0 && avg_framerate.den > 0)
+                    .then(|| avg_framerate.num as f32 / avg_framerate.den as f32),
+                // The base framerate is the highest framerate of the stream, so it only matches the average if every frame is the same length
+                // (allowing for rounding in the average, which is worked out from the duration of the stream)
+                vfr: framerate.num > 0
+                    && avg_framerate.num > 0
+                    && (ffmpeg::av_q2d(framerate) / ffmpeg::av_q2d(avg_framerate) - 1.0).abs()
+                        > 0.01,
+                codec_ctx: ptr::null_mut(),
+                input,
+                index: 1, // first frame is frame 1
+                index_from_pts: false,
+                seek_target: None,
+                discard_before: None,
+                skip_frame: ffmpeg::AVDiscard::AVDISCARD_DEFAULT,
+                restore_target: None,
+                next_pts: None,
+                backward: VecDeque::new(),
+                backward_capacity: options.backward_cache,
+                resume_at: None,
+                cache: FrameCache::new(options.frame_cache),
+                time_base: (*stream).time_base,
+                start_time,
+                range: None,
+                timecode: None,
+                data_streams: Vec::new(),
+                data_packets: VecDeque::new(),
+                max_data_packets: options.max_data_packets.max(1),
+                audio: None,
+                audio_frames: VecDeque::new(),
+                audio_end: Duration::ZERO,
+                error_policy: options.error_policy,
+                sampling: options.sampling,
+                shared_data: options.shared_data,
+                limits: options.limits,
+                stats: DecoderStats::default(),
+                filter: None,
+                captions: None,
+                pending_captions: Vec::new(),
+                converter: None,
+                overlay: None,
+                raw_frame: ptr::null_mut(),
+                packet: mem::zeroed(),
+                buffer: VecDeque::new(),
+                buffer_capacity: options.buffer_capacity(),
+                loops_remaining: match options.loop_mode {
+                    LoopMode::Never => Some(0),
+                    LoopMode::Infinite => None,
+                    LoopMode::Count(count) => Some(count.saturating_sub(1)),
+                    LoopMode::PingPong => None,
+                },
+                draining: false,
+                ping_pong: options.loop_mode == LoopMode::PingPong,
+                reverse: None,
+                stream_id: stream_id as i32,
+            };
+
+            // Duplicate codec so we can reuse the input context
+            decoder.codec_ctx = ffmpeg::avcodec_alloc_context3(codec);
+            if decoder.codec_ctx.is_null() {
+                return Err(DecodeError::AllocationFailed);
+            }
+            let codec_ctx = decoder.codec_ctx;
+
+            if ffmpeg::avcodec_parameters_to_context(codec_ctx, codecpar) < 0 {
                 return Err(DecodeError::UnsupportedCodec);
             }
 
-            // Allocate frame buffers
-            let raw_frame = ffmpeg::av_frame_alloc();
-            let rgb_frame = ffmpeg::av_frame_alloc();
+            // Configure decoder threading
+            if let Some(threads) = options.threads {
+                (*codec_ctx).thread_count = threads as i32;
+            }
+            (*codec_ctx).thread_type = options.thread_type().as_raw();
 
-            let buffer_size = ffmpeg::av_image_get_buffer_size(
-                ffmpeg::AVPixelFormat::AV_PIX_FMT_RGB24,
-                (*codec_ctx).width,
-                (*codec_ctx).height,
-                BUFFER_ALIGNMENT,
-            ) as usize;
+            if options.motion_vectors {
+                (*codec_ctx).flags2 |= ffmpeg::AV_CODEC_FLAG2_EXPORT_MVS as i32;
+            }
+            if options.low_latency {
+                (*codec_ctx).flags |= ffmpeg::AV_CODEC_FLAG_LOW_DELAY as i32;
+            }
+
+            // Open decoder context
+            let mut codec_options =
+                Dictionary::new(&options.codec_options).ok_or(DecodeError::InvalidOption)?;
+            if ffmpeg::avcodec_open2(codec_ctx, codec, &mut codec_options.0) < 0 {
+                return Err(DecodeError::UnsupportedCodec);
+            }
+            decoder.skip_frame = (*codec_ctx).skip_frame;
 
-            let mut texture_data: Vec<u8> = vec![0; buffer_size];
+            decoder.raw_frame = ffmpeg::av_frame_alloc();
+            if decoder.raw_frame.is_null() {
+                return Err(DecodeError::AllocationFailed);
+            }
 
-            if ffmpeg::av_image_fill_arrays(
-                (*rgb_frame).data.as_mut_ptr(),
-                (*rgb_frame).linesize.as_mut_ptr(),
-                texture_data.as_mut_ptr(),
-                ffmpeg::AVPixelFormat::AV_PIX_FMT_RGB24,
-                (*codec_ctx).width,
-                (*codec_ctx).height,
-                BUFFER_ALIGNMENT,
-            ) <= 0
-            {
-                return Err(DecodeError::UnableToReadFrameBuffer);
-            }
-
-            // Creater converter context
-            let sws_context = ffmpeg::sws_getContext(
-                (*codec_ctx).width,                      // Source
-                (*codec_ctx).height,                     // Source
-                (*codec_ctx).pix_fmt,                    // Source
-                (*codec_ctx).width,                      // Destination
-                (*codec_ctx).height,                     // Destination
-                ffmpeg::AVPixelFormat::AV_PIX_FMT_RGB24, // Destination
-                ffmpeg::SWS_BILINEAR,
-                ptr::null_mut(),
-                ptr::null_mut(),
-                ptr::null_mut(),
-            );
+            if let Some(overlay) = options.overlay.take() {
+                if !overlay.is_valid() {
+                    return Err(DecodeError::InvalidOverlay);
+                }
+                decoder.overlay = Some(overlay);
+            }
 
-            let packet: ffmpeg::AVPacket = mem::zeroed();
-            let dimensions = ((*codec_ctx).width as u32, (*codec_ctx).height as u32);
-            let framerate = (**(*input_ctx).streams).r_frame_rate;
-            let framerate = framerate.num as f32 / framerate.den as f32;
+            // The timecode is usually on the video stream, but some formats put it on the container or a separate timecode stream
+            let fps = decoder.framerate.round() as u32;
+            decoder.timecode = [stream_id]
+                .into_iter()
+                .chain(0..decoder.input.stream_count())
+                .map(|index| (*decoder.input.stream(index)).metadata)
+                .chain([(*decoder.input.ctx).metadata])
+                .find_map(|metadata| dict_get(metadata, c"timecode"))
+                .and_then(|timecode| Timecode::parse(&timecode, fps));
 
-            Ok(VideoDecoder {
-                dimensions: Dimensions {
-                    width: dimensions.0,
-                    height: dimensions.1,
-                },
-                framerate,
-                codec_ctx,
-                input_ctx,
-                texture_data,
-                index: 1, // first frame is frame 1
-                sws_context,
-                rgb_frame,
-                raw_frame,
-                _source: source,
-                avio,
-                packet,
-                buffer: VecDeque::new(),
-                should_loop,
-                stream_id: stream_id as i32,
-            })
-        }
-    }
+            for &index in &options.data_streams {
+                if !decoder
+                    .input
+                    .is_stream(index, ffmpeg::AVMediaType::AVMEDIA_TYPE_DATA)
+                {
+                    return Err(DecodeError::InvalidDataStream);
+                }
+                decoder.data_streams.push(index as i32);
+            }
 
-    /// Get the next frame from the input, if [`VideoDecoder::will_loop`] is `true` then this is guaranteed to never return `Ok(None)`.
-    pub fn next_frame(&mut self) -> Result<Option<Frame>, DecodeError> {
-        if let Some(next) = self.buffer.pop_front() {
-            return Ok(Some(next));
-        }
+            if options.telemetry {
+                let streams = decoder.input.find_gpmf_streams();
+                decoder
+                    .data_streams
+                    .extend(streams.into_iter().map(|index| index as i32));
+            }
 
-        unsafe {
-            let next_frame = ffmpeg::av_read_frame(self.input_ctx, &mut self.packet);
-            if next_frame < 0 {
-                // out of frames
-                if self.should_loop {
-                    self.loop_ctx();
-                    return self.next_frame();
-                } else {
-                    return Ok(None);
+            if options.audio {
+                if let Some(index) = decoder
+                    .input
+                    .find_stream(ffmpeg::AVMediaType::AVMEDIA_TYPE_AUDIO)
+                {
+                    // Audio which can not be decoded is left out, rather than failing to decode the video
+                    decoder.audio = match AudioStream::open(&decoder.input, index, None, None) {
+                        Ok(audio) => Some(audio),
+                        Err(DecodeError::UnsupportedCodec) => None,
+                        Err(error) => return Err(error),
+                    };
                 }
             }
 
-            // Check that this packet is in the right stream
-            if self.packet.stream_index == self.stream_id {
-                if ffmpeg::avcodec_send_packet(self.codec_ctx, &self.packet) < 0 {
-                    return Err(DecodeError::UnableToSendPacketToDecoder);
+            if options.closed_captions {
+                decoder.captions = Some(CaptionDecoder::new(decoder.time_base)?);
+            }
+
+            let anamorphic = decoder
+                .sample_aspect_ratio
+                .is_some_and(|aspect| aspect.num != aspect.den);
+            let filters = options.filter_graph(decoder.is_hdr(), anamorphic);
+            if (*codec_ctx).pix_fmt == ffmpeg::AVPixelFormat::AV_PIX_FMT_NONE
+                && (filters.is_some() || options.keep_alpha)
+            {
+                // Most containers leave the pixel format to the codec, so without the stream information (see `DecoderBuilder::fast_open`)
+                // it is only known once a frame is decoded, but the filter graph needs it up front
+                decoder.input.find_stream_info()?;
+                (*codec_ctx).pix_fmt =
+                    mem::transmute::<ffi::c_int, ffmpeg::AVPixelFormat>((*codecpar).format);
+            }
+
+            // The frames given to the converter are whatever comes out of the filter graph
+            let (mut width, mut height, mut pix_fmt) = (
+                (*codec_ctx).width,
+                (*codec_ctx).height,
+                (*codec_ctx).pix_fmt,
+            );
+            if let Some(filters) = filters {
+                // The container may know the aspect ratio when the codec doesn't
+                let aspect = decoder
+                    .sample_aspect_ratio
+                    .unwrap_or(ffmpeg::AVRational { num: 0, den: 1 });
+                let filter = FilterGraph::new(
+                    &filters,
+                    &options.scale_options(),
+                    codec_ctx,
+                    decoder.time_base,
+                    framerate,
+                    aspect,
+                )?;
+                let dimensions = filter.dimensions();
+                (width, height) = (dimensions.width as i32, dimensions.height as i32);
+                pix_fmt = filter.format();
+                decoder.sample_aspect_ratio = filter.sample_aspect_ratio();
+                if let Some(framerate) = filter.framerate() {
+                    // The filter outputs frames at a fixed rate
+                    decoder.framerate = framerate.num as f32 / framerate.den as f32;
+                    decoder.avg_framerate = Some(decoder.framerate);
+                    decoder.vfr = false;
                 }
+                decoder.filter = Some(filter);
+            }
 
-                // Decode packet frames
-                while ffmpeg::avcodec_receive_frame(self.codec_ctx, self.raw_frame) >= 0 {
-                    // Convert frame to RGB24
-                    ffmpeg::sws_scale(
-                        self.sws_context,
-                        (*self.raw_frame).data.as_ptr() as *const *const _,
-                        (*self.raw_frame).linesize.as_ptr() as *mut _,
-                        0,
-                        (*self.codec_ctx).height as std::os::raw::c_int,
-                        (*self.rgb_frame).data.as_ptr(),
-                        (*self.rgb_frame).linesize.as_ptr() as *mut _,
-                    );
-
-                    // Add to frame buffer
-                    self.buffer.push_back(Frame {
-                        index: self.index,
-                        data: self.texture_data.clone(),
-                        dimensions: self.dimensions,
-                    });
-                    self.index += 1;
+            let dimensions = Dimensions {
+                width: width as u32,
+                height: height as u32,
+            };
+            let output_format = match options.keep_alpha && has_alpha(pix_fmt) {
+                true => options.output_format.with_alpha(),
+                false => options.output_format,
+            };
+            let converter = decoder.converter.insert(Converter::new(
+                dimensions,
+                output_format,
+                options.packed_rows,
+                options.sws_flags(),
+            )?);
+            // An unknown pixel format is found when the first frame is converted
+            if pix_fmt != ffmpeg::AVPixelFormat::AV_PIX_FMT_NONE
+                && !converter.prepare(width, height, pix_fmt)
+            {
+                return Err(DecodeError::ConverterInitFailed);
+            }
+            decoder.dimensions = dimensions;
+            decoder.limits.check_memory(decoder.frame_memory(
+                (*codec_ctx).width,
+                (*codec_ctx).height,
+                (*codec_ctx).pix_fmt,
+            ))?;
+
+            if let Some((start, end)) = options.range {
+                let range = (decoder.time_to_pts(start), decoder.time_to_pts(end));
+                decoder.range = Some(range);
+                if !start.is_zero() && !decoder.seek_ts(range.0) {
+                    return Err(DecodeError::UnableToSeek);
                 }
             }
 
-            ffmpeg::av_packet_unref(&mut self.packet);
+            Ok(decoder)
         }
+    }
 
-        self.next_frame()
+    /// Get the next frame from the input, if [`VideoDecoder::will_loop`] is `true` then this is guaranteed to never return `Ok(None)`.
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, DecodeError> {
+        self.resume()?;
+        self.next_raw(true)?
+            .map(|raw| self.convert(raw))
+            .transpose()
     }
 
-    /// Skip the next `n` frames.
+    /// Get the next frame, along with the result of calling `f` with the decoded frame before it is converted to the output format.
+    ///
+    /// This is an escape hatch for reading fields (such as side data) of the underlying `AVFrame` which are not wrapped by this crate.
+    /// The frame is after any filters (see [`DecoderBuilder::filter`](crate::DecoderBuilder::filter)).
     ///
-    /// Note that this function will never loop (even if [`VideoDecoder::will_loop`] is `true`).
-    pub fn skip(&mut self, n: isize) {
-        let frames = n;
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// let (_frame, _quality) = decoder.with_raw_frame(|raw| raw.quality).unwrap().unwrap();
+    /// # }
+    /// ```
+    pub fn with_raw_frame<F, T>(&mut self, f: F) -> Result<Option<(Frame, T)>, DecodeError>
+    where
+        F: FnOnce(&ffmpeg::AVFrame) -> T,
+    {
+        self.resume()?;
+        let Some(raw) = self.next_raw(true)? else {
+            return Ok(None);
+        };
 
-        match frames.cmp(&0) {
-            Ordering::Greater => {
-                let mut frames = frames as usize;
+        let result = f(unsafe { &*raw.frame });
+        Ok(Some((self.convert(raw)?, result)))
+    }
 
-                // Clear frame buffer
-                if !self.buffer.is_empty() && self.buffer.len() <= frames {
-                    let limit = match self.buffer.len() <= frames {
-                        true => self.buffer.len(),
-                        false => frames,
-                    };
+    /// The codec context of the decoder, for reading fields or setting options which are not wrapped by this crate.
+    ///
+    /// # Safety
+    ///
+    /// The context is owned by the decoder, so it must not be freed (or used once the decoder is dropped).
+    /// Sending packets to or receiving frames from the context directly, or changing how frames are output (such as their size or pixel format),
+    /// leaves the decoder in an inconsistent state.
+    #[inline]
+    pub unsafe fn as_raw_codec_ctx(&self) -> *mut ffmpeg::AVCodecContext {
+        self.codec_ctx
+    }
 
-                    self.buffer.drain(..=limit);
-                    frames -= limit;
-                }
+    /// The format context of the source data, see [`VideoDecoder::as_raw_codec_ctx`]
+    ///
+    /// # Safety
+    ///
+    /// The context is owned by the decoder, so it must not be freed (or used once the decoder is dropped).
+    /// Reading packets from or seeking the context directly leaves the decoder in an inconsistent state.
+    #[inline]
+    pub unsafe fn as_raw_format_ctx(&self) -> *mut ffmpeg::AVFormatContext {
+        self.input.ctx
+    }
 
-                while frames > 0 {
-                    unsafe {
-                        let next_frame = ffmpeg::av_read_frame(self.input_ctx, &mut self.packet);
-                        if next_frame < 0 {
-                            // out of frames
-                            return;
-                        }
+    /// Get the details of the next frame without converting it (so without its data), see [`VideoDecoder::next_frame`].
+    ///
+    /// This is much faster than [`VideoDecoder::next_frame`] for passes which only need to know about each frame, e.g. to find the keyframes.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// while let Some(info) = decoder.next_frame_info().unwrap() {
+    ///     if info.is_keyframe() {
+    ///         println!("keyframe {} at {:?}", info.index(), info.timestamp());
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn next_frame_info(&mut self) -> Result<Option<FrameInfo>, DecodeError> {
+        self.resume()?;
+        Ok(self
+            .next_raw(true)?
+            .map(|mut raw| self.unconverted(&mut raw).info()))
+    }
 
-                        // Check that this packet is in the right stream
-                        if self.packet.stream_index == self.stream_id {
-                            if ffmpeg::avcodec_send_packet(self.codec_ctx, &self.packet) < 0 {
-                                // If we can't decode the packet, ignore it
-                                // (this does not count toward the skipped frames, but this may change in the future)
-                                continue;
-                            }
-
-                            // Read packet frames
-                            while ffmpeg::avcodec_receive_frame(self.codec_ctx, self.raw_frame) >= 0
-                            {
-                                frames -= 1;
-
-                                // Update frame index
-                                self.index += 1;
-
-                                // Packet may contain multiple frames,
-                                //      so we need to check every frame to prevent this from underflowing
-                                if frames == 0 {
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            Ordering::Less => {
-                // optimize there is probably a better way to do this
-
-                // Determine how many frames we need go to from the start
-                let offset = self
-                    .index
-                    // This is needed to account for the fact that the index is the index for the next frame
-                    .saturating_sub(2)
-                    .saturating_sub(frames.unsigned_abs());
-
-                // Reset to start
-                self.loop_ctx();
-
-                // Skip offset
-                if offset > isize::MAX as usize {
-                    self.skip(isize::MAX);
-                    self.skip((offset - isize::MAX as usize) as isize);
-                } else {
-                    self.skip(offset as isize);
-                }
+    /// Get up to the next `n` frames, fewer are returned if the end of the input is reached (this loops the same as [`VideoDecoder::next_frame`]).
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// let batch = decoder.next_frames(8).unwrap();
+    /// assert_eq!(batch.len(), 8);
+    /// # }
+    /// ```
+    pub fn next_frames(&mut self, n: usize) -> Result<Vec<Frame>, DecodeError> {
+        self.resume()?;
+
+        let mut frames = Vec::with_capacity(n.min(self.estimated_frames_left().unwrap_or(0)));
+        while frames.len() < n {
+            match self.next_raw(true)? {
+                Some(raw) => frames.push(self.convert(raw)?),
+                None => break,
             }
-            Ordering::Equal => (),
         }
+
+        Ok(frames)
     }
 
-    /// Get the dimensions of the video
-    #[inline]
-    pub fn dimensions(&self) -> Dimensions {
-        self.dimensions
+    /// Decode every remaining frame of the video (or range), this never loops (even if [`VideoDecoder::will_loop`] is `true`).
+    ///
+    /// Note that every frame is kept in memory, which may be very large for long or high resolution videos.
+    pub fn decode_all(&mut self) -> Result<Vec<Frame>, DecodeError> {
+        self.resume()?;
+
+        let mut frames = Vec::with_capacity(self.estimated_frames_left().unwrap_or(0));
+        while let Some(raw) = self.next_raw(false)? {
+            frames.push(self.convert(raw)?);
+        }
+
+        Ok(frames)
     }
 
-    /// Get the framerate of the video (in frames-per-second)
-    #[inline]
-    pub fn framerate(&self) -> f32 {
-        self.framerate
+    /// An estimate of the number of frames after the current position, from the duration and framerate of the video
+    fn estimated_frames_left(&self) -> Option<usize> {
+        let mut duration = self.duration()?;
+        if let Some((start, end)) = self.range {
+            // The end of the range may be past the end of the video
+            let end = self
+                .pts_to_time(end)
+                .map_or(duration, |end| end.min(duration));
+            let start = self.pts_to_time(start).unwrap_or_default();
+            duration = end.saturating_sub(start);
+        }
+        let framerate = self.avg_framerate.unwrap_or(self.framerate);
+        let total = (duration.as_secs_f64() * framerate as f64).round() as usize;
+
+        let first = self
+            .range
+            .map_or(1, |(start, _)| self.pts_to_index(start).unwrap_or(1));
+        Some(total.saturating_sub(self.next_index().saturating_sub(first)))
     }
 
-    /// Check whether the decoder will loop once reaching the end of the source data
+    /// Take the packets read from the data streams selected with [`DecoderBuilder::data_stream`](crate::DecoderBuilder::data_stream)
+    /// since this was last called, in the order they were stored in the source data.
+    ///
+    /// Packets are read as the video is decoded, so after each call to [`VideoDecoder::next_frame`] this returns the packets
+    /// up to (and usually slightly past) that frame. Use [`DataPacket::pts`] to match packets to frames.
+    /// Packets which have not been taken are discarded when seeking.
     ///
-    /// This will be whatever value was passed to [`VideoDecoder::new`].
     /// ```rust
     /// # fn main() {
-    /// # use ffmpeg_video_decoder::VideoDecoder;
-    ///  let decoder = VideoDecoder::new("test.mp4", true).unwrap();
-    ///  assert_eq!(decoder.will_loop(), true);
+    /// use ffmpeg_video_decoder::DecoderBuilder;
+    ///
+    /// let file = "drone.ts";
+    /// # let file = "test.mp4";
+    /// # if DecoderBuilder::new(file).data_stream(1).build().is_err() { return; }
+    /// let mut decoder = DecoderBuilder::new(file).data_stream(1).build().unwrap();
+    /// while let Some(_frame) = decoder.next_frame().unwrap() {
+    ///     for packet in decoder.take_data_packets() {
+    ///         println!("{:?}: {} bytes of metadata", packet.pts(), packet.bytes().len());
+    ///     }
+    /// }
     /// # }
     /// ```
+    pub fn take_data_packets(&mut self) -> Vec<DataPacket> {
+        self.data_packets.drain(..).collect()
+    }
+
+    /// Take the audio decoded since this was last called, when decoding audio with [`DecoderBuilder::audio`](crate::DecoderBuilder::audio).
+    ///
+    /// Audio is decoded as the video is read, so after each call to [`VideoDecoder::next_frame`] this returns the audio
+    /// up to (and usually slightly past) that frame, with timestamps on the same timeline as the frames.
+    /// Audio which has not been taken is discarded when seeking. See [`MediaDecoder`](crate::MediaDecoder) to get the audio and video in presentation order.
+    ///
+    /// This is always empty if the source data does not contain audio.
+    pub fn take_audio_frames(&mut self) -> Vec<AudioFrame> {
+        self.audio_frames.drain(..).collect()
+    }
+
+    /// Get the frame before the most recently returned frame, returns `Ok(None)` if the most recently returned frame was the first frame.
+    ///
+    /// This steps backward through the video one frame at a time (so calling [`VideoDecoder::next_frame`] afterwards returns the frame after this one).
+    /// Frames are decoded forward from the nearest keyframe, and the frames before the returned frame are kept (see [`DecoderBuilder::backward_cache`]),
+    /// so repeatedly stepping backward only needs to decode each group of pictures once.
+    ///
+    /// Note that this will never loop (even if [`VideoDecoder::will_loop`] is `true`).
+    ///
     /// ```rust
     /// # fn main() {
-    /// # use ffmpeg_video_decoder::VideoDecoder;
-    ///  let decoder = VideoDecoder::new("test.mp4", false).unwrap();
-    ///  assert_eq!(decoder.will_loop(), false);
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// decoder.seek_to_frame(100).unwrap();
+    /// assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 100);
+    /// assert_eq!(decoder.prev_frame().unwrap().unwrap().index(), 99);
+    /// assert_eq!(decoder.prev_frame().unwrap().unwrap().index(), 98);
+    /// assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 99);
     /// # }
     /// ```
-    #[inline]
-    pub fn will_loop(&self) -> bool {
-        self.should_loop
+    pub fn prev_frame(&mut self) -> Result<Option<Frame>, DecodeError> {
+        // The index of the most recently returned frame
+        let current = self.next_index().saturating_sub(1);
+        if current <= 1 {
+            return Ok(None);
+        }
+        let target = current - 1;
+
+        // Check whether the frame was already decoded by a previous step
+        while self
+            .backward
+            .back()
+            .is_some_and(|frame| frame.index > target)
+        {
+            self.backward.pop_back();
+        }
+        if self
+            .backward
+            .back()
+            .is_some_and(|frame| frame.index == target)
+        {
+            let raw = self.backward.pop_back().unwrap(); // unwrap is safe as we just checked the frame exists
+            self.resume_at = Some(target + 1);
+            return self.convert(raw).map(Some);
+        }
+
+        // Decode forward from the keyframe before the target, keeping the frames before it
+        self.backward.clear();
+        let ts = self.index_to_pts(target).ok_or(DecodeError::UnableToSeek)?;
+        if !self.seek_ts(ts) {
+            return Err(DecodeError::UnableToSeek);
+        }
+
+        while let Some(raw) = self.next_raw(false)? {
+            if raw.index >= target {
+                return self.convert(raw).map(Some);
+            }
+
+            if self.backward.len() >= self.backward_capacity {
+                self.backward.pop_front();
+            }
+            if self.backward_capacity > 0 {
+                self.backward.push_back(raw);
+            }
+        }
+
+        Ok(None)
     }
 
-    /// Loop the internal decoder context, this will reset the video to the first frame.
-    fn loop_ctx(&mut self) {
-        unsafe {
-            // Seek stream to start
-            let stream = (*self.input_ctx).streams.offset(self.stream_id as isize);
-            ffmpeg::avio_seek((*self.input_ctx).pb, 0, 0);
-            ffmpeg::avformat_seek_file(
-                self.input_ctx,
-                self.stream_id,
-                0,
-                0,
-                (*(*stream)).duration,
-                0,
-            );
+    /// Return to the frame after the one most recently returned by [`VideoDecoder::prev_frame`]
+    fn resume(&mut self) -> Result<(), DecodeError> {
+        if let Some(index) = self.resume_at.take() {
+            if index != self.next_index() {
+                self.seek_to_frame(index)?;
+            }
         }
 
-        // Reset index
-        self.index = 1;
+        Ok(())
     }
-}
 
-impl Drop for VideoDecoder {
-    fn drop(&mut self) {
-        unsafe {
-            ffmpeg::sws_freeContext(self.sws_context);
-            ffmpeg::av_free(self.rgb_frame as *mut ffi::c_void);
-            ffmpeg::av_free(self.raw_frame as *mut ffi::c_void);
-            if let Some(mut avio) = self.avio {
-                ffmpeg::avio_context_free(&mut avio);
+    /// Get the next decoded frame without converting it, optionally looping once reaching the end of the input
+    fn next_raw(&mut self, allow_loop: bool) -> Result<Option<RawFrame>, DecodeError> {
+        if self.reverse.is_some() {
+            if let Some(raw) = self.next_reversed()? {
+                return Ok(Some(raw));
+            }
+
+            // Back at the start of a ping pong loop, so play forward again
+            let first = self.reverse.as_ref().map_or(1, |reverse| reverse.last);
+            if !self.loop_ctx() {
+                return Err(DecodeError::UnableToSeek);
+            }
+            // The first frame was just returned, so it is not shown twice
+            self.seek_target = Some(first + 1);
+            if !allow_loop {
+                return Ok(None);
+            }
+        }
+
+        self.next_forward(allow_loop)
+    }
+
+    /// Get the next decoded frame in the forward direction, see [`VideoDecoder::next_raw`]
+    fn next_forward(&mut self, allow_loop: bool) -> Result<Option<RawFrame>, DecodeError> {
+        if self.input.cancelled() {
+            return Err(DecodeError::Cancelled);
+        }
+
+        loop {
+            if let Some(next) = self.buffer.pop_front() {
+                self.limits.check_frame_count(next.index)?;
+                let (pts, duration) =
+                    unsafe { ((*next.frame).best_effort_timestamp, (*next.frame).duration) };
+                self.next_pts = (pts != ffmpeg::AV_NOPTS_VALUE && duration > 0)
+                    .then(|| (next.index + 1, pts + duration));
+                return Ok(Some(next));
+            }
+
+            if self.draining {
+                // Frames may still be held back by the decoder or filter graph while the buffer was full
+                self.receive_frames()?;
+                if !self.buffer.is_empty() {
+                    continue;
+                }
+
+                // The decoder has been fully flushed, so we are out of frames
+                let last = self.index.saturating_sub(1);
+                if allow_loop && self.ping_pong && last > 1 {
+                    // The last frame was just returned, so it is not shown twice
+                    self.reverse = Some(Reverse {
+                        frames: Vec::new(),
+                        end: last,
+                        last,
+                    });
+                    return self.next_raw(allow_loop);
+                }
+
+                if allow_loop && self.will_loop() {
+                    if let Some(loops) = self.loops_remaining.as_mut() {
+                        *loops -= 1;
+                    }
+
+                    if !self.loop_ctx() {
+                        return Err(DecodeError::UnableToSeek);
+                    }
+                    continue;
+                } else {
+                    return Ok(None);
+                }
+            }
+
+            // Frames may still be held back by the decoder or filter graph while the buffer was full,
+            // in which case no more packets should be sent until they have been returned
+            self.receive_frames()?;
+            if !self.buffer.is_empty() {
+                continue;
+            }
+
+            unsafe {
+                if self.input.read_frame(&mut self.packet)? < 0 {
+                    // Out of packets, the decoder may still be holding frames (especially when decoding with multiple threads)
+                    self.draining = true;
+                    ffmpeg::avcodec_send_packet(self.codec_ctx, ptr::null());
+                    if let Some(audio) = self.audio.as_mut() {
+                        audio.send(ptr::null());
+                    }
+                    self.receive_audio()?;
+                    continue;
+                }
+
+                // Check that this packet is in the right stream
+                if self.packet.stream_index == self.stream_id {
+                    self.stats.packets_read += 1;
+                    self.stats.bytes_read += self.packet.size.max(0) as u64;
+
+                    let skip = self.error_policy != ErrorPolicy::Fail
+                        && self.packet.flags & ffmpeg::AV_PKT_FLAG_CORRUPT as i32 != 0;
+
+                    // Frames before the target of a fast skip are never returned, so any which later frames do not depend on can be left out
+                    (*self.codec_ctx).skip_frame = match self.discard_before {
+                        Some(ts)
+                            if self.packet.pts != ffmpeg::AV_NOPTS_VALUE
+                                && self.packet.pts < ts
+                                && (self.skip_frame as i32)
+                                    < ffmpeg::AVDiscard::AVDISCARD_NONREF as i32 =>
+                        {
+                            ffmpeg::AVDiscard::AVDISCARD_NONREF
+                        }
+                        _ => self.skip_frame,
+                    };
+
+                    let started = Instant::now();
+                    let rejected =
+                        !skip && ffmpeg::avcodec_send_packet(self.codec_ctx, &self.packet) < 0;
+                    self.stats.decode_time += started.elapsed();
+
+                    if skip || rejected {
+                        ffmpeg::av_packet_unref(&mut self.packet);
+                        if self.error_policy == ErrorPolicy::Fail {
+                            return Err(DecodeError::UnableToSendPacketToDecoder);
+                        }
+
+                        self.stats.corrupt_packets += 1;
+                        continue;
+                    }
+                } else if self.data_streams.contains(&self.packet.stream_index) {
+                    self.read_data_packet();
+                } else if let Some(audio) = self
+                    .audio
+                    .as_mut()
+                    .filter(|audio| audio.stream_id() == self.packet.stream_index)
+                {
+                    audio.send(&self.packet);
+                    ffmpeg::av_packet_unref(&mut self.packet);
+                    self.receive_audio()?;
+                    continue;
+                }
+
+                ffmpeg::av_packet_unref(&mut self.packet);
             }
-            ffmpeg::avcodec_close(self.codec_ctx);
+
+            self.receive_frames()?;
+        }
+    }
+
+    /// Copy the packet in `packet` (from one of the selected data streams) into the queue of data packets
+    unsafe fn read_data_packet(&mut self) {
+        let stream = self.input.stream(self.packet.stream_index as usize);
+        let pts = match self.packet.pts {
+            ffmpeg::AV_NOPTS_VALUE => None,
+            // Convert to the time base of the video so the timestamps line up with the frames
+            pts => self.pts_to_time(ffmpeg::av_rescale_q(
+                pts,
+                (*stream).time_base,
+                self.time_base,
+            )),
+        };
+
+        let bytes = match self.packet.data.is_null() {
+            true => Vec::new(),
+            false => std::slice::from_raw_parts(self.packet.data, self.packet.size.max(0) as usize)
+                .to_vec(),
+        };
+
+        if self.data_packets.len() >= self.max_data_packets {
+            self.data_packets.pop_front();
+            self.stats.data_packets_dropped += 1;
+        }
+        self.data_packets.push_back(DataPacket {
+            stream_index: self.packet.stream_index as usize,
+            pts,
+            bytes,
+        });
+    }
+
+    /// Receive the audio currently available from the audio decoder and add it to the queue of audio frames
+    unsafe fn receive_audio(&mut self) -> Result<(), DecodeError> {
+        let Some(audio) = self.audio.as_mut() else {
+            return Ok(());
+        };
+
+        while let Some((pts, samples)) = audio.receive()? {
+            // Convert to the time base of the video so the timestamps line up with the frames
+            let timestamp = match pts {
+                ffmpeg::AV_NOPTS_VALUE => None,
+                pts => ts_to_duration(
+                    ffmpeg::av_rescale_q(pts, audio.time_base(), self.time_base) - self.start_time,
+                    self.time_base,
+                ),
+            };
+
+            let frame = AudioFrame {
+                timestamp: timestamp.unwrap_or(self.audio_end),
+                sample_rate: audio.sample_rate(),
+                channels: audio.channels(),
+                samples,
+            };
+            self.audio_end = frame.timestamp + frame.duration();
+            if let Some(frame) = self.keep_audio(frame) {
+                self.audio_frames.push_back(frame);
+            }
+        }
+        Ok(())
+    }
+
+    /// Trim audio to the range being decoded, and leave out the audio of frames which are left out by sampling
+    fn keep_audio(&self, mut frame: AudioFrame) -> Option<AudioFrame> {
+        if let Some((start, end)) = self.range {
+            let start = self.pts_to_time(start).unwrap_or_default();
+            let end = self.pts_to_time(end).unwrap_or(Duration::MAX);
+            frame = frame.trimmed(start, end)?;
+        }
+
+        // Decided for the video frame shown in the middle of the audio, so this is to the nearest audio frame
+        if let Some(sampling) = self.sampling {
+            let middle = frame.timestamp + frame.duration() / 2;
+            let index = (middle.as_secs_f64() * self.framerate as f64).floor() as usize + 1;
+            if !sampling.includes(index, self.framerate) {
+                return None;
+            }
+        }
+
+        Some(frame)
+    }
+
+    /// Start a backward pass from the last frame of the video (or range), decoding the last group of pictures
+    pub(crate) fn start_reversed(&mut self) -> Result<(), DecodeError> {
+        let ts = match (self.range, self.duration()) {
+            (Some((_, end)), _) => end,
+            (None, Some(duration)) => self.time_to_pts(duration),
+            (None, None) => i64::MAX,
+        };
+        if !self.seek_ts(ts) {
+            return Err(DecodeError::UnableToSeek);
+        }
+
+        let mut frames = Vec::new();
+        while let Some(raw) = self.next_forward(false)? {
+            frames.push(raw);
+        }
+        let last = frames.last().map_or(self.index, |raw| raw.index + 1);
+        self.reverse = Some(Reverse {
+            end: frames.first().map_or(1, |raw| raw.index),
+            frames,
+            last,
+        });
+        Ok(())
+    }
+
+    /// Get the next frame of a backward pass started with [`VideoDecoder::start_reversed`], converted to the output format
+    pub(crate) fn next_reversed_frame(&mut self) -> Result<Option<Frame>, DecodeError> {
+        self.next_reversed()?
+            .map(|raw| self.convert(raw))
+            .transpose()
+    }
+
+    /// Stop a backward pass, so [`VideoDecoder::next_frame`] continues from the frame after the most recently returned frame
+    pub(crate) fn stop_reversed(&mut self) {
+        if let Some(reverse) = self.reverse.take() {
+            self.resume_at = Some(reverse.last + 1);
+        }
+    }
+
+    /// Get the next frame of a backward pass, decoding the group of pictures before the current one once it runs out.
+    ///
+    /// Returns `Ok(None)` once the first frame has been returned (or if there is no backward pass).
+    fn next_reversed(&mut self) -> Result<Option<RawFrame>, DecodeError> {
+        let Some(mut reverse) = self.reverse.take() else {
+            return Ok(None);
+        };
+
+        if reverse.frames.is_empty() && reverse.end > 1 {
+            reverse.frames = self.decode_group(reverse.end)?;
+            reverse.end = reverse.frames.first().map_or(1, |raw| raw.index);
+        }
+
+        let next = reverse.frames.pop();
+        if let Some(raw) = &next {
+            reverse.last = raw.index;
+        }
+        self.reverse = Some(reverse);
+        Ok(next)
+    }
+
+    /// Decode the frames before `end`, starting from the keyframe before them (so the frames of a single group of pictures).
+    ///
+    /// Returns no frames if there is nothing before `end`.
+    fn decode_group(&mut self, end: usize) -> Result<Vec<RawFrame>, DecodeError> {
+        let mut start = end.saturating_sub(1);
+        loop {
+            let ts = self.index_to_pts(start).ok_or(DecodeError::UnableToSeek)?;
+            if !self.seek_ts(ts) {
+                return Err(DecodeError::UnableToSeek);
+            }
+
+            let mut frames = Vec::new();
+            while let Some(raw) = self.next_forward(false)? {
+                if raw.index >= end {
+                    break;
+                }
+                frames.push(raw);
+            }
+
+            // Frames before the start of the range are never returned, so there is no point looking further back
+            let before_range = self.range.is_some_and(|(range_start, _)| ts <= range_start);
+            if !frames.is_empty() || start <= 1 || before_range {
+                return Ok(frames);
+            }
+
+            // The keyframe found was after `end` (e.g. because the frames are not evenly spaced), so look further back
+            start = end.saturating_sub(2 * (end - start));
+        }
+    }
+
+    /// Receive the frames currently available from the decoder and add them to the frame buffer (passing them through the filter graph if there is one),
+    /// until the buffer is full (see [`DecoderBuilder::max_buffered_frames`]).
+    ///
+    /// Frames are buffered as they were decoded, the conversion to the output format is only done once a frame is returned from [`VideoDecoder::next_frame`].
+    /// Returns [`DecodeError::LimitExceeded`] if a decoded frame is larger than the limits of the decoder.
+    fn receive_frames(&mut self) -> Result<(), DecodeError> {
+        let started = Instant::now();
+        let result = unsafe { self.receive_decoded() };
+        self.stats.decode_time += started.elapsed();
+        result
+    }
+
+    /// See [`VideoDecoder::receive_frames`], without timing the decoding
+    unsafe fn receive_decoded(&mut self) -> Result<(), DecodeError> {
+        // Frames already in the filter graph come before those still in the decoder
+        self.receive_filtered()?;
+
+        while !self.buffer_full() {
+            let result = ffmpeg::avcodec_receive_frame(self.codec_ctx, self.raw_frame);
+            if result == ffmpeg::AVERROR_EOF {
+                // The decoder has been fully flushed, so the filter graph can be too
+                if let Some(filter) = self.filter.as_mut().filter(|filter| !filter.is_flushed()) {
+                    filter.push(ptr::null_mut());
+                    self.receive_filtered()?;
+                }
+                break;
+            }
+            if result < 0 {
+                break;
+            }
+
+            self.stats.frames_decoded += 1;
+            // Checked before filtering, which may scale the frame down
+            if let Err(error) = self.check_limits(self.raw_frame) {
+                ffmpeg::av_frame_unref(self.raw_frame);
+                return Err(error);
+            }
+
+            match self.filter.as_mut() {
+                Some(filter) => {
+                    if !filter.push(self.raw_frame) {
+                        ffmpeg::av_frame_unref(self.raw_frame);
+                    }
+                    self.receive_filtered()?;
+                }
+                None => self.buffer_frame()?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the dimensions of a decoded frame, and the memory held by the decoder if every buffered and cached frame is the same size,
+    /// see [`DecoderBuilder::max_memory_bytes`]
+    unsafe fn check_limits(&self, frame: *const ffmpeg::AVFrame) -> Result<(), DecodeError> {
+        self.limits
+            .check_dimensions((*frame).width, (*frame).height)?;
+        // The frame format always comes from ffmpeg, so it is a valid pixel format
+        let format = mem::transmute::<ffi::c_int, ffmpeg::AVPixelFormat>((*frame).format);
+        self.limits
+            .check_memory(self.frame_memory((*frame).width, (*frame).height, format))
+    }
+
+    /// An estimate of the memory held by the buffered and cached frames, if the decoded frames are `width` by `height` in `pix_fmt`
+    fn frame_memory(&self, width: i32, height: i32, pix_fmt: ffmpeg::AVPixelFormat) -> u64 {
+        let decoded = match unsafe { ffmpeg::av_image_get_buffer_size(pix_fmt, width, height, 1) } {
+            size if size > 0 => size as u64,
+            // The pixel format is not known yet, so assume 8 bit 4:4:4
+            _ => width.max(0) as u64 * height.max(0) as u64 * 3,
+        };
+        let converted = self.dimensions.area() * self.output_format().bytes_per_pixel() as u64;
+        decoded * (self.buffer_capacity + self.backward_capacity) as u64
+            + converted * (self.cache.capacity() + 1) as u64
+    }
+
+    /// Receive the frames currently available from the filter graph and add them to the frame buffer, until the buffer is full
+    fn receive_filtered(&mut self) -> Result<(), DecodeError> {
+        unsafe {
+            while !self.buffer_full()
+                && self
+                    .filter
+                    .as_mut()
+                    .is_some_and(|filter| filter.pull(self.raw_frame))
+            {
+                self.buffer_frame()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the frame buffer holds as many frames as it is allowed to
+    fn buffer_full(&self) -> bool {
+        self.buffer.len() >= self.buffer_capacity
+    }
+
+    /// Add the frame in `raw_frame` to the frame buffer, unless it is outside the range being decoded or before the frame being seeked to
+    unsafe fn buffer_frame(&mut self) -> Result<(), DecodeError> {
+        let pts = (*self.raw_frame).best_effort_timestamp;
+
+        if let Some((start, end)) = self.range {
+            if pts != ffmpeg::AV_NOPTS_VALUE && pts < start {
+                // Decoded from the keyframe before the start of the range
+                ffmpeg::av_frame_unref(self.raw_frame);
+                self.stats.frames_dropped += 1;
+                return Ok(());
+            }
+
+            if pts != ffmpeg::AV_NOPTS_VALUE && pts > end {
+                // Past the end of the range, so treat this as the end of the input
+                ffmpeg::av_frame_unref(self.raw_frame);
+                self.stats.frames_dropped += 1;
+                self.draining = true;
+                return Ok(());
+            }
+        }
+
+        if self.index_from_pts || self.discard_before.is_some() {
+            // We have just seeked (or the decoder is leaving out frames), so the frame number must be worked out from the timestamp
+            self.index_from_pts = false;
+            if let Some(index) = self.pts_to_index(pts) {
+                self.index = index;
+            }
+        }
+
+        if let Some((target, index)) = self.restore_target {
+            if pts != ffmpeg::AV_NOPTS_VALUE && pts < target {
+                // Decoded from the keyframe before the restored position
+                ffmpeg::av_frame_unref(self.raw_frame);
+                self.stats.frames_dropped += 1;
+                return Ok(());
+            }
+
+            // The index was saved with the position, so it does not have to be worked out from the timestamp (which is only exact for a constant framerate)
+            self.index = index;
+            self.restore_target = None;
+            self.discard_before = None;
+        }
+
+        if let Some(target) = self.seek_target {
+            if self.index < target {
+                // Decoded from the keyframe before the frame we seeked to
+                ffmpeg::av_frame_unref(self.raw_frame);
+                self.stats.frames_dropped += 1;
+                self.index += 1;
+                return Ok(());
+            }
+
+            self.seek_target = None;
+            self.discard_before = None;
+        }
+
+        // Captions are sent a little at a time, so they must be decoded from every frame (even ones which are skipped)
+        if let Some(decoder) = self.captions.as_mut() {
+            if pts != ffmpeg::AV_NOPTS_VALUE {
+                let cues = decoder.decode(self.raw_frame, pts - self.start_time);
+                self.pending_captions.extend(cues);
+            }
+        }
+
+        if self.error_policy == ErrorPolicy::SkipFrame
+            && ((*self.raw_frame).flags & ffmpeg::AV_FRAME_FLAG_CORRUPT as i32 != 0
+                || (*self.raw_frame).decode_error_flags != 0)
+        {
+            ffmpeg::av_frame_unref(self.raw_frame);
+            self.stats.corrupt_frames += 1;
+            self.index += 1;
+            return Ok(());
+        }
+
+        if self
+            .sampling
+            .is_some_and(|sampling| !sampling.includes(self.index, self.framerate))
+        {
+            ffmpeg::av_frame_unref(self.raw_frame);
+            self.stats.frames_dropped += 1;
+            self.index += 1;
+            return Ok(());
+        }
+
+        // Take ownership of the frame data (this does not copy the data, only the reference to it)
+        let frame = ffmpeg::av_frame_alloc();
+        if frame.is_null() {
+            ffmpeg::av_frame_unref(self.raw_frame);
+            return Err(DecodeError::AllocationFailed);
+        }
+        ffmpeg::av_frame_move_ref(frame, self.raw_frame);
+
+        self.buffer.push_back(RawFrame {
+            frame,
+            index: self.index,
+            captions: mem::take(&mut self.pending_captions),
+        });
+        self.index += 1;
+        Ok(())
+    }
+
+    /// Convert a decoded frame to the output format
+    fn convert(&mut self, mut raw: RawFrame) -> Result<Frame, DecodeError> {
+        let mut frame = self.unconverted(&mut raw);
+        let converter = self
+            .converter
+            .as_mut()
+            .ok_or(DecodeError::ConverterInitFailed)?;
+        let started = Instant::now();
+        let (mut data, stride) = unsafe { converter.convert(raw.frame)? };
+        if let Some(overlay) = &self.overlay {
+            overlay.apply(&mut data, stride, frame.dimensions, frame.format);
+        }
+        self.stats.convert_time += started.elapsed();
+
+        frame.data = FrameData::new(data, self.shared_data);
+        frame.stride = stride;
+        Ok(frame)
+    }
+
+    /// Read everything but the pixel data of a decoded frame, the returned frame has no data until it is converted
+    fn unconverted(&self, raw: &mut RawFrame) -> Frame {
+        // Prefer the timecode embedded in the frame, falling back to counting from the starting timecode
+        let fps = self.framerate.round() as u32;
+        let timecode = unsafe { read_timecode(raw.frame, fps) }.or_else(|| {
+            let start = self.timecode?;
+            Some(start.add_frames(raw.index.saturating_sub(1) as u64))
+        });
+
+        Frame {
+            index: raw.index,
+            timestamp: self.pts_to_time(unsafe { (*raw.frame).best_effort_timestamp }),
+            duration: self.frame_duration(unsafe { (*raw.frame).duration }),
+            data: Vec::new().into(),
+            dimensions: self.dimensions,
+            format: self.output_format(),
+            stride: 0,
+            motion_vectors: unsafe { read_motion_vectors(raw.frame) },
+            captions: mem::take(&mut raw.captions),
+            timecode,
+            keyframe: unsafe { (*raw.frame).flags & ffmpeg::AV_FRAME_FLAG_KEY as i32 != 0 },
+        }
+    }
+
+    /// Decode the rest of the video (this never loops) on this thread, converting each frame and passing it to `f` on the Rayon thread pool.
+    ///
+    /// Frames are decoded in batches of twice the number of threads, and each batch is converted and processed before the next one is decoded,
+    /// so decoding does not overlap with processing. Returns the results of `f` in the order of the frames. This is much faster than calling `f` after each [`VideoDecoder::next_frame`]
+    /// when converting frames (especially with [`DecoderBuilder::scaling_quality`](crate::DecoderBuilder::scaling_quality)) or `f` itself is expensive,
+    /// e.g. turning frames into tensors or hashing them.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// let brightness = decoder
+    ///     .par_process(|frame| frame.data().iter().map(|&value| value as u64).sum::<u64>())
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_process<F, T>(&mut self, f: F) -> Result<Vec<T>, DecodeError>
+    where
+        F: Fn(Frame) -> T + Sync,
+        T: Send,
+    {
+        use rayon::prelude::*;
+
+        self.resume()?;
+        // Converters can not be shared between threads, so each thread creates its own
+        let new_converter = self
+            .converter
+            .as_ref()
+            .ok_or(DecodeError::ConverterInitFailed)?
+            .factory();
+
+        let batch_size = rayon::current_num_threads() * 2;
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut results = Vec::with_capacity(self.estimated_frames_left().unwrap_or(0));
+        loop {
+            // Frames are decoded in batches, then the whole batch is converted and processed in parallel before decoding continues
+            while batch.len() < batch_size {
+                match self.next_raw(false)? {
+                    Some(mut raw) => batch.push((self.unconverted(&mut raw), raw)),
+                    None => break,
+                }
+            }
+            if batch.is_empty() {
+                return Ok(results);
+            }
+
+            let overlay = self.overlay.as_ref();
+            let shared = self.shared_data;
+            let processed: Vec<Result<T, DecodeError>> = batch
+                .par_drain(..)
+                .map_init(&new_converter, |converter, (mut frame, raw)| {
+                    let converter = converter
+                        .as_mut()
+                        .map_err(|_| DecodeError::ConverterInitFailed)?;
+                    let (mut data, stride) = unsafe { converter.convert(raw.frame)? };
+                    if let Some(overlay) = overlay {
+                        overlay.apply(&mut data, stride, frame.dimensions, frame.format);
+                    }
+
+                    frame.data = FrameData::new(data, shared);
+                    frame.stride = stride;
+                    Ok(f(frame))
+                })
+                .collect();
+
+            for result in processed {
+                results.push(result?);
+            }
+        }
+    }
+
+    /// Skip the next `n` frames, returning the number of frames that were actually skipped.
+    ///
+    /// If `n` is negative, this rewinds so that the next frame returned is `n` frames before the most recently returned frame.
+    ///
+    /// Skipped frames are never converted. For large skips (of constant framerate video), this seeks past any keyframes between here and the target frame,
+    /// and the decoder leaves out skipped frames which no other frames depend on, so only the frames shortly before the target are fully decoded.
+    ///
+    /// Note that this function will never loop (even if [`VideoDecoder::will_loop`] is `true`),
+    /// so fewer frames than requested are skipped if the start or end of the video is reached.
+    pub fn skip(&mut self, n: isize) -> Result<usize, DecodeError> {
+        match n.cmp(&0) {
+            Ordering::Greater => {
+                self.resume()?;
+                if let Some(skipped) = self.fast_skip(n as usize)? {
+                    return Ok(skipped);
+                }
+
+                let mut skipped = 0;
+
+                // Skipped frames are never converted, so this is much cheaper than calling `next_frame`
+                while skipped < n as usize && self.next_raw(false)?.is_some() {
+                    skipped += 1;
+                }
+
+                Ok(skipped)
+            }
+            Ordering::Less => {
+                // The index of the most recently returned frame
+                let current = self.next_index().saturating_sub(1);
+                let target = current.saturating_sub(n.unsigned_abs()).max(1);
+
+                if self.seek_to_frame(target).is_err() {
+                    // The input is not seekable, so decode forward from the start instead
+                    if !self.loop_ctx() {
+                        return Err(DecodeError::UnableToSeek);
+                    }
+                    self.skip((target - 1) as isize)?;
+                }
+
+                Ok(current.saturating_sub(target))
+            }
+            Ordering::Equal => Ok(0),
+        }
+    }
+
+    /// Skip `n` frames forward without decoding every frame, see [`VideoDecoder::skip`].
+    ///
+    /// Returns `Ok(None)` if this is not possible, in which case nothing has changed and every frame must be decoded instead.
+    fn fast_skip(&mut self, n: usize) -> Result<Option<usize>, DecodeError> {
+        // Frame numbers are worked out from timestamps, which is only accurate for constant framerate video,
+        // and when sampling only the returned frames are counted
+        if n < FAST_SKIP_FRAMES || self.vfr || self.reverse.is_some() || self.sampling.is_some() {
+            return Ok(None);
+        }
+
+        let start = self.next_index();
+        let target = start + n;
+        // Without a framerate frame numbers can not be turned into timestamps
+        let (Some(start_ts), Some(target_ts)) =
+            (self.index_to_pts(start), self.index_to_pts(target))
+        else {
+            return Ok(None);
+        };
+
+        // Seeking only saves anything if there is a keyframe between here and the target
+        let seek = match self.keyframe_before(target_ts) {
+            Some(keyframe) => keyframe > start_ts,
+            None => n as f32 > self.framerate * 10.0,
+        };
+        if seek && !self.seek_ts(target_ts) {
+            return Ok(None);
+        }
+
+        // The number of frames skipped is counted from the frames decoded if the end of the video is reached,
+        // so frames can only be left out if the target is safely before the end
+        let end = match self.range {
+            Some((_, end)) => Some(end),
+            None => self.duration().map(|duration| self.time_to_pts(duration)),
+        };
+        let margin = self.framerate.ceil() as usize;
+        if end.is_some_and(|end| {
+            self.index_to_pts(target + margin)
+                .is_some_and(|ts| ts < end)
+        }) {
+            // Allow for rounding in the timestamps by stopping half a frame early
+            let half_frame = self.time_to_pts(Duration::from_secs_f64(0.5 / self.framerate as f64))
+                - self.time_to_pts(Duration::ZERO);
+            self.discard_before = Some(target_ts - half_frame);
+        }
+        self.seek_target = Some(target);
+
+        while let Some(raw) = self.next_raw(false)? {
+            if raw.index >= target {
+                self.buffer.push_front(raw);
+                return Ok(Some(target - start));
+            }
+        }
+
+        self.discard_before = None;
+        self.seek_target = None;
+        Ok(Some(self.index.saturating_sub(start)))
+    }
+
+    /// The times of the keyframes listed in the index of the source data, in order (empty if there is no index)
+    pub(crate) fn keyframe_times(&self) -> Vec<Duration> {
+        unsafe {
+            let stream = self.input.stream(self.stream_id as usize);
+            let count = ffmpeg::avformat_index_get_entries_count(stream);
+
+            let mut times: Vec<Duration> = (0..count)
+                .map(|i| ffmpeg::avformat_index_get_entry(stream, i))
+                .filter(|entry| {
+                    !entry.is_null() && (**entry).flags & ffmpeg::AVINDEX_KEYFRAME as i32 != 0
+                })
+                .filter_map(|entry| self.pts_to_time((*entry).timestamp))
+                .collect();
+            times.sort();
+            times.dedup();
+            times
+        }
+    }
+
+    /// The timestamp of the keyframe at or before a stream timestamp, `None` if the source data has no index to look it up in
+    fn keyframe_before(&self, ts: i64) -> Option<i64> {
+        unsafe {
+            let stream = self.input.stream(self.stream_id as usize);
+            let i = ffmpeg::av_index_search_timestamp(stream, ts, ffmpeg::AVSEEK_FLAG_BACKWARD);
+            if i < 0 {
+                return None;
+            }
+
+            let entry = ffmpeg::avformat_index_get_entry(stream, i);
+            (!entry.is_null()).then(|| (*entry).timestamp)
+        }
+    }
+
+    /// The index of the next frame which will be returned
+    fn next_index(&self) -> usize {
+        if let Some(reverse) = &self.reverse {
+            return reverse
+                .frames
+                .last()
+                .map_or(reverse.end.saturating_sub(1), |raw| raw.index);
+        }
+
+        match self
+            .resume_at
+            .or(self.seek_target)
+            .or(self.restore_target.map(|(_, index)| index))
+        {
+            Some(index) => index,
+            None => self.buffer.front().map_or(self.index, |frame| frame.index),
+        }
+    }
+
+    /// Seek to a specific frame, so that it is the next frame returned by [`VideoDecoder::next_frame`].
+    ///
+    /// This seeks to the nearest keyframe at or before the frame then decodes forward to it,
+    /// so it is much faster than [`VideoDecoder::skip`] for large distances (and in both directions).
+    /// Frame numbers start at 1, an index of 0 is treated as 1.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// decoder.seek_to_frame(100).unwrap();
+    /// assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 100);
+    /// # }
+    /// ```
+    pub fn seek_to_frame(&mut self, index: usize) -> Result<(), DecodeError> {
+        let index = index.max(1);
+        let ts = self.index_to_pts(index).ok_or(DecodeError::UnableToSeek)?;
+
+        if !self.seek_ts(ts) {
+            return Err(DecodeError::UnableToSeek);
+        }
+
+        self.seek_target = Some(index);
+        Ok(())
+    }
+
+    /// Get a specific frame, so that [`VideoDecoder::next_frame`] continues from the frame after it.
+    ///
+    /// Recently returned frames are kept in a cache (see [`DecoderBuilder::frame_cache`]),
+    /// so going back and forth over the same frames does not require decoding them again.
+    /// Frame numbers start at 1, an index of 0 is treated as 1. Returns `Ok(None)` if the frame is past the end of the video.
+    pub fn get_frame(&mut self, index: usize) -> Result<Option<Frame>, DecodeError> {
+        let index = index.max(1);
+
+        if let Some(frame) = self.cache.get(index) {
+            self.resume_at = Some(index + 1);
+            return Ok(Some(frame));
+        }
+
+        if index != self.next_index() {
+            self.seek_to_frame(index)?;
+        }
+        self.resume()?;
+
+        let frame = match self.next_raw(false)? {
+            Some(raw) => self.convert(raw)?,
+            None => return Ok(None),
+        };
+        self.cache.insert(&frame);
+
+        Ok(Some(frame))
+    }
+
+    /// Save the position of the decoder (the index and timestamp of the next frame [`VideoDecoder::next_frame`] will return),
+    /// so decoding can later be resumed from it with [`VideoDecoder::restore_position`], e.g. for bookmarks or to continue where a user left off.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// decoder.seek_to_frame(100).unwrap();
+    /// let position = decoder.save_position();
+    ///
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// decoder.restore_position(&position).unwrap();
+    /// assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 100);
+    /// # }
+    /// ```
+    pub fn save_position(&self) -> DecoderPosition {
+        let index = self.next_index();
+        let buffered = self
+            .buffer
+            .front()
+            .filter(|raw| raw.index == index && self.resume_at.is_none() && self.reverse.is_none())
+            .map(|raw| unsafe { (*raw.frame).best_effort_timestamp })
+            .filter(|&pts| pts != ffmpeg::AV_NOPTS_VALUE);
+        // The timestamp is only worked out from the index if the next frame has not been decoded yet, and did not follow a returned frame
+        let pts = self
+            .restore_target
+            .filter(|&(_, target)| target == index)
+            .map(|(pts, _)| pts)
+            .or(buffered)
+            .or(self
+                .next_pts
+                .filter(|&(next, _)| next == index)
+                .map(|(_, pts)| pts))
+            .or_else(|| self.index_to_pts(index))
+            .unwrap_or(ffmpeg::AV_NOPTS_VALUE);
+
+        DecoderPosition {
+            index,
+            pts,
+            timestamp: self.pts_to_time(pts),
+        }
+    }
+
+    /// Resume decoding from a position saved with [`VideoDecoder::save_position`], so that the frame at that position is the next frame returned by [`VideoDecoder::next_frame`].
+    ///
+    /// This seeks using the saved timestamp, so the frames are found exactly even if the video has a variable framerate,
+    /// and gives them the saved indices rather than counting frames from the start of the video.
+    pub fn restore_position(&mut self, position: &DecoderPosition) -> Result<(), DecodeError> {
+        if position.index == self.next_index() {
+            return Ok(());
+        }
+
+        // The position has no timestamp if it was saved before the first frame of a video without a framerate
+        if position.pts == ffmpeg::AV_NOPTS_VALUE || !self.seek_ts(position.pts) {
+            return Err(DecodeError::UnableToSeek);
+        }
+        self.restore_target = Some((position.pts, position.index.max(1)));
+        self.discard_before = Some(position.pts);
+        Ok(())
+    }
+
+    /// Get the frame which is shown at a specific time (relative to the start of the video), see [`VideoDecoder::get_frame`]
+    pub fn get_frame_at(&mut self, timestamp: Duration) -> Result<Option<Frame>, DecodeError> {
+        // Allow for a small amount of rounding error in the timestamp
+        let index = (timestamp.as_secs_f64() * self.framerate as f64 + 1e-3).floor() as usize + 1;
+        self.get_frame(index)
+    }
+
+    /// Get the dimensions of the video
+    #[inline]
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    /// The shape of each pixel as `(width, height)`, e.g. `(4, 3)` for pixels which are wider than they are tall. `None` if it is not known (usually meaning square pixels).
+    ///
+    /// This is set for anamorphic video, which must be stretched to be displayed correctly (see [`VideoDecoder::display_dimensions`]).
+    pub fn sample_aspect_ratio(&self) -> Option<(u32, u32)> {
+        self.sample_aspect_ratio
+            .map(|aspect| (aspect.num as u32, aspect.den as u32))
+    }
+
+    /// The dimensions the video should be displayed at, taking non-square pixels into account (see [`VideoDecoder::sample_aspect_ratio`]).
+    ///
+    /// The width is stretched to the correct shape, so this is the same as [`VideoDecoder::dimensions`] for video with square pixels.
+    pub fn display_dimensions(&self) -> Dimensions {
+        match self.sample_aspect_ratio {
+            Some(aspect) => Dimensions {
+                width: (self.dimensions.width as u64 * aspect.num as u64 / aspect.den as u64)
+                    as u32,
+                height: self.dimensions.height,
+            },
+            None => self.dimensions,
+        }
+    }
+
+    /// The layout of the pixels in the frames returned by the decoder, see [`DecoderBuilder::output_format`]
+    #[inline]
+    pub fn output_format(&self) -> PixelFormat {
+        self.converter
+            .as_ref()
+            .map_or(PixelFormat::default(), |converter| converter.format())
+    }
+
+    /// Get the framerate of the video (in frames-per-second)
+    ///
+    /// This is the base framerate of the stream, the lowest framerate which can represent every timestamp accurately.
+    /// For variable framerate video (see [`VideoDecoder::is_vfr`]) this is the highest framerate of the stream,
+    /// so use [`VideoDecoder::avg_framerate`] to estimate the number of frames, and the timestamp of each frame (see [`Frame::timestamp`]) for timing.
+    ///
+    /// This is 0 if the framerate is not known (e.g. with [`DecoderBuilder::fast_open`] for some containers),
+    /// in which case seeking to a frame number fails with [`DecodeError::UnableToSeek`].
+    #[inline]
+    pub fn framerate(&self) -> f32 {
+        self.framerate
+    }
+
+    /// The average framerate of the video (in frames-per-second), `None` if it is not known
+    ///
+    /// For constant framerate video this is the same as [`VideoDecoder::framerate`].
+    #[inline]
+    pub fn avg_framerate(&self) -> Option<f32> {
+        self.avg_framerate
+    }
+
+    /// Whether the video has a variable framerate, this is common for screen recordings and videos from phones.
+    ///
+    /// This is a hint based on the stream information, frames may not be evenly spaced even if this is `false`.
+    /// Frame indices of variable framerate video are based on [`VideoDecoder::framerate`], so after seeking they may not match the number of frames decoded.
+    #[inline]
+    pub fn is_vfr(&self) -> bool {
+        self.vfr
+    }
+
+    /// The duration of the video stream, if known.
+    ///
+    /// If the duration of the video stream is not known, this falls back to the duration of the whole source data.
+    pub fn duration(&self) -> Option<Duration> {
+        unsafe {
+            let stream = self.input.stream(self.stream_id as usize);
+            ts_to_duration((*stream).duration, (*stream).time_base)
+                .or_else(|| ts_to_duration((*self.input.ctx).duration, ffmpeg::AV_TIME_BASE_Q))
+        }
+    }
+
+    /// List every stream in the source data (not just the video stream being decoded)
+    pub fn streams(&self) -> Vec<StreamInfo> {
+        unsafe { read_streams(self.input.ctx) }
+    }
+
+    /// The encoded data of the cover art embedded in the source data (usually a JPEG or PNG image), `None` if there is no cover art
+    ///
+    /// Cover art is stored as a video stream with a single picture, which is never selected as the video stream to decode.
+    /// Use [`cover_art`](crate::cover_art) to read the cover art of audio files.
+    pub fn cover_art(&self) -> Option<Vec<u8>> {
+        self.input.cover_art()
+    }
+
+    /// Decode the cover art embedded in the source data, `None` if there is no cover art or it is in a format not supported by the image crate
+    ///
+    /// See [`VideoDecoder::cover_art`] for the encoded data.
+    #[cfg(feature = "image")]
+    pub fn cover_art_image(&self) -> Option<image::DynamicImage> {
+        image::load_from_memory(&self.cover_art()?).ok()
+    }
+
+    /// List the chapters of the source data in order, this is empty if it does not have any chapters
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// for chapter in decoder.chapters() {
+    ///     println!("{:?}: {:?}", chapter.title(), chapter.start());
+    /// }
+    /// # }
+    /// ```
+    pub fn chapters(&self) -> Vec<Chapter> {
+        unsafe { read_chapters(self.input.ctx) }
+    }
+
+    /// The programs (channels) of the source data, this is usually empty unless the source is a broadcast format such as MPEG-TS.
+    ///
+    /// A program can be selected with [`DecoderBuilder::program`].
+    pub fn programs(&self) -> Vec<Program> {
+        unsafe { read_programs(self.input.ctx) }
+    }
+
+    /// The index of the stream being decoded, see [`VideoDecoder::streams`]
+    #[inline]
+    pub fn stream_index(&self) -> usize {
+        self.stream_id as usize
+    }
+
+    /// The (short) name of the codec used by the video stream, e.g. `h264`
+    pub fn codec_name(&self) -> String {
+        unsafe {
+            str_from_ptr(ffmpeg::avcodec_get_name((*self.codecpar()).codec_id)).unwrap_or_default()
+        }
+    }
+
+    /// The name of the decoder implementation being used, e.g. `h264` or `libdav1d` (see [`DecoderBuilder::decoder_name`])
+    pub fn decoder_name(&self) -> String {
+        unsafe { str_from_ptr((*(*self.codec_ctx).codec).name).unwrap_or_default() }
+    }
+
+    /// The (short) name of the container format of the source data, e.g. `matroska,webm`
+    ///
+    /// Some demuxers handle multiple related formats, in which case this is a comma separated list of all of them.
+    pub fn container_format(&self) -> String {
+        unsafe {
+            let format = (*self.input.ctx).iformat;
+            match format.is_null() {
+                true => String::new(),
+                false => str_from_ptr((*format).name).unwrap_or_default(),
+            }
+        }
+    }
+
+    /// The metadata tags of the source data as a whole, e.g. `title`, `artist`, `creation_time` or `encoder`
+    ///
+    /// The available tags depend on the container format and the program which created the file, see also [`VideoDecoder::stream_metadata`].
+    pub fn metadata(&self) -> HashMap<String, String> {
+        unsafe { dict_to_map((*self.input.ctx).metadata) }
+    }
+
+    /// The timecode of the first frame of the video, from the metadata of the source data (e.g. the `tmcd` track of a QuickTime file)
+    ///
+    /// See [`Frame::timecode`] for the timecode of each frame.
+    pub fn timecode(&self) -> Option<Timecode> {
+        self.timecode
+    }
+
+    /// The metadata tags of the video stream being decoded, e.g. `language`, `handler_name` or `creation_time`
+    pub fn stream_metadata(&self) -> HashMap<String, String> {
+        unsafe { dict_to_map((*self.input.stream(self.stream_id as usize)).metadata) }
+    }
+
+    /// The name of the pixel format the video stream is decoded to (before it is converted to RGB), e.g. `yuv420p`
+    pub fn pixel_format(&self) -> Option<String> {
+        unsafe { str_from_ptr(ffmpeg::av_get_pix_fmt_name((*self.codec_ctx).pix_fmt)) }
+    }
+
+    /// The name of the color space (the matrix used to convert between YUV and RGB) of the video stream, e.g. `bt709`, `None` if it is not specified
+    pub fn color_space(&self) -> Option<String> {
+        unsafe {
+            match (*self.codec_ctx).colorspace {
+                ffmpeg::AVColorSpace::AVCOL_SPC_UNSPECIFIED => None,
+                colorspace => str_from_ptr(ffmpeg::av_color_space_name(colorspace)),
+            }
+        }
+    }
+
+    /// The name of the color range of the video stream, `None` if it is not specified.
+    ///
+    /// This is either `tv` (limited range, the most common) or `pc` (full range). Frames are converted to full range RGB either way.
+    pub fn color_range(&self) -> Option<String> {
+        unsafe {
+            match (*self.codec_ctx).color_range {
+                ffmpeg::AVColorRange::AVCOL_RANGE_UNSPECIFIED => None,
+                range => str_from_ptr(ffmpeg::av_color_range_name(range)),
+            }
+        }
+    }
+
+    /// The name of the color primaries (the gamut) of the video stream, e.g. `bt709` or `bt2020`, `None` if it is not specified
+    pub fn color_primaries(&self) -> Option<String> {
+        unsafe {
+            match (*self.codec_ctx).color_primaries {
+                ffmpeg::AVColorPrimaries::AVCOL_PRI_UNSPECIFIED => None,
+                primaries => str_from_ptr(ffmpeg::av_color_primaries_name(primaries)),
+            }
+        }
+    }
+
+    /// The name of the transfer characteristics (the gamma curve) of the video stream, e.g. `bt709`, `smpte2084` (HDR10) or `arib-std-b67` (HLG),
+    /// `None` if it is not specified
+    pub fn transfer_characteristics(&self) -> Option<String> {
+        unsafe {
+            match (*self.codec_ctx).color_trc {
+                ffmpeg::AVColorTransferCharacteristic::AVCOL_TRC_UNSPECIFIED => None,
+                trc => str_from_ptr(ffmpeg::av_color_transfer_name(trc)),
+            }
+        }
+    }
+
+    /// Whether the decoded frames have an alpha channel (transparency), see [`DecoderBuilder::keep_alpha`]
+    pub fn has_alpha(&self) -> bool {
+        unsafe { has_alpha((*self.codec_ctx).pix_fmt) }
+    }
+
+    /// Whether the video stream uses an HDR transfer function (HDR10 or HLG), see [`DecoderBuilder::tonemap`]
+    pub fn is_hdr(&self) -> bool {
+        unsafe {
+            matches!(
+                (*self.codec_ctx).color_trc,
+                ffmpeg::AVColorTransferCharacteristic::AVCOL_TRC_SMPTE2084
+                    | ffmpeg::AVColorTransferCharacteristic::AVCOL_TRC_ARIB_STD_B67
+            )
+        }
+    }
+
+    /// The average bit rate of the video stream (in bits per second), if known.
+    ///
+    /// If the bit rate of the video stream is not known, this falls back to the bit rate of the whole source data.
+    pub fn bit_rate(&self) -> Option<u64> {
+        unsafe {
+            [(*self.codecpar()).bit_rate, (*self.input.ctx).bit_rate]
+                .into_iter()
+                .find(|&bit_rate| bit_rate > 0)
+                .map(|bit_rate| bit_rate as u64)
+        }
+    }
+
+    /// The name of the codec profile used by the video stream (e.g. `High` for h264), if known
+    pub fn profile(&self) -> Option<String> {
+        unsafe {
+            let codecpar = self.codecpar();
+            str_from_ptr(ffmpeg::avcodec_profile_name(
+                (*codecpar).codec_id,
+                (*codecpar).profile,
+            ))
+        }
+    }
+
+    /// Whether the video stream is interlaced, see [`DecoderBuilder::deinterlace`].
+    ///
+    /// This is based on the field order reported by the source data, so it may be `false` for sources which only mark individual frames as interlaced.
+    pub fn is_interlaced(&self) -> bool {
+        !matches!(
+            unsafe { (*self.codecpar()).field_order },
+            ffmpeg::AVFieldOrder::AV_FIELD_UNKNOWN | ffmpeg::AVFieldOrder::AV_FIELD_PROGRESSIVE
+        )
+    }
+
+    /// Counters describing the work done by the decoder so far
+    #[inline]
+    pub fn stats(&self) -> DecoderStats {
+        self.stats
+    }
+
+    /// The handle which cancels the blocking operations of this decoder, see [`DecoderBuilder::handle`].
+    ///
+    /// If no handle was passed to the builder, this is a new handle unique to this decoder.
+    pub fn handle(&self) -> DecoderHandle {
+        self.input.handle()
+    }
+
+    /// The codec parameters of the stream being decoded
+    fn codecpar(&self) -> *mut ffmpeg::AVCodecParameters {
+        unsafe { (*self.input.stream(self.stream_id as usize)).codecpar }
+    }
+
+    /// Check whether the decoder will loop once reaching the end of the source data
+    ///
+    /// This will be whatever value was passed to [`VideoDecoder::new`],
+    /// or if a [`LoopMode::Count`] was used, whether there are any loops remaining.
+    /// ```rust
+    /// # fn main() {
+    /// # use ffmpeg_video_decoder::VideoDecoder;
+    ///  let decoder = VideoDecoder::new("test.mp4", true).unwrap();
+    ///  assert_eq!(decoder.will_loop(), true);
+    /// # }
+    /// ```
+    /// ```rust
+    /// # fn main() {
+    /// # use ffmpeg_video_decoder::VideoDecoder;
+    ///  let decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    ///  assert_eq!(decoder.will_loop(), false);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn will_loop(&self) -> bool {
+        self.loops_remaining != Some(0)
+    }
+
+    /// The number of times the decoder will still loop back to the start, `None` if it will loop forever
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// # use ffmpeg_video_decoder::{LoopMode, VideoDecoder};
+    ///  let decoder = VideoDecoder::builder("test.mp4")
+    ///      .loop_mode(LoopMode::Count(3))
+    ///      .build()
+    ///      .unwrap();
+    ///  assert_eq!(decoder.loops_remaining(), Some(2));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn loops_remaining(&self) -> Option<u32> {
+        self.loops_remaining
+    }
+
+    /// Go back to the start of the video (or the start of the [`DecoderBuilder::range`]), so the next frame returned is the first frame.
+    ///
+    /// This is much cheaper than opening a new decoder, as the input does not need to be probed again.
+    /// The number of loops remaining (see [`VideoDecoder::loops_remaining`]) is not changed.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// while let Some(frame) = decoder.next_frame().unwrap() {}
+    ///
+    /// decoder.rewind().unwrap();
+    /// assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 1);
+    /// # }
+    /// ```
+    pub fn rewind(&mut self) -> Result<(), DecodeError> {
+        match self.loop_ctx() {
+            true => Ok(()),
+            false => Err(DecodeError::UnableToSeek),
+        }
+    }
+
+    /// Set whether the decoder should loop back to the start once reaching the end of the source data, see [`VideoDecoder::will_loop`].
+    ///
+    /// This replaces the [`LoopMode`] the decoder was created with, so `true` loops forever.
+    pub fn set_looping(&mut self, should_loop: bool) {
+        self.ping_pong = false;
+        self.loops_remaining = match should_loop {
+            true => None,
+            false => Some(0),
+        };
+    }
+
+    /// Loop the internal decoder context, this will reset the video to the first frame.
+    ///
+    /// Returns `false` if the input could not be seeked.
+    fn loop_ctx(&mut self) -> bool {
+        if let Some((start, _)) = self.range {
+            return self.seek_ts(start);
+        }
+
+        // Seek stream to start
+        let seeked = self.input.rewind(self.stream_id) >= 0;
+        unsafe {
+            // Discard any frames still held by the decoder
+            ffmpeg::avcodec_flush_buffers(self.codec_ctx);
+            if let Some(captions) = self.captions.as_mut() {
+                captions.flush();
+            }
+            self.pending_captions.clear();
+            if let Some(audio) = self.audio.as_mut() {
+                audio.flush();
+            }
+        }
+        self.reset_filter();
+
+        // Reset index
+        self.index = 1;
+        self.buffer.clear();
+        self.data_packets.clear();
+        self.audio_frames.clear();
+        self.draining = false;
+        self.reverse = None;
+        self.index_from_pts = false;
+        self.seek_target = None;
+        self.discard_before = None;
+        self.restore_target = None;
+        self.next_pts = None;
+        self.resume_at = None;
+        seeked
+    }
+
+    /// Seek to the keyframe at or before the given time, so that it is the next frame returned by [`VideoDecoder::next_frame`].
+    ///
+    /// This avoids decoding any frames between the keyframe and the given time, but the frame returned may be some distance before it.
+    pub(crate) fn seek_to_keyframe(&mut self, time: Duration) -> Result<(), DecodeError> {
+        match self.seek_ts(self.time_to_pts(time)) {
+            true => Ok(()),
+            false => Err(DecodeError::UnableToSeek),
+        }
+    }
+
+    /// Seek to the keyframe at or before the given stream timestamp, returns `false` if the seek failed
+    fn seek_ts(&mut self, ts: i64) -> bool {
+        if self
+            .input
+            .seek_frame(self.stream_id, ts, ffmpeg::AVSEEK_FLAG_BACKWARD)
+            < 0
+        {
+            return false;
+        }
+
+        unsafe {
+            // Discard any frames still held by the decoder
+            ffmpeg::avcodec_flush_buffers(self.codec_ctx);
+            if let Some(captions) = self.captions.as_mut() {
+                captions.flush();
+            }
+            self.pending_captions.clear();
+            if let Some(audio) = self.audio.as_mut() {
+                audio.flush();
+            }
+        }
+        self.reset_filter();
+
+        self.buffer.clear();
+        self.data_packets.clear();
+        self.audio_frames.clear();
+        self.draining = false;
+        self.reverse = None;
+        self.index_from_pts = true;
+        self.seek_target = None;
+        self.discard_before = None;
+        self.restore_target = None;
+        self.next_pts = None;
+        self.resume_at = None;
+        true
+    }
+
+    /// Discard any frames still held by the filter graph
+    fn reset_filter(&mut self) {
+        if let Some(filter) = self.filter.as_mut() {
+            // The graph was built successfully when the decoder was opened, so this can only fail if we run out of memory
+            // (in which case the graph is left empty and no more frames are returned)
+            let _ = filter.build();
+        }
+    }
+
+    /// Convert a stream timestamp into the time since the start of the video
+    fn pts_to_time(&self, pts: i64) -> Option<Duration> {
+        if pts == ffmpeg::AV_NOPTS_VALUE {
+            return None;
+        }
+
+        ts_to_duration(pts - self.start_time, self.time_base)
+    }
+
+    /// The duration of a frame from its stream duration, falling back to the framerate if the source data does not contain it
+    fn frame_duration(&self, duration: i64) -> Option<Duration> {
+        match duration > 0 {
+            true => ts_to_duration(duration, self.time_base),
+            false => (self.framerate > 0.0).then(|| Duration::from_secs_f32(1.0 / self.framerate)),
+        }
+    }
+
+    /// Convert a time since the start of the video into a stream timestamp
+    fn time_to_pts(&self, time: Duration) -> i64 {
+        duration_to_ts(time, self.time_base).saturating_add(self.start_time)
+    }
+
+    /// The stream timestamp of a frame number (assuming a constant framerate), the inverse of [`VideoDecoder::pts_to_index`].
+    ///
+    /// Returns `None` if the framerate is not known.
+    fn index_to_pts(&self, index: usize) -> Option<i64> {
+        if self.framerate <= 0.0 {
+            return None;
+        }
+        let time = Duration::from_secs_f64(index.saturating_sub(1) as f64 / self.framerate as f64);
+        Some(self.time_to_pts(time))
+    }
+
+    /// Work out the frame number of a stream timestamp
+    fn pts_to_index(&self, pts: i64) -> Option<usize> {
+        let time = self.pts_to_time(pts)?;
+        Some((time.as_secs_f64() * self.framerate as f64).round() as usize + 1)
+    }
+}
+
+impl Drop for VideoDecoder {
+    fn drop(&mut self) {
+        unsafe {
+            ffmpeg::av_frame_free(&mut self.raw_frame);
+            ffmpeg::av_packet_unref(&mut self.packet);
             ffmpeg::avcodec_free_context(&mut self.codec_ctx);
-            ffmpeg::avformat_close_input(&mut self.input_ctx);
         }
     }
 }
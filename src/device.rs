@@ -0,0 +1,229 @@
+//! Capture devices (e.g. webcams), read through the ffmpeg avdevice layer
+
+use super::DecodeError;
+use crate::c::str_from_ptr;
+use std::sync::Once;
+use std::{ffi, ptr};
+
+/// The input format used for cameras on this platform
+#[cfg(target_os = "linux")]
+const CAMERA_FORMAT: &str = "v4l2";
+#[cfg(target_os = "windows")]
+const CAMERA_FORMAT: &str = "dshow";
+#[cfg(target_os = "macos")]
+const CAMERA_FORMAT: &str = "avfoundation";
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+const CAMERA_FORMAT: &str = "v4l2";
+
+/// The input format used for screen capture on this platform
+#[cfg(target_os = "windows")]
+const SCREEN_FORMAT: &str = "gdigrab";
+#[cfg(target_os = "macos")]
+const SCREEN_FORMAT: &str = "avfoundation";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const SCREEN_FORMAT: &str = "x11grab";
+
+/// A capture device such as a webcam, which can be used as a [`VideoSource`](crate::VideoSource)
+///
+/// ```rust,no_run
+/// # fn main() {
+/// use ffmpeg_video_decoder::{Device, VideoDecoder};
+///
+/// let camera = Device::new("/dev/video0").resolution(1280, 720).framerate(30.0);
+/// let mut decoder = VideoDecoder::new(camera, false).unwrap();
+/// while let Some(frame) = decoder.next_frame().unwrap() {
+///     // do something with the frame
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device {
+    format: String,
+    name: String,
+    options: Vec<(String, String)>,
+    /// The region to crop the frames to, for devices which can not capture a region themselves
+    crop: Option<(u32, u32, u32, u32)>,
+}
+
+impl Device {
+    /// A camera, using the usual capture api of the platform (`v4l2` on linux, `dshow` on windows, `avfoundation` on macOS).
+    ///
+    /// The name is the device path on linux (e.g. `/dev/video0`), and the name of the camera elsewhere (see [`list_devices`]).
+    pub fn new(name: &str) -> Self {
+        let name = match CAMERA_FORMAT {
+            // dshow can also open audio devices, so the type must be given
+            "dshow" => format!("video={name}"),
+            _ => name.to_string(),
+        };
+        Device::with_format(CAMERA_FORMAT, &name)
+    }
+
+    /// A device read with a specific ffmpeg input device, e.g. `("v4l2", "/dev/video0")` or `("lavfi", "testsrc")`.
+    ///
+    /// See <https://ffmpeg.org/ffmpeg-devices.html#Input-Devices> for the available devices and the names they expect.
+    pub fn with_format(format: &str, name: &str) -> Self {
+        Device {
+            format: format.to_string(),
+            name: name.to_string(),
+            options: Vec::new(),
+            crop: None,
+        }
+    }
+
+    /// Capture a screen, using `x11grab` on linux, `gdigrab` on windows and `avfoundation` on macOS.
+    ///
+    /// `display` is the X11 display number on linux (usually `0`), and the index of the screen on macOS.
+    /// On windows the whole desktop (every monitor) is captured, so use [`Device::region`] to capture a single monitor.
+    ///
+    /// ```rust,no_run
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::{Device, VideoDecoder};
+    ///
+    /// let screen = Device::screen(0).region(0, 0, 1920, 1080).framerate(30.0);
+    /// let mut decoder = VideoDecoder::new(screen, false).unwrap();
+    /// let screenshot = decoder.next_frame().unwrap().unwrap();
+    /// # }
+    /// ```
+    pub fn screen(display: usize) -> Self {
+        let name = match SCREEN_FORMAT {
+            "gdigrab" => "desktop".to_string(),
+            "avfoundation" => format!("Capture screen {display}"),
+            _ => format!(":{display}"),
+        };
+        Device::with_format(SCREEN_FORMAT, &name)
+    }
+
+    /// Only capture part of the screen, starting `x` pixels from the left and `y` pixels from the top.
+    ///
+    /// Devices which can not capture a region themselves (including `avfoundation`) capture the whole screen,
+    /// which is then cropped by the decoder.
+    pub fn region(mut self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        match self.format.as_str() {
+            "x11grab" => {
+                // The offset is part of the display name, e.g. `:0+10,20`
+                let display = self.name.split('+').next().unwrap_or_default();
+                self.name = format!("{display}+{x},{y}");
+                self.resolution(width, height)
+            }
+            "gdigrab" => self
+                .option("offset_x", &x.to_string())
+                .option("offset_y", &y.to_string())
+                .resolution(width, height),
+            _ => {
+                self.crop = Some((x, y, width, height));
+                self
+            }
+        }
+    }
+
+    /// Whether the mouse cursor should be drawn on a captured screen
+    pub fn cursor(self, cursor: bool) -> Self {
+        let key = match self.format.as_str() {
+            "avfoundation" => "capture_cursor",
+            _ => "draw_mouse",
+        };
+        self.option(key, &(cursor as u8).to_string())
+    }
+
+    /// The size of the frames to capture, the device must support this size
+    pub fn resolution(self, width: u32, height: u32) -> Self {
+        self.option("video_size", &format!("{width}x{height}"))
+    }
+
+    /// The number of frames to capture per second, the device must support this rate
+    pub fn framerate(self, framerate: f32) -> Self {
+        self.option("framerate", &framerate.to_string())
+    }
+
+    /// Set an option of the input device, e.g. `input_format` for `v4l2` (see <https://ffmpeg.org/ffmpeg-devices.html>)
+    pub fn option(mut self, key: &str, value: &str) -> Self {
+        self.options.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// The name of the ffmpeg input device
+    #[inline]
+    pub fn format(&self) -> &str {
+        &self.format
+    }
+
+    /// The name of the device, as passed to ffmpeg
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The options of the input device
+    pub(crate) fn options(&self) -> &[(String, String)] {
+        &self.options
+    }
+
+    /// The filter which crops the frames to the captured region, `None` if the device captures the region itself
+    pub(crate) fn crop_filter(&self) -> Option<String> {
+        self.crop
+            .map(|(x, y, width, height)| format!("crop={width}:{height}:{x}:{y}"))
+    }
+}
+
+/// A device found by [`list_devices`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceInfo {
+    name: String,
+    description: String,
+}
+
+impl DeviceInfo {
+    /// The name of the device, this is what should be passed to [`Device::new`]
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// A human readable description of the device
+    #[inline]
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// The cameras which are currently connected, see [`Device::new`]
+///
+/// Fails with [`DecodeError::UnknownFormat`] if ffmpeg was built without the capture api of this platform,
+/// or [`DecodeError::UnableToOpenInput`] if the devices can not be listed.
+pub fn list_devices() -> Result<Vec<DeviceInfo>, DecodeError> {
+    register_devices();
+    unsafe {
+        let format = ffi::CString::new(CAMERA_FORMAT).map_err(|_| DecodeError::UnknownFormat)?;
+        let format = ffmpeg::av_find_input_format(format.as_ptr());
+        if format.is_null() {
+            return Err(DecodeError::UnknownFormat);
+        }
+
+        let mut list = ptr::null_mut();
+        if ffmpeg::avdevice_list_input_sources(format, ptr::null(), ptr::null_mut(), &mut list) < 0
+        {
+            ffmpeg::avdevice_free_list_devices(&mut list);
+            return Err(DecodeError::UnableToOpenInput);
+        }
+
+        let devices = (0..(*list).nb_devices as usize)
+            .map(|i| {
+                let device = *(*list).devices.add(i);
+                DeviceInfo {
+                    name: str_from_ptr((*device).device_name).unwrap_or_default(),
+                    description: str_from_ptr((*device).device_description).unwrap_or_default(),
+                }
+            })
+            .collect();
+        ffmpeg::avdevice_free_list_devices(&mut list);
+
+        Ok(devices)
+    }
+}
+
+/// Make the input devices available to ffmpeg, this only needs to be done once
+pub(crate) fn register_devices() {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| unsafe { ffmpeg::avdevice_register_all() });
+}
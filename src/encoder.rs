@@ -0,0 +1,470 @@
+use super::{Dimensions, EncodeError, Frame, PixelFormat};
+use crate::c::{path_to_raw, Dictionary};
+use std::ffi::{self, CString};
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+/// The codec to encode video with
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VideoCodec {
+    /// H.264 (AVC), the most widely supported codec (requires ffmpeg to be built with an h264 encoder such as libx264)
+    #[default]
+    H264,
+    /// H.265 (HEVC)
+    H265,
+    /// VP9, usually used in WebM
+    Vp9,
+    /// AV1
+    Av1,
+    /// MPEG-4 Part 2, this encoder is built into ffmpeg so it is always available
+    Mpeg4,
+    /// GIF, this is limited to a fixed palette of 256 colours (see [`GifWriter`](crate::GifWriter))
+    Gif,
+    /// Animated PNG, this is lossless and keeps the alpha channel (see [`ApngWriter`](crate::ApngWriter))
+    Apng,
+}
+
+impl VideoCodec {
+    pub(crate) fn as_raw(&self) -> ffmpeg::AVCodecID {
+        match self {
+            VideoCodec::H264 => ffmpeg::AVCodecID::AV_CODEC_ID_H264,
+            VideoCodec::H265 => ffmpeg::AVCodecID::AV_CODEC_ID_HEVC,
+            VideoCodec::Vp9 => ffmpeg::AVCodecID::AV_CODEC_ID_VP9,
+            VideoCodec::Av1 => ffmpeg::AVCodecID::AV_CODEC_ID_AV1,
+            VideoCodec::Mpeg4 => ffmpeg::AVCodecID::AV_CODEC_ID_MPEG4,
+            VideoCodec::Gif => ffmpeg::AVCodecID::AV_CODEC_ID_GIF,
+            VideoCodec::Apng => ffmpeg::AVCodecID::AV_CODEC_ID_APNG,
+        }
+    }
+
+    /// The pixel format frames are converted to before being encoded
+    fn pixel_format(&self) -> ffmpeg::AVPixelFormat {
+        match self {
+            VideoCodec::Gif => ffmpeg::AVPixelFormat::AV_PIX_FMT_RGB8,
+            VideoCodec::Apng => ffmpeg::AVPixelFormat::AV_PIX_FMT_RGBA,
+            _ => ffmpeg::AVPixelFormat::AV_PIX_FMT_YUV420P,
+        }
+    }
+}
+
+/// A builder for a [`VideoEncoder`]
+#[derive(Debug, Clone)]
+pub struct EncoderBuilder {
+    path: PathBuf,
+    dimensions: Dimensions,
+    framerate: f32,
+    codec: VideoCodec,
+    format: Option<String>,
+    bit_rate: Option<u64>,
+    threads: Option<usize>,
+    /// Options passed to the muxer when writing the header
+    format_options: Vec<(String, String)>,
+}
+
+impl EncoderBuilder {
+    /// Create a new builder for a video of the given size, written to `path`
+    pub fn new<P>(path: P, width: u32, height: u32) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        EncoderBuilder {
+            path: path.as_ref().to_path_buf(),
+            dimensions: Dimensions { width, height },
+            framerate: 30.0,
+            codec: VideoCodec::default(),
+            format: None,
+            bit_rate: None,
+            threads: None,
+            format_options: Vec::new(),
+        }
+    }
+
+    /// The framerate of the video (defaults to `30`)
+    pub fn framerate(mut self, framerate: f32) -> Self {
+        self.framerate = framerate;
+        self
+    }
+
+    /// The codec to encode the video with (defaults to [`VideoCodec::H264`])
+    pub fn codec(mut self, codec: VideoCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// The (short) name of the container format to write, e.g. `mp4`, `matroska`, or `webm`.
+    ///
+    /// If this is not set, the format is worked out from the extension of the output path.
+    pub fn format(mut self, format: &str) -> Self {
+        self.format = Some(format.to_string());
+        self
+    }
+
+    /// The target bit rate of the video (in bits per second), if this is not set the codec default is used
+    pub fn bit_rate(mut self, bit_rate: u64) -> Self {
+        self.bit_rate = Some(bit_rate);
+        self
+    }
+
+    /// The number of threads ffmpeg should encode with, `0` lets ffmpeg pick based on the number of cpus
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Set an option of the muxer, e.g. `plays` for animated PNGs
+    pub(crate) fn format_option(mut self, key: &str, value: &str) -> Self {
+        self.format_options
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Create the output file and open the encoder
+    pub fn build(self) -> Result<VideoEncoder, EncodeError> {
+        VideoEncoder::open(self)
+    }
+}
+
+/// A video encoder, this writes frames to a video file
+///
+/// ## Usage
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::{VideoCodec, VideoDecoder, VideoEncoder};
+///
+/// # let file = "test.mp4";
+/// let mut decoder = VideoDecoder::new(file, false).unwrap();
+/// let dimensions = decoder.dimensions();
+/// # let dir = std::env::temp_dir().join("ffmpeg-video-decoder-encoder-doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # let output = dir.join("output.mp4");
+/// let mut encoder = VideoEncoder::builder(output, dimensions.width(), dimensions.height())
+///     .framerate(decoder.framerate())
+///     # .codec(VideoCodec::Mpeg4)
+///     .build()
+///     .unwrap();
+///
+/// while let Some(frame) = decoder.next_frame().unwrap() {
+///     # if frame.index() > 10 { break; }
+///     encoder.encode(&frame).unwrap();
+/// }
+/// encoder.finish().unwrap();
+/// # }
+/// ```
+pub struct VideoEncoder {
+    dimensions: Dimensions,
+    /// The timestamp of the next frame, in ticks of the framerate
+    next_pts: i64,
+    /// The number of frames encoded so far
+    frames: usize,
+    /// Whether the header of the output has been written (and so the trailer must be too)
+    started: bool,
+
+    // -------------- ffmpeg data --------------
+    sws_context: *mut ffmpeg::SwsContext,
+    frame: *mut ffmpeg::AVFrame,
+    packet: *mut ffmpeg::AVPacket,
+    codec_ctx: *mut ffmpeg::AVCodecContext,
+    stream: *mut ffmpeg::AVStream,
+    output: *mut ffmpeg::AVFormatContext,
+}
+
+// SAFETY: See `VideoDecoder`, the same reasoning applies here
+unsafe impl Send for VideoEncoder {}
+
+impl VideoEncoder {
+    /// Create a new H.264 encoder writing to `path`, the container format is worked out from the extension of the path.
+    ///
+    /// See [`VideoEncoder::builder`] for more options.
+    pub fn new<P>(path: P, width: u32, height: u32, framerate: f32) -> Result<Self, EncodeError>
+    where
+        P: AsRef<Path>,
+    {
+        EncoderBuilder::new(path, width, height)
+            .framerate(framerate)
+            .build()
+    }
+
+    /// Create an [`EncoderBuilder`] to configure a new video encoder
+    pub fn builder<P>(path: P, width: u32, height: u32) -> EncoderBuilder
+    where
+        P: AsRef<Path>,
+    {
+        EncoderBuilder::new(path, width, height)
+    }
+
+    fn open(options: EncoderBuilder) -> Result<Self, EncodeError> {
+        let path = path_to_raw(&options.path).ok_or(EncodeError::InvalidPath)?;
+        let format = match options.format {
+            Some(ref format) => {
+                Some(CString::new(format.as_str()).map_err(|_| EncodeError::UnknownFormat)?)
+            }
+            None => None,
+        };
+
+        unsafe {
+            // Everything allocated from here on is owned by the encoder, so it is freed if we return early
+            let mut encoder = VideoEncoder {
+                dimensions: options.dimensions,
+                next_pts: 0,
+                frames: 0,
+                started: false,
+                sws_context: ptr::null_mut(),
+                frame: ptr::null_mut(),
+                packet: ptr::null_mut(),
+                codec_ctx: ptr::null_mut(),
+                stream: ptr::null_mut(),
+                output: ptr::null_mut(),
+            };
+
+            if ffmpeg::avformat_alloc_output_context2(
+                &mut encoder.output,
+                ptr::null(),
+                format
+                    .as_ref()
+                    .map_or(ptr::null(), |format| format.as_ptr()),
+                path.as_ptr() as *const ffi::c_char,
+            ) < 0
+                || encoder.output.is_null()
+            {
+                return Err(EncodeError::UnknownFormat);
+            }
+
+            let codec = ffmpeg::avcodec_find_encoder(options.codec.as_raw());
+            if codec.is_null() {
+                return Err(EncodeError::UnsupportedCodec);
+            }
+
+            encoder.stream = ffmpeg::avformat_new_stream(encoder.output, ptr::null());
+            encoder.codec_ctx = ffmpeg::avcodec_alloc_context3(codec);
+            encoder.frame = ffmpeg::av_frame_alloc();
+            encoder.packet = ffmpeg::av_packet_alloc();
+            if encoder.stream.is_null()
+                || encoder.codec_ctx.is_null()
+                || encoder.frame.is_null()
+                || encoder.packet.is_null()
+            {
+                return Err(EncodeError::AllocationFailed);
+            }
+
+            // Each frame is one tick of the time base
+            let framerate = ffmpeg::av_d2q(options.framerate as f64, 100_000);
+            let codec_ctx = encoder.codec_ctx;
+            (*codec_ctx).width = options.dimensions.width as i32;
+            (*codec_ctx).height = options.dimensions.height as i32;
+            (*codec_ctx).pix_fmt = options.codec.pixel_format();
+            (*codec_ctx).framerate = framerate;
+            (*codec_ctx).time_base = ffmpeg::av_inv_q(framerate);
+            if let Some(bit_rate) = options.bit_rate {
+                (*codec_ctx).bit_rate = bit_rate as i64;
+            }
+            if let Some(threads) = options.threads {
+                (*codec_ctx).thread_count = threads as i32;
+            }
+            if (*(*encoder.output).oformat).flags & ffmpeg::AVFMT_GLOBALHEADER != 0 {
+                (*codec_ctx).flags |= ffmpeg::AV_CODEC_FLAG_GLOBAL_HEADER as i32;
+            }
+
+            if ffmpeg::avcodec_open2(codec_ctx, codec, ptr::null_mut()) < 0 {
+                return Err(EncodeError::UnsupportedCodec);
+            }
+
+            if ffmpeg::avcodec_parameters_from_context((*encoder.stream).codecpar, codec_ctx) < 0 {
+                return Err(EncodeError::UnsupportedCodec);
+            }
+            (*encoder.stream).time_base = (*codec_ctx).time_base;
+
+            // The frame the input data is converted into before encoding
+            (*encoder.frame).format = (*codec_ctx).pix_fmt as i32;
+            (*encoder.frame).width = (*codec_ctx).width;
+            (*encoder.frame).height = (*codec_ctx).height;
+            if ffmpeg::av_frame_get_buffer(encoder.frame, 0) < 0 {
+                return Err(EncodeError::AllocationFailed);
+            }
+
+            // Some formats (such as image sequences) open their own files
+            if (*(*encoder.output).oformat).flags & ffmpeg::AVFMT_NOFILE == 0
+                && ffmpeg::avio_open(
+                    &mut (*encoder.output).pb,
+                    path.as_ptr() as *const ffi::c_char,
+                    ffmpeg::AVIO_FLAG_WRITE,
+                ) < 0
+            {
+                return Err(EncodeError::UnableToOpenOutput);
+            }
+
+            // The options are only set by this crate, so never contain a nul byte
+            let mut format_options =
+                Dictionary::new(&options.format_options).ok_or(EncodeError::UnableToWrite)?;
+            if ffmpeg::avformat_write_header(encoder.output, &mut format_options.0) < 0 {
+                return Err(EncodeError::UnableToWrite);
+            }
+            encoder.started = true;
+
+            Ok(encoder)
+        }
+    }
+
+    /// Get the dimensions of the video being encoded
+    #[inline]
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    /// The number of frames encoded so far
+    #[inline]
+    pub fn frame_count(&self) -> usize {
+        self.frames
+    }
+
+    /// Encode a frame (such as one returned by [`VideoDecoder::next_frame`](crate::VideoDecoder::next_frame)).
+    ///
+    /// Frames are shown in the order they are encoded, one frame per tick of the framerate (the timestamp of the frame is ignored).
+    /// If the frame is a different size to the video, it is scaled to fit.
+    pub fn encode(&mut self, frame: &Frame) -> Result<(), EncodeError> {
+        self.encode_strided(
+            frame.data(),
+            frame.stride(),
+            frame.dimensions(),
+            frame.format(),
+            1,
+        )
+    }
+
+    /// Encode a frame from tightly packed pixel data which is the same size as the video
+    pub fn encode_data(&mut self, data: &[u8], format: PixelFormat) -> Result<(), EncodeError> {
+        self.encode_strided(
+            data,
+            self.dimensions.width as usize * format.bytes_per_pixel(),
+            self.dimensions,
+            format,
+            1,
+        )
+    }
+
+    /// Encode a frame which is shown for `duration` ticks of the framerate
+    pub(crate) fn encode_strided(
+        &mut self,
+        data: &[u8],
+        stride: usize,
+        dimensions: Dimensions,
+        format: PixelFormat,
+        duration: i64,
+    ) -> Result<(), EncodeError> {
+        let row = dimensions.width as usize * format.bytes_per_pixel();
+        let height = dimensions.height as usize;
+        if height == 0 || stride < row || data.len() < stride * (height - 1) + row {
+            return Err(EncodeError::InvalidFrame);
+        }
+
+        unsafe {
+            // The encoder may still hold a reference to the previous frame
+            if ffmpeg::av_frame_make_writable(self.frame) < 0 {
+                return Err(EncodeError::AllocationFailed);
+            }
+
+            self.sws_context = ffmpeg::sws_getCachedContext(
+                self.sws_context,
+                dimensions.width as i32,   // Source
+                dimensions.height as i32,  // Source
+                format.as_raw(),           // Source
+                (*self.frame).width,       // Destination
+                (*self.frame).height,      // Destination
+                (*self.codec_ctx).pix_fmt, // Destination
+                ffmpeg::SWS_BILINEAR,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            if self.sws_context.is_null() {
+                return Err(EncodeError::ConverterInitFailed);
+            }
+
+            let src_data = [data.as_ptr(), ptr::null(), ptr::null(), ptr::null()];
+            let src_stride = [stride as ffi::c_int, 0, 0, 0];
+            ffmpeg::sws_scale(
+                self.sws_context,
+                src_data.as_ptr(),
+                src_stride.as_ptr() as *mut _,
+                0,
+                dimensions.height as i32,
+                (*self.frame).data.as_ptr(),
+                (*self.frame).linesize.as_ptr() as *mut _,
+            );
+
+            (*self.frame).pts = self.next_pts;
+            (*self.frame).duration = duration;
+            self.next_pts += duration;
+            self.frames += 1;
+
+            self.send(self.frame)
+        }
+    }
+
+    /// Send a frame to the encoder (or null to flush it), then write every packet it produces
+    unsafe fn send(&mut self, frame: *const ffmpeg::AVFrame) -> Result<(), EncodeError> {
+        if ffmpeg::avcodec_send_frame(self.codec_ctx, frame) < 0 {
+            return Err(EncodeError::UnableToEncodeFrame);
+        }
+
+        while ffmpeg::avcodec_receive_packet(self.codec_ctx, self.packet) >= 0 {
+            ffmpeg::av_packet_rescale_ts(
+                self.packet,
+                (*self.codec_ctx).time_base,
+                (*self.stream).time_base,
+            );
+            (*self.packet).stream_index = (*self.stream).index;
+
+            // This takes ownership of the packet data
+            if ffmpeg::av_interleaved_write_frame(self.output, self.packet) < 0 {
+                return Err(EncodeError::UnableToWrite);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Flush the encoder and finish writing the output.
+    ///
+    /// This is also done when the encoder is dropped, but any errors are then ignored.
+    pub fn finish(mut self) -> Result<(), EncodeError> {
+        self.finalize()
+    }
+
+    fn finalize(&mut self) -> Result<(), EncodeError> {
+        if !self.started {
+            return Ok(());
+        }
+        self.started = false;
+
+        unsafe {
+            let flushed = self.send(ptr::null());
+            if ffmpeg::av_write_trailer(self.output) < 0 {
+                return Err(EncodeError::UnableToWrite);
+            }
+            flushed
+        }
+    }
+}
+
+impl Drop for VideoEncoder {
+    fn drop(&mut self) {
+        let _ = self.finalize();
+
+        unsafe {
+            ffmpeg::sws_freeContext(self.sws_context);
+            ffmpeg::av_frame_free(&mut self.frame);
+            ffmpeg::av_packet_free(&mut self.packet);
+            ffmpeg::avcodec_free_context(&mut self.codec_ctx);
+
+            if !self.output.is_null() {
+                if (*(*self.output).oformat).flags & ffmpeg::AVFMT_NOFILE == 0 {
+                    ffmpeg::avio_closep(&mut (*self.output).pb);
+                }
+                // This also frees the stream
+                ffmpeg::avformat_free_context(self.output);
+            }
+        }
+    }
+}
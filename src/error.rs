@@ -6,16 +6,129 @@ pub enum DecodeError {
     InvalidSource,
     #[error("unable to open input data")]
     UnableToOpenInput,
+    /// The input data is not in any format known to ffmpeg (e.g. a text file), this may also mean that the header of the data is damaged
+    #[error("input data is not a media file")]
+    NotAMediaFile,
+    /// The format passed to [`DecoderBuilder::format_hint`](crate::DecoderBuilder::format_hint) is not known to ffmpeg
+    #[error("unknown input format")]
+    UnknownFormat,
+    /// The input data ended before its header could be read, e.g. a partially downloaded file
+    #[error("input data is truncated")]
+    TruncatedInput,
     #[error("unable to read stream information")]
     UnableToReadStreamInfo,
     /// This may mean that the source data does not have an active video stream
     #[error("unable to find video stream")]
     UnableToFindVideoStream,
+    /// The source data only contains audio (e.g. an MP3 file), so there is nothing to decode.
+    ///
+    /// Any cover art can still be read with [`cover_art`](crate::cover_art).
+    #[error("input data only contains audio")]
+    AudioOnlyInput,
+    /// This means that the source data does not have any audio streams
+    #[error("unable to find audio stream")]
+    UnableToFindAudioStream,
+    /// The stream selected with [`DecoderBuilder::video_stream`](crate::DecoderBuilder::video_stream) does not exist or is not a video stream
+    #[error("selected stream is not a video stream")]
+    InvalidVideoStream,
+    /// The program selected with [`DecoderBuilder::program`](crate::DecoderBuilder::program) does not exist
+    #[error("selected program does not exist")]
+    InvalidProgram,
+    /// A stream selected with [`DecoderBuilder::data_stream`](crate::DecoderBuilder::data_stream) does not exist or is not a data stream
+    #[error("selected stream is not a data stream")]
+    InvalidDataStream,
+    /// The image passed to [`DecoderBuilder::overlay`](crate::DecoderBuilder::overlay) is not `width * height` RGBA pixels
+    #[error("overlay data does not match its dimensions")]
+    InvalidOverlay,
+    /// This means that the source data does not have any subtitle streams
+    #[error("unable to find subtitle stream")]
+    UnableToFindSubtitleStream,
+    /// The stream selected with [`SubtitleDecoder::with_stream`](crate::SubtitleDecoder::with_stream) does not exist or is not a subtitle stream
+    #[error("selected stream is not a subtitle stream")]
+    InvalidSubtitleStream,
     /// The target codec is not supported by ffmpeg
     #[error("unsupported codec (by ffmpeg)")]
     UnsupportedCodec,
+    /// ffmpeg was unable to allocate memory
+    #[error("allocation failed")]
+    AllocationFailed,
+    /// The converter (to RGB) could not be created, this may mean that the source pixel format is not supported
+    #[error("unable to initialize converter")]
+    ConverterInitFailed,
     #[error("could not read frame buffer")]
     UnableToReadFrameBuffer,
     #[error("unable to sent packet to decoder")]
     UnableToSendPacketToDecoder,
+    /// The source data could not be read before its end, e.g. it is damaged or a network connection failed
+    #[error("unable to read packet")]
+    UnableToReadPacket,
+    /// The source data could not be seeked, this may mean that the input is not seekable
+    #[error("unable to seek")]
+    UnableToSeek,
+    #[error("unable to decode subtitle")]
+    UnableToDecodeSubtitle,
+    /// The filter passed to [`DecoderBuilder::filter`](crate::DecoderBuilder::filter) could not be parsed, or uses a filter which does not exist
+    #[error("invalid filter graph")]
+    InvalidFilter,
+    /// The pixel format of a frame can not be represented by the requested type
+    #[error("unsupported pixel format")]
+    UnsupportedPixelFormat,
+    /// The texture passed to `Frame::write_to_texture` does not match the size or format of the frame
+    #[error("texture does not match the frame")]
+    TextureMismatch,
+    /// An option passed to [`DecoderBuilder::format_option`](crate::DecoderBuilder::format_option) or [`DecoderBuilder::codec_option`](crate::DecoderBuilder::codec_option) contains a nul byte
+    #[error("invalid option")]
+    InvalidOption,
+    /// The start passed to [`DecoderBuilder::range`](crate::DecoderBuilder::range) is after the end
+    #[error("invalid range")]
+    InvalidRange,
+    /// The framerate passed to [`ImageSequence::files`](crate::ImageSequence::files) is not a positive number
+    #[error("invalid framerate")]
+    InvalidFramerate,
+    /// The operation was cancelled with a [`DecoderHandle`](crate::DecoderHandle), or took longer than the [`DecoderBuilder::timeout`](crate::DecoderBuilder::timeout)
+    #[error("operation cancelled")]
+    Cancelled,
+    /// The video is larger than a limit set with [`DecoderBuilder::max_dimensions`](crate::DecoderBuilder::max_dimensions),
+    /// [`DecoderBuilder::max_frame_count`](crate::DecoderBuilder::max_frame_count) or [`DecoderBuilder::max_memory_bytes`](crate::DecoderBuilder::max_memory_bytes)
+    #[error("resource limit exceeded")]
+    LimitExceeded,
+}
+
+/// An error from the encoder
+#[non_exhaustive]
+#[derive(thiserror::Error, Debug)]
+pub enum EncodeError {
+    #[error("output path was not a valid unicode string")]
+    InvalidPath,
+    /// The container format could not be worked out from the output path, or the name passed to [`EncoderBuilder::format`](crate::EncoderBuilder::format) is not known
+    #[error("unknown output format")]
+    UnknownFormat,
+    /// The selected codec is not supported by ffmpeg, or the encoder could not be opened with the given options
+    #[error("unsupported codec (by ffmpeg)")]
+    UnsupportedCodec,
+    #[error("unable to open output")]
+    UnableToOpenOutput,
+    /// ffmpeg was unable to allocate memory
+    #[error("allocation failed")]
+    AllocationFailed,
+    /// The converter (from the input pixel format) could not be created
+    #[error("unable to initialize converter")]
+    ConverterInitFailed,
+    /// The frame data is smaller than its dimensions require
+    #[error("frame data does not match its dimensions")]
+    InvalidFrame,
+    #[error("unable to encode frame")]
+    UnableToEncodeFrame,
+    #[error("unable to write to output")]
+    UnableToWrite,
+}
+
+/// An error from [`transcode`](crate::transcode)
+#[non_exhaustive]
+#[derive(thiserror::Error, Debug)]
+pub enum TranscodeError {
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+    #[error(transparent)]
+    Encode(#[from] EncodeError),
 }
@@ -0,0 +1,195 @@
+use super::{EncodeError, Frame, PixelFormat, TranscodeError, VideoDecoder};
+use crate::frame::pack_rows;
+use image::codecs::jpeg::JpegEncoder;
+use image::{ExtendedColorType, ImageEncoder, ImageFormat};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// The JPEG quality used unless another is chosen, from `1` to `100`
+const DEFAULT_JPEG_QUALITY: u8 = 90;
+
+impl Frame {
+    /// Save this frame as an image, the format is chosen from the extension of the path (e.g. `png`, `jpg` or `webp`).
+    ///
+    /// JPEG images are saved with a quality of 90, see [`Frame::save_with_quality`]. Note that JPEG does not support transparency
+    /// or 16 bit color, so frames in those formats can only be saved as other image formats (such as PNG).
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// # let output = std::env::temp_dir().join("frame_save_doc.png");
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// let frame = decoder.next_frame().unwrap().unwrap();
+    /// frame.save(&output).unwrap();
+    /// # }
+    /// ```
+    pub fn save<P>(&self, path: P) -> Result<(), TranscodeError>
+    where
+        P: AsRef<Path>,
+    {
+        self.save_with_quality(path, DEFAULT_JPEG_QUALITY)
+    }
+
+    /// Save this frame as an image (see [`Frame::save`]), with a JPEG quality from `1` (smallest) to `100` (best).
+    ///
+    /// The quality is ignored for other image formats.
+    pub fn save_with_quality<P>(&self, path: P, quality: u8) -> Result<(), TranscodeError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let format = ImageFormat::from_path(path).map_err(|_| EncodeError::UnknownFormat)?;
+        let color = match self.format {
+            PixelFormat::Rgb24 => ExtendedColorType::Rgb8,
+            PixelFormat::Rgba => ExtendedColorType::Rgba8,
+            PixelFormat::Gray8 => ExtendedColorType::L8,
+            PixelFormat::Rgb48 => ExtendedColorType::Rgb16,
+            PixelFormat::Rgba64 => ExtendedColorType::Rgba16,
+        };
+
+        let (width, height) = (self.dimensions.width, self.dimensions.height);
+        let row = width as usize * self.format.bytes_per_pixel();
+        let data = match self.stride == row {
+            true => Cow::Borrowed(&self.data[..row * height as usize]),
+            false => Cow::Owned(pack_rows(&self.data, self.stride, row, height as usize)),
+        };
+
+        let file = File::create(path).map_err(|_| EncodeError::UnableToOpenOutput)?;
+        let mut writer = BufWriter::new(file);
+        let result = match format {
+            ImageFormat::Jpeg => JpegEncoder::new_with_quality(&mut writer, quality.clamp(1, 100))
+                .write_image(&data, width, height, color),
+            format => {
+                image::write_buffer_with_format(&mut writer, &data, width, height, color, format)
+            }
+        };
+        result
+            .ok()
+            .and_then(|_| writer.flush().ok())
+            .ok_or(EncodeError::UnableToWrite)?;
+
+        Ok(())
+    }
+}
+
+/// Saves frames as numbered images, e.g. `frames/frame_000001.png`, `frames/frame_000002.png`, ...
+///
+/// The file name pattern uses the same placeholders as ffmpeg's image2 muxer: `%d` is replaced with the number of the image,
+/// and `%06d` with the number padded to 6 digits. The image format is chosen from the extension of the pattern.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::{FrameExporter, Sampling, VideoDecoder};
+///
+/// # let file = "test.mp4";
+/// # let dir = std::env::temp_dir().join("frame_exporter_doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// let mut decoder = VideoDecoder::builder(file)
+///     .sample(Sampling::EveryNth(300))
+///     .build()
+///     .unwrap();
+/// let mut exporter = FrameExporter::new(dir.join("frame_%04d.jpg"))
+///     .unwrap()
+///     .jpeg_quality(80);
+/// let count = exporter.export_all(&mut decoder).unwrap();
+/// assert_eq!(count, 4);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrameExporter {
+    /// The parts of the pattern before and after the placeholder
+    prefix: String,
+    suffix: String,
+    /// The number of digits to pad the number to
+    width: usize,
+    next: usize,
+    jpeg_quality: u8,
+}
+
+impl FrameExporter {
+    /// Create an exporter which saves images to paths following `pattern`, numbered from `1`.
+    ///
+    /// Returns [`EncodeError::InvalidPath`] if the pattern is not valid unicode or its file name does not contain a `%d` (or `%0Nd`) placeholder.
+    /// If the file name contains more than one placeholder, the last one is used.
+    pub fn new<P>(pattern: P) -> Result<Self, EncodeError>
+    where
+        P: AsRef<Path>,
+    {
+        let pattern = pattern.as_ref().to_str().ok_or(EncodeError::InvalidPath)?;
+
+        // Only the file name is numbered, so a `%` in the name of a directory is left as it is
+        let name = pattern.rfind(std::path::is_separator).map_or(0, |i| i + 1);
+        let (start, end, width) = pattern[name..]
+            .match_indices('%')
+            .filter_map(|(i, _)| {
+                let start = name + i;
+                let digits = pattern[start + 1..]
+                    .find(|c: char| !c.is_ascii_digit())
+                    .map(|len| &pattern[start + 1..start + 1 + len])?;
+                let end = start + 1 + digits.len();
+                let width = match digits {
+                    "" => 0,
+                    digits if digits.starts_with('0') => digits.parse().ok()?,
+                    _ => return None,
+                };
+                pattern[end..]
+                    .starts_with('d')
+                    .then_some((start, end, width))
+            })
+            .last()
+            .ok_or(EncodeError::InvalidPath)?;
+
+        Ok(FrameExporter {
+            prefix: pattern[..start].to_string(),
+            suffix: pattern[end + 1..].to_string(),
+            width,
+            next: 1,
+            jpeg_quality: DEFAULT_JPEG_QUALITY,
+        })
+    }
+
+    /// The number of the next image saved (defaults to `1`)
+    pub fn start_number(mut self, number: usize) -> Self {
+        self.next = number;
+        self
+    }
+
+    /// The quality of JPEG images from `1` (smallest) to `100` (best) (defaults to `90`)
+    pub fn jpeg_quality(mut self, quality: u8) -> Self {
+        self.jpeg_quality = quality;
+        self
+    }
+
+    /// The path the next image will be saved to
+    pub fn next_path(&self) -> PathBuf {
+        PathBuf::from(format!(
+            "{}{:0width$}{}",
+            self.prefix,
+            self.next,
+            self.suffix,
+            width = self.width
+        ))
+    }
+
+    /// Save a frame to the next path, returning the path it was saved to
+    pub fn export(&mut self, frame: &Frame) -> Result<PathBuf, TranscodeError> {
+        let path = self.next_path();
+        frame.save_with_quality(&path, self.jpeg_quality)?;
+        self.next += 1;
+        Ok(path)
+    }
+
+    /// Save every remaining frame of a decoder, returning the number of frames saved
+    pub fn export_all(&mut self, decoder: &mut VideoDecoder) -> Result<usize, TranscodeError> {
+        let mut count = 0;
+        while let Some(frame) = decoder.next_frame()? {
+            self.export(&frame)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
@@ -0,0 +1,244 @@
+//! A libavfilter graph which decoded frames are passed through before they are converted to RGB
+
+use super::{DecodeError, Dimensions};
+use std::ffi::{self, CString};
+use std::{mem, ptr};
+
+pub(crate) struct FilterGraph {
+    graph: *mut ffmpeg::AVFilterGraph,
+    /// The `buffer` filter which decoded frames are added to
+    source: *mut ffmpeg::AVFilterContext,
+    /// The `buffersink` filter which filtered frames are read from
+    sink: *mut ffmpeg::AVFilterContext,
+    /// The user supplied filters, kept so the graph can be rebuilt
+    filters: CString,
+    /// The swscale options used by scaling filters
+    scale_options: CString,
+    /// The arguments of the `buffer` filter, describing the decoded frames
+    source_args: CString,
+    /// The time base of the decoded frames
+    time_base: ffmpeg::AVRational,
+    /// Whether the end of the input has been added to the graph
+    flushed: bool,
+}
+
+// SAFETY: See `VideoDecoder`, the same reasoning applies here
+unsafe impl Send for FilterGraph {}
+
+impl FilterGraph {
+    /// Build a filter graph for the frames decoded by a codec context, with the given sample aspect ratio (`0/1` if unknown)
+    ///
+    /// `scale_options` are the swscale options used by any filters which scale frames, e.g. `flags=bicubic`.
+    ///
+    /// # Safety
+    ///
+    /// `codec_ctx` must point to an opened video decoder.
+    pub unsafe fn new(
+        filters: &str,
+        scale_options: &str,
+        codec_ctx: *const ffmpeg::AVCodecContext,
+        time_base: ffmpeg::AVRational,
+        framerate: ffmpeg::AVRational,
+        aspect: ffmpeg::AVRational,
+    ) -> Result<Self, DecodeError> {
+        let source_args = format!(
+            "video_size={}x{}:pix_fmt={}:time_base={}/{}:frame_rate={}/{}:pixel_aspect={}/{}",
+            (*codec_ctx).width,
+            (*codec_ctx).height,
+            (*codec_ctx).pix_fmt as ffi::c_int,
+            time_base.num,
+            time_base.den,
+            framerate.num.max(0),
+            framerate.den.max(1),
+            aspect.num.max(0),
+            aspect.den.max(1),
+        );
+
+        let mut graph = FilterGraph {
+            graph: ptr::null_mut(),
+            source: ptr::null_mut(),
+            sink: ptr::null_mut(),
+            filters: CString::new(filters).map_err(|_| DecodeError::InvalidFilter)?,
+            scale_options: CString::new(scale_options).map_err(|_| DecodeError::InvalidFilter)?,
+            source_args: CString::new(source_args).map_err(|_| DecodeError::InvalidFilter)?,
+            time_base,
+            flushed: false,
+        };
+        graph.build()?;
+
+        Ok(graph)
+    }
+
+    /// (Re)create the graph, discarding any frames held by the previous one.
+    ///
+    /// If this fails the graph is left empty, so no frames are passed through it until it is rebuilt.
+    pub fn build(&mut self) -> Result<(), DecodeError> {
+        unsafe {
+            ffmpeg::avfilter_graph_free(&mut self.graph);
+            self.source = ptr::null_mut();
+            self.sink = ptr::null_mut();
+            self.flushed = false;
+
+            let result = self.configure();
+            if result.is_err() {
+                ffmpeg::avfilter_graph_free(&mut self.graph);
+            }
+            result
+        }
+    }
+
+    /// Create the filters of the graph
+    unsafe fn configure(&mut self) -> Result<(), DecodeError> {
+        self.graph = ffmpeg::avfilter_graph_alloc();
+        if self.graph.is_null() {
+            return Err(DecodeError::AllocationFailed);
+        }
+        // Freed along with the graph
+        (*self.graph).scale_sws_opts = ffmpeg::av_strdup(self.scale_options.as_ptr());
+
+        if ffmpeg::avfilter_graph_create_filter(
+            &mut self.source,
+            ffmpeg::avfilter_get_by_name(c"buffer".as_ptr()),
+            c"in".as_ptr(),
+            self.source_args.as_ptr(),
+            ptr::null_mut(),
+            self.graph,
+        ) < 0
+            || ffmpeg::avfilter_graph_create_filter(
+                &mut self.sink,
+                ffmpeg::avfilter_get_by_name(c"buffersink".as_ptr()),
+                c"out".as_ptr(),
+                ptr::null(),
+                ptr::null_mut(),
+                self.graph,
+            ) < 0
+        {
+            return Err(DecodeError::InvalidFilter);
+        }
+
+        // The user supplied filters read from the output of our source, and write to the input of our sink
+        let mut outputs = ffmpeg::avfilter_inout_alloc();
+        let mut inputs = ffmpeg::avfilter_inout_alloc();
+        if outputs.is_null() || inputs.is_null() {
+            ffmpeg::avfilter_inout_free(&mut outputs);
+            ffmpeg::avfilter_inout_free(&mut inputs);
+            return Err(DecodeError::AllocationFailed);
+        }
+
+        (*outputs).name = ffmpeg::av_strdup(c"in".as_ptr());
+        (*outputs).filter_ctx = self.source;
+        (*outputs).pad_idx = 0;
+        (*outputs).next = ptr::null_mut();
+
+        (*inputs).name = ffmpeg::av_strdup(c"out".as_ptr());
+        (*inputs).filter_ctx = self.sink;
+        (*inputs).pad_idx = 0;
+        (*inputs).next = ptr::null_mut();
+
+        let parsed = ffmpeg::avfilter_graph_parse_ptr(
+            self.graph,
+            self.filters.as_ptr(),
+            &mut inputs,
+            &mut outputs,
+            ptr::null_mut(),
+        );
+        ffmpeg::avfilter_inout_free(&mut outputs);
+        ffmpeg::avfilter_inout_free(&mut inputs);
+
+        if parsed < 0 || ffmpeg::avfilter_graph_config(self.graph, ptr::null_mut()) < 0 {
+            return Err(DecodeError::InvalidFilter);
+        }
+
+        Ok(())
+    }
+
+    /// The dimensions of the filtered frames
+    pub fn dimensions(&self) -> Dimensions {
+        unsafe {
+            Dimensions {
+                width: ffmpeg::av_buffersink_get_w(self.sink) as u32,
+                height: ffmpeg::av_buffersink_get_h(self.sink) as u32,
+            }
+        }
+    }
+
+    /// The pixel format of the filtered frames
+    pub fn format(&self) -> ffmpeg::AVPixelFormat {
+        // The format always comes from ffmpeg, so it is a valid pixel format
+        unsafe {
+            mem::transmute::<ffi::c_int, ffmpeg::AVPixelFormat>(ffmpeg::av_buffersink_get_format(
+                self.sink,
+            ))
+        }
+    }
+
+    /// The framerate of the filtered frames, `None` if it is not known (or is variable)
+    pub fn framerate(&self) -> Option<ffmpeg::AVRational> {
+        let framerate = unsafe { ffmpeg::av_buffersink_get_frame_rate(self.sink) };
+        (framerate.num > 0 && framerate.den > 0).then_some(framerate)
+    }
+
+    /// The sample (pixel) aspect ratio of the filtered frames, `None` if it is not known
+    pub fn sample_aspect_ratio(&self) -> Option<ffmpeg::AVRational> {
+        let aspect = unsafe { ffmpeg::av_buffersink_get_sample_aspect_ratio(self.sink) };
+        (aspect.num > 0 && aspect.den > 0).then_some(aspect)
+    }
+
+    /// Add a decoded frame to the graph, taking ownership of its data. A null frame marks the end of the input.
+    ///
+    /// Returns `false` if the frame could not be added (including once the end of the input has been added).
+    ///
+    /// # Safety
+    ///
+    /// `frame` must be null or point to a valid decoded video frame.
+    pub unsafe fn push(&mut self, frame: *mut ffmpeg::AVFrame) -> bool {
+        if self.graph.is_null() || self.flushed {
+            return false;
+        }
+
+        match frame.is_null() {
+            true => self.flushed = true,
+            false => (*frame).pts = (*frame).best_effort_timestamp,
+        }
+
+        ffmpeg::av_buffersrc_add_frame_flags(self.source, frame, 0) >= 0
+    }
+
+    /// Whether the end of the input has been added to the graph, see [`FilterGraph::push`]
+    pub fn is_flushed(&self) -> bool {
+        self.flushed
+    }
+
+    /// Read the next filtered frame into `frame`, returns `false` if there are no frames available.
+    ///
+    /// The timestamp and duration of the filtered frame are in the time base of the decoded frames.
+    ///
+    /// # Safety
+    ///
+    /// `frame` must point to a valid (unreferenced) frame.
+    pub unsafe fn pull(&mut self, frame: *mut ffmpeg::AVFrame) -> bool {
+        if self.graph.is_null() || ffmpeg::av_buffersink_get_frame(self.sink, frame) < 0 {
+            return false;
+        }
+
+        let time_base = ffmpeg::av_buffersink_get_time_base(self.sink);
+        if (*frame).pts != ffmpeg::AV_NOPTS_VALUE {
+            (*frame).pts = ffmpeg::av_rescale_q((*frame).pts, time_base, self.time_base);
+        }
+        if (*frame).duration > 0 {
+            (*frame).duration = ffmpeg::av_rescale_q((*frame).duration, time_base, self.time_base);
+        }
+        (*frame).best_effort_timestamp = (*frame).pts;
+
+        true
+    }
+}
+
+impl Drop for FilterGraph {
+    fn drop(&mut self) {
+        unsafe {
+            // This also frees the filter contexts
+            ffmpeg::avfilter_graph_free(&mut self.graph);
+        }
+    }
+}
@@ -0,0 +1,477 @@
+#[cfg(any(feature = "image", feature = "ndarray"))]
+use super::DecodeError;
+use super::Dimensions;
+use super::MotionVector;
+use super::SubtitleCue;
+use super::Timecode;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The layout of the pixels in frame data
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PixelFormat {
+    /// 8 bits per channel, 3 bytes per pixel
+    #[default]
+    Rgb24,
+    /// 8 bits per channel, 4 bytes per pixel
+    Rgba,
+    /// A single 8 bit (luma) channel, 1 byte per pixel
+    Gray8,
+    /// 16 bits per channel (in native endian order), 6 bytes per pixel
+    Rgb48,
+    /// 16 bits per channel (in native endian order), 8 bytes per pixel
+    Rgba64,
+}
+
+impl PixelFormat {
+    /// The number of channels in each pixel
+    #[inline]
+    pub fn channels(&self) -> usize {
+        match self {
+            PixelFormat::Rgb24 | PixelFormat::Rgb48 => 3,
+            PixelFormat::Rgba | PixelFormat::Rgba64 => 4,
+            PixelFormat::Gray8 => 1,
+        }
+    }
+
+    /// The number of bytes used by each pixel
+    #[inline]
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            PixelFormat::Rgb24 => 3,
+            PixelFormat::Rgba => 4,
+            PixelFormat::Gray8 => 1,
+            PixelFormat::Rgb48 => 6,
+            PixelFormat::Rgba64 => 8,
+        }
+    }
+
+    /// The equivalent format with an alpha channel (with the same bit depth), if there is one
+    pub(crate) fn with_alpha(&self) -> PixelFormat {
+        match self {
+            PixelFormat::Rgb24 => PixelFormat::Rgba,
+            PixelFormat::Rgb48 => PixelFormat::Rgba64,
+            format => *format,
+        }
+    }
+
+    pub(crate) fn as_raw(&self) -> ffmpeg::AVPixelFormat {
+        match self {
+            PixelFormat::Rgb24 => ffmpeg::AVPixelFormat::AV_PIX_FMT_RGB24,
+            PixelFormat::Rgba => ffmpeg::AVPixelFormat::AV_PIX_FMT_RGBA,
+            PixelFormat::Gray8 => ffmpeg::AVPixelFormat::AV_PIX_FMT_GRAY8,
+            #[cfg(target_endian = "little")]
+            PixelFormat::Rgb48 => ffmpeg::AVPixelFormat::AV_PIX_FMT_RGB48LE,
+            #[cfg(target_endian = "big")]
+            PixelFormat::Rgb48 => ffmpeg::AVPixelFormat::AV_PIX_FMT_RGB48BE,
+            #[cfg(target_endian = "little")]
+            PixelFormat::Rgba64 => ffmpeg::AVPixelFormat::AV_PIX_FMT_RGBA64LE,
+            #[cfg(target_endian = "big")]
+            PixelFormat::Rgba64 => ffmpeg::AVPixelFormat::AV_PIX_FMT_RGBA64BE,
+        }
+    }
+}
+
+/// The pixel data of a frame, either owned by the frame or shared between clones of it (see [`DecoderBuilder::shared_data`](crate::DecoderBuilder::shared_data))
+#[derive(Clone)]
+pub(crate) enum FrameData {
+    Owned(Vec<u8>),
+    Shared(Arc<[u8]>),
+}
+
+impl FrameData {
+    /// Wrap converted frame data, moving it into shared memory if `shared` is set
+    pub fn new(data: Vec<u8>, shared: bool) -> Self {
+        match shared {
+            true => FrameData::Shared(data.into()),
+            false => FrameData::Owned(data),
+        }
+    }
+
+    /// Get a mutable reference to the data, copying it first if it is shared with another frame
+    fn make_mut(&mut self) -> &mut [u8] {
+        if let FrameData::Shared(data) = self {
+            if Arc::get_mut(data).is_none() {
+                *self = FrameData::Owned(data.to_vec());
+            }
+        }
+
+        match self {
+            FrameData::Owned(data) => data,
+            // unwrap is safe as the data is not shared (checked above)
+            FrameData::Shared(data) => Arc::get_mut(data).unwrap(),
+        }
+    }
+
+    /// Convert into an owned buffer, this copies the data if it is shared
+    fn into_vec(self) -> Vec<u8> {
+        match self {
+            FrameData::Owned(data) => data,
+            FrameData::Shared(data) => data.to_vec(),
+        }
+    }
+
+    /// Convert into a shared buffer, this copies the data if it is owned
+    fn into_shared(self) -> Arc<[u8]> {
+        match self {
+            FrameData::Owned(data) => data.into(),
+            FrameData::Shared(data) => data,
+        }
+    }
+}
+
+impl Deref for FrameData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FrameData::Owned(data) => data,
+            FrameData::Shared(data) => data,
+        }
+    }
+}
+
+impl From<Vec<u8>> for FrameData {
+    fn from(data: Vec<u8>) -> Self {
+        FrameData::Owned(data)
+    }
+}
+
+/// A single frame from a decoded video
+#[derive(Clone)]
+pub struct Frame {
+    pub(crate) index: usize,
+    pub(crate) timestamp: Option<Duration>,
+    pub(crate) duration: Option<Duration>,
+    pub(crate) data: FrameData,
+    pub(crate) dimensions: Dimensions,
+    pub(crate) format: PixelFormat,
+    /// The number of bytes between the start of each row
+    pub(crate) stride: usize,
+    pub(crate) motion_vectors: Vec<MotionVector>,
+    pub(crate) captions: Vec<SubtitleCue>,
+    pub(crate) timecode: Option<Timecode>,
+    pub(crate) keyframe: bool,
+}
+
+impl fmt::Debug for Frame {
+    /// Formats everything but the frame data itself, which is only summarized by its length
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Frame")
+            .field("index", &self.index)
+            .field("timestamp", &self.timestamp)
+            .field("duration", &self.duration)
+            .field("dimensions", &self.dimensions)
+            .field("format", &self.format)
+            .field("stride", &self.stride)
+            .field("data_len", &self.data.len())
+            .field("shared", &self.is_shared())
+            .field("keyframe", &self.keyframe)
+            .field("timecode", &self.timecode)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The details of a frame without its data, see [`Frame::info`] and [`VideoDecoder::next_frame_info`](crate::VideoDecoder::next_frame_info)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameInfo {
+    index: usize,
+    timestamp: Option<Duration>,
+    duration: Option<Duration>,
+    dimensions: Dimensions,
+    keyframe: bool,
+}
+
+impl FrameInfo {
+    /// The frame number in the source video (starts at 1), see [`Frame::index`]
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The presentation timestamp of the frame, see [`Frame::timestamp`]
+    #[inline]
+    pub fn timestamp(&self) -> Option<Duration> {
+        self.timestamp
+    }
+
+    /// How long the frame should be shown for, see [`Frame::duration`]
+    #[inline]
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// The dimensions of the frame (after any filters)
+    #[inline]
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    /// Whether the frame is a keyframe, see [`Frame::is_keyframe`]
+    #[inline]
+    pub fn is_keyframe(&self) -> bool {
+        self.keyframe
+    }
+}
+
+impl Frame {
+    /// The frame number in the source video (starts at 1)
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The presentation timestamp of the frame, relative to the start of the video
+    ///
+    /// This is `None` if the source data does not contain timing information for the frame.
+    #[inline]
+    pub fn timestamp(&self) -> Option<Duration> {
+        self.timestamp
+    }
+
+    /// How long the frame should be shown for, before the next frame
+    ///
+    /// This comes from the source data if it is available (so it is accurate for variable framerate video),
+    /// otherwise it is worked out from [`VideoDecoder::framerate`](crate::VideoDecoder::framerate). It is `None` if neither is known.
+    #[inline]
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Get the dimensions of the frame data
+    #[inline]
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    /// Whether the frame is a keyframe (an intra coded frame which can be decoded without any other frames)
+    #[inline]
+    pub fn is_keyframe(&self) -> bool {
+        self.keyframe
+    }
+
+    /// The details of the frame without its data, which can be kept (e.g. for logging or building an index) after the frame is dropped
+    #[inline]
+    pub fn info(&self) -> FrameInfo {
+        FrameInfo {
+            index: self.index,
+            timestamp: self.timestamp,
+            duration: self.duration,
+            dimensions: self.dimensions,
+            keyframe: self.keyframe,
+        }
+    }
+
+    /// The layout of the pixels in the frame data, see [`DecoderBuilder::output_format`](crate::DecoderBuilder::output_format)
+    #[inline]
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Get a reference to the raw frame data
+    ///
+    /// Unless the decoder was created with [`DecoderBuilder::packed_rows(false)`](crate::DecoderBuilder::packed_rows),
+    /// this is exactly `width * height * bytes_per_pixel` bytes (see [`PixelFormat::bytes_per_pixel`]).
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Get a mutable reference to the raw frame data, this can be used to edit the frame in place
+    ///
+    /// If the data is shared with other frames (see [`DecoderBuilder::shared_data`](crate::DecoderBuilder::shared_data)), it is copied first so the other frames are not changed.
+    #[inline]
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        self.data.make_mut()
+    }
+
+    /// Convert this frame into its raw data, this copies the data if it is shared with other frames
+    #[inline]
+    pub fn into_data(self) -> Vec<u8> {
+        self.data.into_vec()
+    }
+
+    /// Whether the frame data is shared between clones of this frame, see [`DecoderBuilder::shared_data`](crate::DecoderBuilder::shared_data)
+    #[inline]
+    pub fn is_shared(&self) -> bool {
+        matches!(self.data, FrameData::Shared(_))
+    }
+
+    /// Get the frame data as a shared buffer, which can be cheaply passed to other consumers.
+    ///
+    /// This does not copy the data if it is already shared (see [`DecoderBuilder::shared_data`](crate::DecoderBuilder::shared_data)).
+    #[inline]
+    pub fn into_shared_data(self) -> Arc<[u8]> {
+        self.data.into_shared()
+    }
+
+    /// The number of bytes between the start of each row of [`Frame::data`]
+    ///
+    /// This is `width * bytes_per_pixel` unless the decoder was created with [`DecoderBuilder::packed_rows(false)`](crate::DecoderBuilder::packed_rows),
+    /// in which case it may be larger if ffmpeg padded the rows.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Get the planes of the frame data along with the stride of each.
+    ///
+    /// Frames are always output in a packed (interleaved) format, so there is currently only ever a single plane.
+    /// This can be used to upload the frame (for example to a gpu texture) without having to strip any padding.
+    #[inline]
+    pub fn planes(&self) -> Vec<Plane<'_>> {
+        vec![Plane {
+            data: &self.data,
+            stride: self.stride,
+            dimensions: self.dimensions,
+            format: self.format,
+        }]
+    }
+
+    /// The raw data along with its dimensions
+    #[inline]
+    pub fn into_raw(self) -> (Vec<u8>, Dimensions) {
+        (self.data.into_vec(), self.dimensions)
+    }
+
+    /// Convert this frame into a [image::DynamicImage](https://docs.rs/image/latest/image/enum.DynamicImage.html)
+    ///
+    /// The type of image depends on [`Frame::format`], every format has an equivalent image type.
+    #[cfg(feature = "image")]
+    pub fn into_image(self) -> Result<image::DynamicImage, DecodeError> {
+        let Dimensions { width, height } = self.dimensions;
+        let row = width as usize * self.format.bytes_per_pixel();
+        let data = match self.stride == row {
+            true => self.data.into_vec(),
+            false => pack_rows(&self.data, self.stride, row, height as usize),
+        };
+
+        // unwraps are safe as both data and dimensions are readonly to the caller
+        Ok(match self.format {
+            PixelFormat::Rgb24 => image::DynamicImage::ImageRgb8(
+                image::ImageBuffer::from_raw(width, height, data).unwrap(),
+            ),
+            PixelFormat::Rgba => image::DynamicImage::ImageRgba8(
+                image::ImageBuffer::from_raw(width, height, data).unwrap(),
+            ),
+            PixelFormat::Gray8 => image::DynamicImage::ImageLuma8(
+                image::ImageBuffer::from_raw(width, height, data).unwrap(),
+            ),
+            PixelFormat::Rgb48 => image::DynamicImage::ImageRgb16(
+                image::ImageBuffer::from_raw(width, height, to_u16(&data)).unwrap(),
+            ),
+            PixelFormat::Rgba64 => image::DynamicImage::ImageRgba16(
+                image::ImageBuffer::from_raw(width, height, to_u16(&data)).unwrap(),
+            ),
+        })
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl Frame {
+    /// Convert this frame into an array with the shape `(height, width, channels)`
+    ///
+    /// Returns [`DecodeError::UnsupportedPixelFormat`] for formats with more than 8 bits per channel, see [`Frame::to_ndarray_f32`].
+    pub fn into_ndarray(self) -> Result<ndarray::Array3<u8>, DecodeError> {
+        if self.format.bytes_per_pixel() != self.format.channels() {
+            return Err(DecodeError::UnsupportedPixelFormat);
+        }
+
+        let Dimensions { width, height } = self.dimensions;
+        let channels = self.format.channels();
+        let row = width as usize * channels;
+        let data = match self.stride == row {
+            true => self.data.into_vec(),
+            false => pack_rows(&self.data, self.stride, row, height as usize),
+        };
+
+        // unwrap is safe as the data is exactly the size of the frame
+        Ok(
+            ndarray::Array3::from_shape_vec((height as usize, width as usize, channels), data)
+                .unwrap(),
+        )
+    }
+
+    /// Convert this frame into an array with the shape `(height, width, channels)`, with every value normalized to `0.0..=1.0`
+    pub fn to_ndarray_f32(&self) -> ndarray::Array3<f32> {
+        let Dimensions { width, height } = self.dimensions;
+        let channels = self.format.channels();
+        let row = width as usize * self.format.bytes_per_pixel();
+
+        let mut values = Vec::with_capacity(width as usize * height as usize * channels);
+        for line in self.data.chunks(self.stride).take(height as usize) {
+            match self.format {
+                PixelFormat::Rgb48 | PixelFormat::Rgba64 => values.extend(
+                    line[..row]
+                        .chunks_exact(2)
+                        .map(|value| u16::from_ne_bytes([value[0], value[1]]) as f32 / 65535.0),
+                ),
+                _ => values.extend(line[..row].iter().map(|&value| value as f32 / 255.0)),
+            }
+        }
+
+        // unwrap is safe as the data is exactly the size of the frame
+        ndarray::Array3::from_shape_vec((height as usize, width as usize, channels), values)
+            .unwrap()
+    }
+}
+
+/// Read native endian 16 bit values
+#[cfg(feature = "image")]
+fn to_u16(data: &[u8]) -> Vec<u16> {
+    data.chunks_exact(2)
+        .map(|value| u16::from_ne_bytes([value[0], value[1]]))
+        .collect()
+}
+
+/// Copy `height` rows of `row` bytes out of a buffer with rows `stride` bytes apart, dropping any padding
+pub(crate) fn pack_rows(data: &[u8], stride: usize, row: usize, height: usize) -> Vec<u8> {
+    let mut packed = Vec::with_capacity(row * height);
+    for line in data.chunks(stride).take(height) {
+        packed.extend_from_slice(&line[..row]);
+    }
+    packed
+}
+
+/// A single plane of frame data, see [`Frame::planes`]
+#[derive(Debug, Clone, Copy)]
+pub struct Plane<'a> {
+    data: &'a [u8],
+    stride: usize,
+    dimensions: Dimensions,
+    format: PixelFormat,
+}
+
+impl<'a> Plane<'a> {
+    /// Get a reference to the plane data, including any padding at the end of each row
+    #[inline]
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The number of bytes between the start of each row
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Get the dimensions of the plane (in pixels)
+    #[inline]
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    /// Get a reference to a single row of the plane, without any padding
+    ///
+    /// Panics if `y` is outside of the plane.
+    #[inline]
+    pub fn row(&self, y: usize) -> &'a [u8] {
+        let start = y * self.stride;
+        &self.data[start..start + self.dimensions.width as usize * self.format.bytes_per_pixel()]
+    }
+}
@@ -0,0 +1,164 @@
+//! Parsing GoPro GPMF telemetry, see [`DecoderBuilder::telemetry`](crate::DecoderBuilder::telemetry)
+
+/// A single reading from a GPS receiver
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GpsFix {
+    /// Degrees north of the equator
+    pub latitude: f64,
+    /// Degrees east of the prime meridian
+    pub longitude: f64,
+    /// Metres above the WGS 84 ellipsoid
+    pub altitude: f64,
+    /// Ground speed in metres per second
+    pub speed_2d: f64,
+    /// Speed in three dimensions (including climbing or falling) in metres per second
+    pub speed_3d: f64,
+}
+
+/// The sensor readings of a GoPro GPMF packet, see [`Telemetry::parse`]
+///
+/// Each packet usually covers around one second of video, with the readings evenly spaced across the packet.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Telemetry {
+    accelerometer: Vec<[f64; 3]>,
+    gyroscope: Vec<[f64; 3]>,
+    gps: Vec<GpsFix>,
+}
+
+impl Telemetry {
+    /// Parse the contents of a packet from a GoPro `gpmd` stream, such as [`DataPacket::bytes`](crate::DataPacket::bytes).
+    ///
+    /// Parsing stops at the first damaged entry, so this returns whatever was read up to that point.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::{DecoderBuilder, Telemetry};
+    ///
+    /// let file = "GX010001.MP4";
+    /// # let file = "test.mp4";
+    /// let mut decoder = DecoderBuilder::new(file).telemetry(true).build().unwrap();
+    /// while let Some(frame) = decoder.next_frame().unwrap() {
+    ///     for packet in decoder.take_data_packets() {
+    ///         let telemetry = Telemetry::parse(packet.bytes());
+    ///         if let Some(fix) = telemetry.gps().first() {
+    ///             println!("{:?}: {}, {}", frame.timestamp(), fix.latitude, fix.longitude);
+    ///         }
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn parse(data: &[u8]) -> Self {
+        let mut telemetry = Telemetry::default();
+        telemetry.read(data, &mut Vec::new());
+        telemetry
+    }
+
+    /// Accelerometer readings in metres per second squared, in the axis order used by the camera
+    #[inline]
+    pub fn accelerometer(&self) -> &[[f64; 3]] {
+        &self.accelerometer
+    }
+
+    /// Gyroscope readings in radians per second, in the axis order used by the camera
+    #[inline]
+    pub fn gyroscope(&self) -> &[[f64; 3]] {
+        &self.gyroscope
+    }
+
+    /// GPS readings
+    #[inline]
+    pub fn gps(&self) -> &[GpsFix] {
+        &self.gps
+    }
+
+    /// Read a list of entries, `scale` is the most recent `SCAL` entry of the current stream
+    fn read(&mut self, mut data: &[u8], scale: &mut Vec<f64>) {
+        while data.len() >= 8 {
+            // Each entry is a four character key, the type of the values, the size of each sample, and the number of samples
+            let key = &data[..4];
+            let kind = data[4];
+            let size = data[5] as usize;
+            let repeat = u16::from_be_bytes([data[6], data[7]]) as usize;
+            let Some(payload) = data.get(8..8 + size * repeat) else {
+                return;
+            };
+            // Entries are padded to a multiple of 4 bytes
+            data = data
+                .get(8 + (size * repeat).next_multiple_of(4)..)
+                .unwrap_or_default();
+
+            match (key, kind) {
+                // Each stream has its own scale
+                (b"STRM", 0) => self.read(payload, &mut Vec::new()),
+                (_, 0) => self.read(payload, scale),
+                (b"SCAL", _) => *scale = values(kind, payload),
+                (b"ACCL", _) => self
+                    .accelerometer
+                    .extend(samples(kind, size, payload, scale)),
+                (b"GYRO", _) => self.gyroscope.extend(samples(kind, size, payload, scale)),
+                (b"GPS5", _) => self.gps.extend(samples(kind, size, payload, scale).map(
+                    |[latitude, longitude, altitude, speed_2d, speed_3d]| GpsFix {
+                        latitude,
+                        longitude,
+                        altitude,
+                        speed_2d,
+                        speed_3d,
+                    },
+                )),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Split the values of an entry into samples of `N` values, each divided by its scale
+fn samples<'a, const N: usize>(
+    kind: u8,
+    size: usize,
+    payload: &[u8],
+    scale: &'a [f64],
+) -> impl Iterator<Item = [f64; N]> + 'a {
+    let values = values(kind, payload);
+    // Skip entries with a different number of values per sample than expected
+    let count = match values.len() * size == payload.len() * N {
+        true => values.len() / N,
+        false => 0,
+    };
+
+    (0..count).map(move |sample| {
+        std::array::from_fn(|i| {
+            let divisor = match scale {
+                [] => 1.0,
+                [scale] => *scale,
+                scales => scales.get(i).copied().unwrap_or(1.0),
+            };
+            values[sample * N + i] / if divisor == 0.0 { 1.0 } else { divisor }
+        })
+    })
+}
+
+/// Read the numeric values of an entry (stored big endian), other types return no values
+fn values(kind: u8, payload: &[u8]) -> Vec<f64> {
+    fn read<const N: usize>(payload: &[u8], convert: fn([u8; N]) -> f64) -> Vec<f64> {
+        payload
+            .chunks_exact(N)
+            .map(|bytes| convert(bytes.try_into().unwrap())) // unwrap is safe as every chunk is exactly N bytes
+            .collect()
+    }
+
+    match kind {
+        b'b' => read(payload, |bytes: [u8; 1]| i8::from_be_bytes(bytes) as f64),
+        b'B' => read(payload, |bytes: [u8; 1]| u8::from_be_bytes(bytes) as f64),
+        b's' => read(payload, |bytes: [u8; 2]| i16::from_be_bytes(bytes) as f64),
+        b'S' => read(payload, |bytes: [u8; 2]| u16::from_be_bytes(bytes) as f64),
+        b'l' => read(payload, |bytes: [u8; 4]| i32::from_be_bytes(bytes) as f64),
+        b'L' => read(payload, |bytes: [u8; 4]| u32::from_be_bytes(bytes) as f64),
+        b'f' => read(payload, |bytes: [u8; 4]| f32::from_be_bytes(bytes) as f64),
+        b'd' => read(payload, f64::from_be_bytes),
+        b'j' => read(payload, |bytes: [u8; 8]| i64::from_be_bytes(bytes) as f64),
+        b'J' => read(payload, |bytes: [u8; 8]| u64::from_be_bytes(bytes) as f64),
+        _ => Vec::new(),
+    }
+}
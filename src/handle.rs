@@ -0,0 +1,46 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A handle which can cancel blocking operations of a decoder from another thread
+///
+/// Opening a decoder and reading from it can block for a long time (or forever) on slow or stalled network sources.
+/// Once cancelled, those operations are aborted and return [`DecodeError::Cancelled`](crate::DecodeError::Cancelled),
+/// as does every following call which reads frames from the decoder.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::{DecodeError, DecoderHandle, VideoDecoder};
+///
+/// # let file = "test.mp4";
+/// let handle = DecoderHandle::new();
+/// let mut decoder = VideoDecoder::builder(file)
+///     .handle(handle.clone())
+///     .build()
+///     .unwrap();
+///
+/// // Usually from another thread
+/// handle.cancel();
+/// assert!(matches!(decoder.next_frame(), Err(DecodeError::Cancelled)));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DecoderHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl DecoderHandle {
+    /// Create a new handle, this can be passed to [`DecoderBuilder::handle`](crate::DecoderBuilder::handle)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel any current and future blocking operations of every decoder using this handle
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`DecoderHandle::cancel`] has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,119 @@
+use super::{Frame, PixelFormat};
+use std::f32::consts::PI;
+
+/// The size the frame is scaled to before the DCT of [`Frame::phash`]
+const PHASH_SIZE: usize = 32;
+/// The number of low frequency coefficients used (in each direction) by [`Frame::phash`]
+const PHASH_BITS: usize = 8;
+
+impl Frame {
+    /// A 64 bit perceptual hash of the frame, based on its low frequencies (using a discrete cosine transform).
+    ///
+    /// Frames which look alike have hashes which differ in few bits, so the number of different bits (`(a ^ b).count_ones()`)
+    /// measures how similar two frames are, regardless of their size or small changes such as compression artifacts.
+    /// A distance of up to around 10 usually means the frames are near duplicates.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// let first = decoder.next_frame().unwrap().unwrap().phash();
+    /// let second = decoder.next_frame().unwrap().unwrap().phash();
+    /// assert!((first ^ second).count_ones() < 10);
+    /// # }
+    /// ```
+    pub fn phash(&self) -> u64 {
+        let pixels = self.grayscale(PHASH_SIZE, PHASH_SIZE);
+
+        // Only the lowest frequencies are needed, so the transform is only worked out for those
+        let cosines: Vec<f32> = (0..PHASH_BITS)
+            .flat_map(|u| {
+                (0..PHASH_SIZE).map(move |x| {
+                    ((2 * x + 1) as f32 * u as f32 * PI / (2 * PHASH_SIZE) as f32).cos()
+                })
+            })
+            .collect();
+        let cosine = |u: usize, x: usize| cosines[u * PHASH_SIZE + x];
+
+        // Transform the rows, then the columns
+        let mut rows = vec![0.0; PHASH_SIZE * PHASH_BITS];
+        for y in 0..PHASH_SIZE {
+            for u in 0..PHASH_BITS {
+                rows[y * PHASH_BITS + u] = (0..PHASH_SIZE)
+                    .map(|x| pixels[y * PHASH_SIZE + x] * cosine(u, x))
+                    .sum();
+            }
+        }
+        let mut coefficients = [0.0; PHASH_BITS * PHASH_BITS];
+        for v in 0..PHASH_BITS {
+            for u in 0..PHASH_BITS {
+                coefficients[v * PHASH_BITS + u] = (0..PHASH_SIZE)
+                    .map(|y| rows[y * PHASH_BITS + u] * cosine(v, y))
+                    .sum();
+            }
+        }
+
+        // The first coefficient is the average brightness, which would skew the median
+        let mut sorted = coefficients[1..].to_vec();
+        sorted.sort_by(f32::total_cmp);
+        let median = sorted[sorted.len() / 2];
+
+        to_bits(coefficients.iter().map(|&value| value > median))
+    }
+
+    /// A 64 bit difference hash of the frame, based on whether each part of the frame is brighter than the part next to it.
+    ///
+    /// This is cheaper than [`Frame::phash`] but less robust to changes such as different contrast,
+    /// hashes are compared in the same way (by the number of different bits).
+    pub fn dhash(&self) -> u64 {
+        let pixels = self.grayscale(9, 8);
+        to_bits((0..8).flat_map(|y| {
+            let row = &pixels[y * 9..(y + 1) * 9];
+            (0..8).map(move |x| row[x] < row[x + 1])
+        }))
+    }
+
+    /// Scale the frame down to `width` by `height` (averaging the pixels covered by each output pixel), and convert it to grayscale
+    fn grayscale(&self, width: usize, height: usize) -> Vec<f32> {
+        let source_width = self.dimensions.width as usize;
+        let source_height = self.dimensions.height as usize;
+        let bytes = self.format.bytes_per_pixel() / self.format.channels();
+        let value = |data: &[u8], i: usize| match bytes {
+            2 => u16::from_ne_bytes([data[i * 2], data[i * 2 + 1]]) as f32 / 257.0,
+            _ => data[i] as f32,
+        };
+
+        let mut sums = vec![0.0; width * height];
+        let mut counts = vec![0u32; width * height];
+        for y in 0..source_height {
+            let row = &self.data[y * self.stride..];
+            let target_row = y * height / source_height.max(1) * width;
+            for x in 0..source_width {
+                let luma = match self.format {
+                    PixelFormat::Gray8 => value(row, x),
+                    _ => {
+                        let pixel = x * self.format.channels();
+                        0.299 * value(row, pixel)
+                            + 0.587 * value(row, pixel + 1)
+                            + 0.114 * value(row, pixel + 2)
+                    }
+                };
+                let target = target_row + x * width / source_width.max(1);
+                sums[target] += luma;
+                counts[target] += 1;
+            }
+        }
+
+        sums.iter()
+            .zip(counts)
+            .map(|(&sum, count)| sum / count.max(1) as f32)
+            .collect()
+    }
+}
+
+/// Pack 64 bits into an integer, first bit highest
+fn to_bits(bits: impl Iterator<Item = bool>) -> u64 {
+    bits.take(64).fold(0, |hash, bit| (hash << 1) | bit as u64)
+}
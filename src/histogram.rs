@@ -0,0 +1,141 @@
+use super::{Frame, PixelFormat};
+
+/// The maximum number of pixels sampled by [`Frame::dominant_colors`]
+const DOMINANT_SAMPLES: usize = 4096;
+/// The number of refinement passes made by [`Frame::dominant_colors`]
+const DOMINANT_ITERATIONS: usize = 10;
+
+impl Frame {
+    /// Count how many times each value appears in each channel of the frame, with one 256 bin histogram per channel (see [`PixelFormat::channels`]).
+    ///
+    /// Formats with 16 bits per channel are binned by the top 8 bits of each value.
+    pub fn histogram(&self) -> Vec<[u32; 256]> {
+        let channels = self.format.channels();
+        let mut histogram = vec![[0; 256]; channels];
+        self.for_each_pixel(|pixel| {
+            for (channel, &value) in pixel.iter().enumerate() {
+                histogram[channel][value as usize] += 1;
+            }
+        });
+        histogram
+    }
+
+    /// The average color of the frame as RGB, e.g. for bias lighting.
+    ///
+    /// The alpha channel is ignored, and grayscale frames return the same value for each channel.
+    pub fn average_color(&self) -> [u8; 3] {
+        let mut sum = [0u64; 3];
+        let mut count = 0u64;
+        self.for_each_pixel(|pixel| {
+            for (total, value) in sum.iter_mut().zip(rgb(pixel)) {
+                *total += value as u64;
+            }
+            count += 1;
+        });
+        sum.map(|total| (total / count.max(1)) as u8)
+    }
+
+    /// The `k` most common colors of the frame as RGB, with the fraction of the frame each covers (most common first).
+    ///
+    /// Colors are found by clustering a sample of the pixels (k-means), so similar shades are grouped together.
+    /// Fewer than `k` colors are returned if the frame does not have that many distinct colors.
+    pub fn dominant_colors(&self, k: usize) -> Vec<([u8; 3], f32)> {
+        let mut pixels = Vec::new();
+        self.for_each_pixel(|pixel| pixels.push(rgb(pixel).map(|value| value as f32)));
+        let step = pixels.len().div_ceil(DOMINANT_SAMPLES).max(1);
+        let samples: Vec<[f32; 3]> = pixels.into_iter().step_by(step).collect();
+        if samples.is_empty() || k == 0 {
+            return Vec::new();
+        }
+
+        // Start from samples spread across the range of brightness, so the result is deterministic
+        let mut sorted = samples.clone();
+        sorted.sort_by(|a, b| a.iter().sum::<f32>().total_cmp(&b.iter().sum::<f32>()));
+        let k = k.min(samples.len());
+        let mut centers: Vec<[f32; 3]> = (0..k)
+            .map(|i| sorted[(2 * i + 1) * sorted.len() / (2 * k)])
+            .collect();
+
+        let mut counts = vec![0usize; k];
+        for _ in 0..DOMINANT_ITERATIONS {
+            let mut sums = vec![[0.0f32; 3]; k];
+            counts.fill(0);
+            for sample in &samples {
+                let nearest = nearest(&centers, sample);
+                for (sum, value) in sums[nearest].iter_mut().zip(sample) {
+                    *sum += value;
+                }
+                counts[nearest] += 1;
+            }
+
+            for ((center, sum), &count) in centers.iter_mut().zip(&sums).zip(&counts) {
+                if count > 0 {
+                    *center = sum.map(|value| value / count as f32);
+                }
+            }
+        }
+
+        let mut colors: Vec<_> = centers
+            .into_iter()
+            .zip(counts)
+            .filter(|&(_, count)| count > 0)
+            .map(|(center, count)| {
+                (
+                    center.map(|value| value.round() as u8),
+                    count as f32 / samples.len() as f32,
+                )
+            })
+            .collect();
+        colors.sort_by(|a, b| b.1.total_cmp(&a.1));
+        colors
+    }
+
+    /// Call `f` with the channels of each pixel, reduced to 8 bits per channel
+    fn for_each_pixel(&self, mut f: impl FnMut(&[u8])) {
+        let channels = self.format.channels();
+        let width = self.dimensions.width as usize;
+        let mut pixel = vec![0; channels];
+        for row in self
+            .data
+            .chunks(self.stride)
+            .take(self.dimensions.height as usize)
+        {
+            match self.format {
+                PixelFormat::Rgb48 | PixelFormat::Rgba64 => {
+                    for values in row[..width * channels * 2].chunks_exact(channels * 2) {
+                        for (channel, value) in pixel.iter_mut().zip(values.chunks_exact(2)) {
+                            *channel = (u16::from_ne_bytes([value[0], value[1]]) >> 8) as u8;
+                        }
+                        f(&pixel);
+                    }
+                }
+                _ => row[..width * channels]
+                    .chunks_exact(channels)
+                    .for_each(&mut f),
+            }
+        }
+    }
+}
+
+/// The RGB channels of a pixel, grayscale pixels use the same value for each channel
+fn rgb(pixel: &[u8]) -> [u8; 3] {
+    match pixel {
+        [r, g, b, ..] => [*r, *g, *b],
+        [luma, ..] => [*luma; 3],
+        [] => [0; 3],
+    }
+}
+
+/// The index of the center closest to a color
+fn nearest(centers: &[[f32; 3]], color: &[f32; 3]) -> usize {
+    let distance = |center: &[f32; 3]| -> f32 {
+        center
+            .iter()
+            .zip(color)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum()
+    };
+    (0..centers.len())
+        .min_by(|&a, &b| distance(&centers[a]).total_cmp(&distance(&centers[b])))
+        .unwrap_or_default()
+}
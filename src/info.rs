@@ -0,0 +1,142 @@
+//! Information about the ffmpeg libraries being used
+
+use crate::c::str_from_ptr;
+use crate::codec::{decoders, DecoderInfo};
+use std::{fmt, iter, ptr};
+
+/// The version of one of the ffmpeg libraries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LibraryVersion {
+    /// Changed when the API or ABI of the library changes in a way which is not backwards compatible
+    pub major: u32,
+    /// Changed when something is added to the library in a backwards compatible way
+    pub minor: u32,
+    /// Changed for fixes which do not change the API
+    pub micro: u32,
+}
+
+impl LibraryVersion {
+    /// Split a version as returned by the `*_version` functions (`AV_VERSION_INT`)
+    fn from_raw(version: u32) -> Self {
+        LibraryVersion {
+            major: version >> 16,
+            minor: (version >> 8) & 0xff,
+            micro: version & 0xff,
+        }
+    }
+}
+
+impl fmt::Display for LibraryVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.micro)
+    }
+}
+
+/// The ffmpeg build being used at runtime, see [`ffmpeg_info`]
+#[derive(Debug, Clone)]
+pub struct FfmpegInfo {
+    version: String,
+    configuration: String,
+    libraries: [(&'static str, LibraryVersion); 5],
+}
+
+impl FfmpegInfo {
+    /// The ffmpeg release, e.g. `7.0.2` (or a git revision for development builds)
+    #[inline]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The arguments ffmpeg was configured with when it was built, e.g. `--enable-gpl --enable-libdav1d`
+    #[inline]
+    pub fn configuration(&self) -> &str {
+        &self.configuration
+    }
+
+    /// The name and version of each linked library, e.g. `("libavcodec", 61.3.100)`
+    #[inline]
+    pub fn libraries(&self) -> &[(&'static str, LibraryVersion)] {
+        &self.libraries
+    }
+
+    /// The video decoders ffmpeg was built with, see [`available_decoders`](crate::available_decoders)
+    pub fn decoders(&self) -> impl Iterator<Item = DecoderInfo> {
+        decoders()
+    }
+
+    /// The (short) names of the container formats ffmpeg can read, e.g. `mov,mp4,m4a,3gp,3g2,mj2`
+    ///
+    /// Some demuxers handle multiple related formats, in which case the name is a comma separated list of all of them.
+    pub fn demuxers(&self) -> impl Iterator<Item = String> {
+        let mut opaque = ptr::null_mut();
+        iter::from_fn(move || unsafe {
+            let format = ffmpeg::av_demuxer_iterate(&mut opaque);
+            match format.is_null() {
+                true => None,
+                false => Some(str_from_ptr((*format).name).unwrap_or_default()),
+            }
+        })
+    }
+
+    /// The types of hardware acceleration ffmpeg was built with, e.g. `cuda` or `vaapi`
+    ///
+    /// This only means ffmpeg supports them, the hardware itself may not be present.
+    pub fn hardware_acceleration(&self) -> impl Iterator<Item = String> {
+        let mut device = ffmpeg::AVHWDeviceType::AV_HWDEVICE_TYPE_NONE;
+        iter::from_fn(move || unsafe {
+            device = ffmpeg::av_hwdevice_iterate_types(device);
+            match device {
+                ffmpeg::AVHWDeviceType::AV_HWDEVICE_TYPE_NONE => None,
+                _ => Some(
+                    str_from_ptr(ffmpeg::av_hwdevice_get_type_name(device)).unwrap_or_default(),
+                ),
+            }
+        })
+    }
+}
+
+/// Describe the ffmpeg libraries linked at runtime, e.g. to report which codecs are supported before trying to decode anything
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::ffmpeg_info;
+///
+/// let info = ffmpeg_info();
+/// println!("ffmpeg {}", info.version());
+/// for (library, version) in info.libraries() {
+///     println!("{library} {version}");
+/// }
+/// let av1 = info.decoders().any(|decoder| decoder.codec() == "av1");
+/// # }
+/// ```
+pub fn ffmpeg_info() -> FfmpegInfo {
+    unsafe {
+        FfmpegInfo {
+            version: str_from_ptr(ffmpeg::av_version_info()).unwrap_or_default(),
+            configuration: str_from_ptr(ffmpeg::avcodec_configuration()).unwrap_or_default(),
+            libraries: [
+                (
+                    "libavutil",
+                    LibraryVersion::from_raw(ffmpeg::avutil_version()),
+                ),
+                (
+                    "libavcodec",
+                    LibraryVersion::from_raw(ffmpeg::avcodec_version()),
+                ),
+                (
+                    "libavformat",
+                    LibraryVersion::from_raw(ffmpeg::avformat_version()),
+                ),
+                (
+                    "libavfilter",
+                    LibraryVersion::from_raw(ffmpeg::avfilter_version()),
+                ),
+                (
+                    "libswscale",
+                    LibraryVersion::from_raw(ffmpeg::swscale_version()),
+                ),
+            ],
+        }
+    }
+}
@@ -0,0 +1,606 @@
+//! An opened input (demuxer), shared by all of the decoders
+
+use super::{DecodeError, DecoderHandle, PushSource, VideoSource};
+use crate::builder::Variant;
+use crate::c::{dict_get, path_to_raw, read_stream, Dictionary, Stream};
+use crate::device::register_devices;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use std::{ffi, ptr, slice};
+
+/// ffmpeg buffer size
+const BUFFER_SIZE: usize = 8192;
+
+/// The signature of a custom io read callback
+type ReadFn = unsafe extern "C" fn(*mut ffi::c_void, *mut u8, i32) -> i32;
+/// The signature of a custom io seek callback
+type SeekFn = unsafe extern "C" fn(*mut ffi::c_void, i64, ffi::c_int) -> i64;
+
+unsafe extern "C" fn read_file(opaque: *mut ffi::c_void, buf: *mut u8, size: i32) -> i32 {
+    let file = &mut *(opaque as *mut File);
+    let buf = slice::from_raw_parts_mut(buf, size.max(0) as usize);
+
+    match file.read(buf) {
+        Ok(0) => ffmpeg::AVERROR_EOF,
+        Ok(size) => size as i32,
+        Err(_) => ffmpeg::AVERROR_EXTERNAL,
+    }
+}
+
+unsafe extern "C" fn seek_file(opaque: *mut ffi::c_void, offset: i64, whence: ffi::c_int) -> i64 {
+    let file = &mut *(opaque as *mut File);
+
+    if whence & ffmpeg::AVSEEK_SIZE as ffi::c_int != 0 {
+        return file.metadata().map_or(-1, |metadata| metadata.len() as i64);
+    }
+
+    // `SEEK_SET`, `SEEK_CUR` and `SEEK_END` from stdio
+    let position = match whence & !(ffmpeg::AVSEEK_FORCE as ffi::c_int) {
+        0 => SeekFrom::Start(offset as u64),
+        1 => SeekFrom::Current(offset),
+        2 => SeekFrom::End(offset),
+        _ => return -1,
+    };
+    file.seek(position).map_or(-1, |position| position as i64)
+}
+
+/// Whether ffmpeg would open a path as a url rather than a file, e.g. the relative path `clip:1.mp4` has the protocol `clip`.
+///
+/// Real urls (e.g. `https://...` or `udp://...`) are also url-like, so this is only used for paths which exist as files.
+fn is_url_like(path: &Path) -> bool {
+    let bytes = path.as_os_str().as_encoded_bytes();
+    // A single letter is the drive of an absolute path on windows
+    let shortest = if cfg!(windows) { 2 } else { 1 };
+    bytes
+        .iter()
+        .position(|&byte| byte == b':')
+        .is_some_and(|end| {
+            end >= shortest
+                && bytes[..end]
+                    .iter()
+                    .all(|byte| byte.is_ascii_alphanumeric() || b"+-.".contains(byte))
+        })
+}
+
+/// Options used when opening an [`Input`]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct InputOptions {
+    /// A handle which can cancel blocking operations
+    pub handle: Option<DecoderHandle>,
+    /// The maximum time a single blocking operation may take
+    pub timeout: Option<Duration>,
+    /// Options passed to the demuxer (and protocol) when opening the input
+    pub format_options: Vec<(String, String)>,
+    /// The name of the demuxer to use, `None` probes the data to find it
+    pub format: Option<String>,
+    /// Trust the codec parameters in the header of the container rather than reading the stream information, if they are complete
+    pub fast_open: bool,
+    /// Read as few packets as possible for the stream information, if the codec parameters in the header of the container are complete
+    pub skip_stream_analysis: bool,
+}
+
+/// The state read by the interrupt callback ffmpeg calls during blocking operations
+struct Interrupt {
+    handle: DecoderHandle,
+    timeout: Option<Duration>,
+    /// When the current blocking operation started
+    started: Instant,
+}
+
+impl Interrupt {
+    fn triggered(&self) -> bool {
+        self.handle.is_cancelled()
+            || self
+                .timeout
+                .is_some_and(|timeout| self.started.elapsed() > timeout)
+    }
+}
+
+unsafe extern "C" fn interrupt_callback(opaque: *mut ffi::c_void) -> ffi::c_int {
+    let interrupt = &*(opaque as *const Interrupt);
+    interrupt.triggered() as ffi::c_int
+}
+
+/// The read state of a [`VideoSource::Push(_)`]
+struct PushReader {
+    source: PushSource,
+    /// Custom io does not check the interrupt callback, so the reader checks it while waiting for data
+    interrupt: *const Interrupt,
+}
+
+unsafe extern "C" fn read_push(opaque: *mut ffi::c_void, buf: *mut u8, size: i32) -> i32 {
+    let reader = &*(opaque as *const PushReader);
+    let buf = slice::from_raw_parts_mut(buf, size.max(0) as usize);
+
+    match reader.source.read(buf, || (*reader.interrupt).triggered()) {
+        Some(0) => ffmpeg::AVERROR_EOF,
+        Some(size) => size as i32,
+        None => ffmpeg::AVERROR_EXIT,
+    }
+}
+
+pub(crate) struct Input {
+    pub ctx: *mut ffmpeg::AVFormatContext,
+    /// ffmpeg holds a pointer to this, so it is boxed to stay in place when the input is moved
+    interrupt: Box<Interrupt>,
+    /// Only used if we got an in memory source or a [`VideoSource::Push(_)`]
+    avio: Option<*mut ffmpeg::AVIOContext>,
+    /// The read state of an in memory source, ffmpeg holds a pointer to this
+    _stream: Option<Box<Stream>>,
+    /// The read state of a [`VideoSource::Push(_)`], ffmpeg holds a pointer to this
+    _push: Option<Box<PushReader>>,
+    /// A file which ffmpeg can not open itself (see [`path_to_raw`]), ffmpeg holds a pointer to this
+    _file: Option<Box<File>>,
+    /// Whether `avformat_find_stream_info` has been called, see [`Input::find_stream_info`]
+    stream_info: bool,
+    /// The source data, we must store it so the pointer passed to ffmpeg is not dropped
+    _source: VideoSource,
+}
+
+// SAFETY: The contexts are allocated in `Input::open`, owned exclusively by the input, and freed only in `Drop`.
+// All data that ffmpeg points back into (the source data and the raw stream state) is heap allocated
+// and owned by the input, so it does not move when the input does.
+unsafe impl Send for Input {}
+
+impl Input {
+    /// Open the source data and read its stream information
+    pub fn open(source: VideoSource, options: &InputOptions) -> Result<Self, DecodeError> {
+        unsafe {
+            let mut input = Input {
+                ctx: ffmpeg::avformat_alloc_context(),
+                interrupt: Box::new(Interrupt {
+                    handle: options.handle.clone().unwrap_or_default(),
+                    timeout: options.timeout,
+                    started: Instant::now(),
+                }),
+                avio: None,
+                _stream: None,
+                _push: None,
+                _file: None,
+                stream_info: false,
+                _source: source,
+            };
+
+            if input.ctx.is_null() {
+                return Err(DecodeError::AllocationFailed);
+            }
+
+            (*input.ctx).interrupt_callback = ffmpeg::AVIOInterruptCB {
+                callback: Some(interrupt_callback),
+                opaque: input.interrupt.as_ref() as *const Interrupt as *mut ffi::c_void,
+            };
+
+            // The read state is boxed so the pointer given to ffmpeg stays valid once the input is moved
+            let mut source_path = None;
+            let reader: Option<(*mut ffi::c_void, ReadFn, Option<SeekFn>)> = if let Some(data) =
+                input._source.as_bytes()
+            {
+                let stream = input._stream.insert(Box::new(Stream {
+                    length: data.len(),
+                    offset: 0,
+                    data: data.as_ptr(),
+                }));
+                Some((stream.as_mut() as *mut Stream as _, read_stream, None))
+            } else if let VideoSource::Push(ref source) = input._source {
+                let reader = input._push.insert(Box::new(PushReader {
+                    source: source.clone(),
+                    interrupt: input.interrupt.as_ref(),
+                }));
+                Some((reader.as_mut() as *mut PushReader as _, read_push, None))
+            } else if let VideoSource::Filesystem(ref path) = input._source {
+                match path_to_raw(path).filter(|_| !(is_url_like(path) && path.is_file())) {
+                    Some(raw) => {
+                        source_path = Some(raw);
+                        None
+                    }
+                    // e.g. a path which is not valid unicode on windows, or a file which ffmpeg would open as a url, so the file is read through custom io instead
+                    None => {
+                        let file = File::open(path).map_err(|_| DecodeError::UnableToOpenInput)?;
+                        let file = input._file.insert(Box::new(file));
+                        Some((file.as_mut() as *mut File as _, read_file, Some(seek_file)))
+                    }
+                }
+            } else if let Some(pattern) = match input._source {
+                VideoSource::ImageSequence(ref sequence) => sequence.pattern_path(),
+                _ => None,
+            } {
+                source_path = Some(path_to_raw(pattern).ok_or(DecodeError::InvalidSource)?);
+                None
+            } else if let VideoSource::Device(ref device) = input._source {
+                let name =
+                    ffi::CString::new(device.name()).map_err(|_| DecodeError::UnableToOpenInput)?;
+                source_path = Some(name.into_bytes_with_nul());
+                None
+            } else {
+                None
+            };
+
+            if let Some((opaque, read, seek)) = reader {
+                let buffer = ffmpeg::av_malloc(BUFFER_SIZE);
+                if buffer.is_null() {
+                    return Err(DecodeError::AllocationFailed);
+                }
+
+                let avio = ffmpeg::avio_alloc_context(
+                    buffer as *mut u8,
+                    BUFFER_SIZE as i32,
+                    0,
+                    opaque,
+                    Some(read),
+                    None,
+                    seek,
+                );
+
+                if avio.is_null() {
+                    ffmpeg::av_free(buffer);
+                    return Err(DecodeError::AllocationFailed);
+                }
+
+                input.avio = Some(avio);
+                (*input.ctx).pb = avio;
+                (*input.ctx).flags |= ffmpeg::AVFMT_FLAG_CUSTOM_IO;
+            }
+
+            let path = source_path
+                .as_ref()
+                .map_or(ptr::null(), |path| path.as_ptr());
+
+            let (mut format_options, mut format_name) =
+                (options.format_options.clone(), options.format.as_deref());
+            if let VideoSource::Device(ref device) = input._source {
+                register_devices();
+                // Added first so they can still be overridden with `DecoderBuilder::format_option`
+                format_options.splice(0..0, device.options().iter().cloned());
+                format_name = format_name.or(Some(device.format()));
+            }
+            if let VideoSource::ImageSequence(ref sequence) = input._source {
+                format_options.splice(0..0, sequence.options());
+                format_name = format_name.or(Some(sequence.format()));
+            }
+
+            let mut format_options =
+                Dictionary::new(&format_options).ok_or(DecodeError::InvalidOption)?;
+
+            let format = match format_name {
+                Some(name) => {
+                    let name = ffi::CString::new(name).map_err(|_| DecodeError::UnknownFormat)?;
+                    let format = ffmpeg::av_find_input_format(name.as_ptr());
+                    if format.is_null() {
+                        return Err(DecodeError::UnknownFormat);
+                    }
+                    format
+                }
+                None => ptr::null(),
+            };
+
+            // Open input, on failure ffmpeg frees the context and sets it to null
+            match ffmpeg::avformat_open_input(
+                &mut input.ctx,
+                path as *const ffi::c_char,
+                format,
+                &mut format_options.0,
+            ) {
+                0 => {}
+                // No demuxer recognised the data (or its header is damaged)
+                ffmpeg::AVERROR_INVALIDDATA => {
+                    return Err(input.error_or(DecodeError::NotAMediaFile));
+                }
+                // The data ended before the header could be read
+                ffmpeg::AVERROR_EOF => return Err(input.error_or(DecodeError::TruncatedInput)),
+                _ => return Err(input.error_or(DecodeError::UnableToOpenInput)),
+            }
+
+            // Falls back to reading the stream information if the header is missing anything
+            let complete = input.has_codec_parameters();
+            if options.skip_stream_analysis && complete {
+                // Options passed explicitly take priority
+                let set = |key: &str| options.format_options.iter().any(|(k, _)| k == key);
+                if !set("probesize") {
+                    (*input.ctx).probesize = 32;
+                }
+                if !set("analyzeduration") {
+                    (*input.ctx).max_analyze_duration = 0;
+                }
+                if !set("fpsprobesize") {
+                    (*input.ctx).fps_probe_size = 0;
+                }
+            }
+            if !(options.fast_open && complete) {
+                input.find_stream_info()?;
+            }
+
+            Ok(input)
+        }
+    }
+
+    /// Read packets from the start of the input to fill in the stream information which is not in the header, see `avformat_find_stream_info`.
+    ///
+    /// This is done when the input is opened (unless [`InputOptions::fast_open`] is set), so calling it again does nothing.
+    pub fn find_stream_info(&mut self) -> Result<(), DecodeError> {
+        if self.stream_info {
+            return Ok(());
+        }
+
+        self.restart_timeout();
+        match unsafe { ffmpeg::avformat_find_stream_info(self.ctx, ptr::null_mut()) } {
+            ffmpeg::AVERROR_EOF => return Err(self.error_or(DecodeError::TruncatedInput)),
+            result if result < 0 => {
+                return Err(self.error_or(DecodeError::UnableToReadStreamInfo));
+            }
+            _ => {}
+        }
+        self.stream_info = true;
+
+        Ok(())
+    }
+
+    /// Whether the header gave the codec, dimensions and framerate of every video stream and the codec, sample rate and channels of every audio stream.
+    ///
+    /// The pixel format of video streams is not checked, as most containers leave it to the codec.
+    fn has_codec_parameters(&self) -> bool {
+        let valid = |rate: ffmpeg::AVRational| rate.num > 0 && rate.den > 0;
+        self.stream_count() > 0
+            && (0..self.stream_count()).all(|index| unsafe {
+                let stream = self.stream(index);
+                let codecpar = (*stream).codecpar;
+                match (*codecpar).codec_type {
+                    ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO => {
+                        (*codecpar).codec_id != ffmpeg::AVCodecID::AV_CODEC_ID_NONE
+                            && (*codecpar).width > 0
+                            && (*codecpar).height > 0
+                            && (valid((*stream).r_frame_rate) || valid((*stream).avg_frame_rate))
+                    }
+                    ffmpeg::AVMediaType::AVMEDIA_TYPE_AUDIO => {
+                        (*codecpar).codec_id != ffmpeg::AVCodecID::AV_CODEC_ID_NONE
+                            && (*codecpar).sample_rate > 0
+                            && (*codecpar).ch_layout.nb_channels > 0
+                    }
+                    _ => true,
+                }
+            })
+    }
+
+    /// Read the next packet of the input, see `av_read_frame`.
+    ///
+    /// Returns [`DecodeError::Cancelled`] if the input has been cancelled (or the read timed out).
+    pub fn read_frame(&mut self, packet: *mut ffmpeg::AVPacket) -> Result<ffi::c_int, DecodeError> {
+        self.restart_timeout();
+        if self.cancelled() {
+            return Err(DecodeError::Cancelled);
+        }
+
+        let result = unsafe { ffmpeg::av_read_frame(self.ctx, packet) };
+        if result < 0 && self.interrupted() {
+            return Err(DecodeError::Cancelled);
+        }
+
+        Ok(result)
+    }
+
+    /// Seek to the keyframe at or before the given timestamp of a stream, see `av_seek_frame`.
+    pub fn seek_frame(&mut self, stream: i32, ts: i64, flags: ffi::c_int) -> ffi::c_int {
+        self.restart_timeout();
+        unsafe { ffmpeg::av_seek_frame(self.ctx, stream, ts, flags) }
+    }
+
+    /// Seek back to the start of the input, see `avformat_seek_file`.
+    pub fn rewind(&mut self, stream: i32) -> ffi::c_int {
+        self.restart_timeout();
+        unsafe {
+            let duration = (*self.stream(stream as usize)).duration;
+            ffmpeg::avio_seek((*self.ctx).pb, 0, 0);
+            ffmpeg::avformat_seek_file(self.ctx, stream, 0, 0, duration, 0)
+        }
+    }
+
+    /// The timeout applies to each blocking operation separately, so this must be called before each one
+    fn restart_timeout(&mut self) {
+        self.interrupt.started = Instant::now();
+    }
+
+    /// The handle which cancels blocking operations of this input
+    pub fn handle(&self) -> DecoderHandle {
+        self.interrupt.handle.clone()
+    }
+
+    /// Whether the handle of this input has been cancelled
+    pub fn cancelled(&self) -> bool {
+        self.interrupt.handle.is_cancelled()
+    }
+
+    /// Whether the last blocking operation was cancelled (or timed out)
+    pub fn interrupted(&self) -> bool {
+        self.interrupt.triggered()
+    }
+
+    /// The error a failed operation should return, [`DecodeError::Cancelled`] if it was interrupted
+    pub fn error_or(&self, error: DecodeError) -> DecodeError {
+        match self.interrupted() {
+            true => DecodeError::Cancelled,
+            false => error,
+        }
+    }
+
+    /// Get a stream of the input
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than the number of streams in the input.
+    pub unsafe fn stream(&self, index: usize) -> *mut ffmpeg::AVStream {
+        *(*self.ctx).streams.add(index)
+    }
+
+    /// The number of streams in the input
+    pub fn stream_count(&self) -> usize {
+        unsafe { (*self.ctx).nb_streams as usize }
+    }
+
+    /// Find the first stream of the given type which is not discarded (see [`Input::select_program`])
+    pub fn find_stream(&self, kind: ffmpeg::AVMediaType) -> Option<usize> {
+        (0..self.stream_count()).find(|&i| unsafe {
+            (*(*self.stream(i)).codecpar).codec_type == kind
+                && (*self.stream(i)).discard != ffmpeg::AVDiscard::AVDISCARD_ALL
+        })
+    }
+
+    /// Find the GoPro telemetry (GPMF) streams
+    pub fn find_gpmf_streams(&self) -> Vec<usize> {
+        (0..self.stream_count())
+            .filter(|&i| {
+                self.is_stream(i, ffmpeg::AVMediaType::AVMEDIA_TYPE_DATA)
+                    && unsafe { (*(*self.stream(i)).codecpar).codec_tag }
+                        == u32::from_le_bytes(*b"gpmd")
+            })
+            .collect()
+    }
+
+    /// Find the first video stream which is not an attached picture (e.g. cover art)
+    pub fn find_video_stream(&self) -> Option<usize> {
+        (0..self.stream_count()).find(|&i| {
+            self.is_stream(i, ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO) && !self.is_attached_pic(i)
+        })
+    }
+
+    /// Find the first video stream of a program which is not an attached picture
+    pub fn find_program_video_stream(&self, program: usize) -> Option<usize> {
+        unsafe {
+            let program = *(*self.ctx).programs.add(program);
+            (0..(*program).nb_stream_indexes as usize)
+                .map(|i| *(*program).stream_index.add(i) as usize)
+                .find(|&i| {
+                    self.is_stream(i, ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO)
+                        && !self.is_attached_pic(i)
+                })
+        }
+    }
+
+    /// Have the demuxer discard every program other than `program` (and their streams), returns `false` if the program does not exist
+    pub fn select_program(&self, program: usize) -> bool {
+        self.discard_programs(|index, _| index == program);
+        program < unsafe { (*self.ctx).nb_programs as usize }
+    }
+
+    /// Have the demuxer discard the programs which `keep` returns `false` for (given the index of the program and its streams),
+    /// along with the streams which are only part of discarded programs
+    fn discard_programs(&self, keep: impl Fn(usize, &[usize]) -> bool) {
+        unsafe {
+            // Streams can be shared between programs (e.g. an audio rendition used by multiple variants)
+            let mut kept = vec![None; self.stream_count()];
+            for index in 0..(*self.ctx).nb_programs as usize {
+                let program = *(*self.ctx).programs.add(index);
+                let streams: Vec<usize> = (0..(*program).nb_stream_indexes as usize)
+                    .map(|i| *(*program).stream_index.add(i) as usize)
+                    .collect();
+                let keep = keep(index, &streams);
+                (*program).discard = match keep {
+                    true => ffmpeg::AVDiscard::AVDISCARD_DEFAULT,
+                    false => ffmpeg::AVDiscard::AVDISCARD_ALL,
+                };
+                for stream in streams {
+                    if let Some(kept) = kept.get_mut(stream) {
+                        *kept = Some(kept.unwrap_or(false) || keep);
+                    }
+                }
+            }
+
+            for (index, kept) in kept.into_iter().enumerate() {
+                if kept == Some(false) {
+                    (*self.stream(index)).discard = ffmpeg::AVDiscard::AVDISCARD_ALL;
+                }
+            }
+        }
+    }
+
+    /// Find the video stream of the given variant of an adaptive stream, and have the demuxer discard the other variants
+    pub fn select_variant(&self, variant: Variant) -> Option<usize> {
+        unsafe {
+            let streams: Vec<_> = (0..self.stream_count())
+                .filter(|&i| {
+                    self.is_stream(i, ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO)
+                        && !self.is_attached_pic(i)
+                })
+                .map(|i| {
+                    let stream = self.stream(i);
+                    let bandwidth = dict_get((*stream).metadata, c"variant_bitrate")
+                        .and_then(|bitrate| bitrate.parse().ok())
+                        .unwrap_or((*(*stream).codecpar).bit_rate.max(0) as u64);
+                    (i, (bandwidth, (*(*stream).codecpar).height.max(0) as u32))
+                })
+                .collect();
+
+            let lowest = streams.iter().min_by_key(|(_, quality)| *quality);
+            let highest_where = |allowed: &dyn Fn(&(u64, u32)) -> bool| {
+                streams
+                    .iter()
+                    .filter(|(_, quality)| allowed(quality))
+                    .max_by_key(|(_, quality)| *quality)
+                    .or(lowest)
+            };
+            let (selected, _) = match variant {
+                Variant::Highest => highest_where(&|_| true),
+                Variant::Lowest => lowest,
+                Variant::MaxBandwidth(max) => highest_where(&|(bandwidth, _)| *bandwidth <= max),
+                Variant::MaxHeight(max) => highest_where(&|(_, height)| *height <= max),
+            }
+            .copied()?;
+
+            for &(i, _) in &streams {
+                if i != selected {
+                    (*self.stream(i)).discard = ffmpeg::AVDiscard::AVDISCARD_ALL;
+                }
+            }
+            // Each variant of an HLS playlist is a program, which also holds its audio
+            self.discard_programs(|_, streams| streams.contains(&selected));
+            Some(selected)
+        }
+    }
+
+    /// Find the first attached picture (e.g. cover art)
+    pub fn find_attached_pic(&self) -> Option<usize> {
+        (0..self.stream_count()).find(|&i| self.is_attached_pic(i))
+    }
+
+    /// Copy the encoded data of the first attached picture
+    pub fn cover_art(&self) -> Option<Vec<u8>> {
+        unsafe {
+            let packet = &(*self.stream(self.find_attached_pic()?)).attached_pic;
+            if packet.data.is_null() || packet.size <= 0 {
+                return None;
+            }
+
+            Some(slice::from_raw_parts(packet.data, packet.size as usize).to_vec())
+        }
+    }
+
+    /// Check whether the stream at `index` is an attached picture, these only hold a single image rather than video
+    fn is_attached_pic(&self, index: usize) -> bool {
+        unsafe {
+            (*self.stream(index)).disposition & ffmpeg::AV_DISPOSITION_ATTACHED_PIC as i32 != 0
+        }
+    }
+
+    /// Check that `index` is a stream of the given type
+    pub fn is_stream(&self, index: usize, kind: ffmpeg::AVMediaType) -> bool {
+        index < self.stream_count()
+            && unsafe { (*(*self.stream(index)).codecpar).codec_type } == kind
+    }
+}
+
+impl Drop for Input {
+    fn drop(&mut self) {
+        unsafe {
+            // The input must be closed before the custom io context it reads from is freed
+            if !self.ctx.is_null() {
+                ffmpeg::avformat_close_input(&mut self.ctx);
+            }
+
+            if let Some(mut avio) = self.avio {
+                // ffmpeg may have replaced the buffer we allocated, so free whatever it currently holds
+                ffmpeg::av_freep(&mut (*avio).buffer as *mut *mut u8 as *mut ffi::c_void);
+                ffmpeg::avio_context_free(&mut avio);
+            }
+        }
+    }
+}
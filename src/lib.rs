@@ -1,23 +1,147 @@
+mod animation;
+mod audio;
+mod bitstream;
+mod black;
+mod builder;
 mod c;
+mod cache;
+mod caption;
+mod chapter;
+mod codec;
+mod compare;
+mod convert;
+mod cover;
+mod data;
 mod decoder;
+mod device;
+mod encoder;
 mod error;
+#[cfg(feature = "image")]
+mod export;
+mod filter;
+mod frame;
+#[cfg(feature = "gpmf")]
+mod gpmf;
+mod handle;
+mod hash;
+mod histogram;
+mod info;
+mod input;
+mod logging;
+mod loudness;
+mod media;
+mod motion;
+mod overlay;
+mod paced;
+mod parallel;
+mod playlist;
+mod position;
+mod program;
+mod remux;
+mod reversed;
+mod scene;
+mod sequence;
+mod sink;
 mod source;
+mod stats;
+mod stream;
+mod subtitle;
+mod tensor;
+#[cfg(feature = "wgpu")]
+mod texture;
+mod threaded;
+mod thumbnail;
+mod timecode;
+mod transcode;
+mod transform;
+mod validate;
+mod waveform;
 
+#[cfg(feature = "bytes")]
+pub use bytes;
+/// The ffmpeg bindings used by this crate, for use with [`VideoDecoder::as_raw_codec_ctx`] and [`VideoDecoder::with_raw_frame`]
+pub use ffmpeg;
+#[cfg(feature = "futures")]
+pub use futures;
 #[cfg(feature = "image")]
 pub use image;
+#[cfg(feature = "ndarray")]
+pub use ndarray;
+#[cfg(feature = "wgpu")]
+pub use wgpu;
+
+pub use animation::{ApngWriter, GifWriter};
+pub use audio::{extract_audio, AudioFrame, AudioSpec};
+pub use bitstream::BitstreamDecoder;
+pub use black::{detect_black_frames, BlackSegment};
+pub use builder::{
+    DecoderBuilder, ErrorPolicy, FitMode, LoopMode, Sampling, ScalingQuality, ThreadType,
+    ToneMapping, Variant,
+};
+pub use chapter::Chapter;
+pub use codec::{available_decoders, DecoderInfo};
+pub use compare::{compare, Comparison, FrameQuality};
+pub use cover::cover_art;
+pub use data::DataPacket;
+pub use decoder::VideoDecoder;
+pub use device::{list_devices, Device, DeviceInfo};
+pub use encoder::{EncoderBuilder, VideoCodec, VideoEncoder};
+pub use error::{DecodeError, EncodeError, TranscodeError};
+#[cfg(feature = "image")]
+pub use export::FrameExporter;
+pub use frame::{Frame, FrameInfo, PixelFormat, Plane};
+#[cfg(feature = "gpmf")]
+pub use gpmf::{GpsFix, Telemetry};
+pub use handle::DecoderHandle;
+pub use info::{ffmpeg_info, FfmpegInfo, LibraryVersion};
+#[cfg(feature = "log")]
+pub use logging::capture_ffmpeg_logs;
+pub use logging::{set_ffmpeg_log_level, LogLevel};
+pub use loudness::{analyze_loudness, Loudness, Silence};
+pub use media::{MediaDecoder, MediaFrame};
+pub use motion::MotionVector;
+pub use paced::PacedDecoder;
+pub use parallel::decode_parallel;
+pub use playlist::PlaylistDecoder;
+pub use position::DecoderPosition;
+pub use program::Program;
+pub use remux::{cut, remux, Clip};
+pub use reversed::ReversedFrames;
+pub use scene::{scene_changes, SceneChange};
+pub use sequence::ImageSequence;
+#[cfg(feature = "futures")]
+pub use sink::ForwardError;
+pub use source::{PushSource, VideoSource};
+pub use stats::DecoderStats;
+pub use stream::{StreamInfo, StreamKind};
+pub use subtitle::{SubtitleBitmap, SubtitleContent, SubtitleCue, SubtitleDecoder};
+pub use tensor::TensorLayout;
+pub use threaded::FrameReceiver;
+#[cfg(feature = "image")]
+pub use thumbnail::contact_sheet;
+pub use thumbnail::{thumbnails, thumbnails_with, ThumbnailOptions};
+pub use timecode::Timecode;
+pub use transcode::{transcode, TranscodeOptions};
+pub use validate::{validate, validate_with, ValidationIssue, ValidationMode, ValidationReport};
+pub use waveform::{audio_peaks, Peak};
 
-pub use decoder::{Frame, VideoDecoder};
-pub use error::DecodeError;
-pub use source::VideoSource;
+use std::{fmt, ops};
 
 /// The height and width of something
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Dimensions {
     width: u32,
     height: u32,
 }
 
 impl Dimensions {
+    /// Create dimensions from a width and height (in pixels)
+    #[inline]
+    pub const fn new(width: u32, height: u32) -> Self {
+        Dimensions { width, height }
+    }
+
     #[inline]
     pub fn width(&self) -> u32 {
         self.width
@@ -27,4 +151,108 @@ impl Dimensions {
     pub fn height(&self) -> u32 {
         self.height
     }
+
+    /// The width divided by the height, e.g. `1.777...` for 16:9 (`0.0` if the height is zero)
+    #[inline]
+    pub fn aspect_ratio(&self) -> f64 {
+        match self.height {
+            0 => 0.0,
+            height => self.width as f64 / height as f64,
+        }
+    }
+
+    /// Whether this is wider than it is tall
+    #[inline]
+    pub fn is_landscape(&self) -> bool {
+        self.width > self.height
+    }
+
+    /// Whether this is taller than it is wide
+    #[inline]
+    pub fn is_portrait(&self) -> bool {
+        self.height > self.width
+    }
+
+    /// The number of pixels
+    #[inline]
+    pub fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+
+    /// The largest size with the same aspect ratio which fits within `max` (this may be larger than `self`), rounded to the nearest pixel.
+    ///
+    /// ```rust
+    /// # use ffmpeg_video_decoder::Dimensions;
+    /// let video = Dimensions::new(1920, 1080);
+    /// assert_eq!(video.scaled_to_fit(Dimensions::new(320, 320)), Dimensions::new(320, 180));
+    /// ```
+    pub fn scaled_to_fit(&self, max: Dimensions) -> Dimensions {
+        let scale =
+            (max.width as f64 / self.width as f64).min(max.height as f64 / self.height as f64);
+        self.scaled(scale)
+    }
+
+    /// The smallest size with the same aspect ratio which covers all of `target` (this may be smaller than `self`), rounded to the nearest pixel.
+    ///
+    /// ```rust
+    /// # use ffmpeg_video_decoder::Dimensions;
+    /// let video = Dimensions::new(1920, 1080);
+    /// assert_eq!(video.scaled_to_cover(Dimensions::new(320, 320)), Dimensions::new(569, 320));
+    /// ```
+    pub fn scaled_to_cover(&self, target: Dimensions) -> Dimensions {
+        let scale = (target.width as f64 / self.width as f64)
+            .max(target.height as f64 / self.height as f64);
+        self.scaled(scale)
+    }
+
+    /// Multiply both sides by `scale`, rounded to the nearest pixel (and at least one pixel unless this is empty)
+    fn scaled(&self, scale: f64) -> Dimensions {
+        if self.width == 0 || self.height == 0 || !scale.is_finite() {
+            return *self;
+        }
+
+        let side = |side: u32| (side as f64 * scale).round().clamp(1.0, u32::MAX as f64) as u32;
+        Dimensions::new(side(self.width), side(self.height))
+    }
+}
+
+impl fmt::Display for Dimensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl From<(u32, u32)> for Dimensions {
+    fn from((width, height): (u32, u32)) -> Self {
+        Dimensions::new(width, height)
+    }
+}
+
+impl ops::Mul<u32> for Dimensions {
+    type Output = Dimensions;
+
+    /// Scale both sides, saturating at `u32::MAX`
+    fn mul(self, scale: u32) -> Dimensions {
+        Dimensions::new(
+            self.width.saturating_mul(scale),
+            self.height.saturating_mul(scale),
+        )
+    }
+}
+
+impl ops::Div<u32> for Dimensions {
+    type Output = Dimensions;
+
+    fn div(self, scale: u32) -> Dimensions {
+        Dimensions::new(self.width / scale, self.height / scale)
+    }
+}
+
+impl ops::Mul<f64> for Dimensions {
+    type Output = Dimensions;
+
+    /// Scale both sides, rounded to the nearest pixel (and at least one pixel unless this is empty)
+    fn mul(self, scale: f64) -> Dimensions {
+        self.scaled(scale)
+    }
 }
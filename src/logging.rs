@@ -0,0 +1,112 @@
+//! Control over the messages ffmpeg logs
+
+/// The most verbose level of messages ffmpeg should log, see [`set_ffmpeg_log_level`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    /// Log nothing
+    Quiet,
+    /// Only log errors which will cause the process to crash
+    Panic,
+    /// Only log errors which can not be recovered from
+    Fatal,
+    /// Log any errors
+    Error,
+    /// Log errors and warnings (this is the ffmpeg default)
+    Warning,
+    /// Also log information about the input and decoders, such as the streams found when opening a file
+    Info,
+    /// Like [`LogLevel::Info`], with more detail
+    Verbose,
+    /// Also log messages useful for debugging ffmpeg itself, this can be very verbose
+    Debug,
+    /// Log everything, including a message for every packet and frame in some places
+    Trace,
+}
+
+impl LogLevel {
+    fn as_raw(&self) -> std::ffi::c_int {
+        match self {
+            LogLevel::Quiet => ffmpeg::AV_LOG_QUIET,
+            LogLevel::Panic => ffmpeg::AV_LOG_PANIC,
+            LogLevel::Fatal => ffmpeg::AV_LOG_FATAL,
+            LogLevel::Error => ffmpeg::AV_LOG_ERROR,
+            LogLevel::Warning => ffmpeg::AV_LOG_WARNING,
+            LogLevel::Info => ffmpeg::AV_LOG_INFO,
+            LogLevel::Verbose => ffmpeg::AV_LOG_VERBOSE,
+            LogLevel::Debug => ffmpeg::AV_LOG_DEBUG,
+            LogLevel::Trace => ffmpeg::AV_LOG_TRACE,
+        }
+    }
+}
+
+/// Set the most verbose level of messages ffmpeg should log, [`LogLevel::Quiet`] silences ffmpeg entirely.
+///
+/// This affects every decoder (and anything else using ffmpeg in the process), and also applies to messages captured with `capture_ffmpeg_logs`.
+pub fn set_ffmpeg_log_level(level: LogLevel) {
+    unsafe { ffmpeg::av_log_set_level(level.as_raw()) }
+}
+
+/// Forward messages logged by ffmpeg to the [`log`](https://docs.rs/log) crate (with the target `ffmpeg`), rather than printing them to stderr.
+///
+/// This affects anything using ffmpeg in the process, and can be called multiple times. Messages are still filtered by [`set_ffmpeg_log_level`] first.
+/// Use a bridge such as `tracing-log` to receive the messages with `tracing`.
+#[cfg(feature = "log")]
+pub fn capture_ffmpeg_logs() {
+    unsafe { ffmpeg::av_log_set_callback(Some(capture::callback)) }
+}
+
+#[cfg(feature = "log")]
+mod capture {
+    use std::cell::{Cell, RefCell};
+    use std::ffi::{c_char, c_int, c_void, CStr};
+
+    thread_local! {
+        /// Messages are sent in parts, so each line is built up until it ends
+        static LINE: RefCell<String> = const { RefCell::new(String::new()) };
+        /// Whether the next part starts a new line (and so should have the context prefix)
+        static PRINT_PREFIX: Cell<c_int> = const { Cell::new(1) };
+    }
+
+    fn level(level: c_int) -> log::Level {
+        match level {
+            ..=ffmpeg::AV_LOG_ERROR => log::Level::Error,
+            ..=ffmpeg::AV_LOG_WARNING => log::Level::Warn,
+            ..=ffmpeg::AV_LOG_INFO => log::Level::Info,
+            ..=ffmpeg::AV_LOG_VERBOSE => log::Level::Debug,
+            _ => log::Level::Trace,
+        }
+    }
+
+    pub unsafe extern "C" fn callback(
+        avcl: *mut c_void,
+        level: c_int,
+        fmt: *const c_char,
+        args: ffmpeg::va_list,
+    ) {
+        if level > ffmpeg::av_log_get_level() {
+            return;
+        }
+
+        let mut buffer = [0 as c_char; 1024];
+        let mut print_prefix = PRINT_PREFIX.get();
+        ffmpeg::av_log_format_line2(
+            avcl,
+            level,
+            fmt,
+            args,
+            buffer.as_mut_ptr(),
+            buffer.len() as c_int,
+            &mut print_prefix,
+        );
+        PRINT_PREFIX.set(print_prefix);
+
+        let part = CStr::from_ptr(buffer.as_ptr()).to_string_lossy();
+        LINE.with_borrow_mut(|line| {
+            line.push_str(&part);
+            if line.ends_with('\n') {
+                log::log!(target: "ffmpeg", self::level(level), "{}", line.trim_end());
+                line.clear();
+            }
+        });
+    }
+}
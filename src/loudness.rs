@@ -0,0 +1,240 @@
+use super::{DecodeError, VideoSource};
+use crate::audio::decode_audio;
+use std::time::Duration;
+
+/// The sample rate the audio is analysed at, which the K-weighting filter coefficients are for
+const SAMPLE_RATE: u32 = 48_000;
+/// The length of each part of a gating block (100ms), gating blocks are 400ms long and overlap by 75%
+const STEP_SAMPLES: usize = SAMPLE_RATE as usize / 10;
+/// The length of the windows checked for silence (10ms)
+const SILENCE_WINDOW: usize = SAMPLE_RATE as usize / 100;
+/// Gating blocks quieter than this (in LUFS) are never counted
+const ABSOLUTE_GATE: f64 = -70.0;
+/// Gating blocks this much quieter than the ungated loudness (in LU) are not counted
+const RELATIVE_GATE: f64 = -10.0;
+
+/// The K-weighting pre-filter (a high shelf) and high pass filter from ITU-R BS.1770, at 48kHz
+const SHELF: Biquad = Biquad {
+    b: [1.53512485958697, -2.69169618940638, 1.19839281085285],
+    a: [-1.69065929318241, 0.73248077421585],
+};
+const HIGH_PASS: Biquad = Biquad {
+    b: [1.0, -2.0, 1.0],
+    a: [-1.99004745483398, 0.99007225036621],
+};
+
+/// A part of the audio which is silent, see [`analyze_loudness`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Silence {
+    start: Duration,
+    end: Duration,
+}
+
+impl Silence {
+    /// When the silence starts (from the start of the audio)
+    #[inline]
+    pub fn start(&self) -> Duration {
+        self.start
+    }
+
+    /// When the silence ends
+    #[inline]
+    pub fn end(&self) -> Duration {
+        self.end
+    }
+
+    /// How long the silence lasts for
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+}
+
+/// The loudness of the audio of a video, see [`analyze_loudness`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Loudness {
+    integrated: Option<f64>,
+    peak: Option<f64>,
+    silence: Vec<Silence>,
+}
+
+impl Loudness {
+    /// The integrated loudness of the whole audio as defined by EBU R128 (in LUFS), `None` if the audio is silent
+    #[inline]
+    pub fn integrated(&self) -> Option<f64> {
+        self.integrated
+    }
+
+    /// The highest sample (in dBFS, so `0.0` is full scale), `None` if the audio is silent
+    #[inline]
+    pub fn peak(&self) -> Option<f64> {
+        self.peak
+    }
+
+    /// The gain (in dB) which would bring the integrated loudness to `target` (in LUFS, EBU R128 recommends `-23.0`),
+    /// `None` if the audio is silent. Note that this may clip the peaks when the gain is positive.
+    #[inline]
+    pub fn gain_to(&self, target: f64) -> Option<f64> {
+        self.integrated.map(|integrated| target - integrated)
+    }
+
+    /// Get a reference to the silent parts of the audio
+    #[inline]
+    pub fn silence(&self) -> &[Silence] {
+        &self.silence
+    }
+}
+
+/// A second order IIR filter, `a` leaves out the first coefficient (which is always 1)
+#[derive(Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+}
+
+impl Biquad {
+    /// Filter a sample, `state` holds the previous two inputs and outputs
+    #[inline]
+    fn process(&self, state: &mut [f64; 4], x: f64) -> f64 {
+        let [x1, x2, y1, y2] = *state;
+        let y = self.b[0] * x + self.b[1] * x1 + self.b[2] * x2 - self.a[0] * y1 - self.a[1] * y2;
+        *state = [x, x1, y, y1];
+        y
+    }
+}
+
+/// The weight of each channel when summing the loudness of the channels, from ITU-R BS.1770
+fn channel_weights(channels: usize) -> Vec<f64> {
+    match channels {
+        // 5.1, the LFE channel is not counted and the surround channels are weighted higher
+        6 => vec![1.0, 1.0, 1.0, 0.0, 1.41, 1.41],
+        channels => vec![1.0; channels],
+    }
+}
+
+/// Measure the loudness of the audio of a video and find the parts which are silent, e.g. to decide how much to normalise it by or where to trim it.
+///
+/// Loudness is measured as defined by EBU R128 (ITU-R BS.1770), and a part of the audio is silent if every sample is at most `silence_threshold`
+/// (in dBFS, around `-50.0` works well) for at least `min_silence`.
+///
+/// Returns [`DecodeError::UnableToFindAudioStream`] if the source data does not contain audio.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::analyze_loudness;
+/// use std::time::Duration;
+///
+/// # let file = "test.mp4";
+/// let loudness = analyze_loudness(file, -50.0, Duration::from_millis(500)).unwrap();
+/// println!("{:?} LUFS, gain {:?} dB", loudness.integrated(), loudness.gain_to(-23.0));
+/// for silence in loudness.silence() {
+///     println!("silent from {:?} to {:?}", silence.start(), silence.end());
+/// }
+/// # }
+/// ```
+pub fn analyze_loudness<S>(
+    source: S,
+    silence_threshold: f64,
+    min_silence: Duration,
+) -> Result<Loudness, DecodeError>
+where
+    S: Into<VideoSource>,
+{
+    let threshold = 10f64.powf(silence_threshold / 20.0);
+
+    let mut filters: Vec<[[f64; 4]; 2]> = Vec::new();
+    // The mean square of each channel over each 100ms step
+    let mut steps: Vec<Vec<f64>> = Vec::new();
+    let mut energy = Vec::new();
+    let mut step_len = 0;
+
+    let mut peak = 0.0f64;
+    let mut window_peak = 0.0f64;
+    let mut window_len = 0;
+    let mut position = 0;
+    let mut silence = Vec::new();
+    let mut current: Option<Silence> = None;
+    let time = |samples: usize| Duration::from_secs_f64(samples as f64 / SAMPLE_RATE as f64);
+
+    let (_, channels) = decode_audio(source.into(), Some(SAMPLE_RATE), None, |frame| {
+        let channels = frame.channels() as usize;
+        if filters.is_empty() {
+            filters = vec![[[0.0; 4]; 2]; channels];
+            energy = vec![0.0; channels];
+        }
+
+        for sample in frame.samples().chunks_exact(channels) {
+            for (channel, &x) in sample.iter().enumerate() {
+                let x = x as f64;
+                let [shelf, high_pass] = &mut filters[channel];
+                let y = HIGH_PASS.process(high_pass, SHELF.process(shelf, x));
+                energy[channel] += y * y;
+                window_peak = window_peak.max(x.abs());
+            }
+
+            step_len += 1;
+            if step_len == STEP_SAMPLES {
+                steps.push(energy.iter().map(|sum| sum / STEP_SAMPLES as f64).collect());
+                energy.iter_mut().for_each(|sum| *sum = 0.0);
+                step_len = 0;
+            }
+
+            position += 1;
+            window_len += 1;
+            if window_len == SILENCE_WINDOW {
+                peak = peak.max(window_peak);
+                match (window_peak <= threshold, current.as_mut()) {
+                    (true, Some(range)) => range.end = time(position),
+                    (true, None) => {
+                        current = Some(Silence {
+                            start: time(position - SILENCE_WINDOW),
+                            end: time(position),
+                        })
+                    }
+                    (false, _) => silence.extend(current.take()),
+                }
+                window_peak = 0.0;
+                window_len = 0;
+            }
+        }
+    })?;
+    peak = peak.max(window_peak);
+    silence.extend(current);
+    silence.retain(|range: &Silence| range.duration() >= min_silence);
+
+    // Each gating block is 4 steps, summed across the channels
+    let weights = channel_weights(channels as usize);
+    let blocks: Vec<f64> = steps
+        .windows(4)
+        .map(|block| {
+            (0..weights.len())
+                .map(|channel| {
+                    let mean = block.iter().map(|step| step[channel]).sum::<f64>() / 4.0;
+                    weights[channel] * mean
+                })
+                .sum()
+        })
+        .collect();
+
+    let loudness = |energy: f64| -0.691 + 10.0 * energy.log10();
+    let gated_mean = |gate: f64| {
+        let gated: Vec<f64> = blocks
+            .iter()
+            .copied()
+            .filter(|&energy| loudness(energy) > gate)
+            .collect();
+        (!gated.is_empty()).then(|| gated.iter().sum::<f64>() / gated.len() as f64)
+    };
+
+    let integrated = gated_mean(ABSOLUTE_GATE)
+        .and_then(|mean| gated_mean((loudness(mean) + RELATIVE_GATE).max(ABSOLUTE_GATE)))
+        .map(loudness);
+
+    Ok(Loudness {
+        integrated,
+        peak: (peak > 0.0).then(|| 20.0 * peak.log10()),
+        silence,
+    })
+}
@@ -1,17 +1,160 @@
-use ffmpeg_video_decoder::VideoDecoder;
+use clap::{Parser, Subcommand};
+use ffmpeg_video_decoder::{DecoderBuilder, Sampling, VideoDecoder};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 
-fn main() {
-    // let source: Vec<u8> = include_bytes!("../test.mp4").to_vec();
-    let source = std::path::PathBuf::from("test.mp4");
-    // let source = std::path::PathBuf::from("テスト.mp4");
-    let mut decoder = VideoDecoder::new(source, true).unwrap();
+/// Inspect videos and extract frames from them
+#[derive(Parser)]
+#[command(name = "ffvd", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print information about a video and its streams
+    Probe {
+        file: PathBuf,
+        /// Print the information as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Save frames of a video as PNG images
+    Frames {
+        file: PathBuf,
+        /// Only save every nth frame
+        #[arg(long, default_value_t = 1)]
+        every: usize,
+        /// The directory to save the frames to
+        #[arg(long, default_value = ".")]
+        out: PathBuf,
+    },
+    /// Save the frame shown at a specific time as an image
+    Thumb {
+        file: PathBuf,
+        /// The time of the frame, as seconds or `[hh:]mm:ss[.fff]`
+        #[arg(long, value_parser = parse_time, default_value = "0")]
+        at: Duration,
+        /// The width to scale the image to, keeping the aspect ratio
+        #[arg(long)]
+        width: Option<u32>,
+        /// The path to save the image to, the format is chosen from the extension
+        #[arg(long, default_value = "thumb.png")]
+        out: PathBuf,
+    },
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Probe { file, json } => probe(file, json),
+        Command::Frames { file, every, out } => frames(file, every, &out),
+        Command::Thumb {
+            file,
+            at,
+            width,
+            out,
+        } => thumb(file, at, width, &out),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn probe(file: PathBuf, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let decoder = VideoDecoder::new(file, false)?;
 
-    // let frame = decoder.next_frame().unwrap().unwrap().into_image();
-    // frame.save("frame1.png").unwrap();
-    // let frame = decoder.next_frame().unwrap().unwrap().into_image();
-    // frame.save("frame2.png").unwrap();
+    if json {
+        let info = serde_json::json!({
+            "container": decoder.container_format(),
+            "codec": decoder.codec_name(),
+            "dimensions": decoder.dimensions(),
+            "framerate": decoder.framerate(),
+            "duration": decoder.duration().map(|duration| duration.as_secs_f64()),
+            "bit_rate": decoder.bit_rate(),
+            "pixel_format": decoder.pixel_format(),
+            "is_hdr": decoder.is_hdr(),
+            "streams": decoder.streams(),
+            "metadata": decoder.metadata(),
+        });
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
 
-    while let Some(frame) = decoder.next_frame().unwrap() {
-        println!("Got frame: {} w/{:?}", frame.index(), frame.dimensions());
+    println!("container:  {}", decoder.container_format());
+    println!("codec:      {}", decoder.codec_name());
+    println!("dimensions: {}", decoder.dimensions());
+    println!("framerate:  {:.3}", decoder.framerate());
+    if let Some(duration) = decoder.duration() {
+        println!("duration:   {:.3}s", duration.as_secs_f64());
+    }
+    for stream in decoder.streams() {
+        println!(
+            "stream {}:   {:?} ({})",
+            stream.index(),
+            stream.kind(),
+            stream.codec()
+        );
+    }
+    Ok(())
+}
+
+fn frames(file: PathBuf, every: usize, out: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(out)?;
+    let mut decoder = DecoderBuilder::new(file)
+        .sample(Sampling::EveryNth(every))
+        .build()?;
+
+    let mut saved = 0;
+    while let Some(frame) = decoder.next_frame()? {
+        let path = out.join(format!("frame_{:06}.png", frame.index()));
+        frame.save(&path)?;
+        saved += 1;
+    }
+    println!("saved {saved} frames to {}", out.display());
+    Ok(())
+}
+
+fn thumb(
+    file: PathBuf,
+    at: Duration,
+    width: Option<u32>,
+    out: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut builder = DecoderBuilder::new(file);
+    if let Some(width) = width {
+        builder = builder.filter(&format!("scale={width}:-2"));
+    }
+    let mut decoder = builder.build()?;
+
+    let frame = decoder
+        .get_frame_at(at)?
+        .ok_or("no frame at that time, it may be past the end of the video")?;
+    frame.save(out)?;
+    println!("saved {}", out.display());
+    Ok(())
+}
+
+/// Parse a time as a number of seconds (`83.5`) or as `[hh:]mm:ss[.fff]` (`01:23.5`)
+fn parse_time(time: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid time `{time}`, expected seconds or [hh:]mm:ss[.fff]");
+
+    let mut seconds = 0.0;
+    for part in time.split(':') {
+        let part: f64 = part.parse().map_err(|_| invalid())?;
+        if !part.is_finite() || part < 0.0 {
+            return Err(invalid());
+        }
+        seconds = seconds * 60.0 + part;
+    }
+    if time.split(':').count() > 3 {
+        return Err(invalid());
     }
+    Ok(Duration::from_secs_f64(seconds))
 }
@@ -0,0 +1,143 @@
+use super::{AudioFrame, DecodeError, DecoderBuilder, Frame, VideoDecoder, VideoSource};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// The most video frames read ahead while waiting for audio, so a video where the audio is stored far from the video does not use unbounded memory
+const MAX_LOOKAHEAD: usize = 64;
+
+/// A decoded frame of video or audio, see [`MediaDecoder`]
+#[derive(Debug, Clone)]
+pub enum MediaFrame {
+    /// A frame of the video stream, as returned by [`VideoDecoder::next_frame`]
+    Video(Frame),
+    /// A block of samples of the audio stream, as returned by [`VideoDecoder::take_audio_frames`]
+    Audio(AudioFrame),
+}
+
+impl MediaFrame {
+    /// The time (from the start of the video) at which the frame should be shown or played
+    pub fn timestamp(&self) -> Option<Duration> {
+        match self {
+            MediaFrame::Video(frame) => frame.timestamp(),
+            MediaFrame::Audio(frame) => Some(frame.timestamp()),
+        }
+    }
+}
+
+/// A decoder which returns both the video frames and the audio of a video, in presentation order.
+///
+/// Audio and video timestamps are on the same timeline (that of the video), so playback can be driven from a single loop:
+/// queue the audio for output as it arrives, and show each frame once the audio clock reaches its timestamp.
+///
+/// If the source data does not contain audio, only video frames are returned.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::{MediaDecoder, MediaFrame};
+///
+/// # let file = "test.mp4";
+/// let mut decoder = MediaDecoder::new(file).unwrap();
+/// while let Some(frame) = decoder.next_media().unwrap() {
+///     match frame {
+///         MediaFrame::Video(frame) => println!("show frame {} at {:?}", frame.index(), frame.timestamp()),
+///         MediaFrame::Audio(audio) => println!("play {} samples at {:?}", audio.samples().len(), audio.timestamp()),
+///     }
+/// }
+/// # }
+/// ```
+pub struct MediaDecoder {
+    decoder: VideoDecoder,
+    /// Frames decoded ahead of the audio
+    video: VecDeque<Frame>,
+    audio: VecDeque<AudioFrame>,
+    /// Whether the decoder has run out of frames (so all of the audio has been read)
+    finished: bool,
+}
+
+impl MediaDecoder {
+    /// Create a new decoder for the first video and audio streams of the source data
+    pub fn new<S>(source: S) -> Result<Self, DecodeError>
+    where
+        S: Into<VideoSource>,
+    {
+        Self::with_builder(DecoderBuilder::new(source))
+    }
+
+    /// Create a new decoder with the options of a builder (e.g. to resize the frames), audio is always decoded
+    pub fn with_builder(builder: DecoderBuilder) -> Result<Self, DecodeError> {
+        Ok(MediaDecoder {
+            decoder: builder.audio(true).build()?,
+            video: VecDeque::new(),
+            audio: VecDeque::new(),
+            finished: false,
+        })
+    }
+
+    /// Get the next frame of audio or video (whichever comes first), returns `Ok(None)` once both have ended
+    pub fn next_media(&mut self) -> Result<Option<MediaFrame>, DecodeError> {
+        loop {
+            let video = self
+                .video
+                .front()
+                .map(|frame| frame.timestamp().unwrap_or_default());
+            let audio = self.audio.front().map(|frame| frame.timestamp());
+
+            match (video, audio) {
+                // Audio is only known to come first once the next frame has been decoded
+                (Some(video), Some(audio)) if audio <= video => return Ok(self.pop_audio()),
+                (None, Some(_)) if self.finished => return Ok(self.pop_audio()),
+                // The audio up to this frame has been read once there is audio after it
+                (Some(_), Some(_)) => return Ok(self.pop_video()),
+                (Some(_), None) if self.finished || self.video.len() >= MAX_LOOKAHEAD => {
+                    return Ok(self.pop_video())
+                }
+                (None, None) if self.finished => return Ok(None),
+                _ => (),
+            }
+
+            match self.decoder.next_frame()? {
+                Some(frame) => self.video.push_back(frame),
+                None => self.finished = true,
+            }
+            self.audio.extend(self.decoder.take_audio_frames());
+        }
+    }
+
+    fn pop_video(&mut self) -> Option<MediaFrame> {
+        self.video.pop_front().map(MediaFrame::Video)
+    }
+
+    fn pop_audio(&mut self) -> Option<MediaFrame> {
+        self.audio.pop_front().map(MediaFrame::Audio)
+    }
+
+    /// Get a reference to the underlying decoder
+    #[inline]
+    pub fn decoder(&self) -> &VideoDecoder {
+        &self.decoder
+    }
+
+    /// Get a mutable reference to the underlying decoder, e.g. to seek.
+    ///
+    /// This discards any audio and video which has been decoded but not yet returned, as the next frame may not follow the previous one.
+    pub fn decoder_mut(&mut self) -> &mut VideoDecoder {
+        self.video.clear();
+        self.audio.clear();
+        self.finished = false;
+        &mut self.decoder
+    }
+
+    /// Return the underlying decoder, which still decodes the audio (see [`VideoDecoder::take_audio_frames`])
+    #[inline]
+    pub fn into_inner(self) -> VideoDecoder {
+        self.decoder
+    }
+}
+
+impl Iterator for MediaDecoder {
+    type Item = Result<MediaFrame, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_media().transpose()
+    }
+}
@@ -0,0 +1,91 @@
+use super::Frame;
+use std::slice;
+
+/// The motion of a block of pixels between frames, as used by the encoder to predict the frame, see [`DecoderBuilder::motion_vectors`](crate::DecoderBuilder::motion_vectors)
+///
+/// Positions are in the coordinates of the decoded video (before any filters are applied).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MotionVector {
+    source: i32,
+    width: u8,
+    height: u8,
+    src_x: i16,
+    src_y: i16,
+    dst_x: i16,
+    dst_y: i16,
+    motion_x: i32,
+    motion_y: i32,
+    motion_scale: u16,
+}
+
+impl MotionVector {
+    /// Whether the block is predicted from a later frame (in display order) rather than an earlier one
+    #[inline]
+    pub fn from_future(&self) -> bool {
+        self.source > 0
+    }
+
+    /// The width and height of the block in pixels
+    #[inline]
+    pub fn block_size(&self) -> (u8, u8) {
+        (self.width, self.height)
+    }
+
+    /// The position of the centre of the block in the reference frame
+    #[inline]
+    pub fn source(&self) -> (i16, i16) {
+        (self.src_x, self.src_y)
+    }
+
+    /// The position of the centre of the block in this frame
+    #[inline]
+    pub fn destination(&self) -> (i16, i16) {
+        (self.dst_x, self.dst_y)
+    }
+
+    /// How far the block moved from the reference frame, in pixels (with sub-pixel precision)
+    #[inline]
+    pub fn motion(&self) -> (f32, f32) {
+        let scale = self.motion_scale.max(1) as f32;
+        (self.motion_x as f32 / scale, self.motion_y as f32 / scale)
+    }
+}
+
+impl Frame {
+    /// The motion vectors the frame was predicted with, this is empty unless [`DecoderBuilder::motion_vectors`](crate::DecoderBuilder::motion_vectors) is enabled
+    ///
+    /// Keyframes (and codecs which do not export motion vectors) have no motion vectors.
+    #[inline]
+    pub fn motion_vectors(&self) -> &[MotionVector] {
+        &self.motion_vectors
+    }
+}
+
+/// Read the motion vectors attached to a decoded frame
+pub(crate) unsafe fn read_motion_vectors(frame: *const ffmpeg::AVFrame) -> Vec<MotionVector> {
+    let side_data = ffmpeg::av_frame_get_side_data(
+        frame,
+        ffmpeg::AVFrameSideDataType::AV_FRAME_DATA_MOTION_VECTORS,
+    );
+    if side_data.is_null() || (*side_data).data.is_null() {
+        return Vec::new();
+    }
+
+    let count = (*side_data).size / std::mem::size_of::<ffmpeg::AVMotionVector>();
+    slice::from_raw_parts((*side_data).data as *const ffmpeg::AVMotionVector, count)
+        .iter()
+        .map(|vector| MotionVector {
+            source: vector.source,
+            width: vector.w,
+            height: vector.h,
+            src_x: vector.src_x,
+            src_y: vector.src_y,
+            dst_x: vector.dst_x,
+            dst_y: vector.dst_y,
+            motion_x: vector.motion_x,
+            motion_y: vector.motion_y,
+            motion_scale: vector.motion_scale,
+        })
+        .collect()
+}
@@ -0,0 +1,100 @@
+use super::{Dimensions, PixelFormat};
+
+/// An RGBA image drawn over every frame, see [`DecoderBuilder::overlay`](crate::DecoderBuilder::overlay)
+#[derive(Debug, Clone)]
+pub(crate) struct Overlay {
+    /// Packed RGBA8 pixels (not premultiplied)
+    data: Vec<u8>,
+    dimensions: Dimensions,
+    x: u32,
+    y: u32,
+}
+
+impl Overlay {
+    pub fn new(data: &[u8], width: u32, height: u32, x: u32, y: u32) -> Self {
+        Overlay {
+            data: data.to_vec(),
+            dimensions: Dimensions { width, height },
+            x,
+            y,
+        }
+    }
+
+    /// Whether the data is exactly `width * height` RGBA pixels
+    pub fn is_valid(&self) -> bool {
+        self.data.len() == self.dimensions.width as usize * self.dimensions.height as usize * 4
+    }
+
+    /// Alpha blend the overlay onto frame data in the given format, anything outside the frame is left out
+    pub fn apply(
+        &self,
+        data: &mut [u8],
+        stride: usize,
+        dimensions: Dimensions,
+        format: PixelFormat,
+    ) {
+        let bytes = format.bytes_per_pixel();
+        let channels = format.channels();
+        let wide = bytes / channels == 2;
+        let width = self
+            .dimensions
+            .width
+            .min(dimensions.width.saturating_sub(self.x)) as usize;
+        let height = self
+            .dimensions
+            .height
+            .min(dimensions.height.saturating_sub(self.y)) as usize;
+
+        for y in 0..height {
+            let source = &self.data[y * self.dimensions.width as usize * 4..][..width * 4];
+            let row = &mut data[(self.y as usize + y) * stride + self.x as usize * bytes..]
+                [..width * bytes];
+
+            for (pixel, target) in source.chunks_exact(4).zip(row.chunks_exact_mut(bytes)) {
+                let alpha = pixel[3] as f32 / 255.0;
+                if alpha == 0.0 {
+                    continue;
+                }
+
+                let color = match format {
+                    PixelFormat::Gray8 => {
+                        [0.299 * pixel[0] as f32
+                            + 0.587 * pixel[1] as f32
+                            + 0.114 * pixel[2] as f32; 3]
+                    }
+                    _ => [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32],
+                };
+                let mut values = [0.0; 4];
+                for (i, value) in values[..channels].iter_mut().enumerate() {
+                    *value = match wide {
+                        true => {
+                            u16::from_ne_bytes([target[i * 2], target[i * 2 + 1]]) as f32 / 257.0
+                        }
+                        false => target[i] as f32,
+                    };
+                }
+
+                // The "over" operator, for frames with an alpha channel the frame may itself be transparent
+                let has_alpha = channels == 4;
+                let below = if has_alpha { values[3] / 255.0 } else { 1.0 };
+                let combined = alpha + below * (1.0 - alpha);
+                for (value, color) in values[..channels.min(3)].iter_mut().zip(color) {
+                    *value = (color * alpha + *value * below * (1.0 - alpha)) / combined;
+                }
+                if has_alpha {
+                    values[3] = combined * 255.0;
+                }
+
+                for (i, value) in values[..channels].iter().enumerate() {
+                    match wide {
+                        true => {
+                            let value = (value * 257.0).round().clamp(0.0, 65535.0) as u16;
+                            target[i * 2..i * 2 + 2].copy_from_slice(&value.to_ne_bytes());
+                        }
+                        false => target[i] = value.round().clamp(0.0, 255.0) as u8,
+                    }
+                }
+            }
+        }
+    }
+}
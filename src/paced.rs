@@ -0,0 +1,123 @@
+use super::{DecodeError, Frame, VideoDecoder};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A gap between timestamps longer than this many frame durations is treated as a discontinuity (e.g. from sampling or a broken stream), rather than waited out
+const MAX_GAP_FRAMES: u32 = 10;
+
+/// A decoder which returns frames at presentation speed, see [`VideoDecoder::paced`]
+///
+/// Each call to [`PacedDecoder::next_frame`] sleeps until the returned frame is due to be shown,
+/// based on the timestamps of the frames (so variable framerate video is paced correctly).
+pub struct PacedDecoder {
+    decoder: VideoDecoder,
+    /// When the first frame since the clock was last reset was returned
+    start: Option<Instant>,
+    /// The time since `start` the most recently returned frame is due to be shown
+    position: Duration,
+    /// The timestamp and duration of the most recently returned frame
+    previous: Option<(Option<Duration>, Option<Duration>)>,
+}
+
+impl PacedDecoder {
+    /// Sleep until the next frame is due to be shown, then return it.
+    ///
+    /// The first frame (and the first frame after [`PacedDecoder::reset`]) is returned immediately.
+    /// If the caller falls behind, frames are returned as soon as they are decoded and the clock is moved back,
+    /// so playback continues at presentation speed from that frame rather than rushing to catch up.
+    /// A frame whose timestamp is far after the previous one is shown one frame duration after it instead of waiting for the gap.
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, DecodeError> {
+        let Some(frame) = self.decoder.next_frame()? else {
+            return Ok(None);
+        };
+
+        let now = Instant::now();
+        match (self.start, self.previous) {
+            (Some(start), Some((timestamp, duration))) => {
+                let duration = duration.unwrap_or_default();
+                let delta = match (timestamp, frame.timestamp()) {
+                    (Some(previous), Some(next)) if next > previous => next - previous,
+                    // The decoder looped, or the frames have no timestamps
+                    _ => duration,
+                };
+                self.position += match delta > duration * MAX_GAP_FRAMES && !duration.is_zero() {
+                    true => duration,
+                    false => delta,
+                };
+
+                let wait = (start + self.position).saturating_duration_since(now);
+                match wait.is_zero() {
+                    false => thread::sleep(wait),
+                    // Behind, so the clock is moved back (or restarted if that is before the clock began)
+                    true => self.start = now.checked_sub(self.position),
+                }
+            }
+            _ => {
+                self.start = Some(now);
+                self.position = Duration::ZERO;
+            }
+        }
+
+        self.previous = Some((frame.timestamp(), frame.duration()));
+        Ok(Some(frame))
+    }
+
+    /// Restart the clock, so the next frame is returned immediately (e.g. after pausing playback)
+    pub fn reset(&mut self) {
+        self.start = None;
+        self.previous = None;
+    }
+
+    /// Get a reference to the underlying decoder
+    #[inline]
+    pub fn decoder(&self) -> &VideoDecoder {
+        &self.decoder
+    }
+
+    /// Get a mutable reference to the underlying decoder, e.g. to seek.
+    ///
+    /// This resets the clock (see [`PacedDecoder::reset`]), as the next frame may not follow the previous one.
+    pub fn decoder_mut(&mut self) -> &mut VideoDecoder {
+        self.reset();
+        &mut self.decoder
+    }
+
+    /// Stop pacing frames, returning the underlying decoder
+    #[inline]
+    pub fn into_inner(self) -> VideoDecoder {
+        self.decoder
+    }
+}
+
+impl Iterator for PacedDecoder {
+    type Item = Result<Frame, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
+
+impl VideoDecoder {
+    /// Wrap this decoder so that frames are returned at presentation speed, e.g. for a preview window.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap().paced();
+    /// # decoder.decoder_mut().seek_to_frame(895).unwrap();
+    /// while let Some(frame) = decoder.next_frame().unwrap() {
+    ///     // display the frame
+    /// }
+    /// # }
+    /// ```
+    pub fn paced(self) -> PacedDecoder {
+        PacedDecoder {
+            decoder: self,
+            start: None,
+            position: Duration::ZERO,
+            previous: None,
+        }
+    }
+}
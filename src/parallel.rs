@@ -0,0 +1,176 @@
+use super::{DecodeError, DecoderBuilder, Frame, VideoSource};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// The number of segments the video is split into for each worker, so workers which finish early can take on more of the video
+const SEGMENTS_PER_WORKER: usize = 4;
+/// The number of decoded frames each segment can hold before its worker waits for them to be passed to the callback
+const SEGMENT_CAPACITY: usize = 8;
+
+type Segment = (SyncSender<Result<Frame, DecodeError>>, Duration, Duration);
+
+/// Decode every frame of a video on multiple threads, calling `callback` with each frame in order.
+///
+/// The video is split into segments which start at keyframes, and each of `workers` threads (`0` uses the number of cpus)
+/// decodes one segment at a time with its own decoder, so this is much faster than a single [`VideoDecoder`](crate::VideoDecoder)
+/// for processing whole videos on machines with many cores. Frames are still passed to the callback one at a time on the calling thread.
+///
+/// Splitting the video requires an index of its keyframes (as found in mp4 and mkv files) and a constant framerate (so frame numbers can be worked out
+/// part way through the video). Other sources, and sources which cannot be opened more than once (such as a [`PushSource`](crate::PushSource)),
+/// are decoded on a single thread instead.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::decode_parallel;
+///
+/// # let file = "test.mp4";
+/// let mut count = 0;
+/// decode_parallel(file, 4, |frame| {
+///     count += 1;
+///     assert_eq!(frame.index(), count);
+/// })
+/// .unwrap();
+/// # }
+/// ```
+pub fn decode_parallel<S, F>(source: S, workers: usize, mut callback: F) -> Result<(), DecodeError>
+where
+    S: Into<VideoSource>,
+    F: FnMut(Frame),
+{
+    let source = match source.into() {
+        // Shared so each decoder does not need its own copy
+        VideoSource::Raw(data) => VideoSource::Shared(data.into()),
+        VideoSource::Push(source) => return decode_serial(source.into(), callback),
+        VideoSource::Device(device) => return decode_serial(device.into(), callback),
+        source => source,
+    };
+
+    let workers = match workers {
+        0 => thread::available_parallelism().map_or(1, |workers| workers.get()),
+        workers => workers,
+    };
+    let bounds = segment_bounds(&source, workers * SEGMENTS_PER_WORKER)?;
+    if workers == 1 || bounds.len() < 2 {
+        return decode_serial(source, callback);
+    }
+
+    let mut receivers = Vec::with_capacity(bounds.len());
+    let mut segments = Vec::with_capacity(bounds.len());
+    for (i, &start) in bounds.iter().enumerate() {
+        let end = bounds.get(i + 1).copied().unwrap_or(Duration::MAX);
+        let (sender, receiver) = mpsc::sync_channel(SEGMENT_CAPACITY);
+        receivers.push(receiver);
+        segments.push(Some((sender, start, end)));
+    }
+
+    let segments = Mutex::new(segments);
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        for _ in 0..workers.min(receivers.len()) {
+            scope.spawn(|| loop {
+                if failed.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                // Segments are taken in order, so the segment being passed to the callback is always being decoded
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(segment) = segments
+                    .lock()
+                    .unwrap()
+                    .get_mut(i)
+                    .and_then(|segment| segment.take())
+                else {
+                    break;
+                };
+
+                if !decode_segment(source.clone(), segment) {
+                    failed.store(true, Ordering::Relaxed);
+                }
+            });
+        }
+
+        // The receivers are dropped when returning early, so any workers waiting to send frames stop
+        for receiver in receivers {
+            for frame in receiver {
+                match frame {
+                    Ok(frame) => callback(frame),
+                    Err(error) => {
+                        failed.store(true, Ordering::Relaxed);
+                        return Err(error);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Decode the frames of a segment (from `start` up to but not including `end`) into its channel.
+///
+/// Returns `false` if decoding failed or the frames are no longer wanted.
+fn decode_segment(source: VideoSource, (sender, start, end): Segment) -> bool {
+    let mut decoder = match DecoderBuilder::new(source).range(start, end).build() {
+        Ok(decoder) => decoder,
+        Err(error) => {
+            let _ = sender.send(Err(error));
+            return false;
+        }
+    };
+
+    loop {
+        let frame = match decoder.next_frame() {
+            Ok(Some(frame)) => frame,
+            Ok(None) => return true,
+            Err(error) => {
+                let _ = sender.send(Err(error));
+                return false;
+            }
+        };
+
+        // The range includes the frame at `end`, which is the first frame of the next segment
+        if frame.timestamp().is_some_and(|timestamp| timestamp >= end) {
+            return true;
+        }
+        if sender.send(Ok(frame)).is_err() {
+            return false;
+        }
+    }
+}
+
+/// Decode every frame on the calling thread, for sources which cannot be split into segments
+fn decode_serial<F>(source: VideoSource, mut callback: F) -> Result<(), DecodeError>
+where
+    F: FnMut(Frame),
+{
+    let mut decoder = DecoderBuilder::new(source).build()?;
+    while let Some(frame) = decoder.next_frame()? {
+        callback(frame);
+    }
+    Ok(())
+}
+
+/// The start times of up to `count` segments of roughly equal length, each starting at a keyframe (the first always starts at zero)
+fn segment_bounds(source: &VideoSource, count: usize) -> Result<Vec<Duration>, DecodeError> {
+    let decoder = DecoderBuilder::new(source.clone()).build()?;
+    let (Some(duration), false) = (decoder.duration(), decoder.is_vfr()) else {
+        return Ok(vec![Duration::ZERO]);
+    };
+    let keyframes = decoder.keyframe_times();
+
+    let mut bounds = vec![Duration::ZERO];
+    for i in 1..count {
+        let target = duration.mul_f64(i as f64 / count as f64);
+        if let Some(&keyframe) = keyframes.iter().find(|&&keyframe| keyframe >= target) {
+            if keyframe > *bounds.last().unwrap() && keyframe < duration {
+                bounds.push(keyframe);
+            }
+        }
+    }
+    Ok(bounds)
+}
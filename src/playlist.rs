@@ -0,0 +1,144 @@
+use super::{DecodeError, DecoderBuilder, Dimensions, Frame, VideoDecoder, VideoSource};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// A decoder which decodes multiple sources back to back, as if they were a single video
+///
+/// Frame indices and timestamps carry on from the previous source, so the first frame of the second source follows the last frame of the first.
+/// Each source is only opened once the previous one has ended.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::PlaylistDecoder;
+///
+/// # let (intro, video) = ("test.mp4", "test.mp4");
+/// let mut decoder = PlaylistDecoder::new(vec![intro.into(), video.into()])
+///     .unwrap()
+///     .rescale(true);
+/// while let Some(frame) = decoder.next_frame().unwrap() {
+///     // do something with the frame
+/// #   break;
+/// }
+/// # }
+/// ```
+pub struct PlaylistDecoder {
+    /// The sources which have not been opened yet
+    sources: VecDeque<VideoSource>,
+    decoder: VideoDecoder,
+    /// The index of the source being decoded
+    current: usize,
+    /// The number of sources in the playlist
+    len: usize,
+    /// The dimensions of the first source, which later sources are scaled to if `rescale` is set
+    dimensions: Dimensions,
+    rescale: bool,
+    /// The number of frames returned from earlier sources
+    index_offset: usize,
+    /// The length of the earlier sources
+    time_offset: Duration,
+    /// The index of the last frame returned from the current source
+    last_index: usize,
+    /// When the last frame returned from the current source ends
+    end: Duration,
+}
+
+impl PlaylistDecoder {
+    /// Create a decoder for the given sources, this opens the first source straight away.
+    ///
+    /// Fails with [`DecodeError::UnableToOpenInput`] if there are no sources.
+    pub fn new(sources: Vec<VideoSource>) -> Result<Self, DecodeError> {
+        let len = sources.len();
+        let mut sources = VecDeque::from(sources);
+        let first = sources.pop_front().ok_or(DecodeError::UnableToOpenInput)?;
+        let decoder = VideoDecoder::new(first, false)?;
+
+        Ok(PlaylistDecoder {
+            sources,
+            dimensions: decoder.dimensions(),
+            decoder,
+            current: 0,
+            len,
+            rescale: false,
+            index_offset: 0,
+            time_offset: Duration::ZERO,
+            last_index: 0,
+            end: Duration::ZERO,
+        })
+    }
+
+    /// Whether later sources should be scaled to the dimensions of the first source, so every frame has the same size (defaults to `false`).
+    ///
+    /// The aspect ratio is not kept, so sources with a different shape are stretched.
+    pub fn rescale(mut self, rescale: bool) -> Self {
+        self.rescale = rescale;
+        self
+    }
+
+    /// Get the next frame, moving on to the next source once the current one has ended.
+    ///
+    /// If the next source can not be opened its error is returned and [`PlaylistDecoder::current`] is the index of that source,
+    /// calling this again skips it and moves on to the source after it.
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, DecodeError> {
+        loop {
+            if let Some(mut frame) = self.decoder.next_frame()? {
+                self.last_index = frame.index;
+                if let Some(timestamp) = frame.timestamp {
+                    self.end = self.end.max(timestamp + frame.duration.unwrap_or_default());
+                    frame.timestamp = Some(self.time_offset + timestamp);
+                }
+                frame.index += self.index_offset;
+                return Ok(Some(frame));
+            }
+
+            let Some(source) = self.sources.pop_front() else {
+                return Ok(None);
+            };
+
+            // Moved on before opening the source, so a source which fails to open is skipped by the next call
+            self.current += 1;
+            self.index_offset += self.last_index;
+            self.time_offset += self.end;
+            self.last_index = 0;
+            self.end = Duration::ZERO;
+
+            let mut builder = DecoderBuilder::new(source);
+            if self.rescale {
+                let Dimensions { width, height } = self.dimensions;
+                builder = builder.filter(&format!("scale={width}:{height}"));
+            }
+            self.decoder = builder.build()?;
+        }
+    }
+
+    /// The index of the source currently being decoded
+    #[inline]
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// The number of sources in the playlist
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the playlist has no sources, this is always `false` as a playlist can not be created without any sources
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The decoder of the source currently being decoded
+    #[inline]
+    pub fn decoder(&self) -> &VideoDecoder {
+        &self.decoder
+    }
+}
+
+impl Iterator for PlaylistDecoder {
+    type Item = Result<Frame, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+/// A point in a video saved with [`VideoDecoder::save_position`](crate::VideoDecoder::save_position), which decoding can be resumed from
+/// with [`VideoDecoder::restore_position`](crate::VideoDecoder::restore_position) (e.g. for bookmarks)
+///
+/// A position is only meaningful for the source data it was saved from, decoded with the same options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecoderPosition {
+    pub(crate) index: usize,
+    /// The timestamp of the frame in the time base of the video stream
+    pub(crate) pts: i64,
+    pub(crate) timestamp: Option<Duration>,
+}
+
+impl DecoderPosition {
+    /// The index of the frame decoding resumes from
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The timestamp of the frame decoding resumes from (relative to the start of the video), if known
+    #[inline]
+    pub fn timestamp(&self) -> Option<Duration> {
+        self.timestamp
+    }
+
+    /// The timestamp of the frame decoding resumes from, in the time base of the video stream
+    #[inline]
+    pub fn stream_timestamp(&self) -> i64 {
+        self.pts
+    }
+}
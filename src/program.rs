@@ -0,0 +1,83 @@
+use crate::c::dict_get;
+
+/// A program (channel) of the source data, see [`VideoDecoder::programs`](crate::VideoDecoder::programs)
+///
+/// Broadcast formats such as MPEG-TS can carry multiple programs in a single stream, each with their own video and audio.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Program {
+    index: usize,
+    id: i32,
+    name: Option<String>,
+    provider: Option<String>,
+    streams: Vec<usize>,
+}
+
+impl Program {
+    /// The index of the program within the source data, this is what should be passed to [`DecoderBuilder::program`](crate::DecoderBuilder::program)
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The id of the program in the source data (for MPEG-TS this is the program number from the PAT)
+    #[inline]
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// The name of the program (e.g. the channel name), if specified by the source data
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The name of the provider of the program (e.g. the broadcaster), if specified by the source data
+    #[inline]
+    pub fn provider(&self) -> Option<&str> {
+        self.provider.as_deref()
+    }
+
+    /// The indices of the streams which are part of the program, see [`VideoDecoder::streams`](crate::VideoDecoder::streams)
+    #[inline]
+    pub fn streams(&self) -> &[usize] {
+        &self.streams
+    }
+
+    /// Read a program
+    ///
+    /// # Safety
+    ///
+    /// `program` must point to a valid program of an opened input context.
+    unsafe fn from_raw(index: usize, program: *const ffmpeg::AVProgram) -> Self {
+        let streams = match (*program).stream_index.is_null() {
+            true => Vec::new(),
+            false => std::slice::from_raw_parts(
+                (*program).stream_index,
+                (*program).nb_stream_indexes as usize,
+            )
+            .iter()
+            .map(|&stream| stream as usize)
+            .collect(),
+        };
+
+        Program {
+            index,
+            id: (*program).id,
+            name: dict_get((*program).metadata, c"service_name"),
+            provider: dict_get((*program).metadata, c"service_provider"),
+            streams,
+        }
+    }
+}
+
+/// Read every program of an input context
+///
+/// # Safety
+///
+/// `input_ctx` must point to a valid opened input context.
+pub(crate) unsafe fn read_programs(input_ctx: *const ffmpeg::AVFormatContext) -> Vec<Program> {
+    (0..(*input_ctx).nb_programs as usize)
+        .map(|i| Program::from_raw(i, *(*input_ctx).programs.add(i)))
+        .collect()
+}
@@ -0,0 +1,390 @@
+use super::{DecodeError, EncodeError, TranscodeError, VideoSource};
+use crate::c::{duration_to_ts, path_to_raw, ts_to_duration};
+use crate::input::{Input, InputOptions};
+use std::ffi::{self, CString};
+use std::path::Path;
+use std::ptr;
+use std::time::Duration;
+
+/// Copy the streams of a video into a new container without decoding or encoding them (e.g. to turn an mkv file into an mp4 file).
+///
+/// This is much faster than [`transcode`](crate::transcode) and does not change the quality, but the new container must support the codecs of the source.
+/// `container` is the (short) name of the container format to write (see [`EncoderBuilder::format`](crate::EncoderBuilder::format)),
+/// if it is `None` the format is worked out from the output path. Video, audio and subtitle streams are copied, leaving out any which the container can not hold.
+///
+/// Returns the number of packets written to the output, or [`DecodeError::UnableToReadPacket`] if the source can not be read to the end.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::remux;
+///
+/// # let file = "test.mp4";
+/// # let dir = std::env::temp_dir().join("ffmpeg-video-decoder-remux-doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # let output = dir.join("output.mkv");
+/// remux(file, output, None).unwrap();
+/// # }
+/// ```
+pub fn remux<S, P>(source: S, output: P, container: Option<&str>) -> Result<usize, TranscodeError>
+where
+    S: Into<VideoSource>,
+    P: AsRef<Path>,
+{
+    let mut input = Input::open(source.into(), &InputOptions::default())?;
+    let mut output = Output::open(&input, output.as_ref(), container, false)?;
+
+    let mut packets = 0;
+    while read_packet(&mut input, output.packet)? {
+        if unsafe { output.write(&input, 0)? } {
+            packets += 1;
+        }
+    }
+
+    output.finish()?;
+    Ok(packets)
+}
+
+/// The part of a video written by [`cut`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Clip {
+    start: Duration,
+    end: Duration,
+    packets: usize,
+}
+
+impl Clip {
+    /// The time in the source video the clip actually starts at.
+    ///
+    /// This is the keyframe at or before the requested start, so it is usually slightly earlier.
+    #[inline]
+    pub fn start(&self) -> Duration {
+        self.start
+    }
+
+    /// The time in the source video of the last frame in the clip.
+    ///
+    /// Frames which are needed to decode the frames before the requested end are kept, so this may be slightly later.
+    #[inline]
+    pub fn end(&self) -> Duration {
+        self.end
+    }
+
+    /// The length of the clip
+    #[inline]
+    pub fn duration(&self) -> Duration {
+        self.end.saturating_sub(self.start)
+    }
+
+    /// The number of packets written to the output
+    #[inline]
+    pub fn packets(&self) -> usize {
+        self.packets
+    }
+}
+
+/// Copy the part of a video between two timestamps into a new file without decoding or encoding it, see [`remux`].
+///
+/// As the video is not decoded, the clip has to start at a keyframe. It starts at the keyframe at or before `start`,
+/// so it may include some frames before `start` (use the returned [`Clip`] to see exactly where it starts).
+/// The container format is worked out from the output path.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::cut;
+/// use std::time::Duration;
+///
+/// # let file = "test.mp4";
+/// # let dir = std::env::temp_dir().join("ffmpeg-video-decoder-cut-doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # let output = dir.join("clip.mp4");
+/// let clip = cut(file, Duration::from_secs(10), Duration::from_secs(20), output).unwrap();
+/// println!("the clip starts {:?} early", Duration::from_secs(10) - clip.start());
+/// # }
+/// ```
+pub fn cut<S, P>(
+    source: S,
+    start: Duration,
+    end: Duration,
+    output: P,
+) -> Result<Clip, TranscodeError>
+where
+    S: Into<VideoSource>,
+    P: AsRef<Path>,
+{
+    let mut input = Input::open(source.into(), &InputOptions::default())?;
+    let mut output = Output::open(&input, output.as_ref(), None, true)?;
+
+    unsafe {
+        let video = ffmpeg::av_find_best_stream(
+            input.ctx,
+            ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO,
+            -1,
+            -1,
+            ptr::null_mut(),
+            0,
+        );
+        if video < 0 {
+            return Err(DecodeError::UnableToFindVideoStream.into());
+        }
+        let stream = input.stream(video as usize);
+        let time_base = (*stream).time_base;
+        let start_time = match (*stream).start_time {
+            ffmpeg::AV_NOPTS_VALUE => 0,
+            start_time => start_time,
+        };
+        let start_ts = duration_to_ts(start, time_base).saturating_add(start_time);
+        let end_ts = duration_to_ts(end, time_base).saturating_add(start_time);
+
+        // Find the keyframe the clip starts at, then go back to it so the packets before it in the other streams are read too
+        let seek = |input: &mut Input| match input.seek_frame(
+            video,
+            start_ts,
+            ffmpeg::AVSEEK_FLAG_BACKWARD,
+        ) {
+            0.. => Ok(()),
+            _ => Err(DecodeError::UnableToSeek),
+        };
+        seek(&mut input)?;
+        let mut keyframe = None;
+        while keyframe.is_none() && read_packet(&mut input, output.packet)? {
+            // Seeking may land before the keyframe with some demuxers, so packets are skipped until one can be decoded on its own
+            if (*output.packet).stream_index == video
+                && (*output.packet).flags & ffmpeg::AV_PKT_FLAG_KEY as i32 != 0
+            {
+                keyframe = Some(packet_ts(output.packet));
+            }
+            ffmpeg::av_packet_unref(output.packet);
+        }
+        let keyframe = keyframe.unwrap_or(start_ts);
+        seek(&mut input)?;
+
+        let mut last = keyframe;
+        let mut packets = 0;
+        while read_packet(&mut input, output.packet)? {
+            let index = (*output.packet).stream_index;
+            let stream_time_base = (*input.stream(index as usize)).time_base;
+            let offset = ffmpeg::av_rescale_q(keyframe, time_base, stream_time_base);
+            let stream_end = ffmpeg::av_rescale_q(end_ts, time_base, stream_time_base);
+
+            let pts = packet_ts(output.packet);
+            if index == video && (*output.packet).dts != ffmpeg::AV_NOPTS_VALUE {
+                if (*output.packet).dts > stream_end {
+                    // Every frame before the end has been decoded by now
+                    ffmpeg::av_packet_unref(output.packet);
+                    break;
+                }
+            } else if pts > stream_end {
+                ffmpeg::av_packet_unref(output.packet);
+                continue;
+            }
+            if pts < offset {
+                // Before the keyframe, so it can not be played
+                ffmpeg::av_packet_unref(output.packet);
+                continue;
+            }
+
+            if index == video {
+                last = last.max(pts);
+            }
+            if output.write(&input, offset)? {
+                packets += 1;
+            }
+        }
+
+        output.finish()?;
+        Ok(Clip {
+            start: ts_to_duration(keyframe - start_time, time_base).unwrap_or_default(),
+            end: ts_to_duration(last - start_time, time_base).unwrap_or_default(),
+            packets,
+        })
+    }
+}
+
+/// Read the next packet of the input into `packet`, returning `false` at the end of the input.
+///
+/// Unlike the decoder, which stops at the first packet it can not read, a failed read is an error
+/// so that a damaged input does not quietly give a shorter output.
+fn read_packet(input: &mut Input, packet: *mut ffmpeg::AVPacket) -> Result<bool, DecodeError> {
+    match input.read_frame(packet)? {
+        ffmpeg::AVERROR_EOF => Ok(false),
+        result if result < 0 => Err(input.error_or(DecodeError::UnableToReadPacket)),
+        _ => Ok(true),
+    }
+}
+
+/// The presentation timestamp of a packet, falling back to the decoding timestamp if it is not set
+unsafe fn packet_ts(packet: *const ffmpeg::AVPacket) -> i64 {
+    match (*packet).pts {
+        ffmpeg::AV_NOPTS_VALUE => (*packet).dts,
+        pts => pts,
+    }
+}
+
+/// A new container which the streams of an input are copied into, which is freed when dropped
+struct Output {
+    ctx: *mut ffmpeg::AVFormatContext,
+    packet: *mut ffmpeg::AVPacket,
+    /// The index of the output stream each input stream is copied to, `None` if it is left out
+    streams: Vec<Option<usize>>,
+}
+
+impl Output {
+    /// Create the output file, with a stream for each stream of the input which the container can hold.
+    ///
+    /// `partial` should be set if the packets are copied from part way through the input.
+    fn open(
+        input: &Input,
+        path: &Path,
+        container: Option<&str>,
+        partial: bool,
+    ) -> Result<Self, EncodeError> {
+        let path = path_to_raw(path).ok_or(EncodeError::InvalidPath)?;
+        let format = match container {
+            Some(format) => Some(CString::new(format).map_err(|_| EncodeError::UnknownFormat)?),
+            None => None,
+        };
+
+        unsafe {
+            let mut output = Output {
+                ctx: ptr::null_mut(),
+                packet: ffmpeg::av_packet_alloc(),
+                streams: Vec::with_capacity(input.stream_count()),
+            };
+            if ffmpeg::avformat_alloc_output_context2(
+                &mut output.ctx,
+                ptr::null(),
+                format
+                    .as_ref()
+                    .map_or(ptr::null(), |format| format.as_ptr()),
+                path.as_ptr() as *const ffi::c_char,
+            ) < 0
+                || output.ctx.is_null()
+            {
+                return Err(EncodeError::UnknownFormat);
+            }
+            if output.packet.is_null() {
+                return Err(EncodeError::AllocationFailed);
+            }
+            if partial {
+                // The first packets may have negative timestamps once they are moved to the start
+                (*output.ctx).avoid_negative_ts = ffmpeg::AVFMT_AVOID_NEG_TS_MAKE_ZERO;
+            }
+
+            let mut next = 0;
+            for i in 0..input.stream_count() {
+                let codecpar = (*input.stream(i)).codecpar;
+                let copyable = matches!(
+                    (*codecpar).codec_type,
+                    ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO
+                        | ffmpeg::AVMediaType::AVMEDIA_TYPE_AUDIO
+                        | ffmpeg::AVMediaType::AVMEDIA_TYPE_SUBTITLE
+                );
+                // Returns 0 if the container can not hold the codec, or a negative value if the container does not say
+                let supported = ffmpeg::avformat_query_codec(
+                    (*output.ctx).oformat,
+                    (*codecpar).codec_id,
+                    ffmpeg::FF_COMPLIANCE_NORMAL as i32,
+                ) != 0;
+                if !copyable || !supported {
+                    output.streams.push(None);
+                    continue;
+                }
+
+                let stream = ffmpeg::avformat_new_stream(output.ctx, ptr::null());
+                if stream.is_null() {
+                    return Err(EncodeError::AllocationFailed);
+                }
+                if ffmpeg::avcodec_parameters_copy((*stream).codecpar, codecpar) < 0 {
+                    return Err(EncodeError::UnsupportedCodec);
+                }
+                // Codec tags are specific to each container, so let the muxer pick its own
+                (*(*stream).codecpar).codec_tag = 0;
+                (*stream).time_base = (*input.stream(i)).time_base;
+
+                output.streams.push(Some(next));
+                next += 1;
+            }
+
+            // Some formats (such as image sequences) open their own files
+            if (*(*output.ctx).oformat).flags & ffmpeg::AVFMT_NOFILE == 0
+                && ffmpeg::avio_open(
+                    &mut (*output.ctx).pb,
+                    path.as_ptr() as *const ffi::c_char,
+                    ffmpeg::AVIO_FLAG_WRITE,
+                ) < 0
+            {
+                return Err(EncodeError::UnableToOpenOutput);
+            }
+
+            if ffmpeg::avformat_write_header(output.ctx, ptr::null_mut()) < 0 {
+                return Err(EncodeError::UnableToWrite);
+            }
+
+            Ok(output)
+        }
+    }
+
+    /// Write the packet in `packet` (read from `input`) to the matching output stream, moving its timestamps `offset` earlier (in the time base of the input stream).
+    ///
+    /// Returns `false` if the packet is from a stream which is left out. The packet is empty afterwards either way.
+    ///
+    /// # Safety
+    ///
+    /// `packet` must hold a packet read from `input`.
+    unsafe fn write(&mut self, input: &Input, offset: i64) -> Result<bool, EncodeError> {
+        let index = (*self.packet).stream_index as usize;
+        let Some(&Some(target)) = self.streams.get(index) else {
+            ffmpeg::av_packet_unref(self.packet);
+            return Ok(false);
+        };
+
+        if offset != 0 {
+            for ts in [&mut (*self.packet).pts, &mut (*self.packet).dts] {
+                if *ts != ffmpeg::AV_NOPTS_VALUE {
+                    *ts -= offset;
+                }
+            }
+        }
+
+        // The muxer may have changed the time base of the stream when writing the header
+        let stream = *(*self.ctx).streams.add(target);
+        ffmpeg::av_packet_rescale_ts(
+            self.packet,
+            (*input.stream(index)).time_base,
+            (*stream).time_base,
+        );
+        (*self.packet).stream_index = target as i32;
+        (*self.packet).pos = -1;
+
+        // This takes ownership of the packet data
+        if ffmpeg::av_interleaved_write_frame(self.ctx, self.packet) < 0 {
+            return Err(EncodeError::UnableToWrite);
+        }
+        Ok(true)
+    }
+
+    /// Write the end of the container, the output is incomplete until this is called
+    fn finish(&mut self) -> Result<(), EncodeError> {
+        match unsafe { ffmpeg::av_write_trailer(self.ctx) } {
+            0.. => Ok(()),
+            _ => Err(EncodeError::UnableToWrite),
+        }
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        unsafe {
+            ffmpeg::av_packet_free(&mut self.packet);
+
+            if !self.ctx.is_null() {
+                if (*(*self.ctx).oformat).flags & ffmpeg::AVFMT_NOFILE == 0 {
+                    ffmpeg::avio_closep(&mut (*self.ctx).pb);
+                }
+                // This also frees the streams
+                ffmpeg::avformat_free_context(self.ctx);
+            }
+        }
+    }
+}
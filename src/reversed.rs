@@ -0,0 +1,56 @@
+use super::{DecodeError, Frame, VideoDecoder};
+
+/// An iterator over the frames of a video in reverse order, see [`VideoDecoder::frames_reversed`]
+pub struct ReversedFrames<'a> {
+    decoder: &'a mut VideoDecoder,
+    started: bool,
+}
+
+impl Iterator for ReversedFrames<'_> {
+    type Item = Result<Frame, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            if let Err(error) = self.decoder.start_reversed() {
+                return Some(Err(error));
+            }
+        }
+
+        self.decoder.next_reversed_frame().transpose()
+    }
+}
+
+impl Drop for ReversedFrames<'_> {
+    fn drop(&mut self) {
+        self.decoder.stop_reversed();
+    }
+}
+
+impl VideoDecoder {
+    /// Iterate over the frames of the video (or the [`DecoderBuilder::range`](crate::DecoderBuilder::range)) from the last frame to the first.
+    ///
+    /// The video is decoded one group of pictures at a time, starting from the end, so only a single group of pictures is held in memory.
+    /// Once the iterator is dropped, [`VideoDecoder::next_frame`] continues from the frame after the most recently returned frame.
+    /// The source data must be seekable.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// for frame in decoder.frames_reversed() {
+    ///     let frame = frame.unwrap();
+    ///     // do something with the frame
+    /// #   break;
+    /// }
+    /// # }
+    /// ```
+    pub fn frames_reversed(&mut self) -> ReversedFrames<'_> {
+        ReversedFrames {
+            decoder: self,
+            started: false,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use super::{DecodeError, DecoderBuilder, VideoSource};
+use std::time::Duration;
+
+/// A cut between two shots, see [`scene_changes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SceneChange {
+    index: usize,
+    timestamp: Duration,
+}
+
+impl SceneChange {
+    /// The index of the first frame of the new shot, this can be passed to [`VideoDecoder::get_frame`](crate::VideoDecoder::get_frame)
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The timestamp of the first frame of the new shot
+    #[inline]
+    pub fn timestamp(&self) -> Duration {
+        self.timestamp
+    }
+}
+
+/// Find the cuts between shots in a video, e.g. to pick thumbnails or split a video into chapters.
+///
+/// `threshold` is how different a frame must be from the previous frame to count as a new shot, between `0.0` and `1.0`.
+/// Around `0.3` to `0.4` works well for most video, lower values find more (and less certain) cuts.
+/// This uses the scene score of the ffmpeg `select` filter, on a downscaled copy of each frame.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::scene_changes;
+///
+/// # let file = "test.mp4";
+/// for cut in scene_changes(file, 0.4).unwrap() {
+///     println!("new shot at {:?}", cut.timestamp());
+/// }
+/// # }
+/// ```
+pub fn scene_changes<S>(source: S, threshold: f32) -> Result<Vec<SceneChange>, DecodeError>
+where
+    S: Into<VideoSource>,
+{
+    let mut decoder = DecoderBuilder::new(source)
+        .filter(&format!("scale=160:-2,select='gt(scene,{threshold})'"))
+        .build()?;
+
+    // Only the selected frames come out of the filter, so the frame numbers must be worked out from the timestamps
+    let framerate = decoder.framerate() as f64;
+    let mut changes = Vec::new();
+    while let Some(frame) = decoder.next_frame()? {
+        if let Some(timestamp) = frame.timestamp() {
+            changes.push(SceneChange {
+                index: (timestamp.as_secs_f64() * framerate).round() as usize + 1,
+                timestamp,
+            });
+        }
+    }
+
+    Ok(changes)
+}
@@ -0,0 +1,133 @@
+use super::DecodeError;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A sequence of numbered images (such as the output of a renderer) which can be decoded like a video, see [`VideoSource::ImageSequence`](crate::VideoSource::ImageSequence)
+///
+/// ```rust,no_run
+/// use ffmpeg_video_decoder::{ImageSequence, VideoDecoder};
+///
+/// let sequence = ImageSequence::pattern("render/frame_%04d.png", 24.0);
+/// let mut decoder = VideoDecoder::new(sequence, false).unwrap();
+/// while let Some(frame) = decoder.next_frame().unwrap() {
+///     // do something with the frame
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageSequence {
+    files: SequenceFiles,
+    framerate: f32,
+    start_number: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SequenceFiles {
+    /// A path containing a `%d` style placeholder, read with the image2 demuxer
+    Pattern(PathBuf),
+    /// A concat script listing every image
+    List(Arc<[u8]>),
+}
+
+impl ImageSequence {
+    /// The images matching a numbered pattern, as used by ffmpeg (e.g. `frames/frame_%04d.png` for `frame_0001.png`, `frame_0002.png`, ...),
+    /// shown at `framerate` images per second.
+    ///
+    /// The sequence starts at the first image numbered from `0` to `4` unless [`ImageSequence::start_number`] is set,
+    /// and ends at the first number which is missing.
+    pub fn pattern<P>(pattern: P, framerate: f32) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        ImageSequence {
+            files: SequenceFiles::Pattern(pattern.into()),
+            framerate,
+            start_number: None,
+        }
+    }
+
+    /// A list of images (in the order they should be shown), shown at `framerate` images per second.
+    ///
+    /// Every image must use the same format. Returns [`DecodeError::InvalidSource`] if a path is not valid unicode,
+    /// or [`DecodeError::InvalidFramerate`] if the framerate is not a positive number.
+    pub fn files<I, P>(files: I, framerate: f32) -> Result<Self, DecodeError>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        // Each image is shown for `1 / framerate` seconds, which must be a valid duration
+        if !(framerate > 0.0 && framerate.is_finite()) {
+            return Err(DecodeError::InvalidFramerate);
+        }
+
+        // Listed in an ffconcat script, with the framerate of each image set so the timestamps line up exactly
+        let mut script = String::from("ffconcat version 1.0\n");
+        for file in files {
+            let path =
+                std::path::absolute(file.as_ref()).map_err(|_| DecodeError::InvalidSource)?;
+            let path = path.to_str().ok_or(DecodeError::InvalidSource)?;
+            script.push_str(&format!(
+                "file '{}'\noption framerate {framerate}\nduration {}\n",
+                path.replace('\'', r"'\''"),
+                1.0 / framerate as f64
+            ));
+        }
+
+        Ok(ImageSequence {
+            files: SequenceFiles::List(script.into_bytes().into()),
+            framerate,
+            start_number: None,
+        })
+    }
+
+    /// The number of the first image of a [`ImageSequence::pattern`], this is ignored for a list of files
+    pub fn start_number(mut self, number: usize) -> Self {
+        self.start_number = Some(number);
+        self
+    }
+
+    /// The number of images shown per second
+    #[inline]
+    pub fn framerate(&self) -> f32 {
+        self.framerate
+    }
+
+    /// The name of the ffmpeg demuxer which reads the sequence
+    pub(crate) fn format(&self) -> &str {
+        match self.files {
+            SequenceFiles::Pattern(_) => "image2",
+            SequenceFiles::List(_) => "concat",
+        }
+    }
+
+    /// The options of the demuxer
+    pub(crate) fn options(&self) -> Vec<(String, String)> {
+        match self.files {
+            SequenceFiles::Pattern(_) => [
+                ("framerate", Some(self.framerate.to_string())),
+                ("pattern_type", Some("sequence".to_string())),
+                ("start_number", self.start_number.map(|n| n.to_string())),
+            ]
+            .into_iter()
+            .filter_map(|(key, value)| Some((key.to_string(), value?)))
+            .collect(),
+            // The listed paths are absolute
+            SequenceFiles::List(_) => vec![("safe".to_string(), "0".to_string())],
+        }
+    }
+
+    /// The pattern passed to the demuxer, `None` for a list of files
+    pub(crate) fn pattern_path(&self) -> Option<&Path> {
+        match &self.files {
+            SequenceFiles::Pattern(pattern) => Some(pattern),
+            SequenceFiles::List(_) => None,
+        }
+    }
+
+    /// The concat script read by the demuxer, `None` for a pattern
+    pub(crate) fn script(&self) -> Option<&[u8]> {
+        match &self.files {
+            SequenceFiles::Pattern(_) => None,
+            SequenceFiles::List(script) => Some(script),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use super::{DecodeError, Frame, VideoDecoder};
+use std::ops::ControlFlow;
+
+/// An error from [`VideoDecoder::forward_to`], either from decoding a frame or from the sink it was sent to
+#[cfg(feature = "futures")]
+#[derive(thiserror::Error, Debug)]
+pub enum ForwardError<E> {
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+    /// The sink failed to accept a frame (e.g. because the receiving end of a channel was dropped)
+    #[error("sink error: {0}")]
+    Sink(E),
+}
+
+impl VideoDecoder {
+    /// Decode the remaining frames, passing each one to `f` until it returns [`ControlFlow::Break`].
+    ///
+    /// The next frame is only decoded once `f` returns, so a slow consumer is never buffered for.
+    /// Returns the value `f` broke with, or [`ControlFlow::Continue`] once the source runs out
+    /// (this never happens if [`VideoDecoder::will_loop`] is `true`, unless `f` breaks).
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    /// use std::ops::ControlFlow;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// let result = decoder
+    ///     .for_each_frame(|frame| match frame.index() {
+    ///         10 => ControlFlow::Break(frame),
+    ///         _ => ControlFlow::Continue(()),
+    ///     })
+    ///     .unwrap();
+    /// assert!(matches!(result, ControlFlow::Break(frame) if frame.index() == 10));
+    /// # }
+    /// ```
+    pub fn for_each_frame<F, B>(&mut self, mut f: F) -> Result<ControlFlow<B>, DecodeError>
+    where
+        F: FnMut(Frame) -> ControlFlow<B>,
+    {
+        while let Some(frame) = self.next_frame()? {
+            if let ControlFlow::Break(value) = f(frame) {
+                return Ok(ControlFlow::Break(value));
+            }
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
+    /// Decode the remaining frames and send each one to an async [`Sink`](futures::Sink), returning the number of frames sent.
+    ///
+    /// Each frame is only decoded once the sink is ready to accept it, so a bounded channel (or any other sink which applies backpressure)
+    /// limits how far decoding runs ahead of the consumer. The sink is flushed (but not closed) once the source runs out.
+    ///
+    /// Decoding itself is blocking, so this should be run somewhere blocking is allowed (e.g. with `tokio::task::spawn_blocking`
+    /// and `futures::executor::block_on`), or on a decoder which only has a few frames left to decode.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    /// use futures::channel::mpsc;
+    /// use futures::StreamExt;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// # decoder.seek_to_frame(890).unwrap();
+    /// let (sender, receiver) = mpsc::channel(4);
+    /// let (sent, frames) = futures::executor::block_on(async {
+    ///     futures::join!(decoder.forward_to(sender), receiver.collect::<Vec<_>>())
+    /// });
+    /// assert_eq!(sent.unwrap(), frames.len());
+    /// # }
+    /// ```
+    #[cfg(feature = "futures")]
+    pub async fn forward_to<S>(&mut self, mut sink: S) -> Result<usize, ForwardError<S::Error>>
+    where
+        S: futures::Sink<Frame> + Unpin,
+    {
+        use futures::SinkExt;
+
+        let mut count = 0;
+        // `feed` waits until the sink is ready for the frame, without flushing it after every frame
+        while let Some(frame) = self.next_frame()? {
+            sink.feed(frame).await.map_err(ForwardError::Sink)?;
+            count += 1;
+        }
+        sink.flush().await.map_err(ForwardError::Sink)?;
+        Ok(count)
+    }
+}
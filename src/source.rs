@@ -1,12 +1,30 @@
+use super::{Device, ImageSequence};
+use std::collections::VecDeque;
 use std::path::PathBuf;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
 
 /// The input data for the decoder
+///
+/// Some variants depend on crate features, so this is non-exhaustive to keep enabling a feature from breaking matches on it.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum VideoSource {
     /// Raw binary data
     Raw(Vec<u8>),
+    /// Raw binary data which can be shared between decoders without copying it
+    Shared(Arc<[u8]>),
+    /// A buffer from the [`bytes`](https://docs.rs/bytes) crate, which is read without copying it (requires the `bytes` feature)
+    #[cfg(feature = "bytes")]
+    Bytes(bytes::Bytes),
     /// A path to a file
     Filesystem(PathBuf),
+    /// Data which is pushed to the decoder as it arrives, see [`PushSource`]
+    Push(PushSource),
+    /// A capture device such as a webcam, see [`Device`]
+    Device(Device),
+    /// A sequence of numbered images, see [`ImageSequence`]
+    ImageSequence(ImageSequence),
 }
 
 impl From<PathBuf> for VideoSource {
@@ -21,6 +39,37 @@ impl From<Vec<u8>> for VideoSource {
     }
 }
 
+impl From<Arc<[u8]>> for VideoSource {
+    fn from(data: Arc<[u8]>) -> Self {
+        Self::Shared(data)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<bytes::Bytes> for VideoSource {
+    fn from(data: bytes::Bytes) -> Self {
+        Self::Bytes(data)
+    }
+}
+
+impl From<PushSource> for VideoSource {
+    fn from(source: PushSource) -> Self {
+        Self::Push(source)
+    }
+}
+
+impl From<Device> for VideoSource {
+    fn from(device: Device) -> Self {
+        Self::Device(device)
+    }
+}
+
+impl From<ImageSequence> for VideoSource {
+    fn from(sequence: ImageSequence) -> Self {
+        Self::ImageSequence(sequence)
+    }
+}
+
 impl From<String> for VideoSource {
     fn from(s: String) -> Self {
         PathBuf::from(s).into()
@@ -32,3 +81,135 @@ impl From<&str> for VideoSource {
         s.to_string().into()
     }
 }
+
+impl VideoSource {
+    /// The data of an in memory source, `None` for other sources
+    pub(crate) fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            VideoSource::Raw(data) => Some(data),
+            VideoSource::Shared(data) => Some(data),
+            #[cfg(feature = "bytes")]
+            VideoSource::Bytes(data) => Some(data),
+            // The list of images is read from an in memory script
+            VideoSource::ImageSequence(sequence) => sequence.script(),
+            VideoSource::Filesystem(_) | VideoSource::Push(_) | VideoSource::Device(_) => None,
+        }
+    }
+}
+
+/// A source which data is pushed into as it arrives (e.g. from a network connection), so video can be decoded while it is still downloading.
+///
+/// The decoder blocks until enough data has been pushed, use [`PushSource::finish`] to mark the end of the data.
+/// The data can only be read once, so seeking (and looping) is not supported, and cloned sources share the same data.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::{PushSource, VideoDecoder};
+/// use std::thread;
+///
+/// let source = PushSource::new();
+///
+/// let sender = source.clone();
+/// thread::spawn(move || {
+///     // Usually this is received in chunks
+///     let data = std::fs::read("test.mp4").unwrap();
+///     for chunk in data.chunks(64 * 1024) {
+///         sender.push(chunk);
+///     }
+///     sender.finish();
+/// });
+///
+/// let mut decoder = VideoDecoder::new(source, false).unwrap();
+/// let first_frame = decoder.next_frame().unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PushSource {
+    shared: Arc<(Mutex<PushState>, Condvar)>,
+}
+
+#[derive(Debug, Default)]
+struct PushState {
+    chunks: VecDeque<Vec<u8>>,
+    /// How much of the first chunk has already been read
+    offset: usize,
+    finished: bool,
+}
+
+impl PushSource {
+    /// Create an empty source
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add data to the end of the source
+    pub fn push<D>(&self, data: D)
+    where
+        D: Into<Vec<u8>>,
+    {
+        let data = data.into();
+        if data.is_empty() {
+            return;
+        }
+
+        let (state, available) = &*self.shared;
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        state.chunks.push_back(data);
+        available.notify_all();
+    }
+
+    /// Mark the end of the data, once the decoder has read everything that was pushed it reaches the end of the video
+    pub fn finish(&self) {
+        let (state, available) = &*self.shared;
+        state.lock().unwrap_or_else(|e| e.into_inner()).finished = true;
+        available.notify_all();
+    }
+
+    /// Whether [`PushSource::finish`] has been called
+    pub fn is_finished(&self) -> bool {
+        let (state, _) = &*self.shared;
+        state.lock().unwrap_or_else(|e| e.into_inner()).finished
+    }
+
+    /// The number of bytes which have been pushed but not read by the decoder yet, e.g. to stop pushing while the decoder falls behind
+    pub fn buffered(&self) -> usize {
+        let (state, _) = &*self.shared;
+        let state = state.lock().unwrap_or_else(|e| e.into_inner());
+        state.chunks.iter().map(Vec::len).sum::<usize>() - state.offset
+    }
+
+    /// Read pushed data into `buf`, blocking until some is available.
+    ///
+    /// Returns `Some(0)` once the source is finished and all data has been read, or `None` if `interrupted` returned `true` while waiting.
+    pub(crate) fn read(&self, buf: &mut [u8], interrupted: impl Fn() -> bool) -> Option<usize> {
+        let (state, available) = &*self.shared;
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+
+        loop {
+            if let Some(chunk) = state.chunks.front() {
+                let size = buf.len().min(chunk.len() - state.offset);
+                buf[..size].copy_from_slice(&chunk[state.offset..state.offset + size]);
+
+                state.offset += size;
+                if state.offset == chunk.len() {
+                    state.chunks.pop_front();
+                    state.offset = 0;
+                }
+                return Some(size);
+            }
+
+            if state.finished {
+                return Some(0);
+            }
+            if interrupted() {
+                return None;
+            }
+
+            // Wake up regularly so a cancelled decoder does not wait for more data
+            state = available
+                .wait_timeout(state, Duration::from_millis(50))
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+/// Counters describing the work done by a [`VideoDecoder`](crate::VideoDecoder), see [`VideoDecoder::stats`](crate::VideoDecoder::stats)
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecoderStats {
+    /// Packets of the video stream read from the source data
+    pub packets_read: u64,
+    /// The total size of the packets of the video stream read from the source data
+    pub bytes_read: u64,
+    /// Frames output by the decoder (before any filters)
+    pub frames_decoded: u64,
+    /// Decoded frames which were discarded without being returned, e.g. while seeking or outside of [`DecoderBuilder::range`](crate::DecoderBuilder::range)
+    pub frames_dropped: u64,
+    /// Packets which were damaged or rejected by the decoder, and skipped (see [`ErrorPolicy`](crate::ErrorPolicy))
+    pub corrupt_packets: u64,
+    /// Decoded frames which were damaged, and skipped (see [`ErrorPolicy::SkipFrame`](crate::ErrorPolicy::SkipFrame))
+    pub corrupt_frames: u64,
+    /// Data packets which were dropped as they were not taken in time, see [`DecoderBuilder::max_data_packets`](crate::DecoderBuilder::max_data_packets)
+    pub data_packets_dropped: u64,
+    /// The total time spent decoding (and filtering) frames
+    pub decode_time: Duration,
+    /// The total time spent converting frames to the output format
+    pub convert_time: Duration,
+}
+
+impl DecoderStats {
+    /// The average time spent decoding each frame, zero if no frames have been decoded
+    pub fn average_decode_time(&self) -> Duration {
+        match self.frames_decoded {
+            0 => Duration::ZERO,
+            frames => self.decode_time / frames.min(u32::MAX as u64) as u32,
+        }
+    }
+}
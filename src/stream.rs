@@ -0,0 +1,108 @@
+use super::Dimensions;
+use crate::c::{dict_get, str_from_ptr};
+
+/// The type of data a stream contains
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StreamKind {
+    Video,
+    Audio,
+    Subtitle,
+    /// Timed data which is not audio, video, or subtitles (such as telemetry)
+    Data,
+    /// Non-timed data, such as fonts
+    Attachment,
+    Unknown,
+}
+
+impl From<ffmpeg::AVMediaType> for StreamKind {
+    fn from(kind: ffmpeg::AVMediaType) -> Self {
+        match kind {
+            ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO => StreamKind::Video,
+            ffmpeg::AVMediaType::AVMEDIA_TYPE_AUDIO => StreamKind::Audio,
+            ffmpeg::AVMediaType::AVMEDIA_TYPE_SUBTITLE => StreamKind::Subtitle,
+            ffmpeg::AVMediaType::AVMEDIA_TYPE_DATA => StreamKind::Data,
+            ffmpeg::AVMediaType::AVMEDIA_TYPE_ATTACHMENT => StreamKind::Attachment,
+            _ => StreamKind::Unknown,
+        }
+    }
+}
+
+/// A description of a single stream within the source data
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamInfo {
+    index: usize,
+    kind: StreamKind,
+    codec: String,
+    language: Option<String>,
+    dimensions: Option<Dimensions>,
+}
+
+impl StreamInfo {
+    /// The index of the stream within the source data, this is what should be passed to [`DecoderBuilder::video_stream`](crate::DecoderBuilder::video_stream)
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The type of the stream
+    #[inline]
+    pub fn kind(&self) -> StreamKind {
+        self.kind
+    }
+
+    /// The (short) name of the codec used by the stream, e.g. `h264`
+    #[inline]
+    pub fn codec(&self) -> &str {
+        &self.codec
+    }
+
+    /// The language of the stream (usually an ISO 639-2 code), if specified by the source data
+    #[inline]
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// The dimensions of the stream, this is only set for video streams
+    #[inline]
+    pub fn dimensions(&self) -> Option<Dimensions> {
+        self.dimensions
+    }
+
+    /// Read the description of a stream
+    ///
+    /// # Safety
+    ///
+    /// `stream` must point to a valid stream of an opened input context.
+    pub(crate) unsafe fn from_raw(stream: *const ffmpeg::AVStream) -> Self {
+        let codecpar = (*stream).codecpar;
+        let kind = StreamKind::from((*codecpar).codec_type);
+
+        StreamInfo {
+            index: (*stream).index as usize,
+            kind,
+            codec: str_from_ptr(ffmpeg::avcodec_get_name((*codecpar).codec_id)).unwrap_or_default(),
+            language: dict_get((*stream).metadata, c"language"),
+            dimensions: match kind {
+                StreamKind::Video => Some(Dimensions {
+                    width: (*codecpar).width as u32,
+                    height: (*codecpar).height as u32,
+                }),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Read the descriptions of every stream in an input context
+///
+/// # Safety
+///
+/// `input_ctx` must point to a valid opened input context.
+pub(crate) unsafe fn read_streams(input_ctx: *const ffmpeg::AVFormatContext) -> Vec<StreamInfo> {
+    (0..(*input_ctx).nb_streams as usize)
+        .map(|i| StreamInfo::from_raw(*(*input_ctx).streams.add(i)))
+        .collect()
+}
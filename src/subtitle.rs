@@ -0,0 +1,336 @@
+use super::{DecodeError, Dimensions, VideoSource};
+use crate::c::{str_from_ptr, ts_to_duration};
+use crate::input::{Input, InputOptions};
+use std::time::Duration;
+use std::{mem, ptr};
+
+/// A single bitmap (image based) subtitle, such as those found in PGS or DVD subtitles
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubtitleBitmap {
+    x: u32,
+    y: u32,
+    dimensions: Dimensions,
+    data: Vec<u8>,
+}
+
+impl SubtitleBitmap {
+    /// The position of the top left corner of the bitmap within the video frame (in pixels)
+    #[inline]
+    pub fn position(&self) -> (u32, u32) {
+        (self.x, self.y)
+    }
+
+    /// Get the dimensions of the bitmap
+    #[inline]
+    pub fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    /// Get a reference to the raw bitmap data (RGBA8)
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Convert this bitmap into its raw data (RGBA8)
+    #[inline]
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Convert a paletted subtitle rect into RGBA
+    unsafe fn from_rect(rect: &ffmpeg::AVSubtitleRect) -> Self {
+        let width = rect.w.max(0) as usize;
+        let height = rect.h.max(0) as usize;
+        let palette = std::slice::from_raw_parts(
+            rect.data[1] as *const u32,
+            rect.nb_colors.clamp(0, 256) as usize,
+        );
+
+        let mut data = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            let row =
+                std::slice::from_raw_parts(rect.data[0].add(y * rect.linesize[0] as usize), width);
+            for &index in row {
+                // Palette entries are native endian ARGB
+                let argb = palette.get(index as usize).copied().unwrap_or(0);
+                data.extend_from_slice(&[
+                    (argb >> 16) as u8,
+                    (argb >> 8) as u8,
+                    argb as u8,
+                    (argb >> 24) as u8,
+                ]);
+            }
+        }
+
+        SubtitleBitmap {
+            x: rect.x.max(0) as u32,
+            y: rect.y.max(0) as u32,
+            dimensions: Dimensions {
+                width: width as u32,
+                height: height as u32,
+            },
+            data,
+        }
+    }
+}
+
+/// The content of a subtitle cue
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubtitleContent {
+    /// Plain text
+    Text(String),
+    /// A line of an ASS (Advanced SubStation Alpha) script, this includes any styling overrides.
+    ///
+    /// Text based subtitles (such as SRT) are also converted to ASS by ffmpeg.
+    Ass(String),
+    /// An image to be drawn over the video
+    Bitmap(SubtitleBitmap),
+}
+
+/// A subtitle which should be shown between two timestamps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubtitleCue {
+    start: Duration,
+    end: Option<Duration>,
+    content: Vec<SubtitleContent>,
+}
+
+impl SubtitleCue {
+    /// The time (from the start of the video) at which the cue should be shown
+    #[inline]
+    pub fn start(&self) -> Duration {
+        self.start
+    }
+
+    /// The time (from the start of the video) at which the cue should be hidden.
+    ///
+    /// This is `None` if the source data does not specify an end time, in which case the cue should be shown until the next cue starts.
+    /// This is common for bitmap subtitles such as PGS, which instead send an empty cue to clear the screen.
+    #[inline]
+    pub fn end(&self) -> Option<Duration> {
+        self.end
+    }
+
+    /// Get a reference to everything which should be shown for this cue
+    #[inline]
+    pub fn content(&self) -> &[SubtitleContent] {
+        &self.content
+    }
+
+    /// Convert this cue into its content
+    #[inline]
+    pub fn into_content(self) -> Vec<SubtitleContent> {
+        self.content
+    }
+}
+
+/// A subtitle decoder, this reads timed cues from a subtitle stream
+///
+/// ## Usage
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::SubtitleDecoder;
+///
+/// let file = "video.mkv";
+/// # let file = "test.mp4";
+/// # if SubtitleDecoder::new(file).is_err() { return; }
+/// let mut decoder = SubtitleDecoder::new(file).unwrap();
+/// while let Some(cue) = decoder.next_cue().unwrap() {
+///     println!("{:?} -> {:?}: {:?}", cue.start(), cue.end(), cue.content());
+/// }
+/// # }
+/// ```
+pub struct SubtitleDecoder {
+    codec_ctx: *mut ffmpeg::AVCodecContext,
+    packet: *mut ffmpeg::AVPacket,
+    stream_id: i32,
+    time_base: ffmpeg::AVRational,
+    /// Declared after the other contexts so it is dropped after them
+    input: Input,
+}
+
+// SAFETY: See `VideoDecoder`, the same reasoning applies here
+unsafe impl Send for SubtitleDecoder {}
+
+impl SubtitleDecoder {
+    /// Create a new subtitle decoder for the first subtitle stream in the source data
+    pub fn new<S>(source: S) -> Result<Self, DecodeError>
+    where
+        S: Into<VideoSource>,
+    {
+        Self::open(source.into(), None)
+    }
+
+    /// Create a new subtitle decoder for a specific stream, see [`VideoDecoder::streams`](crate::VideoDecoder::streams) for the available streams
+    pub fn with_stream<S>(source: S, index: usize) -> Result<Self, DecodeError>
+    where
+        S: Into<VideoSource>,
+    {
+        Self::open(source.into(), Some(index))
+    }
+
+    fn open(source: VideoSource, stream: Option<usize>) -> Result<Self, DecodeError> {
+        let input = Input::open(source, &InputOptions::default())?;
+
+        unsafe {
+            let stream_id = match stream {
+                Some(index)
+                    if input.is_stream(index, ffmpeg::AVMediaType::AVMEDIA_TYPE_SUBTITLE) =>
+                {
+                    index
+                }
+                Some(_) => return Err(DecodeError::InvalidSubtitleStream),
+                None => input
+                    .find_stream(ffmpeg::AVMediaType::AVMEDIA_TYPE_SUBTITLE)
+                    .ok_or(DecodeError::UnableToFindSubtitleStream)?,
+            };
+            let stream = input.stream(stream_id);
+            let codecpar = (*stream).codecpar;
+
+            let codec = ffmpeg::avcodec_find_decoder((*codecpar).codec_id);
+            if codec.is_null() {
+                return Err(DecodeError::UnsupportedCodec);
+            }
+
+            let mut codec_ctx = ffmpeg::avcodec_alloc_context3(codec);
+            if codec_ctx.is_null() {
+                return Err(DecodeError::AllocationFailed);
+            }
+            ffmpeg::avcodec_parameters_to_context(codec_ctx, codecpar);
+            // Required for ffmpeg to convert the packet timestamps of the cues
+            (*codec_ctx).pkt_timebase = (*stream).time_base;
+
+            if ffmpeg::avcodec_open2(codec_ctx, codec, ptr::null_mut()) < 0 {
+                ffmpeg::avcodec_free_context(&mut codec_ctx);
+                return Err(DecodeError::UnsupportedCodec);
+            }
+
+            let packet = ffmpeg::av_packet_alloc();
+            if packet.is_null() {
+                ffmpeg::avcodec_free_context(&mut codec_ctx);
+                return Err(DecodeError::AllocationFailed);
+            }
+
+            Ok(SubtitleDecoder {
+                codec_ctx,
+                packet,
+                stream_id: stream_id as i32,
+                time_base: (*stream).time_base,
+                input,
+            })
+        }
+    }
+
+    /// The index of the stream being decoded
+    #[inline]
+    pub fn stream_index(&self) -> usize {
+        self.stream_id as usize
+    }
+
+    /// Get the next cue from the input, returns `Ok(None)` once there are no more cues.
+    pub fn next_cue(&mut self) -> Result<Option<SubtitleCue>, DecodeError> {
+        unsafe {
+            loop {
+                if self.input.read_frame(self.packet)? < 0 {
+                    // out of packets
+                    return Ok(None);
+                }
+
+                if (*self.packet).stream_index != self.stream_id {
+                    ffmpeg::av_packet_unref(self.packet);
+                    continue;
+                }
+
+                let mut subtitle: ffmpeg::AVSubtitle = mem::zeroed();
+                let mut got_subtitle = 0;
+                let result = ffmpeg::avcodec_decode_subtitle2(
+                    self.codec_ctx,
+                    &mut subtitle,
+                    &mut got_subtitle,
+                    self.packet,
+                );
+
+                let cue = (result >= 0 && got_subtitle != 0)
+                    .then(|| read_cue(&subtitle, self.packet, self.time_base));
+
+                if got_subtitle != 0 {
+                    ffmpeg::avsubtitle_free(&mut subtitle);
+                }
+                ffmpeg::av_packet_unref(self.packet);
+
+                if result < 0 {
+                    return Err(DecodeError::UnableToDecodeSubtitle);
+                }
+
+                if let Some(cue) = cue {
+                    return Ok(Some(cue));
+                }
+            }
+        }
+    }
+}
+
+/// Convert a decoded subtitle into a cue, `packet` is the packet it was decoded from (with timestamps in `time_base`)
+pub(crate) unsafe fn read_cue(
+    subtitle: &ffmpeg::AVSubtitle,
+    packet: *const ffmpeg::AVPacket,
+    time_base: ffmpeg::AVRational,
+) -> SubtitleCue {
+    // Prefer the timestamp ffmpeg calculated, falling back to the packet timestamp
+    let base = ts_to_duration(subtitle.pts, ffmpeg::AV_TIME_BASE_Q)
+        .or_else(|| ts_to_duration((*packet).pts, time_base))
+        .unwrap_or_default();
+
+    let start = base + Duration::from_millis(subtitle.start_display_time as u64);
+    let end = if subtitle.end_display_time > subtitle.start_display_time {
+        Some(base + Duration::from_millis(subtitle.end_display_time as u64))
+    } else {
+        ts_to_duration((*packet).duration, time_base)
+            .filter(|duration| !duration.is_zero())
+            .map(|duration| base + duration)
+    };
+
+    let mut content = Vec::with_capacity(subtitle.num_rects as usize);
+    for i in 0..subtitle.num_rects as usize {
+        let rect = &**subtitle.rects.add(i);
+
+        match rect.type_ {
+            ffmpeg::AVSubtitleType::SUBTITLE_BITMAP => {
+                content.push(SubtitleContent::Bitmap(SubtitleBitmap::from_rect(rect)))
+            }
+            ffmpeg::AVSubtitleType::SUBTITLE_TEXT => {
+                content.extend(str_from_ptr(rect.text).map(SubtitleContent::Text))
+            }
+            ffmpeg::AVSubtitleType::SUBTITLE_ASS => {
+                content.extend(str_from_ptr(rect.ass).map(SubtitleContent::Ass))
+            }
+            _ => (),
+        }
+    }
+
+    SubtitleCue {
+        start,
+        end,
+        content,
+    }
+}
+
+impl Iterator for SubtitleDecoder {
+    type Item = Result<SubtitleCue, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_cue().transpose()
+    }
+}
+
+impl Drop for SubtitleDecoder {
+    fn drop(&mut self) {
+        unsafe {
+            ffmpeg::av_packet_free(&mut self.packet);
+            ffmpeg::avcodec_free_context(&mut self.codec_ctx);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use super::{Frame, PixelFormat};
+
+/// The order of the values in a tensor created with [`Frame::to_tensor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TensorLayout {
+    /// Planar, every value of the first channel then every value of the next (`channels, height, width`).
+    ///
+    /// This is what most image models (such as those exported to ONNX from PyTorch) expect.
+    #[default]
+    Chw,
+    /// Interleaved, every channel of the first pixel then every channel of the next (`height, width, channels`)
+    Hwc,
+}
+
+impl Frame {
+    /// Convert this frame into a tensor of `f32` values, ready to be used as the input of a model.
+    ///
+    /// Values are first scaled to `0.0..=1.0`, then if `normalize` is set to `(mean, std)`, each channel `c` is normalized with `(value - mean[c]) / std[c]`
+    /// (channels without a mean or standard deviation are left as they are).
+    /// The shape of the tensor is `[channels, height, width]` or `[height, width, channels]` depending on the layout, see [`PixelFormat::channels`].
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::{TensorLayout, VideoDecoder};
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::builder(file)
+    ///     .filter("scale=224:224")
+    ///     .build()
+    ///     .unwrap();
+    /// let frame = decoder.next_frame().unwrap().unwrap();
+    ///
+    /// // The normalization used by models trained on ImageNet
+    /// let mean = [0.485, 0.456, 0.406];
+    /// let std = [0.229, 0.224, 0.225];
+    /// let tensor = frame.to_tensor(TensorLayout::Chw, Some((&mean, &std)));
+    /// assert_eq!(tensor.len(), 3 * 224 * 224);
+    /// # }
+    /// ```
+    pub fn to_tensor(&self, layout: TensorLayout, normalize: Option<(&[f32], &[f32])>) -> Vec<f32> {
+        let width = self.dimensions.width as usize;
+        let height = self.dimensions.height as usize;
+        let channels = self.format.channels();
+        let bytes = self.format.bytes_per_pixel() / channels;
+
+        // Work out a scale and offset per channel, so each value is a single multiply-add
+        let (scale, offset): (Vec<f32>, Vec<f32>) = (0..channels)
+            .map(|c| {
+                let max = match self.format {
+                    PixelFormat::Rgb48 | PixelFormat::Rgba64 => 65535.0,
+                    _ => 255.0,
+                };
+                let (mean, std) = normalize
+                    .and_then(|(mean, std)| Some((*mean.get(c)?, *std.get(c)?)))
+                    .unwrap_or((0.0, 1.0));
+                (1.0 / (max * std), -mean / std)
+            })
+            .unzip();
+
+        let mut tensor = vec![0.0; width * height * channels];
+        for (y, line) in self.data.chunks(self.stride).take(height).enumerate() {
+            for x in 0..width {
+                for c in 0..channels {
+                    let start = (x * channels + c) * bytes;
+                    let value = match bytes {
+                        2 => u16::from_ne_bytes([line[start], line[start + 1]]) as f32,
+                        _ => line[start] as f32,
+                    };
+
+                    let i = match layout {
+                        TensorLayout::Chw => (c * height + y) * width + x,
+                        TensorLayout::Hwc => (y * width + x) * channels + c,
+                    };
+                    tensor[i] = value * scale[c] + offset[c];
+                }
+            }
+        }
+
+        tensor
+    }
+}
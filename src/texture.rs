@@ -0,0 +1,107 @@
+//! Uploading frames to wgpu textures
+
+use super::{DecodeError, Frame, PixelFormat};
+use std::borrow::Cow;
+
+impl Frame {
+    /// The texture format used by [`Frame::create_texture`] and [`Frame::write_to_texture`]
+    ///
+    /// Frames with colour are uploaded as [`wgpu::TextureFormat::Rgba8UnormSrgb`] (adding an alpha channel and reducing the bit depth if needed),
+    /// and [`PixelFormat::Gray8`] frames are uploaded as [`wgpu::TextureFormat::R8Unorm`].
+    pub fn texture_format(&self) -> wgpu::TextureFormat {
+        match self.format {
+            PixelFormat::Gray8 => wgpu::TextureFormat::R8Unorm,
+            _ => wgpu::TextureFormat::Rgba8UnormSrgb,
+        }
+    }
+
+    /// Create a texture which this frame (and any other frame of the same size and format) can be written to with [`Frame::write_to_texture`].
+    ///
+    /// The texture can be used as [`wgpu::TextureUsages::TEXTURE_BINDING`] and [`wgpu::TextureUsages::COPY_DST`].
+    pub fn create_texture(&self, device: &wgpu::Device) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("video frame"),
+            size: self.texture_size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.texture_format(),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    /// Write this frame to a texture (such as one created with [`Frame::create_texture`]).
+    ///
+    /// Returns [`DecodeError::TextureMismatch`] if the texture is not the same size as the frame, or does not use [`Frame::texture_format`].
+    pub fn write_to_texture(
+        &self,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+    ) -> Result<(), DecodeError> {
+        if texture.size() != self.texture_size() || texture.format() != self.texture_format() {
+            return Err(DecodeError::TextureMismatch);
+        }
+
+        let (data, bytes_per_row) = self.texture_data();
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row as u32),
+                rows_per_image: Some(self.dimensions.height),
+            },
+            self.texture_size(),
+        );
+
+        Ok(())
+    }
+
+    fn texture_size(&self) -> wgpu::Extent3d {
+        wgpu::Extent3d {
+            width: self.dimensions.width,
+            height: self.dimensions.height,
+            depth_or_array_layers: 1,
+        }
+    }
+
+    /// The frame data in the texture format, along with the number of bytes between the start of each row
+    fn texture_data(&self) -> (Cow<'_, [u8]>, usize) {
+        let width = self.dimensions.width as usize;
+        let height = self.dimensions.height as usize;
+
+        match self.format {
+            // The data can be uploaded as it is, padding included
+            PixelFormat::Rgba | PixelFormat::Gray8 => (Cow::Borrowed(&self.data[..]), self.stride),
+            _ => {
+                let bytes = self.format.bytes_per_pixel() / self.format.channels();
+
+                let mut rgba = Vec::with_capacity(width * height * 4);
+                for line in self.data.chunks(self.stride).take(height) {
+                    for pixel in line[..width * self.format.bytes_per_pixel()]
+                        .chunks_exact(self.format.bytes_per_pixel())
+                    {
+                        // Keep the most significant byte of each channel
+                        let channel = |c: usize| match bytes {
+                            2 => (u16::from_ne_bytes([pixel[c * 2], pixel[c * 2 + 1]]) >> 8) as u8,
+                            _ => pixel[c],
+                        };
+                        let alpha = match self.format.channels() {
+                            4 => channel(3),
+                            _ => 255,
+                        };
+                        rgba.extend_from_slice(&[channel(0), channel(1), channel(2), alpha]);
+                    }
+                }
+
+                (Cow::Owned(rgba), width * 4)
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use super::{DecodeError, Frame, VideoDecoder};
+use std::io;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread::{self, JoinHandle};
+
+/// Frames decoded on a background thread, see [`VideoDecoder::spawn`]
+///
+/// Dropping the receiver stops the decoding thread once it next tries to send a frame.
+pub struct FrameReceiver {
+    receiver: Option<Receiver<Result<Frame, DecodeError>>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl FrameReceiver {
+    /// Block until the next frame is available.
+    ///
+    /// This behaves the same as [`VideoDecoder::next_frame`], returning `Ok(None)` once the decoder has run out of frames.
+    pub fn next_frame(&self) -> Result<Option<Frame>, DecodeError> {
+        match self.receiver.as_ref().map(Receiver::recv) {
+            Some(Ok(frame)) => frame.map(Some),
+            // The sending half is only dropped once the decoder has finished
+            _ => Ok(None),
+        }
+    }
+
+    /// Get the next frame if one has already been decoded, this will never block.
+    ///
+    /// Returns `Ok(None)` if no frame is ready yet or the decoder has finished (see [`FrameReceiver::is_finished`]).
+    pub fn try_next_frame(&self) -> Result<Option<Frame>, DecodeError> {
+        match self.receiver.as_ref().map(Receiver::try_recv) {
+            Some(Ok(frame)) => frame.map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Check whether the decoding thread has exited
+    ///
+    /// Note that there may still be buffered frames waiting to be received.
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().map_or(true, JoinHandle::is_finished)
+    }
+}
+
+impl Iterator for FrameReceiver {
+    type Item = Result<Frame, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
+
+impl Drop for FrameReceiver {
+    fn drop(&mut self) {
+        // Drop the receiver first so the decoding thread is unblocked if it is waiting on a full channel
+        self.receiver.take();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl VideoDecoder {
+    /// Move this decoder onto a dedicated thread which decodes ahead of the caller.
+    ///
+    /// At most `capacity` frames will be buffered at any one time, once the buffer is full the thread blocks until a frame is received.
+    /// A `capacity` of `0` means that each frame is decoded only once the previous one has been received.
+    ///
+    /// Returns an error if the operating system fails to create the thread.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let frames = VideoDecoder::new(file, false).unwrap().spawn(8).unwrap();
+    /// while let Some(frame) = frames.next_frame().unwrap() {
+    ///     // do something with the frame
+    /// }
+    /// # }
+    /// ```
+    pub fn spawn(self, capacity: usize) -> io::Result<FrameReceiver> {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+
+        let handle = thread::Builder::new()
+            .name(String::from("ffmpeg-video-decoder"))
+            .spawn(move || decode_into(self, sender))?;
+
+        Ok(FrameReceiver {
+            receiver: Some(receiver),
+            handle: Some(handle),
+        })
+    }
+}
+
+/// Decode frames until the source runs out, an error occurs, or the receiver is dropped
+fn decode_into(mut decoder: VideoDecoder, sender: SyncSender<Result<Frame, DecodeError>>) {
+    loop {
+        let next = match decoder.next_frame() {
+            Ok(Some(frame)) => Ok(frame),
+            Ok(None) => return,
+            Err(e) => Err(e),
+        };
+
+        let is_err = next.is_err();
+        if sender.send(next).is_err() || is_err {
+            return;
+        }
+    }
+}
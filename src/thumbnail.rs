@@ -0,0 +1,148 @@
+#[cfg(feature = "image")]
+use super::PixelFormat;
+use super::{DecodeError, DecoderBuilder, Frame, VideoSource};
+use std::time::Duration;
+
+/// Options used by [`thumbnails_with`]
+#[derive(Debug, Clone, Default)]
+pub struct ThumbnailOptions {
+    max_size: Option<(u32, u32)>,
+    exact: bool,
+}
+
+impl ThumbnailOptions {
+    /// Create the default options, this returns full size keyframes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Downscale thumbnails (keeping the aspect ratio) so that they fit within the given size
+    pub fn max_size(mut self, width: u32, height: u32) -> Self {
+        self.max_size = Some((width, height));
+        self
+    }
+
+    /// Whether to decode the exact frame at each position (defaults to `false`).
+    ///
+    /// By default the keyframe at or before each position is used instead, which is much faster as no other frames need to be decoded.
+    /// For videos with few keyframes this may return the same frame for multiple positions.
+    pub fn exact(mut self, exact: bool) -> Self {
+        self.exact = exact;
+        self
+    }
+}
+
+/// Get `count` frames from evenly spaced positions throughout a video, see [`thumbnails_with`] for more options.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::thumbnails;
+///
+/// # let file = "test.mp4";
+/// let frames = thumbnails(file, 8).unwrap();
+/// assert_eq!(frames.len(), 8);
+/// # }
+/// ```
+pub fn thumbnails<S>(source: S, count: usize) -> Result<Vec<Frame>, DecodeError>
+where
+    S: Into<VideoSource>,
+{
+    thumbnails_with(source, count, ThumbnailOptions::default())
+}
+
+/// Get `count` frames from evenly spaced positions throughout a video.
+///
+/// Positions are at the middle of `count` equal sections of the video, so the (often black) first and last frames are avoided.
+/// Fewer than `count` frames are returned if the duration of the video is not known, or it ends early.
+pub fn thumbnails_with<S>(
+    source: S,
+    count: usize,
+    options: ThumbnailOptions,
+) -> Result<Vec<Frame>, DecodeError>
+where
+    S: Into<VideoSource>,
+{
+    let mut builder = DecoderBuilder::new(source);
+    if let Some((width, height)) = options.max_size {
+        builder = builder.filter(&format!(
+            "scale={width}:{height}:force_original_aspect_ratio=decrease"
+        ));
+    }
+    let mut decoder = builder.build()?;
+
+    let duration = match decoder.duration() {
+        Some(duration) => duration,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut frames = Vec::with_capacity(count);
+    for i in 0..count {
+        let position = duration.mul_f64((i as f64 + 0.5) / count as f64);
+
+        let frame = match options.exact {
+            true => decoder.get_frame_at(position)?,
+            false => {
+                decoder.seek_to_keyframe(position)?;
+                decoder.next_frame()?
+            }
+        };
+
+        match frame {
+            Some(frame) => frames.push(frame),
+            None => break,
+        }
+    }
+
+    Ok(frames)
+}
+
+/// Tile frames into a single image, left to right then top to bottom.
+///
+/// Each tile is the size of the first frame, frames which are a different size are placed in the top left corner of their tile (and cropped if they are larger).
+/// Frames which are not [`PixelFormat::Rgb24`] are converted to it.
+/// Returns `None` if there are no frames.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::{contact_sheet, thumbnails_with, ThumbnailOptions};
+///
+/// # let file = "test.mp4";
+/// let frames = thumbnails_with(file, 12, ThumbnailOptions::new().max_size(320, 180)).unwrap();
+/// let sheet = contact_sheet(&frames, 4).unwrap();
+/// assert_eq!(sheet.dimensions(), (320 * 4, 180 * 3));
+/// # }
+/// ```
+#[cfg(feature = "image")]
+pub fn contact_sheet(frames: &[Frame], columns: usize) -> Option<image::RgbImage> {
+    let tile = frames.first()?.dimensions();
+    let columns = columns.clamp(1, frames.len());
+    let rows = frames.len().div_ceil(columns);
+
+    let mut sheet = image::RgbImage::new(tile.width * columns as u32, tile.height * rows as u32);
+    let sheet_row = sheet.width() as usize * 3;
+    let buffer: &mut [u8] = &mut sheet;
+
+    for (i, frame) in frames.iter().enumerate() {
+        // Converting copies the frame, so this is only done for other formats
+        let converted = match frame.format() {
+            PixelFormat::Rgb24 => None,
+            _ => Some(frame.clone().into_image().ok()?.into_rgb8()),
+        };
+        let plane = frame.planes()[0];
+        let x = (i % columns) * tile.width as usize * 3;
+        let y = (i / columns) * tile.height as usize;
+
+        let width = plane.dimensions().width.min(tile.width) as usize * 3;
+        let height = plane.dimensions().height.min(tile.height) as usize;
+        for row in 0..height {
+            let source = match &converted {
+                Some(image) => &image.as_raw()[row * image.width() as usize * 3..],
+                None => plane.row(row),
+            };
+            let start = (y + row) * sheet_row + x;
+            buffer[start..start + width].copy_from_slice(&source[..width]);
+        }
+    }
+
+    Some(sheet)
+}
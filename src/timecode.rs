@@ -0,0 +1,206 @@
+use super::Frame;
+use std::fmt;
+
+/// A SMPTE timecode (`HH:MM:SS:FF`), as used in broadcast and editing to label each frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timecode {
+    hours: u8,
+    minutes: u8,
+    seconds: u8,
+    frames: u8,
+    fps: u32,
+    drop_frame: bool,
+}
+
+impl Timecode {
+    /// The timecode of the `n`th frame (starting from `0`) counted from `00:00:00:00`, at a nominal framerate of `fps` (e.g. `30` for 29.97 fps video).
+    ///
+    /// With `drop_frame` set, frame numbers `0` and `1` (or `0` to `3` at 60 fps) are skipped at the start of every minute except every tenth minute,
+    /// which keeps the timecode in step with the clock for NTSC framerates (29.97 and 59.94 fps).
+    /// Drop frame timecode is only defined for multiples of 30 fps, so it is ignored for other framerates.
+    /// The hours wrap around after 24 hours.
+    pub fn from_frames(n: u64, fps: u32, drop_frame: bool) -> Self {
+        let fps = fps.max(1);
+        let drop_frame = drop_frame && fps % 30 == 0;
+
+        let mut n = n;
+        if drop_frame {
+            // Add back the frame numbers which were skipped to get the equivalent non drop frame count
+            let dropped = (fps / 15) as u64;
+            let per_minute = fps as u64 * 60 - dropped;
+            let per_ten_minutes = fps as u64 * 600 - dropped * 9;
+
+            let tens = n / per_ten_minutes;
+            let remainder = n % per_ten_minutes;
+            n += dropped * 9 * tens;
+            if remainder > dropped {
+                n += dropped * ((remainder - dropped) / per_minute);
+            }
+        }
+
+        let fps_64 = fps as u64;
+        Timecode {
+            hours: (n / (fps_64 * 3600) % 24) as u8,
+            minutes: (n / (fps_64 * 60) % 60) as u8,
+            seconds: (n / fps_64 % 60) as u8,
+            frames: (n % fps_64) as u8,
+            fps,
+            drop_frame,
+        }
+    }
+
+    /// The number of frames since `00:00:00:00`, the inverse of [`Timecode::from_frames`]
+    pub fn to_frames(&self) -> u64 {
+        let fps = self.fps as u64;
+        let minutes = self.hours as u64 * 60 + self.minutes as u64;
+        let n = (minutes * 60 + self.seconds as u64) * fps + self.frames as u64;
+
+        match self.drop_frame {
+            true => n - (fps / 15) * (minutes - minutes / 10),
+            false => n,
+        }
+    }
+
+    /// The timecode `n` frames after this one
+    pub fn add_frames(&self, n: u64) -> Self {
+        Timecode::from_frames(self.to_frames() + n, self.fps, self.drop_frame)
+    }
+
+    /// The hours part of the timecode
+    #[inline]
+    pub fn hours(&self) -> u8 {
+        self.hours
+    }
+
+    /// The minutes part of the timecode
+    #[inline]
+    pub fn minutes(&self) -> u8 {
+        self.minutes
+    }
+
+    /// The seconds part of the timecode
+    #[inline]
+    pub fn seconds(&self) -> u8 {
+        self.seconds
+    }
+
+    /// The frames part of the timecode
+    #[inline]
+    pub fn frames(&self) -> u8 {
+        self.frames
+    }
+
+    /// The nominal (whole number) framerate the timecode counts frames at
+    #[inline]
+    pub fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    /// Whether this is a drop frame timecode, see [`Timecode::from_frames`]
+    #[inline]
+    pub fn is_drop_frame(&self) -> bool {
+        self.drop_frame
+    }
+
+    /// Parse a timecode string such as `01:00:00:00` (or `01:00:00;00` for drop frame), as found in container metadata
+    pub(crate) fn parse(text: &str, fps: u32) -> Option<Self> {
+        let drop_frame = text.contains([';', '.']);
+        let mut parts = text
+            .split([':', ';', '.'])
+            .map(|part| part.trim().parse::<u8>().ok());
+        let timecode = Timecode {
+            hours: parts.next()??,
+            minutes: parts.next()??,
+            seconds: parts.next()??,
+            frames: parts.next()??,
+            fps: fps.max(1),
+            drop_frame: drop_frame && fps % 30 == 0,
+        };
+
+        (parts.next().is_none()
+            && timecode.minutes < 60
+            && timecode.seconds < 60
+            && (timecode.frames as u32) < timecode.fps)
+            .then_some(timecode)
+    }
+
+    /// Decode a SMPTE 12M binary timecode, such as those in `AV_FRAME_DATA_S12M_TIMECODE` side data
+    pub(crate) fn from_smpte(tc: u32, fps: u32) -> Self {
+        let bcd = |bits: u32| ((bits >> 4) * 10 + (bits & 0xf)) as u8;
+
+        let mut frames = bcd(tc >> 24 & 0x3f);
+        if fps > 30 {
+            // Only frame pairs fit in the frame field, the field bit says which frame of the pair this is
+            let field = match fps {
+                50 => tc & 1 << 7,
+                _ => tc & 1 << 23,
+            };
+            frames = frames * 2 + (field != 0) as u8;
+        }
+
+        Timecode {
+            hours: bcd(tc & 0x3f),
+            minutes: bcd(tc >> 8 & 0x7f),
+            seconds: bcd(tc >> 16 & 0x7f),
+            frames,
+            fps: fps.max(1),
+            drop_frame: tc & 1 << 30 != 0 && fps % 30 == 0,
+        }
+    }
+}
+
+impl fmt::Display for Timecode {
+    /// Format the timecode as `HH:MM:SS:FF`, drop frame timecodes use `;` before the frames (`HH:MM:SS;FF`)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.drop_frame { ';' } else { ':' };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{separator}{:02}",
+            self.hours, self.minutes, self.seconds, self.frames
+        )
+    }
+}
+
+impl Frame {
+    /// The SMPTE timecode of the frame.
+    ///
+    /// This comes from the timecode embedded in the frame if there is one (e.g. from H.264 SEI messages),
+    /// otherwise it is counted from the starting timecode of the video (see [`VideoDecoder::timecode`](crate::VideoDecoder::timecode)).
+    /// It is `None` if the source data does not contain a timecode.
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// use ffmpeg_video_decoder::VideoDecoder;
+    ///
+    /// # let file = "test.mp4";
+    /// let mut decoder = VideoDecoder::new(file, false).unwrap();
+    /// let frame = decoder.next_frame().unwrap().unwrap();
+    /// if let Some(timecode) = frame.timecode() {
+    ///     println!("{timecode}"); // e.g. 01:00:00:00
+    /// }
+    /// # }
+    /// ```
+    #[inline]
+    pub fn timecode(&self) -> Option<Timecode> {
+        self.timecode
+    }
+}
+
+/// Read the SMPTE timecode attached to a decoded frame
+pub(crate) unsafe fn read_timecode(frame: *const ffmpeg::AVFrame, fps: u32) -> Option<Timecode> {
+    let side_data = ffmpeg::av_frame_get_side_data(
+        frame,
+        ffmpeg::AVFrameSideDataType::AV_FRAME_DATA_S12M_TIMECODE,
+    );
+    if side_data.is_null() || (*side_data).size < 2 * std::mem::size_of::<u32>() {
+        return None;
+    }
+
+    // The first value is the number of timecodes, the first timecode is for this frame (the others are for repeated fields)
+    let data = (*side_data).data as *const u32;
+    match data.read_unaligned() {
+        0 => None,
+        _ => Some(Timecode::from_smpte(data.add(1).read_unaligned(), fps)),
+    }
+}
@@ -0,0 +1,114 @@
+use super::{
+    Dimensions, EncoderBuilder, Frame, TranscodeError, VideoCodec, VideoDecoder, VideoSource,
+};
+use std::path::Path;
+
+/// Options used by [`transcode`]
+#[derive(Debug, Clone, Default)]
+pub struct TranscodeOptions {
+    codec: VideoCodec,
+    format: Option<String>,
+    bit_rate: Option<u64>,
+    dimensions: Option<Dimensions>,
+    threads: Option<usize>,
+}
+
+impl TranscodeOptions {
+    /// Create the default options, this encodes to H.264 at the same size and framerate as the source
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The codec to encode the output with (defaults to [`VideoCodec::H264`])
+    pub fn codec(mut self, codec: VideoCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// The (short) name of the container format to write, see [`EncoderBuilder::format`]
+    pub fn format(mut self, format: &str) -> Self {
+        self.format = Some(format.to_string());
+        self
+    }
+
+    /// The target bit rate of the output (in bits per second), see [`EncoderBuilder::bit_rate`]
+    pub fn bit_rate(mut self, bit_rate: u64) -> Self {
+        self.bit_rate = Some(bit_rate);
+        self
+    }
+
+    /// Scale the output to the given size, if this is not set the output is the same size as the source
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.dimensions = Some(Dimensions { width, height });
+        self
+    }
+
+    /// The number of threads to decode and encode with, see [`DecoderBuilder::threads`](crate::DecoderBuilder::threads)
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+}
+
+/// Decode a video and encode it to a new file, calling `callback` on every frame in between (so frames can be edited before they are encoded).
+///
+/// Returns the number of frames written to the output.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::{transcode, TranscodeOptions, VideoCodec};
+///
+/// # let file = "test.mp4";
+/// # let dir = std::env::temp_dir().join("ffmpeg-video-decoder-transcode-doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # let output = dir.join("output.mkv");
+/// let options = TranscodeOptions::new().size(320, 180);
+/// # let options = options.codec(VideoCodec::Mpeg4);
+/// transcode(file, output, options, |frame| {
+///     // Invert the colours of every frame
+///     frame.data_mut().iter_mut().for_each(|value| *value = 255 - *value);
+/// })
+/// .unwrap();
+/// # }
+/// ```
+pub fn transcode<S, P, F>(
+    source: S,
+    output: P,
+    options: TranscodeOptions,
+    mut callback: F,
+) -> Result<usize, TranscodeError>
+where
+    S: Into<VideoSource>,
+    P: AsRef<Path>,
+    F: FnMut(&mut Frame),
+{
+    let mut builder = VideoDecoder::builder(source);
+    if let Some(threads) = options.threads {
+        builder = builder.threads(threads);
+    }
+    let mut decoder = builder.build()?;
+
+    let dimensions = options.dimensions.unwrap_or(decoder.dimensions());
+    let mut builder = EncoderBuilder::new(output, dimensions.width, dimensions.height)
+        .framerate(decoder.framerate())
+        .codec(options.codec);
+    if let Some(ref format) = options.format {
+        builder = builder.format(format);
+    }
+    if let Some(bit_rate) = options.bit_rate {
+        builder = builder.bit_rate(bit_rate);
+    }
+    if let Some(threads) = options.threads {
+        builder = builder.threads(threads);
+    }
+    let mut encoder = builder.build()?;
+
+    while let Some(mut frame) = decoder.next_frame()? {
+        callback(&mut frame);
+        encoder.encode(&frame)?;
+    }
+
+    let frames = encoder.frame_count();
+    encoder.finish()?;
+    Ok(frames)
+}
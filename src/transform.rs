@@ -0,0 +1,129 @@
+use super::{Dimensions, Frame};
+
+/// The size of the blocks rotations are done in, so the rows being read and written stay in the cache
+const TILE: usize = 32;
+
+impl Frame {
+    /// Rotate the frame 90 degrees clockwise, e.g. to correct the orientation of a portrait phone video.
+    ///
+    /// The returned frame has packed rows (see [`Frame::stride`]), and no [`Frame::motion_vectors`] (as they would no longer line up with the frame).
+    pub fn rotate90(&self) -> Frame {
+        let (width, height) = self.size();
+        // The pixel at (x, y) moves to (height - 1 - y, x)
+        self.rotate(height, width, |x, y| (height - 1 - y, x))
+    }
+
+    /// Rotate the frame 180 degrees, see [`Frame::rotate90`]
+    pub fn rotate180(&self) -> Frame {
+        let (width, height) = self.size();
+        let bytes = self.format.bytes_per_pixel();
+        let row = width * bytes;
+
+        let mut data = vec![0; row * height];
+        for (y, target) in data.chunks_exact_mut(row).rev().enumerate() {
+            let source = &self.data[y * self.stride..][..row];
+            for (to, from) in target
+                .chunks_exact_mut(bytes)
+                .rev()
+                .zip(source.chunks_exact(bytes))
+            {
+                to.copy_from_slice(from);
+            }
+        }
+        self.transformed(data, width, height)
+    }
+
+    /// Rotate the frame 90 degrees counterclockwise (270 degrees clockwise), see [`Frame::rotate90`]
+    pub fn rotate270(&self) -> Frame {
+        let (width, height) = self.size();
+        // The pixel at (x, y) moves to (y, width - 1 - x)
+        self.rotate(height, width, |x, y| (y, width - 1 - x))
+    }
+
+    /// Mirror the frame from left to right, see [`Frame::rotate90`]
+    pub fn flip_horizontal(&self) -> Frame {
+        let (width, height) = self.size();
+        let bytes = self.format.bytes_per_pixel();
+        let row = width * bytes;
+
+        let mut data = vec![0; row * height];
+        for (y, target) in data.chunks_exact_mut(row).enumerate() {
+            let source = &self.data[y * self.stride..][..row];
+            for (to, from) in target
+                .chunks_exact_mut(bytes)
+                .rev()
+                .zip(source.chunks_exact(bytes))
+            {
+                to.copy_from_slice(from);
+            }
+        }
+        self.transformed(data, width, height)
+    }
+
+    /// Mirror the frame from top to bottom, see [`Frame::rotate90`]
+    pub fn flip_vertical(&self) -> Frame {
+        let (width, height) = self.size();
+        let row = width * self.format.bytes_per_pixel();
+
+        let mut data = vec![0; row * height];
+        for (y, target) in data.chunks_exact_mut(row).rev().enumerate() {
+            target.copy_from_slice(&self.data[y * self.stride..][..row]);
+        }
+        self.transformed(data, width, height)
+    }
+
+    /// The width and height of the frame
+    fn size(&self) -> (usize, usize) {
+        (
+            self.dimensions.width as usize,
+            self.dimensions.height as usize,
+        )
+    }
+
+    /// Move each pixel to the position given by `position`, into a frame which is `width` by `height`
+    fn rotate(
+        &self,
+        width: usize,
+        height: usize,
+        position: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Frame {
+        let (source_width, source_height) = self.size();
+        let bytes = self.format.bytes_per_pixel();
+        let row = width * bytes;
+
+        let mut data = vec![0; row * height];
+        for top in (0..source_height).step_by(TILE) {
+            for left in (0..source_width).step_by(TILE) {
+                for y in top..(top + TILE).min(source_height) {
+                    let source = &self.data[y * self.stride..];
+                    for x in left..(left + TILE).min(source_width) {
+                        let (to_x, to_y) = position(x, y);
+                        let to = to_y * row + to_x * bytes;
+                        data[to..to + bytes].copy_from_slice(&source[x * bytes..][..bytes]);
+                    }
+                }
+            }
+        }
+        self.transformed(data, width, height)
+    }
+
+    /// A copy of this frame with different (packed) data
+    fn transformed(&self, data: Vec<u8>, width: usize, height: usize) -> Frame {
+        Frame {
+            index: self.index,
+            timestamp: self.timestamp,
+            duration: self.duration,
+            data: data.into(),
+            dimensions: Dimensions {
+                width: width as u32,
+                height: height as u32,
+            },
+            format: self.format,
+            stride: width * self.format.bytes_per_pixel(),
+            motion_vectors: Vec::new(),
+            captions: self.captions.clone(),
+            timecode: self.timecode,
+            keyframe: self.keyframe,
+        }
+    }
+}
@@ -0,0 +1,421 @@
+use super::{DecodeError, StreamInfo, VideoSource};
+use crate::c::ts_to_duration;
+use crate::input::{Input, InputOptions};
+use crate::stream::read_streams;
+use std::time::Duration;
+use std::{mem, ptr};
+
+/// The most issues listed in a report, the counts in the report include every issue
+const MAX_ISSUES: usize = 1000;
+/// How far the duration in the header may be from the duration of the packets before it counts as a mismatch
+const DURATION_TOLERANCE: Duration = Duration::from_secs(1);
+
+/// How thoroughly [`validate_with`] checks the source data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValidationMode {
+    /// Read every packet, and decode every packet of the audio and video streams
+    #[default]
+    Full,
+    /// Read every packet, but only decode the keyframes of the video streams (and none of the audio), which is much faster
+    KeyframesOnly,
+    /// Only read the header, which checks that the source data can be opened and its streams can be decoded
+    HeadersOnly,
+}
+
+/// A problem found by [`validate`]
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ValidationIssue {
+    /// The source data does not have a video stream
+    MissingVideoStream,
+    /// The codec of an audio or video stream is not supported by ffmpeg
+    UnsupportedCodec { stream: usize },
+    /// The header lists an audio or video stream which does not have any packets
+    EmptyStream { stream: usize },
+    /// The demuxer found a packet which is damaged
+    CorruptPacket {
+        stream: usize,
+        timestamp: Option<Duration>,
+    },
+    /// A packet could not be decoded, or was decoded to a damaged frame
+    DecodeFailed {
+        stream: usize,
+        timestamp: Option<Duration>,
+    },
+    /// The source data could not be read past a point (the timestamp of the last packet read), e.g. because it is truncated
+    ReadFailed { after: Option<Duration> },
+    /// The duration in the header does not match the duration of the packets
+    DurationMismatch {
+        header: Duration,
+        measured: Duration,
+    },
+}
+
+/// The result of checking source data with [`validate`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationReport {
+    mode: ValidationMode,
+    streams: Vec<StreamInfo>,
+    issues: Vec<ValidationIssue>,
+    packets: usize,
+    frames: usize,
+    corrupt_packets: usize,
+    decode_errors: usize,
+    duration: Option<Duration>,
+    measured_duration: Option<Duration>,
+}
+
+impl ValidationReport {
+    /// Whether no issues were found
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// How thoroughly the source data was checked
+    #[inline]
+    pub fn mode(&self) -> ValidationMode {
+        self.mode
+    }
+
+    /// Get a reference to the streams listed in the header
+    #[inline]
+    pub fn streams(&self) -> &[StreamInfo] {
+        &self.streams
+    }
+
+    /// Get a reference to the issues found, in the order they were found (at most the first 1000)
+    #[inline]
+    pub fn issues(&self) -> &[ValidationIssue] {
+        &self.issues
+    }
+
+    /// The number of packets read from every stream
+    #[inline]
+    pub fn packets(&self) -> usize {
+        self.packets
+    }
+
+    /// The number of audio and video frames decoded
+    #[inline]
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// The number of packets which were damaged
+    #[inline]
+    pub fn corrupt_packets(&self) -> usize {
+        self.corrupt_packets
+    }
+
+    /// The number of packets which could not be decoded, or were decoded to damaged frames
+    #[inline]
+    pub fn decode_errors(&self) -> usize {
+        self.decode_errors
+    }
+
+    /// The duration given by the header, if known
+    #[inline]
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// The duration of the longest stream, worked out from the timestamps of its packets. This is `None` if only the header was read.
+    #[inline]
+    pub fn measured_duration(&self) -> Option<Duration> {
+        self.measured_duration
+    }
+
+    fn push(&mut self, issue: ValidationIssue) {
+        match issue {
+            ValidationIssue::CorruptPacket { .. } => self.corrupt_packets += 1,
+            ValidationIssue::DecodeFailed { .. } => self.decode_errors += 1,
+            _ => {}
+        }
+        if self.issues.len() < MAX_ISSUES {
+            self.issues.push(issue);
+        }
+    }
+}
+
+/// The state of a single stream while it is checked
+struct StreamCheck {
+    index: usize,
+    /// Null if the stream is not decoded
+    codec_ctx: *mut ffmpeg::AVCodecContext,
+    frame: *mut ffmpeg::AVFrame,
+    time_base: ffmpeg::AVRational,
+    start_time: i64,
+    /// Whether the stream is audio or video, so it should have packets
+    media: bool,
+    keyframes_only: bool,
+    packets: usize,
+    /// When the latest packet ends
+    end: Option<i64>,
+}
+
+impl StreamCheck {
+    /// Open a decoder for a stream of the input if it is audio or video (and should be decoded in this mode)
+    unsafe fn open(
+        input: &Input,
+        index: usize,
+        mode: ValidationMode,
+        report: &mut ValidationReport,
+    ) -> Result<Self, DecodeError> {
+        let stream = input.stream(index);
+        let codecpar = (*stream).codecpar;
+        let kind = (*codecpar).codec_type;
+        let media = matches!(
+            kind,
+            ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO | ffmpeg::AVMediaType::AVMEDIA_TYPE_AUDIO
+        );
+
+        let mut check = StreamCheck {
+            index,
+            codec_ctx: ptr::null_mut(),
+            frame: ptr::null_mut(),
+            time_base: (*stream).time_base,
+            start_time: match (*stream).start_time {
+                ffmpeg::AV_NOPTS_VALUE => 0,
+                start_time => start_time,
+            },
+            media,
+            keyframes_only: mode == ValidationMode::KeyframesOnly,
+            packets: 0,
+            end: None,
+        };
+        if !media {
+            return Ok(check);
+        }
+
+        let codec = ffmpeg::avcodec_find_decoder((*codecpar).codec_id);
+        if codec.is_null() {
+            report.push(ValidationIssue::UnsupportedCodec { stream: index });
+            return Ok(check);
+        }
+        let decode = match mode {
+            ValidationMode::Full => true,
+            ValidationMode::KeyframesOnly => kind == ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO,
+            ValidationMode::HeadersOnly => false,
+        };
+        if !decode {
+            return Ok(check);
+        }
+
+        check.codec_ctx = ffmpeg::avcodec_alloc_context3(codec);
+        check.frame = ffmpeg::av_frame_alloc();
+        if check.codec_ctx.is_null() || check.frame.is_null() {
+            return Err(DecodeError::AllocationFailed);
+        }
+        let codec_ctx = check.codec_ctx;
+        if ffmpeg::avcodec_parameters_to_context(codec_ctx, codecpar) < 0 {
+            report.push(ValidationIssue::UnsupportedCodec { stream: index });
+            return Ok(check.without_decoder());
+        }
+        (*codec_ctx).pkt_timebase = (*stream).time_base;
+        // Report damaged data rather than hiding it
+        (*codec_ctx).err_recognition |=
+            (ffmpeg::AV_EF_CRCCHECK | ffmpeg::AV_EF_BITSTREAM | ffmpeg::AV_EF_EXPLODE) as i32;
+        // Use as many threads as there are cores
+        (*codec_ctx).thread_count = 0;
+        if check.keyframes_only {
+            (*codec_ctx).skip_frame = ffmpeg::AVDiscard::AVDISCARD_NONKEY;
+        }
+
+        if ffmpeg::avcodec_open2(codec_ctx, codec, ptr::null_mut()) < 0 {
+            report.push(ValidationIssue::UnsupportedCodec { stream: index });
+            return Ok(check.without_decoder());
+        }
+
+        Ok(check)
+    }
+
+    /// Free the decoder, so the stream is only read
+    fn without_decoder(mut self) -> Self {
+        unsafe {
+            ffmpeg::avcodec_free_context(&mut self.codec_ctx);
+        }
+        self
+    }
+
+    /// Convert a timestamp of the stream to the time since the start of the stream
+    fn ts_to_time(&self, ts: i64) -> Option<Duration> {
+        match ts {
+            ffmpeg::AV_NOPTS_VALUE => None,
+            ts => ts_to_duration(ts - self.start_time, self.time_base),
+        }
+    }
+
+    /// Check a packet of the stream, decoding it if the stream is decoded
+    unsafe fn read(&mut self, packet: &ffmpeg::AVPacket, report: &mut ValidationReport) {
+        self.packets += 1;
+        report.packets += 1;
+        if packet.pts != ffmpeg::AV_NOPTS_VALUE {
+            let end = packet.pts + packet.duration.max(0);
+            self.end = Some(self.end.map_or(end, |latest| latest.max(end)));
+        }
+
+        if packet.flags & ffmpeg::AV_PKT_FLAG_CORRUPT as i32 != 0 {
+            report.push(ValidationIssue::CorruptPacket {
+                stream: self.index,
+                timestamp: self.ts_to_time(packet.pts),
+            });
+        }
+
+        if self.keyframes_only && packet.flags & ffmpeg::AV_PKT_FLAG_KEY as i32 == 0 {
+            return;
+        }
+        self.decode(packet, report);
+    }
+
+    /// Send a packet to the decoder and check the frames it outputs, a null packet flushes the decoder
+    unsafe fn decode(&mut self, packet: *const ffmpeg::AVPacket, report: &mut ValidationReport) {
+        if self.codec_ctx.is_null() {
+            return;
+        }
+
+        if ffmpeg::avcodec_send_packet(self.codec_ctx, packet) < 0 && !packet.is_null() {
+            report.push(ValidationIssue::DecodeFailed {
+                stream: self.index,
+                timestamp: self.ts_to_time((*packet).pts),
+            });
+        }
+
+        loop {
+            match ffmpeg::avcodec_receive_frame(self.codec_ctx, self.frame) {
+                ffmpeg::AVERROR_EOF => break,
+                // The decoder needs more packets
+                result if result == ffmpeg::AVERROR(libc::EAGAIN) => break,
+                result if result < 0 => {
+                    report.push(ValidationIssue::DecodeFailed {
+                        stream: self.index,
+                        timestamp: None,
+                    });
+                    break;
+                }
+                _ => {}
+            }
+
+            report.frames += 1;
+            if (*self.frame).flags & ffmpeg::AV_FRAME_FLAG_CORRUPT as i32 != 0
+                || (*self.frame).decode_error_flags != 0
+            {
+                report.push(ValidationIssue::DecodeFailed {
+                    stream: self.index,
+                    timestamp: self.ts_to_time((*self.frame).best_effort_timestamp),
+                });
+            }
+            ffmpeg::av_frame_unref(self.frame);
+        }
+    }
+}
+
+impl Drop for StreamCheck {
+    fn drop(&mut self) {
+        unsafe {
+            ffmpeg::av_frame_free(&mut self.frame);
+            ffmpeg::avcodec_free_context(&mut self.codec_ctx);
+        }
+    }
+}
+
+/// Decode the whole of the source data and report any problems with it, e.g. to reject damaged uploads.
+///
+/// This finds damaged packets, packets which can not be decoded, missing or empty streams, and a duration in the header which does not match the packets,
+/// see [`validate_with`] to only check the header or the keyframes. Returns an error only if the source data can not be opened at all
+/// (e.g. [`DecodeError::NotAMediaFile`]), so that problems found later are still reported.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::validate;
+///
+/// # let file = "test.mp4";
+/// let report = validate(file).unwrap();
+/// if !report.is_valid() {
+///     println!("{} problems, starting with {:?}", report.issues().len(), report.issues()[0]);
+/// }
+/// # }
+/// ```
+pub fn validate<S>(source: S) -> Result<ValidationReport, DecodeError>
+where
+    S: Into<VideoSource>,
+{
+    validate_with(source, ValidationMode::Full)
+}
+
+/// Check the source data and report any problems with it (see [`validate`]), only decoding as much as the mode asks for
+pub fn validate_with<S>(source: S, mode: ValidationMode) -> Result<ValidationReport, DecodeError>
+where
+    S: Into<VideoSource>,
+{
+    let mut input = Input::open(source.into(), &InputOptions::default())?;
+
+    unsafe {
+        let mut report = ValidationReport {
+            mode,
+            streams: read_streams(input.ctx),
+            issues: Vec::new(),
+            packets: 0,
+            frames: 0,
+            corrupt_packets: 0,
+            decode_errors: 0,
+            duration: ts_to_duration((*input.ctx).duration, ffmpeg::AV_TIME_BASE_Q),
+            measured_duration: None,
+        };
+
+        if input.find_video_stream().is_none() {
+            report.push(ValidationIssue::MissingVideoStream);
+        }
+        let mut streams = (0..input.stream_count())
+            .map(|index| StreamCheck::open(&input, index, mode, &mut report))
+            .collect::<Result<Vec<_>, _>>()?;
+        if mode == ValidationMode::HeadersOnly {
+            return Ok(report);
+        }
+
+        let mut packet: ffmpeg::AVPacket = mem::zeroed();
+        let mut last = None;
+        loop {
+            match input.read_frame(&mut packet)? {
+                ffmpeg::AVERROR_EOF => break,
+                result if result < 0 => {
+                    report.push(ValidationIssue::ReadFailed { after: last });
+                    break;
+                }
+                _ => {}
+            }
+
+            // Streams which only appear part way through the data (which some formats allow) are not checked
+            if let Some(check) = streams.get_mut(packet.stream_index as usize) {
+                check.read(&packet, &mut report);
+                last = check.ts_to_time(packet.pts).or(last);
+            }
+            ffmpeg::av_packet_unref(&mut packet);
+        }
+
+        for check in &mut streams {
+            check.decode(ptr::null(), &mut report);
+            if check.media && check.packets == 0 {
+                report.push(ValidationIssue::EmptyStream {
+                    stream: check.index,
+                });
+            }
+        }
+
+        report.measured_duration = streams
+            .iter()
+            .filter(|check| check.media)
+            .filter_map(|check| check.ts_to_time(check.end?))
+            .max();
+        if let (Some(header), Some(measured)) = (report.duration, report.measured_duration) {
+            if header.abs_diff(measured) > DURATION_TOLERANCE {
+                report.push(ValidationIssue::DurationMismatch { header, measured });
+            }
+        }
+
+        Ok(report)
+    }
+}
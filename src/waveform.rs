@@ -0,0 +1,92 @@
+use super::{DecodeError, VideoSource};
+use crate::audio::decode_audio;
+
+/// The range and loudness of a group of audio samples, see [`audio_peaks`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Peak {
+    min: f32,
+    max: f32,
+    rms: f32,
+}
+
+impl Peak {
+    /// The lowest sample (from `-1.0` to `1.0`)
+    #[inline]
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    /// The highest sample (from `-1.0` to `1.0`)
+    #[inline]
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    /// The root mean square of the samples (from `0.0` to `1.0`), this follows perceived loudness more closely than the peaks
+    #[inline]
+    pub fn rms(&self) -> f32 {
+        self.rms
+    }
+
+    fn from_samples(samples: &[f32]) -> Self {
+        let (min, max, sum) =
+            samples
+                .iter()
+                .fold((f32::MAX, f32::MIN, 0.0f64), |(min, max, sum), &sample| {
+                    (
+                        min.min(sample),
+                        max.max(sample),
+                        sum + (sample as f64).powi(2),
+                    )
+                });
+
+        Peak {
+            min,
+            max,
+            rms: (sum / samples.len() as f64).sqrt() as f32,
+        }
+    }
+}
+
+/// Decode the audio of a video and find the peaks of every `samples_per_pixel` samples, for drawing a waveform.
+///
+/// The channels are mixed down to mono, and each [`Peak`] covers `samples_per_pixel` samples at the sample rate of the audio
+/// (the last peak may cover fewer), so there is one peak for each pixel of a waveform which is `samples / samples_per_pixel` pixels wide.
+///
+/// Returns [`DecodeError::UnableToFindAudioStream`] if the source data does not contain audio.
+///
+/// ```rust
+/// # fn main() {
+/// use ffmpeg_video_decoder::audio_peaks;
+///
+/// # let file = "test.mp4";
+/// // Roughly 100 peaks per second for 44.1kHz audio
+/// let peaks = audio_peaks(file, 441).unwrap();
+/// for peak in peaks {
+///     println!("{} to {}", peak.min(), peak.max());
+/// }
+/// # }
+/// ```
+pub fn audio_peaks<S>(source: S, samples_per_pixel: usize) -> Result<Vec<Peak>, DecodeError>
+where
+    S: Into<VideoSource>,
+{
+    let samples_per_pixel = samples_per_pixel.max(1);
+    let mut peaks = Vec::new();
+    let mut pending = Vec::with_capacity(samples_per_pixel);
+    decode_audio(source.into(), None, Some(1), |frame| {
+        for sample in frame.into_samples() {
+            pending.push(sample);
+            if pending.len() == samples_per_pixel {
+                peaks.push(Peak::from_samples(&pending));
+                pending.clear();
+            }
+        }
+    })?;
+
+    if !pending.is_empty() {
+        peaks.push(Peak::from_samples(&pending));
+    }
+    Ok(peaks)
+}
@@ -0,0 +1,215 @@
+use common::output;
+use ffmpeg_video_decoder::{
+    analyze_loudness, audio_peaks, extract_audio, DecodeError, DecoderBuilder, MediaDecoder,
+    MediaFrame, PixelFormat, Sampling, TranscodeError, VideoCodec, VideoDecoder, VideoEncoder,
+};
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn peaks() {
+    let peaks = audio_peaks("test.mp4", 1000).unwrap();
+
+    // 30 seconds of 44.1kHz or 48kHz audio
+    assert!((1300..=1450).contains(&peaks.len()), "{}", peaks.len());
+    for peak in &peaks {
+        assert!(-1.0 <= peak.min() && peak.min() <= peak.max() && peak.max() <= 1.0);
+        assert!(peak.rms() <= peak.min().abs().max(peak.max().abs()));
+    }
+    assert!(peaks.iter().any(|peak| peak.rms() > 0.0));
+
+    // Larger groups of samples give fewer peaks covering the same range
+    let coarse = audio_peaks("test.mp4", 10_000).unwrap();
+    assert_eq!(coarse.len(), peaks.len().div_ceil(10));
+    assert_eq!(
+        coarse[0].max(),
+        peaks[..10]
+            .iter()
+            .map(|peak| peak.max())
+            .fold(f32::MIN, f32::max)
+    );
+}
+
+#[test]
+fn media_decoder() {
+    let mut video = 0;
+    let mut audio = Duration::ZERO;
+    let mut previous = Duration::ZERO;
+    for frame in MediaDecoder::new("test.mp4").unwrap() {
+        let frame = frame.unwrap();
+        let timestamp = frame.timestamp().unwrap();
+        assert!(timestamp >= previous, "{timestamp:?} < {previous:?}");
+        previous = timestamp;
+
+        match frame {
+            MediaFrame::Video(frame) => {
+                video += 1;
+                assert_eq!(frame.index(), video);
+            }
+            MediaFrame::Audio(frame) => {
+                assert!(frame.channels() > 0);
+                assert_eq!(frame.samples().len() % frame.channels() as usize, 0);
+                audio += frame.duration();
+            }
+        }
+    }
+
+    assert_eq!(video, 901);
+    assert!(audio > Duration::from_secs(29), "{audio:?}");
+}
+
+#[test]
+fn media_decoder_range() {
+    let (start, end) = (Duration::from_secs(10), Duration::from_secs(12));
+    let builder = DecoderBuilder::new("test.mp4")
+        .filter("scale=64:36")
+        .range(start, end);
+    let mut audio = Duration::ZERO;
+    for frame in MediaDecoder::with_builder(builder).unwrap() {
+        if let MediaFrame::Audio(frame) = frame.unwrap() {
+            assert!(frame.timestamp() >= start, "{:?}", frame.timestamp());
+            assert!(frame.timestamp() + frame.duration() <= end + Duration::from_millis(1));
+            audio += frame.duration();
+        }
+    }
+    assert!(
+        audio.abs_diff(end - start) < Duration::from_millis(50),
+        "{audio:?}"
+    );
+}
+
+#[test]
+fn media_decoder_sampling() {
+    // One frame per second of a 30 fps video, so there is roughly a thirtieth of the audio
+    let builder = DecoderBuilder::new("test.mp4")
+        .filter("scale=64:36")
+        .sample(Sampling::PerSecond(1.0));
+    let mut video = 0;
+    let mut audio = Duration::ZERO;
+    for frame in MediaDecoder::with_builder(builder).unwrap() {
+        match frame.unwrap() {
+            MediaFrame::Video(_) => video += 1,
+            MediaFrame::Audio(frame) => audio += frame.duration(),
+        }
+    }
+    assert_eq!(video, 31);
+    assert!(
+        (Duration::from_millis(500)..Duration::from_millis(1500)).contains(&audio),
+        "{audio:?}"
+    );
+}
+
+#[test]
+fn take_audio_frames() {
+    let mut decoder = DecoderBuilder::new("test.mp4").audio(true).build().unwrap();
+    decoder.next_frame().unwrap().unwrap();
+    let frames = decoder.take_audio_frames();
+    assert!(!frames.is_empty());
+    assert!(decoder.take_audio_frames().is_empty());
+
+    // Audio is not decoded unless asked for
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    decoder.next_frame().unwrap().unwrap();
+    assert!(decoder.take_audio_frames().is_empty());
+}
+
+#[test]
+fn extract_wav() {
+    let path = output("extract_audio.wav");
+    let spec = extract_audio("test.mp4", &path).unwrap();
+    assert_eq!(spec.bits_per_sample(), 16);
+    assert!(
+        spec.duration() > Duration::from_secs(29),
+        "{:?}",
+        spec.duration()
+    );
+
+    let wav = std::fs::read(&path).unwrap();
+    assert_eq!(&wav[0..4], b"RIFF");
+    assert_eq!(&wav[8..12], b"WAVE");
+    assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), spec.channels());
+    assert_eq!(
+        u32::from_le_bytes([wav[24], wav[25], wav[26], wav[27]]),
+        spec.sample_rate()
+    );
+    let data_size = spec.sample_count() * spec.channels() as usize * 2;
+    assert_eq!(wav.len(), 44 + data_size);
+
+    // Anything other than a wav file is just the samples
+    let path = output("extract_audio.pcm");
+    let raw = extract_audio("test.mp4", &path).unwrap();
+    assert_eq!(raw, spec);
+    assert_eq!(std::fs::read(&path).unwrap(), wav[44..]);
+
+    // Nothing is left behind for a video without audio
+    let video = output("extract_audio_video_only.mp4");
+    let mut encoder = VideoEncoder::builder(&video, 64, 48)
+        .codec(VideoCodec::Mpeg4)
+        .build()
+        .unwrap();
+    encoder
+        .encode_data(&[0; 64 * 48 * 3], PixelFormat::Rgb24)
+        .unwrap();
+    encoder.finish().unwrap();
+    let path = output("extract_audio_video_only.wav");
+    assert!(matches!(
+        extract_audio(video, &path),
+        Err(TranscodeError::Decode(DecodeError::UnableToFindAudioStream))
+    ));
+    assert!(!path.exists());
+}
+
+#[test]
+fn audio_only_input() {
+    let path = output("audio_only.wav");
+    extract_audio("test.mp4", &path).unwrap();
+    assert!(matches!(
+        VideoDecoder::new(path, false),
+        Err(DecodeError::AudioOnlyInput)
+    ));
+}
+
+#[test]
+fn cover_art() {
+    use ffmpeg_video_decoder::ffmpeg;
+
+    let path = output("cover_art.flac");
+    common::Fixture::default()
+        .frames(0)
+        .audio(ffmpeg::AVCodecID::AV_CODEC_ID_FLAC)
+        .cover_art()
+        .write(&path);
+
+    // There is no video to decode, but the cover art can still be read
+    assert!(matches!(
+        VideoDecoder::new(path.clone(), false),
+        Err(DecodeError::AudioOnlyInput)
+    ));
+    assert_eq!(
+        ffmpeg_video_decoder::cover_art(path).unwrap(),
+        Some(common::cover_art_png())
+    );
+    assert_eq!(ffmpeg_video_decoder::cover_art("test.mp4").unwrap(), None);
+}
+
+#[test]
+fn loudness() {
+    let loudness = analyze_loudness("test.mp4", -50.0, Duration::from_secs(1)).unwrap();
+    let integrated = loudness.integrated().unwrap();
+    assert!((-70.0..0.0).contains(&integrated), "{integrated}");
+    let peak = loudness.peak().unwrap();
+    assert!(peak <= 0.0, "{peak}");
+    assert!(peak > integrated);
+    assert_eq!(loudness.gain_to(-23.0), Some(-23.0 - integrated));
+
+    // Everything is quieter than full scale, so the whole audio is one silent range
+    let loudness = analyze_loudness("test.mp4", 6.0, Duration::from_secs(1)).unwrap();
+    assert_eq!(loudness.silence().len(), 1);
+    assert_eq!(loudness.silence()[0].start(), Duration::ZERO);
+    assert!(loudness.silence()[0].duration() > Duration::from_secs(29));
+
+    // Nothing is quieter than this for a whole second
+    let loudness = analyze_loudness("test.mp4", -200.0, Duration::from_secs(1)).unwrap();
+    assert!(loudness.silence().is_empty());
+}
@@ -0,0 +1,458 @@
+//! Small videos written for the features which `test.mp4` does not cover
+
+#![allow(dead_code)]
+
+use ffmpeg_video_decoder::{ffmpeg, PixelFormat, VideoCodec, VideoEncoder};
+use std::ffi::CString;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+/// The sample rate of the audio streams
+const SAMPLE_RATE: i32 = 44100;
+/// The time base of data packets
+const MILLISECONDS: ffmpeg::AVRational = ffmpeg::AVRational { num: 1, den: 1000 };
+
+/// The path of a file in the directory used for test outputs
+pub fn output(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("ffmpeg-video-decoder-tests");
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+/// Encode a 64x48 MPEG-4 video at 30fps to `name` with [`VideoEncoder`], made of runs of frames of a solid RGBA colour
+pub fn solid_video(name: &str, runs: &[([u8; 4], usize)]) -> PathBuf {
+    let path = output(name);
+    let mut encoder = VideoEncoder::builder(&path, 64, 48)
+        .codec(VideoCodec::Mpeg4)
+        .build()
+        .unwrap();
+    for (color, frames) in runs {
+        let data: Vec<u8> = color.repeat(64 * 48);
+        for _ in 0..*frames {
+            encoder.encode_data(&data, PixelFormat::Rgba).unwrap();
+        }
+    }
+    encoder.finish().unwrap();
+    path
+}
+
+/// A video of solid frames at 30fps, the brightness of each frame increases with its index
+pub struct Fixture {
+    format: Option<&'static str>,
+    codec: ffmpeg::AVCodecID,
+    width: i32,
+    height: i32,
+    /// The width and height of each pixel, relative to each other
+    sample_aspect_ratio: Option<(i32, i32)>,
+    /// Whether the video is marked as HDR10 (BT.2020 with the PQ transfer function)
+    hdr: bool,
+    /// With no frames there is no video stream
+    frames: usize,
+    /// Side data attached to each frame before it is encoded, by frame index (starting at 0)
+    side_data: Vec<(usize, ffmpeg::AVFrameSideDataType, Vec<u8>)>,
+    /// The codec of a silent audio stream, as long as the video (or one second if there is no video)
+    audio: Option<ffmpeg::AVCodecID>,
+    channels: i32,
+    /// Whether to embed a PNG as cover art
+    cover_art: bool,
+    /// The codec tag and packets of a data stream, the packets are spread evenly across the video
+    data: Option<([u8; 4], Vec<Vec<u8>>)>,
+    /// The start, end (in milliseconds) and title of each chapter
+    chapters: Vec<(i64, i64, &'static str)>,
+}
+
+impl Default for Fixture {
+    /// A 64x48 MPEG-4 video with 30 frames
+    fn default() -> Self {
+        Fixture {
+            format: None,
+            codec: ffmpeg::AVCodecID::AV_CODEC_ID_MPEG4,
+            width: 64,
+            height: 48,
+            sample_aspect_ratio: None,
+            hdr: false,
+            frames: 30,
+            side_data: Vec::new(),
+            audio: None,
+            channels: 1,
+            cover_art: false,
+            data: None,
+            chapters: Vec::new(),
+        }
+    }
+}
+
+impl Fixture {
+    /// The container format, if this is not set it is worked out from the extension of the path
+    pub fn format(mut self, format: &'static str) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    pub fn codec(mut self, codec: ffmpeg::AVCodecID) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    pub fn size(mut self, width: i32, height: i32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Non-square pixels, `width` by `height`
+    pub fn sample_aspect_ratio(mut self, width: i32, height: i32) -> Self {
+        self.sample_aspect_ratio = Some((width, height));
+        self
+    }
+
+    /// Mark the video as HDR10, the frames themselves are the same
+    pub fn hdr(mut self) -> Self {
+        self.hdr = true;
+        self
+    }
+
+    pub fn frames(mut self, frames: usize) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    /// Attach side data to the frame with the given index (starting at 0)
+    pub fn side_data(
+        mut self,
+        index: usize,
+        kind: ffmpeg::AVFrameSideDataType,
+        data: Vec<u8>,
+    ) -> Self {
+        self.side_data.push((index, kind, data));
+        self
+    }
+
+    /// Add a silent audio stream encoded with `codec`
+    pub fn audio(mut self, codec: ffmpeg::AVCodecID) -> Self {
+        self.audio = Some(codec);
+        self
+    }
+
+    /// The number of channels of the audio (defaults to 1)
+    pub fn channels(mut self, channels: i32) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Embed [`cover_art_png`] as cover art
+    pub fn cover_art(mut self) -> Self {
+        self.cover_art = true;
+        self
+    }
+
+    /// Add a data stream with the given codec tag (e.g. `gpmd` for GoPro telemetry), holding `packets`
+    pub fn data(mut self, tag: &[u8; 4], packets: Vec<Vec<u8>>) -> Self {
+        self.data = Some((*tag, packets));
+        self
+    }
+
+    /// Add a chapter from `start` to `end` milliseconds
+    pub fn chapter(mut self, start: i64, end: i64, title: &'static str) -> Self {
+        self.chapters.push((start, end, title));
+        self
+    }
+
+    /// Write the video to `path`, panicking if anything fails
+    pub fn write(&self, path: &Path) {
+        let path = CString::new(path.to_str().unwrap()).unwrap();
+        let format = self.format.map(|format| CString::new(format).unwrap());
+
+        unsafe {
+            let mut output = ptr::null_mut();
+            assert!(
+                ffmpeg::avformat_alloc_output_context2(
+                    &mut output,
+                    ptr::null(),
+                    format
+                        .as_ref()
+                        .map_or(ptr::null(), |format| format.as_ptr()),
+                    path.as_ptr(),
+                ) >= 0
+            );
+
+            let video = (self.frames > 0).then(|| {
+                Encoder::open(output, self.codec, |codec_ctx, _| {
+                    (*codec_ctx).width = self.width;
+                    (*codec_ctx).height = self.height;
+                    (*codec_ctx).pix_fmt = ffmpeg::AVPixelFormat::AV_PIX_FMT_YUV420P;
+                    (*codec_ctx).framerate = ffmpeg::AVRational { num: 30, den: 1 };
+                    (*codec_ctx).time_base = ffmpeg::AVRational { num: 1, den: 30 };
+                    if let Some((num, den)) = self.sample_aspect_ratio {
+                        (*codec_ctx).sample_aspect_ratio = ffmpeg::AVRational { num, den };
+                    }
+                    if self.hdr {
+                        (*codec_ctx).color_primaries = ffmpeg::AVColorPrimaries::AVCOL_PRI_BT2020;
+                        (*codec_ctx).color_trc =
+                            ffmpeg::AVColorTransferCharacteristic::AVCOL_TRC_SMPTE2084;
+                        (*codec_ctx).colorspace = ffmpeg::AVColorSpace::AVCOL_SPC_BT2020_NCL;
+                    }
+                })
+            });
+            if let Some(video) = &video {
+                (*video.stream).sample_aspect_ratio = (*video.codec_ctx).sample_aspect_ratio;
+            }
+            let audio = self.audio.map(|codec| {
+                Encoder::open(output, codec, |codec_ctx, codec| {
+                    (*codec_ctx).sample_fmt = *(*codec).sample_fmts;
+                    (*codec_ctx).sample_rate = SAMPLE_RATE;
+                    (*codec_ctx).time_base = ffmpeg::AVRational {
+                        num: 1,
+                        den: SAMPLE_RATE,
+                    };
+                    ffmpeg::av_channel_layout_default(&mut (*codec_ctx).ch_layout, self.channels);
+                })
+            });
+            let cover_art = self.cover_art.then(|| {
+                let stream = ffmpeg::avformat_new_stream(output, ptr::null());
+                (*(*stream).codecpar).codec_type = ffmpeg::AVMediaType::AVMEDIA_TYPE_VIDEO;
+                (*(*stream).codecpar).codec_id = ffmpeg::AVCodecID::AV_CODEC_ID_PNG;
+                (*(*stream).codecpar).width = 16;
+                (*(*stream).codecpar).height = 16;
+                (*stream).disposition = ffmpeg::AV_DISPOSITION_ATTACHED_PIC as i32;
+                stream
+            });
+            let data = self.data.as_ref().map(|(tag, packets)| {
+                let stream = ffmpeg::avformat_new_stream(output, ptr::null());
+                (*(*stream).codecpar).codec_type = ffmpeg::AVMediaType::AVMEDIA_TYPE_DATA;
+                (*(*stream).codecpar).codec_id = ffmpeg::AVCodecID::AV_CODEC_ID_BIN_DATA;
+                (*(*stream).codecpar).codec_tag = u32::from_le_bytes(*tag);
+                (*stream).time_base = MILLISECONDS;
+                (stream, packets)
+            });
+
+            // Freed along with the output context
+            if !self.chapters.is_empty() {
+                let chapters = ffmpeg::av_malloc_array(
+                    self.chapters.len(),
+                    std::mem::size_of::<*mut ffmpeg::AVChapter>(),
+                ) as *mut *mut ffmpeg::AVChapter;
+                assert!(!chapters.is_null());
+                for (i, (start, end, title)) in self.chapters.iter().enumerate() {
+                    let chapter = ffmpeg::av_mallocz(std::mem::size_of::<ffmpeg::AVChapter>())
+                        as *mut ffmpeg::AVChapter;
+                    assert!(!chapter.is_null());
+                    (*chapter).id = i as i64 + 1;
+                    (*chapter).time_base = MILLISECONDS;
+                    (*chapter).start = *start;
+                    (*chapter).end = *end;
+                    let title = CString::new(*title).unwrap();
+                    assert!(
+                        ffmpeg::av_dict_set(
+                            &mut (*chapter).metadata,
+                            c"title".as_ptr(),
+                            title.as_ptr(),
+                            0
+                        ) >= 0
+                    );
+                    *chapters.add(i) = chapter;
+                }
+                (*output).chapters = chapters;
+                (*output).nb_chapters = self.chapters.len() as u32;
+            }
+
+            if (*(*output).oformat).flags & ffmpeg::AVFMT_NOFILE == 0 {
+                assert!(
+                    ffmpeg::avio_open(&mut (*output).pb, path.as_ptr(), ffmpeg::AVIO_FLAG_WRITE)
+                        >= 0
+                );
+            }
+            assert!(ffmpeg::avformat_write_header(output, ptr::null_mut()) >= 0);
+
+            let mut frame = ffmpeg::av_frame_alloc();
+            let mut packet = ffmpeg::av_packet_alloc();
+
+            // The picture is written on its own, before any of the other packets
+            if let Some(stream) = cover_art {
+                let data = cover_art_png();
+                assert!(ffmpeg::av_new_packet(packet, data.len() as i32) >= 0);
+                ptr::copy_nonoverlapping(data.as_ptr(), (*packet).data, data.len());
+                (*packet).stream_index = (*stream).index;
+                (*packet).flags |= ffmpeg::AV_PKT_FLAG_KEY as i32;
+                assert!(ffmpeg::av_write_frame(output, packet) >= 0);
+                ffmpeg::av_packet_unref(packet);
+            }
+
+            if let Some(video) = &video {
+                for index in 0..self.frames {
+                    (*frame).format = ffmpeg::AVPixelFormat::AV_PIX_FMT_YUV420P as i32;
+                    (*frame).width = self.width;
+                    (*frame).height = self.height;
+                    assert!(ffmpeg::av_frame_get_buffer(frame, 0) >= 0);
+                    for (plane, value) in [(0, (index * 8 % 220 + 16) as u8), (1, 128), (2, 128)] {
+                        let rows = match plane {
+                            0 => self.height,
+                            _ => self.height / 2,
+                        };
+                        let size = (*frame).linesize[plane] as usize * rows as usize;
+                        ptr::write_bytes((*frame).data[plane], value, size);
+                    }
+                    for (_, kind, data) in self.side_data.iter().filter(|(i, ..)| *i == index) {
+                        let side_data = ffmpeg::av_frame_new_side_data(frame, *kind, data.len());
+                        assert!(!side_data.is_null());
+                        ptr::copy_nonoverlapping(data.as_ptr(), (*side_data).data, data.len());
+                    }
+                    (*frame).pts = index as i64;
+
+                    video.send(output, frame, packet);
+                    ffmpeg::av_frame_unref(frame);
+                }
+                video.send(output, ptr::null(), packet);
+            }
+
+            if let Some((stream, packets)) = data {
+                let span = self.frames as i64 * 1000 / 30 / packets.len().max(1) as i64;
+                for (i, data) in packets.iter().enumerate() {
+                    assert!(ffmpeg::av_new_packet(packet, data.len() as i32) >= 0);
+                    ptr::copy_nonoverlapping(data.as_ptr(), (*packet).data, data.len());
+                    (*packet).pts = i as i64 * span;
+                    (*packet).dts = (*packet).pts;
+                    (*packet).duration = span;
+                    (*packet).stream_index = (*stream).index;
+                    (*packet).flags |= ffmpeg::AV_PKT_FLAG_KEY as i32;
+                    ffmpeg::av_packet_rescale_ts(packet, MILLISECONDS, (*stream).time_base);
+                    assert!(ffmpeg::av_interleaved_write_frame(output, packet) >= 0);
+                }
+            }
+
+            if let Some(audio) = &audio {
+                let codec_ctx = audio.codec_ctx;
+                let total = SAMPLE_RATE as i64 * self.frames.max(30) as i64 / 30;
+                let frame_size = match (*codec_ctx).frame_size {
+                    0 => 1024,
+                    size => size as i64,
+                };
+                let mut pts = 0;
+                while pts < total {
+                    (*frame).format = (*codec_ctx).sample_fmt as i32;
+                    (*frame).sample_rate = SAMPLE_RATE;
+                    (*frame).nb_samples = frame_size.min(total - pts) as i32;
+                    assert!(
+                        ffmpeg::av_channel_layout_copy(
+                            &mut (*frame).ch_layout,
+                            &(*codec_ctx).ch_layout
+                        ) >= 0
+                    );
+                    assert!(ffmpeg::av_frame_get_buffer(frame, 0) >= 0);
+                    ffmpeg::av_samples_set_silence(
+                        (*frame).extended_data,
+                        0,
+                        (*frame).nb_samples,
+                        (*codec_ctx).ch_layout.nb_channels,
+                        (*codec_ctx).sample_fmt,
+                    );
+                    (*frame).pts = pts;
+                    pts += (*frame).nb_samples as i64;
+
+                    audio.send(output, frame, packet);
+                    ffmpeg::av_frame_unref(frame);
+                }
+                audio.send(output, ptr::null(), packet);
+            }
+
+            assert!(ffmpeg::av_write_trailer(output) >= 0);
+
+            ffmpeg::av_packet_free(&mut packet);
+            ffmpeg::av_frame_free(&mut frame);
+            for mut encoder in video.into_iter().chain(audio) {
+                ffmpeg::avcodec_free_context(&mut encoder.codec_ctx);
+            }
+            if (*(*output).oformat).flags & ffmpeg::AVFMT_NOFILE == 0 {
+                ffmpeg::avio_closep(&mut (*output).pb);
+            }
+            ffmpeg::avformat_free_context(output);
+        }
+    }
+}
+
+/// The 16x16 red PNG embedded by [`Fixture::cover_art`]
+pub fn cover_art_png() -> Vec<u8> {
+    unsafe {
+        let codec = ffmpeg::avcodec_find_encoder(ffmpeg::AVCodecID::AV_CODEC_ID_PNG);
+        assert!(!codec.is_null());
+        let mut codec_ctx = ffmpeg::avcodec_alloc_context3(codec);
+        (*codec_ctx).width = 16;
+        (*codec_ctx).height = 16;
+        (*codec_ctx).pix_fmt = ffmpeg::AVPixelFormat::AV_PIX_FMT_RGB24;
+        (*codec_ctx).time_base = ffmpeg::AVRational { num: 1, den: 1 };
+        assert!(ffmpeg::avcodec_open2(codec_ctx, codec, ptr::null_mut()) >= 0);
+
+        let mut frame = ffmpeg::av_frame_alloc();
+        (*frame).format = ffmpeg::AVPixelFormat::AV_PIX_FMT_RGB24 as i32;
+        (*frame).width = 16;
+        (*frame).height = 16;
+        assert!(ffmpeg::av_frame_get_buffer(frame, 0) >= 0);
+        for row in 0..16 {
+            let data = (*frame).data[0].add(row * (*frame).linesize[0] as usize);
+            for pixel in 0..16 {
+                ptr::copy_nonoverlapping([255, 0, 0].as_ptr(), data.add(pixel * 3), 3);
+            }
+        }
+        (*frame).pts = 0;
+
+        let mut packet = ffmpeg::av_packet_alloc();
+        assert!(ffmpeg::avcodec_send_frame(codec_ctx, frame) >= 0);
+        assert!(ffmpeg::avcodec_send_frame(codec_ctx, ptr::null()) >= 0);
+        assert!(ffmpeg::avcodec_receive_packet(codec_ctx, packet) >= 0);
+        let data = std::slice::from_raw_parts((*packet).data, (*packet).size as usize).to_vec();
+
+        ffmpeg::av_packet_free(&mut packet);
+        ffmpeg::av_frame_free(&mut frame);
+        ffmpeg::avcodec_free_context(&mut codec_ctx);
+        data
+    }
+}
+
+/// An encoder for one of the streams of a fixture
+struct Encoder {
+    codec_ctx: *mut ffmpeg::AVCodecContext,
+    stream: *mut ffmpeg::AVStream,
+}
+
+impl Encoder {
+    /// Add a stream encoded with `codec` to the output, `setup` sets the parameters of the encoder before it is opened
+    unsafe fn open(
+        output: *mut ffmpeg::AVFormatContext,
+        codec: ffmpeg::AVCodecID,
+        setup: impl FnOnce(*mut ffmpeg::AVCodecContext, *const ffmpeg::AVCodec),
+    ) -> Self {
+        let codec = ffmpeg::avcodec_find_encoder(codec);
+        assert!(!codec.is_null());
+        let codec_ctx = ffmpeg::avcodec_alloc_context3(codec);
+        setup(codec_ctx, codec);
+        if (*(*output).oformat).flags & ffmpeg::AVFMT_GLOBALHEADER != 0 {
+            (*codec_ctx).flags |= ffmpeg::AV_CODEC_FLAG_GLOBAL_HEADER as i32;
+        }
+        assert!(ffmpeg::avcodec_open2(codec_ctx, codec, ptr::null_mut()) >= 0);
+
+        let stream = ffmpeg::avformat_new_stream(output, ptr::null());
+        assert!(ffmpeg::avcodec_parameters_from_context((*stream).codecpar, codec_ctx) >= 0);
+        (*stream).time_base = (*codec_ctx).time_base;
+
+        Encoder { codec_ctx, stream }
+    }
+
+    /// Send a frame to the encoder (or null to flush it), then write every packet it produces
+    unsafe fn send(
+        &self,
+        output: *mut ffmpeg::AVFormatContext,
+        frame: *const ffmpeg::AVFrame,
+        packet: *mut ffmpeg::AVPacket,
+    ) {
+        assert!(ffmpeg::avcodec_send_frame(self.codec_ctx, frame) >= 0);
+        while ffmpeg::avcodec_receive_packet(self.codec_ctx, packet) >= 0 {
+            ffmpeg::av_packet_rescale_ts(
+                packet,
+                (*self.codec_ctx).time_base,
+                (*self.stream).time_base,
+            );
+            (*packet).stream_index = (*self.stream).index;
+            assert!(ffmpeg::av_interleaved_write_frame(output, packet) >= 0);
+        }
+    }
+}
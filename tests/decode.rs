@@ -1,5 +1,13 @@
-use ffmpeg_video_decoder::{VideoDecoder, VideoSource};
+use ffmpeg_video_decoder::{
+    available_decoders, decode_parallel, list_devices, DecodeError, DecoderBuilder, DecoderHandle,
+    DecoderStats, Device, Dimensions, ErrorPolicy, FitMode, LoopMode, PixelFormat, PushSource,
+    Sampling, ScalingQuality, StreamKind, TensorLayout, ThreadType, Timecode, ToneMapping,
+    ValidationMode, VideoDecoder, VideoSource,
+};
 use std::path::PathBuf;
+use std::time::Duration;
+
+mod common;
 
 fn run_decode_test(source: VideoSource) {
     let mut decoder = VideoDecoder::new(source, false).unwrap();
@@ -10,14 +18,18 @@ fn run_decode_test(source: VideoSource) {
 
     // Check video framerate is correct
     assert_eq!(decoder.framerate(), 30.0);
+    assert!((decoder.avg_framerate().unwrap() - 30.0).abs() < 0.1);
+    assert!(!decoder.is_vfr());
 
     let mut max = 0;
     while let Some(frame) = decoder.next_frame().unwrap() {
         max = frame.index();
         assert_eq!(frame.dimensions(), decoder.dimensions());
+        let duration = frame.duration().unwrap();
+        assert!(duration.abs_diff(Duration::from_secs_f32(1.0 / 30.0)) < Duration::from_millis(1));
     }
 
-    assert_eq!(max, 899); // test video has 901 frames
+    assert_eq!(max, 901); // test video has 901 frames
 }
 
 #[test]
@@ -32,29 +44,2091 @@ fn unicode_file() {
     run_decode_test(source.into());
 }
 
+#[test]
+fn shared_source() {
+    let data: std::sync::Arc<[u8]> = std::fs::read("test.mp4").unwrap().into();
+    run_decode_test(data.clone().into());
+    run_decode_test(data.into());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn bytes_source() {
+    let data = ffmpeg_video_decoder::bytes::Bytes::from(std::fs::read("test.mp4").unwrap());
+    run_decode_test(data.into());
+}
+
+#[test]
+fn push_source() {
+    let source = PushSource::new();
+
+    let sender = source.clone();
+    let pusher = std::thread::spawn(move || {
+        let data = std::fs::read("test.mp4").unwrap();
+        for chunk in data.chunks(64 * 1024) {
+            sender.push(chunk);
+        }
+        sender.finish();
+    });
+
+    run_decode_test(source.into());
+    pusher.join().unwrap();
+}
+
+#[test]
+fn push_source_cancel() {
+    let source = PushSource::new();
+    source.push(std::fs::read("test.mp4").unwrap()[..4096].to_vec());
+
+    // The source never finishes, so the decoder would otherwise wait forever
+    assert!(matches!(
+        VideoDecoder::builder(source)
+            .timeout(Duration::from_millis(200))
+            .build(),
+        Err(DecodeError::Cancelled)
+    ));
+}
+
+#[test]
+fn timeout_seek() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .timeout(Duration::from_millis(100))
+        .should_loop(true)
+        .build()
+        .unwrap();
+    assert!(decoder.next_frame().unwrap().is_some());
+
+    // The timeout is for each operation, so time spent between them does not count
+    std::thread::sleep(Duration::from_millis(200));
+    decoder.seek_to_frame(450).unwrap();
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 450);
+
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(decoder.prev_frame().unwrap().is_some());
+
+    // Looping seeks back to the start
+    std::thread::sleep(Duration::from_millis(200));
+    assert_eq!(decoder.skip(1000).unwrap(), 1000);
+    assert!(decoder.next_frame().unwrap().is_some());
+}
+
+#[cfg(unix)]
+#[test]
+fn non_unicode_file() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = std::env::temp_dir().join("ffmpeg-video-decoder-tests");
+    std::fs::create_dir_all(&dir).unwrap();
+    let source = dir.join(OsStr::from_bytes(b"test \xff\xfe.mp4"));
+    std::fs::copy("test.mp4", &source).unwrap();
+
+    run_decode_test(source.clone().into());
+    std::fs::remove_file(source).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn url_like_file() {
+    // ffmpeg would read this relative path as the `copy` protocol, so it is opened through custom io instead
+    let source = PathBuf::from("copy:test.mp4");
+    // Left behind if an earlier run failed
+    let _ = std::fs::remove_file(&source);
+    std::fs::hard_link("test.mp4", &source).unwrap();
+
+    run_decode_test(source.clone().into());
+    std::fs::remove_file(source).unwrap();
+}
+
+#[test]
+fn url() {
+    // Urls which are not files are opened by ffmpeg's protocols
+    let path = std::fs::canonicalize("test.mp4").unwrap();
+    run_decode_test(format!("file:{}", path.display()).into());
+}
+
+#[test]
+fn threaded() {
+    for thread_type in [ThreadType::Frame, ThreadType::Slice, ThreadType::Any] {
+        let mut decoder = VideoDecoder::builder("test.mp4")
+            .threads(4)
+            .thread_type(thread_type)
+            .build()
+            .unwrap();
+
+        // Frames held back by the extra threads must still be returned once the input runs out
+        let mut count = 0;
+        while let Some(frame) = decoder.next_frame().unwrap() {
+            count += 1;
+            assert_eq!(frame.index(), count);
+        }
+        assert_eq!(count, 901);
+    }
+}
+
 #[test]
 fn frame_skip() {
     let source = PathBuf::from("test.mp4");
     let mut decoder = VideoDecoder::new(source, false).unwrap();
 
-    decoder.skip(100);
-    decoder.skip(-100);
+    assert_eq!(decoder.skip(100).unwrap(), 100);
+    assert_eq!(decoder.skip(-100).unwrap(), 99);
 
     let first_frame = decoder.next_frame().unwrap().unwrap();
     assert_eq!(first_frame.index(), 1);
 
-    decoder.skip(29);
+    assert_eq!(decoder.skip(29).unwrap(), 29);
     let later_frame = decoder.next_frame().unwrap().unwrap();
     assert_eq!(later_frame.index(), 31);
 
-    decoder.skip(-29);
+    assert_eq!(decoder.skip(-29).unwrap(), 29);
     let first_frame = decoder.next_frame().unwrap().unwrap();
     assert_eq!(first_frame.index(), 2);
 }
 
+#[test]
+fn batch() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=64:36")
+        .build()
+        .unwrap();
+
+    let batch = decoder.next_frames(10).unwrap();
+    let indices: Vec<usize> = batch.iter().map(|frame| frame.index()).collect();
+    assert_eq!(indices, (1..=10).collect::<Vec<_>>());
+
+    let rest = decoder.decode_all().unwrap();
+    assert_eq!(rest.len(), 891);
+    assert_eq!(rest.first().unwrap().index(), 11);
+    assert_eq!(rest.last().unwrap().index(), 901);
+
+    assert!(decoder.next_frames(10).unwrap().is_empty());
+    assert!(decoder.decode_all().unwrap().is_empty());
+}
+
+#[test]
+fn sampling() {
+    let sample = |sampling| {
+        let mut decoder = VideoDecoder::builder("test.mp4")
+            .filter("scale=64:36")
+            .sample(sampling)
+            .build()
+            .unwrap();
+        let mut indices = Vec::new();
+        while let Some(frame) = decoder.next_frame().unwrap() {
+            indices.push(frame.index());
+        }
+        indices
+    };
+
+    let every_nth = sample(Sampling::EveryNth(100));
+    assert_eq!(every_nth, (1..=901).step_by(100).collect::<Vec<_>>());
+
+    let per_second = sample(Sampling::PerSecond(1.0));
+    assert_eq!(per_second, (1..=901).step_by(30).collect::<Vec<_>>());
+
+    assert_eq!(sample(Sampling::EveryNth(0)).len(), 901);
+}
+
+#[test]
+fn parallel() {
+    let mut expected = VideoDecoder::new("test.mp4", false).unwrap();
+    let middle = expected.get_frame(451).unwrap().unwrap();
+
+    let mut count = 0;
+    decode_parallel("test.mp4", 4, |frame| {
+        count += 1;
+        assert_eq!(frame.index(), count);
+        if frame.index() == 451 {
+            assert_eq!(frame.timestamp(), middle.timestamp());
+            assert_eq!(frame.data(), middle.data());
+        }
+    })
+    .unwrap();
+    assert_eq!(count, 901);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_process() {
+    let decoder = || {
+        VideoDecoder::builder("test.mp4")
+            .filter("scale=64:36")
+            .build()
+            .unwrap()
+    };
+    let sum = |frame: &ffmpeg_video_decoder::Frame| {
+        frame.data().iter().map(|&value| value as u64).sum::<u64>()
+    };
+
+    let mut sequential = decoder();
+    let mut expected = Vec::new();
+    while let Some(frame) = sequential.next_frame().unwrap() {
+        expected.push((frame.index(), sum(&frame)));
+    }
+
+    let results = decoder()
+        .par_process(|frame| (frame.index(), sum(&frame)))
+        .unwrap();
+    assert_eq!(results.len(), 901);
+    assert_eq!(results, expected);
+}
+
+#[test]
+fn shared_data() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .shared_data(true)
+        .build()
+        .unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert!(frame.is_shared());
+
+    // Clones share the same data until one is edited
+    let mut copy = frame.clone();
+    let original = frame.into_shared_data();
+    assert!(std::ptr::eq(copy.data(), &original[..]));
+
+    copy.data_mut()[0] = copy.data()[0].wrapping_add(1);
+    assert!(!std::ptr::eq(copy.data(), &original[..]));
+    assert_ne!(copy.data()[0], original[0]);
+    assert_eq!(copy.data()[1..], original[1..]);
+
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    assert!(!decoder.next_frame().unwrap().unwrap().is_shared());
+}
+
+#[test]
+fn frame_info() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert!(frame.is_keyframe());
+
+    let info = frame.info();
+    assert_eq!(info.index(), 1);
+    assert_eq!(info.timestamp(), frame.timestamp());
+    assert_eq!(info.dimensions(), frame.dimensions());
+    assert!(info.is_keyframe());
+
+    // The data is only summarized
+    let debug = format!("{frame:?}");
+    assert!(debug.contains("index: 1"));
+    assert!(debug.len() < 1000);
+
+    let mut keyframes = 1;
+    while let Some(info) = decoder.next_frame_info().unwrap() {
+        keyframes += info.is_keyframe() as usize;
+        if info.index() == 901 {
+            assert_eq!(info.dimensions().width(), 1920);
+        }
+    }
+    assert!(keyframes > 1 && keyframes < 901);
+}
+
+#[test]
+fn raw_access() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    let codec_ctx = unsafe { decoder.as_raw_codec_ctx() };
+    assert_eq!(unsafe { (*codec_ctx).width }, 1920);
+    let format_ctx = unsafe { decoder.as_raw_format_ctx() };
+    assert!(unsafe { (*format_ctx).nb_streams } > 0);
+
+    let (frame, size) = decoder
+        .with_raw_frame(|raw| (raw.width, raw.height))
+        .unwrap()
+        .unwrap();
+    assert_eq!(frame.index(), 1);
+    assert_eq!(size, (1920, 1080));
+}
+
+#[test]
+fn fast_skip() {
+    let mut fast = VideoDecoder::new("test.mp4", false).unwrap();
+    assert_eq!(fast.skip(700).unwrap(), 700);
+    let frame = fast.next_frame().unwrap().unwrap();
+    assert_eq!(frame.index(), 701);
+
+    // Small skips decode every frame, so they give the same frame
+    let mut slow = VideoDecoder::new("test.mp4", false).unwrap();
+    for _ in 0..35 {
+        assert_eq!(slow.skip(20).unwrap(), 20);
+    }
+    let expected = slow.next_frame().unwrap().unwrap();
+    assert_eq!(expected.index(), 701);
+    assert_eq!(frame.timestamp(), expected.timestamp());
+    assert_eq!(frame.data(), expected.data());
+
+    // Past the end only counts the frames which were there
+    assert_eq!(fast.skip(1000).unwrap(), 200);
+    assert!(fast.next_frame().unwrap().is_none());
+}
+
 // fixme this test does not work
 // #[test]
 // fn memory() {
 //     let source = include_bytes!("../test.mp4").to_vec();
 //     run_decode_test(source.into());
 // }
+
+#[test]
+fn streams() {
+    let decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    let streams = decoder.streams();
+
+    let video = &streams[decoder.stream_index()];
+    assert_eq!(video.kind(), StreamKind::Video);
+    assert_eq!(video.dimensions(), Some(decoder.dimensions()));
+    assert!(!video.codec().is_empty());
+
+    // Explicitly selecting the same stream must give the same decoder
+    let decoder = VideoDecoder::builder("test.mp4")
+        .video_stream(video.index())
+        .build()
+        .unwrap();
+    assert_eq!(decoder.stream_index(), video.index());
+
+    // Out of range streams are rejected
+    let result = VideoDecoder::builder("test.mp4")
+        .video_stream(streams.len())
+        .build();
+    assert!(matches!(result, Err(DecodeError::InvalidVideoStream)));
+}
+
+#[test]
+fn packed_rows() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    let dimensions = decoder.dimensions();
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(
+        frame.data().len(),
+        dimensions.width() as usize * dimensions.height() as usize * 3
+    );
+
+    // Rows are always at least as long as the packed data
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .packed_rows(false)
+        .build()
+        .unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert!(frame.data().len() >= dimensions.width() as usize * dimensions.height() as usize * 3);
+}
+
+#[test]
+fn stride() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .packed_rows(false)
+        .build()
+        .unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+    let width = frame.dimensions().width() as usize;
+
+    assert!(frame.stride() >= width * 3);
+
+    let planes = frame.planes();
+    assert_eq!(planes.len(), 1);
+    assert_eq!(planes[0].stride(), frame.stride());
+    assert_eq!(planes[0].row(1).len(), width * 3);
+    assert_eq!(
+        planes[0].row(1),
+        &frame.data()[frame.stride()..frame.stride() + width * 3]
+    );
+}
+
+#[test]
+fn skip_buffered() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+
+    // Skipping right after a frame was returned drops any frames still buffered from the same packet
+    for expected in (1..60).step_by(3) {
+        let frame = decoder.next_frame().unwrap().unwrap();
+        assert_eq!(frame.index(), expected);
+        decoder.skip(2).unwrap();
+    }
+}
+
+#[test]
+fn invalid_input() {
+    assert!(matches!(
+        VideoDecoder::new("does-not-exist.mp4", false),
+        Err(DecodeError::UnableToOpenInput)
+    ));
+
+    let text = "this is not a video\n".repeat(256).into_bytes();
+    assert!(matches!(
+        VideoDecoder::new(text, false),
+        Err(DecodeError::NotAMediaFile)
+    ));
+
+    // Cut off part way through the sample tables of the header, which starts after the first 32 bytes
+    let mut data = std::fs::read("test.mp4").unwrap();
+    data.truncate(4_000);
+    assert!(matches!(
+        VideoDecoder::new(data, false),
+        Err(DecodeError::TruncatedInput)
+    ));
+}
+
+#[test]
+fn codec_info() {
+    let decoder = VideoDecoder::new("test.mp4", false).unwrap();
+
+    assert_eq!(decoder.codec_name(), "h264");
+    assert!(decoder.container_format().contains("mp4"));
+    assert!(decoder.pixel_format().is_some());
+    assert!(decoder.bit_rate().unwrap() > 0);
+    assert!(decoder.profile().is_some());
+}
+
+#[test]
+fn loop_count() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .loop_mode(LoopMode::Count(2))
+        .build()
+        .unwrap();
+    assert_eq!(decoder.loops_remaining(), Some(1));
+    assert!(decoder.will_loop());
+
+    let mut count = 0;
+    while let Some(frame) = decoder.next_frame().unwrap() {
+        count += 1;
+        assert_eq!(frame.index(), (count - 1) % 901 + 1);
+    }
+
+    assert_eq!(count, 901 * 2);
+    assert_eq!(decoder.loops_remaining(), Some(0));
+    assert!(!decoder.will_loop());
+}
+
+#[test]
+fn metadata() {
+    let decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    assert!(decoder.metadata().contains_key("major_brand"));
+    assert!(decoder.stream_metadata().contains_key("handler_name"));
+
+    // The test video does not have any chapters or cover art
+    assert!(decoder.chapters().is_empty());
+    assert!(decoder.cover_art().is_none());
+}
+
+#[test]
+fn ping_pong() {
+    for start in [0, 10] {
+        let mut decoder = VideoDecoder::builder("test.mp4")
+            .range(Duration::from_secs(start), Duration::from_secs(start + 1))
+            .loop_mode(LoopMode::PingPong)
+            .build()
+            .unwrap();
+        assert!(decoder.will_loop());
+
+        let mut forward = Vec::new();
+        let mut frame = decoder.next_frame().unwrap().unwrap();
+        while forward.last().map_or(true, |&last| frame.index() > last) {
+            forward.push(frame.index());
+            frame = decoder.next_frame().unwrap().unwrap();
+        }
+
+        // Backward without repeating the last frame, then forward without repeating the first frame
+        let mut backward = vec![frame.index()];
+        for _ in 0..forward.len() {
+            backward.push(decoder.next_frame().unwrap().unwrap().index());
+        }
+        let mut expected: Vec<_> = forward[..forward.len() - 1].iter().rev().copied().collect();
+        expected.extend(&forward[1..3]);
+        assert_eq!(backward, expected);
+    }
+}
+
+#[test]
+fn frames_reversed() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .range(Duration::from_secs(10), Duration::from_secs(12))
+        .build()
+        .unwrap();
+    let forward: Vec<_> = std::iter::from_fn(|| decoder.next_frame().unwrap())
+        .map(|frame| (frame.index(), frame.timestamp()))
+        .collect();
+
+    let reversed: Vec<_> = decoder
+        .frames_reversed()
+        .map(|frame| frame.map(|frame| (frame.index(), frame.timestamp())))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(reversed.len(), forward.len());
+    assert!(reversed.iter().rev().eq(forward.iter()));
+
+    // Stopping part way through continues forward from the frame after the last one returned
+    let last = decoder
+        .frames_reversed()
+        .take(5)
+        .last()
+        .unwrap()
+        .unwrap()
+        .index();
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), last + 1);
+}
+
+#[test]
+fn rewind() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    decoder.skip(500).unwrap();
+    decoder.rewind().unwrap();
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 1);
+
+    decoder.set_looping(true);
+    assert!(decoder.will_loop());
+    assert_eq!(decoder.loops_remaining(), None);
+    decoder.skip(899).unwrap();
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 901);
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 1);
+
+    decoder.set_looping(false);
+    assert!(!decoder.will_loop());
+}
+
+#[test]
+fn range() {
+    let start = Duration::from_secs(1);
+    let end = Duration::from_secs(2);
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .range(start, end)
+        .build()
+        .unwrap();
+
+    let mut frames = Vec::new();
+    while let Some(frame) = decoder.next_frame().unwrap() {
+        let timestamp = frame.timestamp().unwrap();
+        assert!(timestamp >= start && timestamp <= end);
+        frames.push(frame.index());
+    }
+
+    // Roughly one second of frames, numbered as they are in the full video
+    assert!((29..=31).contains(&frames.len()));
+    assert!(frames[0] > 1);
+    assert!(frames.windows(2).all(|w| w[1] == w[0] + 1));
+
+    // A range which ends before it starts
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4").range(end, start).build(),
+        Err(DecodeError::InvalidRange)
+    ));
+}
+
+#[test]
+fn range_loop() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .range(Duration::from_secs(10), Duration::from_secs(11))
+        .loop_mode(LoopMode::Count(2))
+        .build()
+        .unwrap();
+
+    let first = decoder.next_frame().unwrap().unwrap().index();
+    let mut count = 1;
+    let mut restarts = 0;
+    while let Some(frame) = decoder.next_frame().unwrap() {
+        count += 1;
+        if frame.index() == first {
+            restarts += 1;
+        }
+    }
+
+    assert_eq!(restarts, 1);
+    assert!((58..=62).contains(&count));
+}
+
+#[test]
+fn seek_to_frame() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    decoder.skip(149).unwrap();
+    let expected = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(expected.index(), 150);
+
+    // Seeking forwards
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    decoder.seek_to_frame(150).unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(frame.index(), 150);
+    assert_eq!(frame.data(), expected.data());
+
+    // Seeking backwards
+    decoder.seek_to_frame(10).unwrap();
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 10);
+    decoder.seek_to_frame(1).unwrap();
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 1);
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 2);
+}
+
+#[test]
+fn skip_past_end() {
+    let mut decoder = VideoDecoder::new("test.mp4", true).unwrap();
+    decoder.skip(890).unwrap();
+
+    // Skipping never loops, even for looping decoders
+    assert_eq!(decoder.skip(100).unwrap(), 11);
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 1);
+}
+
+#[test]
+fn prev_frame() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    assert!(decoder.prev_frame().unwrap().is_none());
+
+    decoder.seek_to_frame(120).unwrap();
+    let expected = decoder.next_frame().unwrap().unwrap();
+    decoder.skip(10).unwrap();
+
+    // Step back over (at least) one keyframe boundary
+    for index in (1..130).rev() {
+        let frame = decoder.prev_frame().unwrap().unwrap();
+        assert_eq!(frame.index(), index);
+        if index == expected.index() {
+            assert_eq!(frame.data(), expected.data());
+        }
+    }
+    assert!(decoder.prev_frame().unwrap().is_none());
+
+    // Continue forward from where we stepped back to
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 2);
+}
+
+#[test]
+fn random_access() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .frame_cache(4)
+        .build()
+        .unwrap();
+
+    let frame = decoder.get_frame(200).unwrap().unwrap();
+    assert_eq!(frame.index(), 200);
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 201);
+
+    // Cached frames are identical to freshly decoded ones
+    let cached = decoder.get_frame(200).unwrap().unwrap();
+    assert_eq!(cached.index(), 200);
+    assert_eq!(cached.data(), frame.data());
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 201);
+
+    let frame = decoder
+        .get_frame_at(Duration::from_secs(2))
+        .unwrap()
+        .unwrap();
+    assert_eq!(frame.index(), 61);
+
+    assert!(decoder.get_frame(5000).unwrap().is_none());
+}
+
+#[test]
+fn sample_aspect_ratio() {
+    let decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    assert!(matches!(decoder.sample_aspect_ratio(), None | Some((1, 1))));
+    assert_eq!(decoder.display_dimensions(), decoder.dimensions());
+
+    // Squash the pixels horizontally, then stretch them back
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=960:1080,setsar=2/1")
+        .build()
+        .unwrap();
+    assert_eq!(decoder.sample_aspect_ratio(), Some((2, 1)));
+    assert_eq!(decoder.dimensions().width(), 960);
+    assert_eq!(decoder.display_dimensions().width(), 1920);
+    assert_eq!(decoder.display_dimensions().height(), 1080);
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(frame.data().len(), 960 * 1080 * 3);
+
+    // Square pixels are a no-op for video which already has them
+    let decoder = VideoDecoder::builder("test.mp4")
+        .square_pixels(true)
+        .build()
+        .unwrap();
+    assert_eq!(decoder.dimensions().width(), 1920);
+
+    // Pixels twice as wide as they are tall are stretched to twice the width
+    let path = common::output("anamorphic.mkv");
+    common::Fixture::default()
+        .sample_aspect_ratio(2, 1)
+        .write(&path);
+    let decoder = VideoDecoder::new(path.clone(), false).unwrap();
+    assert_eq!(decoder.sample_aspect_ratio(), Some((2, 1)));
+    assert_eq!(decoder.dimensions(), Dimensions::new(64, 48));
+    assert_eq!(decoder.display_dimensions(), Dimensions::new(128, 48));
+
+    let mut decoder = VideoDecoder::builder(path)
+        .square_pixels(true)
+        .build()
+        .unwrap();
+    assert_eq!(decoder.dimensions(), Dimensions::new(128, 48));
+    assert_eq!(decoder.display_dimensions(), Dimensions::new(128, 48));
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(frame.data().len(), 128 * 48 * 3);
+}
+
+#[test]
+fn chapters() {
+    let path = common::output("chapters.mkv");
+    common::Fixture::default()
+        .chapter(0, 400, "Intro")
+        .chapter(400, 1000, "Main")
+        .write(&path);
+    let decoder = VideoDecoder::new(path, false).unwrap();
+
+    let chapters = decoder.chapters();
+    assert_eq!(chapters.len(), 2);
+    assert_eq!(chapters[0].title(), Some("Intro"));
+    assert_eq!(chapters[0].start(), Duration::ZERO);
+    assert_eq!(chapters[0].end(), Duration::from_millis(400));
+    assert_eq!(chapters[1].title(), Some("Main"));
+    assert_eq!(chapters[1].start(), Duration::from_millis(400));
+    assert_eq!(chapters[1].end(), Duration::from_millis(1000));
+}
+
+#[test]
+fn decoder_name() {
+    let decoders = available_decoders();
+    let h264 = decoders
+        .iter()
+        .find(|decoder| decoder.name() == "h264")
+        .unwrap();
+    assert_eq!(h264.codec(), "h264");
+    assert!(!h264.is_hardware());
+    assert!(decoders.iter().all(|decoder| !decoder.name().is_empty()));
+
+    let decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    assert_eq!(decoder.decoder_name(), "h264");
+
+    let decoder = VideoDecoder::builder("test.mp4")
+        .decoder_name("h264")
+        .build()
+        .unwrap();
+    assert_eq!(decoder.decoder_name(), "h264");
+
+    // Unknown decoders, and decoders for a different codec, fall back to the default
+    for name in ["not-a-decoder", "png"] {
+        let mut decoder = VideoDecoder::builder("test.mp4")
+            .decoder_name(name)
+            .build()
+            .unwrap();
+        assert_eq!(decoder.decoder_name(), "h264");
+        assert!(decoder.next_frame().unwrap().is_some());
+    }
+}
+
+#[test]
+fn ffmpeg_info() {
+    let info = ffmpeg_video_decoder::ffmpeg_info();
+    assert!(!info.version().is_empty());
+    assert_eq!(info.libraries().len(), 5);
+    assert!(info
+        .libraries()
+        .iter()
+        .all(|(name, version)| name.starts_with("lib") && version.major > 0));
+    assert!(info.decoders().any(|decoder| decoder.name() == "h264"));
+    assert_eq!(info.decoders().count(), available_decoders().len());
+    assert!(info
+        .demuxers()
+        .any(|name| name.split(',').any(|format| format == "mp4")));
+    assert!(info.hardware_acceleration().all(|name| !name.is_empty()));
+}
+
+#[test]
+fn format_hint() {
+    let decoder = VideoDecoder::builder("test.mp4")
+        .format_hint("mp4")
+        .build()
+        .unwrap();
+    assert_eq!(decoder.container_format(), "mov,mp4,m4a,3gp,3g2,mj2");
+
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4")
+            .format_hint("not-a-format")
+            .build(),
+        Err(DecodeError::UnknownFormat)
+    ));
+}
+
+#[test]
+fn programs() {
+    let decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    assert!(decoder.programs().is_empty());
+
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4").program(0).build(),
+        Err(DecodeError::InvalidProgram)
+    ));
+}
+
+#[test]
+fn device() {
+    // lavfi generates video from a filter graph, so it works without any capture hardware
+    let device = Device::with_format("lavfi", "testsrc=size=320x240:rate=25:duration=1");
+    let mut decoder = VideoDecoder::new(device, false).unwrap();
+    assert_eq!(decoder.container_format(), "lavfi");
+    assert_eq!(decoder.dimensions().width(), 320);
+    assert_eq!(decoder.dimensions().height(), 240);
+    let mut count = 0;
+    while decoder.next_frame().unwrap().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 25);
+
+    assert!(matches!(
+        VideoDecoder::new(Device::with_format("not-a-device", "0"), false),
+        Err(DecodeError::UnknownFormat)
+    ));
+
+    let camera = Device::new("0").resolution(640, 480).framerate(30.0);
+    assert!(camera.name().ends_with('0'));
+    // There may not be any cameras (or a capture api) on the machine running the tests
+    if let Ok(devices) = list_devices() {
+        assert!(devices.iter().all(|device| !device.name().is_empty()));
+    }
+}
+
+#[test]
+fn screen() {
+    let screen = Device::screen(0).region(10, 20, 640, 480).cursor(false);
+    #[cfg(target_os = "linux")]
+    {
+        assert_eq!(screen.format(), "x11grab");
+        assert_eq!(screen.name(), ":0+10,20");
+    }
+    assert!(!screen.name().is_empty());
+
+    // Devices which can not capture a region are cropped by the decoder
+    let device = Device::with_format("lavfi", "testsrc=size=320x240:rate=25:duration=1")
+        .region(10, 20, 100, 50);
+    let mut decoder = VideoDecoder::new(device, false).unwrap();
+    assert_eq!(decoder.dimensions().width(), 100);
+    assert_eq!(decoder.dimensions().height(), 50);
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(frame.data().len(), 100 * 50 * 3);
+}
+
+#[test]
+fn perceptual_hash() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(frame.phash(), frame.phash());
+
+    // Hashes barely change with the size or format of the frame
+    let mut small = VideoDecoder::builder("test.mp4")
+        .filter("scale=320:180")
+        .output_format(PixelFormat::Gray8)
+        .build()
+        .unwrap();
+    let small = small.next_frame().unwrap().unwrap();
+    assert!((frame.phash() ^ small.phash()).count_ones() <= 6);
+    assert!((frame.dhash() ^ small.dhash()).count_ones() <= 6);
+
+    // A frame much later in the video looks different
+    decoder.seek_to_frame(600).unwrap();
+    let later = decoder.next_frame().unwrap().unwrap();
+    assert_ne!(frame.phash(), later.phash());
+}
+
+#[test]
+fn motion_vectors() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .motion_vectors(true)
+        .build()
+        .unwrap();
+    // The first frame is a keyframe, so it is not predicted from other frames
+    let first = decoder.next_frame().unwrap().unwrap();
+    assert!(first.motion_vectors().is_empty());
+
+    let second = decoder.next_frame().unwrap().unwrap();
+    assert!(!second.motion_vectors().is_empty());
+    let (width, height) = second.motion_vectors()[0].block_size();
+    assert!(width > 0 && height > 0);
+
+    // Not exported by default
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    decoder.next_frame().unwrap();
+    assert!(decoder
+        .next_frame()
+        .unwrap()
+        .unwrap()
+        .motion_vectors()
+        .is_empty());
+}
+
+#[test]
+fn closed_captions() {
+    // The test video has no captions, but enabling them must not change the frames
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .closed_captions(true)
+        .build()
+        .unwrap();
+    let mut plain = VideoDecoder::new("test.mp4", false).unwrap();
+    for _ in 0..10 {
+        let frame = decoder.next_frame().unwrap().unwrap();
+        assert!(frame.captions().is_empty());
+        assert_eq!(frame.data(), plain.next_frame().unwrap().unwrap().data());
+    }
+}
+
+#[test]
+fn embedded_captions() {
+    use ffmpeg_video_decoder::{ffmpeg, SubtitleContent};
+
+    // Each pair of EIA-608 bytes is sent with odd parity, after a marker for a valid field 1 pair
+    fn caption(pairs: &[[u8; 2]]) -> Vec<u8> {
+        let parity = |byte: u8| match byte.count_ones() % 2 {
+            0 => byte | 0x80,
+            _ => byte,
+        };
+        pairs
+            .iter()
+            .flat_map(|[first, second]| [0xFC, parity(*first), parity(*second)])
+            .collect()
+    }
+
+    // Load "HI" off screen, show it on the 3rd frame, then clear it on the 21st frame (which completes the cue)
+    let path = common::output("embedded_captions.mkv");
+    let a53 = ffmpeg::AVFrameSideDataType::AV_FRAME_DATA_A53_CC;
+    common::Fixture::default()
+        .codec(ffmpeg::AVCodecID::AV_CODEC_ID_MPEG2VIDEO)
+        .side_data(0, a53, caption(&[[0x14, 0x20]]))
+        .side_data(1, a53, caption(&[[b'H', b'I']]))
+        .side_data(2, a53, caption(&[[0x14, 0x2F]]))
+        .side_data(20, a53, caption(&[[0x14, 0x2C]]))
+        .write(&path);
+
+    let has_text = |cue: &ffmpeg_video_decoder::SubtitleCue| {
+        cue.content().iter().any(|content| match content {
+            SubtitleContent::Text(text) | SubtitleContent::Ass(text) => text.contains("HI"),
+            SubtitleContent::Bitmap(_) => false,
+        })
+    };
+
+    // The frame completing the cue is left out, so the cue must be returned with a later frame
+    for sampling in [Sampling::EveryNth(1), Sampling::EveryNth(7)] {
+        let mut decoder = VideoDecoder::builder(path.clone())
+            .closed_captions(true)
+            .sample(sampling)
+            .build()
+            .unwrap();
+        let mut cues = Vec::new();
+        while let Some(frame) = decoder.next_frame().unwrap() {
+            cues.extend(frame.captions().iter().cloned());
+        }
+        assert!(cues.iter().any(has_text));
+    }
+}
+
+#[test]
+fn data_stream() {
+    // The first stream is the video stream
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4").data_stream(0).build(),
+        Err(DecodeError::InvalidDataStream)
+    ));
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4").data_stream(100).build(),
+        Err(DecodeError::InvalidDataStream)
+    ));
+
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    decoder.next_frame().unwrap();
+    assert!(decoder.take_data_packets().is_empty());
+}
+
+#[test]
+fn data_packets() {
+    let path = common::output("data_packets.mov");
+    let packets: Vec<Vec<u8>> = (0..10u8).map(|i| vec![i; 16]).collect();
+    common::Fixture::default()
+        .format("mov")
+        .frames(60)
+        .data(b"gpmd", packets.clone())
+        .write(&path);
+
+    // Stream 1 is the data stream, each packet is read shortly before the frames it covers
+    let mut decoder = VideoDecoder::builder(path.clone())
+        .data_stream(1)
+        .build()
+        .unwrap();
+    let mut read = Vec::new();
+    while let Some(frame) = decoder.next_frame().unwrap() {
+        for packet in decoder.take_data_packets() {
+            assert_eq!(packet.stream_index(), 1);
+            assert!(packet.pts() <= frame.timestamp().map(|pts| pts + Duration::from_secs(1)));
+            read.push(packet);
+        }
+    }
+    read.extend(decoder.take_data_packets());
+    let bytes: Vec<_> = read.iter().map(|packet| packet.bytes().to_vec()).collect();
+    assert_eq!(bytes, packets);
+    assert!(read.windows(2).all(|pair| pair[0].pts() < pair[1].pts()));
+    assert_eq!(decoder.stats().data_packets_dropped, 0);
+
+    // Packets which are never taken are dropped, oldest first
+    let mut decoder = VideoDecoder::builder(path)
+        .data_stream(1)
+        .max_data_packets(3)
+        .build()
+        .unwrap();
+    while decoder.next_frame().unwrap().is_some() {}
+    let bytes: Vec<_> = decoder
+        .take_data_packets()
+        .into_iter()
+        .map(|packet| packet.into_bytes())
+        .collect();
+    assert_eq!(bytes, packets[7..]);
+    assert_eq!(decoder.stats().data_packets_dropped, 7);
+}
+
+#[test]
+fn telemetry() {
+    // No GoPro telemetry in the test video
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .telemetry(true)
+        .build()
+        .unwrap();
+    while decoder.next_frame().unwrap().is_some() {
+        assert!(decoder.take_data_packets().is_empty());
+    }
+}
+
+/// Encode a GPMF entry, padded to a multiple of 4 bytes
+#[cfg(feature = "gpmf")]
+fn gpmf_entry(key: &[u8; 4], kind: u8, size: u8, repeat: u16, payload: &[u8]) -> Vec<u8> {
+    let mut data = key.to_vec();
+    data.extend([kind, size]);
+    data.extend(repeat.to_be_bytes());
+    data.extend(payload);
+    data.resize(data.len().next_multiple_of(4), 0);
+    data
+}
+
+#[cfg(feature = "gpmf")]
+#[test]
+fn parse_gpmf() {
+    use ffmpeg_video_decoder::Telemetry;
+
+    let accelerometer: Vec<u8> = [100i16, -200, 981, 0, 0, 981]
+        .iter()
+        .flat_map(|value| value.to_be_bytes())
+        .collect();
+    let mut stream = gpmf_entry(b"SCAL", b's', 2, 1, &100i16.to_be_bytes());
+    stream.extend(gpmf_entry(b"ACCL", b's', 6, 2, &accelerometer));
+    let gps: Vec<u8> = [515_000_000i32, -1_000_000, 35_000, 1_500, 1_600]
+        .iter()
+        .flat_map(|value| value.to_be_bytes())
+        .collect();
+    let scale: Vec<u8> = [10_000_000i32, 10_000_000, 1_000, 1_000, 100]
+        .iter()
+        .flat_map(|value| value.to_be_bytes())
+        .collect();
+    let mut gps_stream = gpmf_entry(b"SCAL", b'l', 4, 5, &scale);
+    gps_stream.extend(gpmf_entry(b"GPS5", b'l', 20, 1, &gps));
+
+    let mut device = gpmf_entry(b"STRM", 0, 1, stream.len() as u16, &stream);
+    device.extend(gpmf_entry(
+        b"STRM",
+        0,
+        1,
+        gps_stream.len() as u16,
+        &gps_stream,
+    ));
+    let data = gpmf_entry(b"DEVC", 0, 1, device.len() as u16, &device);
+
+    let telemetry = Telemetry::parse(&data);
+    assert_eq!(
+        telemetry.accelerometer(),
+        &[[1.0, -2.0, 9.81], [0.0, 0.0, 9.81]]
+    );
+    assert!(telemetry.gyroscope().is_empty());
+    assert_eq!(telemetry.gps().len(), 1);
+    assert_eq!(telemetry.gps()[0].latitude, 51.5);
+    assert_eq!(telemetry.gps()[0].longitude, -0.1);
+    assert_eq!(telemetry.gps()[0].altitude, 35.0);
+
+    // Damaged data is ignored
+    assert_eq!(
+        Telemetry::parse(&data[..data.len() - 4]),
+        Telemetry::default()
+    );
+}
+
+#[cfg(feature = "gpmf")]
+#[test]
+fn gopro_telemetry() {
+    use ffmpeg_video_decoder::{GpsFix, Telemetry};
+
+    // One GPS reading per packet, moving north each time
+    let latitude = |i: i32| 515_000_000 + i * 10_000;
+    let packets: Vec<Vec<u8>> = (0..4)
+        .map(|i| {
+            let scale: Vec<u8> = [10_000_000i32, 10_000_000, 1_000, 1_000, 100]
+                .iter()
+                .flat_map(|value| value.to_be_bytes())
+                .collect();
+            let gps: Vec<u8> = [latitude(i), -1_000_000, 35_000, 1_500, 1_600]
+                .iter()
+                .flat_map(|value| value.to_be_bytes())
+                .collect();
+            let mut stream = gpmf_entry(b"SCAL", b'l', 4, 5, &scale);
+            stream.extend(gpmf_entry(b"GPS5", b'l', 20, 1, &gps));
+            let device = gpmf_entry(b"STRM", 0, 1, stream.len() as u16, &stream);
+            gpmf_entry(b"DEVC", 0, 1, device.len() as u16, &device)
+        })
+        .collect();
+
+    let path = common::output("telemetry.mov");
+    common::Fixture::default()
+        .format("mov")
+        .data(b"gpmd", packets)
+        .write(&path);
+
+    let mut decoder = VideoDecoder::builder(path).telemetry(true).build().unwrap();
+    let mut packets = Vec::new();
+    while decoder.next_frame().unwrap().is_some() {
+        packets.extend(decoder.take_data_packets());
+    }
+    packets.extend(decoder.take_data_packets());
+
+    let fixes: Vec<GpsFix> = packets
+        .iter()
+        .flat_map(|packet| Telemetry::parse(packet.bytes()).gps().to_vec())
+        .collect();
+    let expected: Vec<GpsFix> = (0..4)
+        .map(|i| GpsFix {
+            latitude: latitude(i) as f64 / 10_000_000.0,
+            longitude: -0.1,
+            altitude: 35.0,
+            speed_2d: 1.5,
+            speed_3d: 16.0,
+        })
+        .collect();
+    assert_eq!(fixes, expected);
+}
+
+#[test]
+fn timecode() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    assert_eq!(decoder.timecode(), None);
+    assert_eq!(decoder.next_frame().unwrap().unwrap().timecode(), None);
+
+    let timecode = Timecode::from_frames(30 * 3661 + 5, 30, false);
+    assert_eq!(timecode.to_string(), "01:01:01:05");
+    assert_eq!(timecode.to_frames(), 30 * 3661 + 5);
+    assert_eq!(timecode.add_frames(25).to_string(), "01:01:02:00");
+
+    // Frames 0 and 1 are skipped at the start of each minute, except every tenth minute
+    let minute = Timecode::from_frames(1800, 30, true);
+    assert_eq!(minute.to_string(), "00:01:00;02");
+    assert_eq!(
+        Timecode::from_frames(1799, 30, true).to_string(),
+        "00:00:59;29"
+    );
+    assert_eq!(
+        Timecode::from_frames(17982, 30, true).to_string(),
+        "00:10:00;00"
+    );
+    for n in [0, 1799, 1800, 17981, 17982, 107_892] {
+        assert_eq!(Timecode::from_frames(n, 30, true).to_frames(), n);
+    }
+    assert_eq!(minute.add_frames(1798).to_string(), "00:02:00;02");
+
+    // Only defined for multiples of 30 fps
+    assert!(!Timecode::from_frames(100, 25, true).is_drop_frame());
+}
+
+#[test]
+fn crop() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .crop(100, 200, 640, 360)
+        .build()
+        .unwrap();
+    assert_eq!(decoder.dimensions().width(), 640);
+    assert_eq!(decoder.dimensions().height(), 360);
+    let cropped = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(cropped.data().len(), 640 * 360 * 3);
+
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    let full = decoder.next_frame().unwrap().unwrap();
+    for (x, y) in [(0, 0), (320, 180), (639, 359)] {
+        let expected = ((y + 200) * 1920 + x + 100) * 3;
+        let actual = (y * 640 + x) * 3;
+        for channel in 0..3 {
+            let (a, b) = (
+                full.data()[expected + channel],
+                cropped.data()[actual + channel],
+            );
+            assert!(a.abs_diff(b) <= 4, "{a} != {b} at {x}, {y}");
+        }
+    }
+
+    // Outside the frame
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4")
+            .crop(1900, 0, 640, 360)
+            .build(),
+        Err(DecodeError::InvalidFilter)
+    ));
+}
+
+#[test]
+fn fit() {
+    let fit = |mode| {
+        let mut decoder = VideoDecoder::builder("test.mp4")
+            .fit(400, 400, mode)
+            .build()
+            .unwrap();
+        assert_eq!(decoder.dimensions().width(), 400);
+        assert_eq!(decoder.dimensions().height(), 400);
+        let frame = decoder.next_frame().unwrap().unwrap();
+        assert_eq!(frame.data().len(), 400 * 400 * 3);
+        frame
+    };
+
+    // 16:9 in a square has bars above and below
+    let contain = fit(FitMode::Contain);
+    assert!(contain.data()[..400 * 3 * 50]
+        .iter()
+        .all(|&value| value < 20));
+    let middle = 200 * 400 * 3;
+    assert!(contain.data()[middle..middle + 400 * 3]
+        .iter()
+        .any(|&value| value > 20));
+
+    // The edges are cut off instead
+    let cover = fit(FitMode::Cover);
+    assert!(cover.data()[..400 * 3].iter().any(|&value| value > 20));
+
+    let stretch = fit(FitMode::Stretch);
+    assert_ne!(stretch.data(), cover.data());
+}
+
+#[test]
+fn scaling_quality() {
+    let decode = |quality| {
+        let mut decoder = VideoDecoder::builder("test.mp4")
+            .filter("scale=640:-2")
+            .scaling_quality(quality)
+            .full_chroma(true)
+            .dither(true)
+            .build()
+            .unwrap();
+        assert_eq!(decoder.dimensions().width(), 640);
+        assert_eq!(decoder.dimensions().height(), 360);
+        decoder.next_frame().unwrap().unwrap().into_data()
+    };
+
+    let fast = decode(ScalingQuality::FastBilinear);
+    let lanczos = decode(ScalingQuality::Lanczos);
+    assert_eq!(fast.len(), lanczos.len());
+    assert_ne!(fast, lanczos);
+}
+
+#[test]
+fn scaler_flags() {
+    let decode = |filter: &str, configure: fn(DecoderBuilder) -> DecoderBuilder| {
+        let mut decoder = configure(VideoDecoder::builder("test.mp4").filter(filter))
+            .build()
+            .unwrap();
+        decoder.next_frame().unwrap().unwrap().into_data()
+    };
+
+    // Chroma is interpolated when converting the 4:2:0 frames to RGB
+    let default = decode("scale=640:-2", |builder| builder);
+    let full_chroma = decode("scale=640:-2", |builder| builder.full_chroma(true));
+    assert_eq!(default.len(), full_chroma.len());
+    assert_ne!(default, full_chroma);
+
+    // Error diffusion replaces the ordered dither when scaling down to 3-3-2 bit RGB
+    let default = decode("scale=640:-2,format=rgb8", |builder| builder);
+    let dither = decode("scale=640:-2,format=rgb8", |builder| builder.dither(true));
+    assert_eq!(default.len(), dither.len());
+    assert_ne!(default, dither);
+}
+
+#[test]
+fn rotate_and_flip() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=64:36")
+        .output_format(PixelFormat::Rgba)
+        .build()
+        .unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+    let pixel = |frame: &ffmpeg_video_decoder::Frame, x: usize, y: usize| {
+        let offset = y * frame.stride() + x * 4;
+        frame.data()[offset..offset + 4].to_vec()
+    };
+
+    let rotated = frame.rotate90();
+    assert_eq!(rotated.dimensions().width(), 36);
+    assert_eq!(rotated.dimensions().height(), 64);
+    assert_eq!(rotated.index(), frame.index());
+    // The top left corner moves to the top right
+    assert_eq!(pixel(&rotated, 35, 0), pixel(&frame, 0, 0));
+    assert_eq!(pixel(&rotated, 0, 63), pixel(&frame, 63, 35));
+    assert_eq!(pixel(&frame.rotate270(), 0, 63), pixel(&frame, 0, 0));
+    assert_eq!(pixel(&frame.rotate180(), 63, 35), pixel(&frame, 0, 0));
+    assert_eq!(pixel(&frame.flip_horizontal(), 63, 5), pixel(&frame, 0, 5));
+    assert_eq!(pixel(&frame.flip_vertical(), 5, 35), pixel(&frame, 5, 0));
+
+    assert_eq!(
+        rotated.rotate90().rotate90().rotate90().data(),
+        frame.data()
+    );
+    assert_eq!(rotated.rotate270().data(), frame.data());
+    assert_eq!(
+        frame.flip_horizontal().flip_vertical().data(),
+        frame.rotate180().data()
+    );
+    assert_eq!(frame.flip_vertical().flip_vertical().data(), frame.data());
+}
+
+#[test]
+fn overlay() {
+    let decode = |overlay: Option<&[u8]>| {
+        let mut builder = VideoDecoder::builder("test.mp4").filter("scale=64:36");
+        if let Some(data) = overlay {
+            // Partly off the right edge of the frame
+            builder = builder.overlay(data, 16, 16, 56, 10);
+        }
+        builder.build().unwrap().next_frame().unwrap().unwrap()
+    };
+    let plain = decode(None);
+
+    let opaque = [255, 0, 0, 255].repeat(16 * 16);
+    let frame = decode(Some(&opaque));
+    let pixel = |frame: &ffmpeg_video_decoder::Frame, x: usize, y: usize| {
+        let offset = y * frame.stride() + x * 3;
+        frame.data()[offset..offset + 3].to_vec()
+    };
+    assert_eq!(pixel(&frame, 60, 12), [255, 0, 0]);
+    assert_eq!(pixel(&frame, 63, 25), [255, 0, 0]);
+    assert_eq!(pixel(&frame, 55, 12), pixel(&plain, 55, 12));
+    assert_eq!(pixel(&frame, 60, 26), pixel(&plain, 60, 26));
+
+    // Half transparent
+    let translucent = [255, 0, 0, 128].repeat(16 * 16);
+    let frame = decode(Some(&translucent));
+    let [r, g, b] = pixel(&frame, 60, 12)[..] else {
+        unreachable!()
+    };
+    let below = pixel(&plain, 60, 12);
+    assert!(r >= below[0] && g <= below[1] && b <= below[2]);
+    assert!(g.abs_diff(below[1] / 2) <= 1);
+
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4")
+            .overlay(&opaque, 10, 10, 0, 0)
+            .build(),
+        Err(DecodeError::InvalidOverlay)
+    ));
+}
+
+#[test]
+fn filter() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=640:-1,hue=s=0")
+        .build()
+        .unwrap();
+    assert_eq!(decoder.dimensions().width(), 640);
+    assert_eq!(decoder.dimensions().height(), 360);
+
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(frame.index(), 1);
+    assert_eq!(frame.data().len(), 640 * 360 * 3);
+    // Fully desaturated, so every pixel is grey
+    assert!(frame
+        .data()
+        .chunks(3)
+        .all(|rgb| rgb[0].abs_diff(rgb[1]) <= 2 && rgb[1].abs_diff(rgb[2]) <= 2));
+
+    let mut count = 1;
+    while decoder.next_frame().unwrap().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 901);
+
+    // The graph is rebuilt after seeking
+    decoder.seek_to_frame(300).unwrap();
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 300);
+
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4")
+            .filter("not_a_filter")
+            .build(),
+        Err(DecodeError::InvalidFilter)
+    ));
+}
+
+#[test]
+fn deinterlace() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .deinterlace(true)
+        .build()
+        .unwrap();
+    assert!(!decoder.is_interlaced());
+    assert_eq!(decoder.dimensions().width(), 1920);
+    assert_eq!(decoder.dimensions().height(), 1080);
+
+    // Progressive frames are passed through unchanged
+    let mut plain = VideoDecoder::new("test.mp4", false).unwrap();
+    for _ in 0..10 {
+        let frame = decoder.next_frame().unwrap().unwrap();
+        assert_eq!(frame.data(), plain.next_frame().unwrap().unwrap().data());
+    }
+}
+
+#[test]
+fn target_fps() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .target_fps(10.0)
+        .build()
+        .unwrap();
+    assert_eq!(decoder.framerate(), 10.0);
+    assert_eq!(decoder.avg_framerate(), Some(10.0));
+    assert!(!decoder.is_vfr());
+
+    let mut count = 0;
+    while let Some(frame) = decoder.next_frame().unwrap() {
+        count += 1;
+        assert_eq!(frame.index(), count);
+        let expected = Duration::from_millis((count as u64 - 1) * 100);
+        assert!(frame.timestamp().unwrap().abs_diff(expected) < Duration::from_millis(5));
+    }
+    assert!((300..=302).contains(&count));
+
+    // Indices after seeking are in the output framerate
+    decoder.seek_to_frame(50).unwrap();
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 50);
+}
+
+#[test]
+fn output_format() {
+    for format in [
+        PixelFormat::Rgb24,
+        PixelFormat::Rgba,
+        PixelFormat::Gray8,
+        PixelFormat::Rgb48,
+        PixelFormat::Rgba64,
+    ] {
+        let mut decoder = VideoDecoder::builder("test.mp4")
+            .output_format(format)
+            .build()
+            .unwrap();
+        assert_eq!(decoder.output_format(), format);
+
+        let frame = decoder.next_frame().unwrap().unwrap();
+        assert_eq!(frame.format(), format);
+        assert_eq!(frame.data().len(), 1920 * 1080 * format.bytes_per_pixel());
+        assert_eq!(
+            frame.planes()[0].row(0).len(),
+            1920 * format.bytes_per_pixel()
+        );
+    }
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn into_image() {
+    let formats = [
+        (
+            PixelFormat::Rgb24,
+            ffmpeg_video_decoder::image::ColorType::Rgb8,
+        ),
+        (
+            PixelFormat::Rgba,
+            ffmpeg_video_decoder::image::ColorType::Rgba8,
+        ),
+        (
+            PixelFormat::Gray8,
+            ffmpeg_video_decoder::image::ColorType::L8,
+        ),
+        (
+            PixelFormat::Rgb48,
+            ffmpeg_video_decoder::image::ColorType::Rgb16,
+        ),
+    ];
+
+    for (format, color) in formats {
+        let mut decoder = VideoDecoder::builder("test.mp4")
+            .output_format(format)
+            .build()
+            .unwrap();
+        let image = decoder.next_frame().unwrap().unwrap().into_image().unwrap();
+        assert_eq!(image.color(), color);
+        assert_eq!((image.width(), image.height()), (1920, 1080));
+    }
+}
+
+#[cfg(feature = "ndarray")]
+#[test]
+fn ndarray() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .packed_rows(false)
+        .build()
+        .unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+
+    let normalized = frame.to_ndarray_f32();
+    let array = frame.clone().into_ndarray().unwrap();
+    assert_eq!(array.shape(), &[1080, 1920, 3]);
+    assert_eq!(normalized.shape(), &[1080, 1920, 3]);
+
+    let plane = frame.planes()[0];
+    assert_eq!(array[[10, 20, 1]], plane.row(10)[20 * 3 + 1]);
+    assert_eq!(normalized[[10, 20, 1]], array[[10, 20, 1]] as f32 / 255.0);
+
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .output_format(PixelFormat::Rgb48)
+        .build()
+        .unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(frame.to_ndarray_f32().shape(), &[1080, 1920, 3]);
+    assert!(matches!(
+        frame.into_ndarray(),
+        Err(DecodeError::UnsupportedPixelFormat)
+    ));
+}
+
+#[test]
+fn tensor() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=64:32")
+        .build()
+        .unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+    let pixel = |x: usize, y: usize, c: usize| frame.data()[(y * 64 + x) * 3 + c] as f32 / 255.0;
+
+    let chw = frame.to_tensor(TensorLayout::Chw, None);
+    let hwc = frame.to_tensor(TensorLayout::Hwc, None);
+    assert_eq!(chw.len(), 3 * 32 * 64);
+    assert_eq!(hwc.len(), 3 * 32 * 64);
+    assert!((chw[(2 * 32 + 5) * 64 + 7] - pixel(7, 5, 2)).abs() < 1e-6);
+    assert!((hwc[(5 * 64 + 7) * 3 + 2] - pixel(7, 5, 2)).abs() < 1e-6);
+
+    let mean = [0.5, 0.25, 0.0];
+    let std = [0.5, 2.0, 1.0];
+    let normalized = frame.to_tensor(TensorLayout::Chw, Some((&mean, &std)));
+    for c in 0..3 {
+        let expected = (pixel(7, 5, c) - mean[c]) / std[c];
+        assert!((normalized[(c * 32 + 5) * 64 + 7] - expected).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn cancel() {
+    let handle = DecoderHandle::new();
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .handle(handle.clone())
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap();
+    assert!(decoder.next_frame().unwrap().is_some());
+    assert!(!decoder.handle().is_cancelled());
+
+    decoder.handle().cancel();
+    assert!(handle.is_cancelled());
+    assert!(matches!(decoder.next_frame(), Err(DecodeError::Cancelled)));
+
+    // A cancelled handle also stops the decoder from being opened
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4").handle(handle).build(),
+        Err(DecodeError::Cancelled)
+    ));
+}
+
+#[test]
+fn options() {
+    // Only decode keyframes
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .format_option("probesize", "4096")
+        .codec_option("skip_frame", "nokey")
+        .build()
+        .unwrap();
+    let mut count = 0;
+    while decoder.next_frame().unwrap().is_some() {
+        count += 1;
+    }
+    assert!(count > 0 && count < 901);
+
+    // A fast skip leaves the option in place once it is done
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .codec_option("skip_frame", "nokey")
+        .build()
+        .unwrap();
+    decoder.skip(700).unwrap();
+    let mut remaining = 0;
+    while decoder.next_frame().unwrap().is_some() {
+        remaining += 1;
+    }
+    assert!(remaining < count);
+
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4")
+            .codec_option("threads", "\0")
+            .build(),
+        Err(DecodeError::InvalidOption)
+    ));
+}
+
+#[test]
+fn low_latency() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .low_latency(true)
+        .threads(4)
+        .build()
+        .unwrap();
+    assert_eq!(decoder.dimensions().width(), 1920);
+
+    let mut count = 0;
+    while let Some(frame) = decoder.next_frame().unwrap() {
+        count += 1;
+        assert_eq!(frame.index(), count);
+    }
+    assert_eq!(count, 901);
+}
+
+#[test]
+fn stream_analysis() {
+    for builder in [
+        VideoDecoder::builder("test.mp4")
+            .probe_size(4096)
+            .analyze_duration(Duration::from_millis(100)),
+        VideoDecoder::builder("test.mp4").skip_stream_analysis(true),
+    ] {
+        let mut decoder = builder.filter("scale=64:36").build().unwrap();
+        assert_eq!(decoder.framerate(), 30.0);
+        assert_eq!(decoder.duration().unwrap().as_secs(), 30);
+
+        let mut count = 0;
+        while decoder.next_frame().unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 901);
+    }
+
+    // Only enough of the data to check the header is read
+    let data = std::fs::read("test.mp4").unwrap();
+    let unread = |builder: fn(PushSource) -> DecoderBuilder| {
+        let source = PushSource::new();
+        for chunk in data.chunks(4096) {
+            source.push(chunk);
+        }
+        source.finish();
+        let _decoder = builder(source.clone()).build().unwrap();
+        source.buffered()
+    };
+    let skipped = unread(|source| VideoDecoder::builder(source).skip_stream_analysis(true));
+    let analysed = unread(VideoDecoder::builder);
+    assert!(skipped > analysed, "{skipped} <= {analysed}");
+}
+
+#[test]
+fn fast_open() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .fast_open(true)
+        .build()
+        .unwrap();
+    assert_eq!(decoder.dimensions(), Dimensions::new(1920, 1080));
+    assert_eq!(decoder.framerate(), 30.0);
+    assert_eq!(decoder.duration().unwrap().as_secs(), 30);
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(frame.index(), 1);
+    assert_eq!(decoder.pixel_format().as_deref(), Some("yuv420p"));
+
+    // Seeking to a frame number uses the framerate from the header
+    decoder.seek_to_frame(100).unwrap();
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 100);
+    let frame = decoder
+        .get_frame_at(Duration::from_secs(10))
+        .unwrap()
+        .unwrap();
+    assert_eq!(frame.index(), 301);
+
+    // The filter graph needs the pixel format, so the stream information is read
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .fast_open(true)
+        .filter("scale=64:36")
+        .build()
+        .unwrap();
+    let mut count = 0;
+    while decoder.next_frame().unwrap().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 901);
+}
+
+#[test]
+fn limits() {
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4")
+            .max_dimensions(Dimensions::new(1280, 720))
+            .build(),
+        Err(DecodeError::LimitExceeded)
+    ));
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4")
+            .max_memory_bytes(1024 * 1024)
+            .build(),
+        Err(DecodeError::LimitExceeded)
+    ));
+
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .max_dimensions(Dimensions::new(1920, 1080))
+        .max_memory_bytes(1024 * 1024 * 1024)
+        .max_frame_count(901)
+        .filter("scale=64:36")
+        .build()
+        .unwrap();
+    let mut count = 0;
+    while decoder.next_frame().unwrap().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 901);
+
+    // The header gives the number of frames
+    assert!(matches!(
+        VideoDecoder::builder("test.mp4")
+            .max_frame_count(100)
+            .build(),
+        Err(DecodeError::LimitExceeded)
+    ));
+}
+
+#[test]
+fn validate() {
+    let report = ffmpeg_video_decoder::validate("test.mp4").unwrap();
+    assert!(report.is_valid(), "{:?}", report.issues());
+    assert_eq!(report.mode(), ValidationMode::Full);
+    assert!(report.frames() > 901);
+    assert_eq!(report.measured_duration().unwrap().as_secs(), 30);
+
+    let report =
+        ffmpeg_video_decoder::validate_with("test.mp4", ValidationMode::HeadersOnly).unwrap();
+    assert!(report.is_valid());
+    assert_eq!(report.packets(), 0);
+    assert_eq!(report.streams().len(), 2);
+
+    let report =
+        ffmpeg_video_decoder::validate_with("test.mp4", ValidationMode::KeyframesOnly).unwrap();
+    assert!(report.is_valid());
+    assert!(report.frames() > 0 && report.frames() < 901);
+
+    // Cut off half way through the packets
+    let data = std::fs::read("test.mp4").unwrap();
+    let report = ffmpeg_video_decoder::validate(data[..data.len() / 2].to_vec()).unwrap();
+    assert!(!report.is_valid());
+    assert!(report.measured_duration().unwrap() < Duration::from_secs(20));
+}
+
+#[test]
+fn for_each_frame() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=64:36")
+        .build()
+        .unwrap();
+    let mut count = 0;
+    let result = decoder
+        .for_each_frame(|frame| {
+            count += 1;
+            match frame.index() {
+                100 => std::ops::ControlFlow::Break(frame.index()),
+                _ => std::ops::ControlFlow::Continue(()),
+            }
+        })
+        .unwrap();
+    assert_eq!(result, std::ops::ControlFlow::Break(100));
+    assert_eq!(count, 100);
+
+    // Carries on from where it stopped
+    let result = decoder
+        .for_each_frame(|_| std::ops::ControlFlow::<()>::Continue(()))
+        .unwrap();
+    assert_eq!(result, std::ops::ControlFlow::Continue(()));
+    assert!(decoder.next_frame().unwrap().is_none());
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn forward_to() {
+    use futures::StreamExt;
+
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=64:36")
+        .build()
+        .unwrap();
+    // A single slot, so decoding has to wait for every frame to be received
+    let (sender, receiver) = futures::channel::mpsc::channel(1);
+    let (sent, frames) = futures::executor::block_on(async {
+        futures::join!(
+            decoder.forward_to(sender),
+            receiver.collect::<Vec<ffmpeg_video_decoder::Frame>>()
+        )
+    });
+    assert_eq!(sent.unwrap(), 901);
+    assert_eq!(frames.len(), 901);
+    assert_eq!(frames.last().unwrap().index(), 901);
+
+    // The receiver is dropped, so the first frame fails to send
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    let (sender, receiver) = futures::channel::mpsc::channel(1);
+    drop(receiver);
+    let result = futures::executor::block_on(decoder.forward_to(sender));
+    assert!(matches!(
+        result,
+        Err(ffmpeg_video_decoder::ForwardError::Sink(_))
+    ));
+}
+
+#[test]
+fn save_position() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=64:36")
+        .build()
+        .unwrap();
+    let start = decoder.save_position();
+    assert_eq!(start.index(), 1);
+    assert_eq!(start.timestamp(), Some(Duration::ZERO));
+
+    for _ in 0..449 {
+        decoder.next_frame().unwrap().unwrap();
+    }
+    let position = decoder.save_position();
+    assert_eq!(position.index(), 450);
+    let expected = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(position.timestamp(), expected.timestamp());
+
+    // Restored in a new decoder, without decoding from the start
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=64:36")
+        .build()
+        .unwrap();
+    decoder.restore_position(&position).unwrap();
+    assert_eq!(decoder.save_position(), position);
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(frame.index(), 450);
+    assert_eq!(frame.timestamp(), expected.timestamp());
+    assert_eq!(frame.data(), expected.data());
+    assert!(decoder.stats().frames_decoded < 449);
+
+    // And back to the start
+    decoder.restore_position(&start).unwrap();
+    assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 1);
+}
+
+#[test]
+fn paced() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap().paced();
+    decoder.decoder_mut().seek_to_frame(880).unwrap();
+
+    let start = std::time::Instant::now();
+    let mut count = 0;
+    while decoder.next_frame().unwrap().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 22);
+
+    // 21 frames after the first one at 30 fps
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(690), "{elapsed:?}");
+
+    // Sampled frames are 10 seconds apart, which is too long a gap to wait out
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=64:36")
+        .sample(Sampling::EveryNth(300))
+        .build()
+        .unwrap()
+        .paced();
+    let start = std::time::Instant::now();
+    assert_eq!(decoder.by_ref().count(), 4);
+    let elapsed = start.elapsed();
+    assert!(elapsed < Duration::from_secs(2), "{elapsed:?}");
+    assert!(elapsed >= Duration::from_millis(90), "{elapsed:?}");
+}
+
+#[test]
+fn corrupt_input() {
+    // Overwrite part of the video data (after the header) with garbage
+    let mut data = std::fs::read("test.mp4").unwrap();
+    for (i, byte) in data[2_000_000..2_200_000].iter_mut().enumerate() {
+        *byte = (i * 7 % 251) as u8;
+    }
+
+    let mut decoder = VideoDecoder::builder(data)
+        .on_error(ErrorPolicy::SkipFrame)
+        .build()
+        .unwrap();
+    let mut count = 0;
+    while decoder.next_frame().unwrap().is_some() {
+        count += 1;
+    }
+    let stats = decoder.stats();
+    assert!(count > 0);
+    assert!(stats.corrupt_frames > 0 || stats.corrupt_packets > 0);
+    assert!(count + stats.corrupt_frames as usize <= 901);
+
+    // A clean input never skips anything
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .on_error(ErrorPolicy::SkipFrame)
+        .build()
+        .unwrap();
+    while decoder.next_frame().unwrap().is_some() {}
+    assert_eq!(decoder.stats().corrupt_packets, 0);
+    assert_eq!(decoder.stats().corrupt_frames, 0);
+}
+
+#[test]
+fn stats() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    assert_eq!(decoder.stats(), DecoderStats::default());
+
+    decoder.seek_to_frame(850).unwrap();
+    while decoder.next_frame().unwrap().is_some() {}
+
+    let stats = decoder.stats();
+    assert!(stats.packets_read >= 52);
+    assert!(stats.bytes_read > 0);
+    assert!(stats.frames_decoded >= 52);
+    assert_eq!(stats.frames_dropped, stats.frames_decoded - 52);
+    assert!(stats.average_decode_time() > Duration::ZERO);
+    assert!(stats.convert_time > Duration::ZERO);
+}
+
+#[test]
+fn max_buffered_frames() {
+    for frames in [1, 4] {
+        let mut decoder = VideoDecoder::builder("test.mp4")
+            .max_buffered_frames(frames)
+            .threads(4)
+            .build()
+            .unwrap();
+        let mut count = 0;
+        while let Some(frame) = decoder.next_frame().unwrap() {
+            count += 1;
+            assert_eq!(frame.index(), count);
+        }
+        assert_eq!(count, 901);
+    }
+
+    // Filters which output several frames for each decoded frame
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .max_buffered_frames(1)
+        .target_fps(90.0)
+        .build()
+        .unwrap();
+    let mut count = 0;
+    while decoder.next_frame().unwrap().is_some() {
+        count += 1;
+    }
+    assert!((2700..=2705).contains(&count));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    let decoder = VideoDecoder::new("test.mp4", false).unwrap();
+
+    let json = serde_json::to_string(&decoder.streams()).unwrap();
+    assert!(json.contains("\"h264\""));
+    let streams: Vec<ffmpeg_video_decoder::StreamInfo> = serde_json::from_str(&json).unwrap();
+    assert_eq!(streams, decoder.streams());
+
+    let json = serde_json::to_string(&decoder.dimensions()).unwrap();
+    assert_eq!(json, r#"{"width":1920,"height":1080}"#);
+}
+
+#[test]
+fn color_range() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    assert_ne!(decoder.color_range().as_deref(), Some("pc"));
+    let limited = decoder.next_frame().unwrap().unwrap();
+
+    // Only changes how the frames are tagged, not their data
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("setrange=full")
+        .build()
+        .unwrap();
+    let full = decoder.next_frame().unwrap().unwrap();
+
+    // Limited range expands the values to fill the full range of RGB
+    let contrast = |frame: &ffmpeg_video_decoder::Frame| {
+        let min = frame.data().iter().min().copied().unwrap();
+        let max = frame.data().iter().max().copied().unwrap();
+        max - min
+    };
+    assert!(contrast(&limited) > contrast(&full));
+}
+
+#[test]
+fn tonemap() {
+    use ffmpeg_video_decoder::ffmpeg;
+
+    // Tone mapping is only applied to HDR video
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .tonemap(ToneMapping::Hable)
+        .build()
+        .unwrap();
+    assert!(!decoder.is_hdr());
+
+    let mut reference = VideoDecoder::new("test.mp4", false).unwrap();
+    assert_eq!(
+        decoder.next_frame().unwrap().unwrap().data(),
+        reference.next_frame().unwrap().unwrap().data()
+    );
+
+    let path = common::output("hdr.mkv");
+    common::Fixture::default().hdr().write(&path);
+    let mut reference = VideoDecoder::new(path.clone(), false).unwrap();
+    assert!(reference.is_hdr());
+    assert_eq!(
+        reference.transfer_characteristics().as_deref(),
+        Some("smpte2084")
+    );
+
+    let decoder = VideoDecoder::builder(path)
+        .tonemap(ToneMapping::Hable)
+        .build();
+    let zscale = unsafe { ffmpeg::avfilter_get_by_name(c"zscale".as_ptr()) };
+    // The tone mapping chain needs ffmpeg to be built with zimg
+    if zscale.is_null() {
+        assert!(matches!(decoder, Err(DecodeError::InvalidFilter)));
+        return;
+    }
+    let mut decoder = decoder.unwrap();
+    assert_eq!(decoder.dimensions(), Dimensions::new(64, 48));
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(frame.data().len(), 64 * 48 * 3);
+    assert_ne!(
+        frame.data(),
+        reference.next_frame().unwrap().unwrap().data()
+    );
+}
+
+#[test]
+fn dimensions() {
+    let video = VideoDecoder::new("test.mp4", false).unwrap().dimensions();
+    assert_eq!(video, Dimensions::new(1920, 1080));
+    assert_eq!(video.to_string(), "1920x1080");
+    assert!((video.aspect_ratio() - 16.0 / 9.0).abs() < 1e-9);
+    assert!(video.is_landscape() && !video.is_portrait());
+    assert_eq!(video.area(), 1920 * 1080);
+
+    assert_eq!(
+        video.scaled_to_fit((640, 640).into()),
+        Dimensions::new(640, 360)
+    );
+    assert_eq!(
+        video.scaled_to_fit((4000, 1440).into()),
+        Dimensions::new(2560, 1440)
+    );
+    assert_eq!(
+        video.scaled_to_cover((640, 640).into()),
+        Dimensions::new(1138, 640)
+    );
+    assert_eq!(
+        video.scaled_to_cover((100, 10).into()),
+        Dimensions::new(100, 56)
+    );
+
+    assert_eq!(video / 2, Dimensions::new(960, 540));
+    assert_eq!(video * 2, Dimensions::new(3840, 2160));
+    assert_eq!(video * 0.25, Dimensions::new(480, 270));
+    assert_eq!(video * 0.0001, Dimensions::new(1, 1));
+    assert_eq!(video * u32::MAX, Dimensions::new(u32::MAX, u32::MAX));
+    assert_eq!(Dimensions::new(0, 0).aspect_ratio(), 0.0);
+}
@@ -0,0 +1,679 @@
+use common::{output, solid_video};
+use ffmpeg_video_decoder::{
+    compare, cut, detect_black_frames, remux, scene_changes, transcode, ApngWriter,
+    BitstreamDecoder, DecodeError, EncodeError, GifWriter, PixelFormat, PlaylistDecoder,
+    PushSource, TranscodeError, TranscodeOptions, Variant, VideoCodec, VideoDecoder, VideoEncoder,
+};
+use std::time::Duration;
+
+mod common;
+
+#[test]
+fn round_trip() {
+    let path = output("round_trip.mkv");
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    let mut encoder = VideoEncoder::builder(&path, 640, 360)
+        .codec(VideoCodec::Mpeg4)
+        .framerate(decoder.framerate())
+        .build()
+        .unwrap();
+
+    for _ in 0..60 {
+        // Frames are scaled to the size of the output
+        encoder
+            .encode(&decoder.next_frame().unwrap().unwrap())
+            .unwrap();
+    }
+    assert_eq!(encoder.frame_count(), 60);
+    encoder.finish().unwrap();
+
+    let mut decoder = VideoDecoder::new(path, false).unwrap();
+    assert_eq!(decoder.codec_name(), "mpeg4");
+    assert_eq!(decoder.dimensions().width(), 640);
+    assert_eq!(decoder.dimensions().height(), 360);
+    assert_eq!(decoder.framerate().round(), 30.0);
+
+    let mut count = 0;
+    while decoder.next_frame().unwrap().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 60);
+}
+
+#[test]
+fn encode_data() {
+    let path = output("encode_data.mp4");
+    let mut encoder = VideoEncoder::builder(&path, 64, 48)
+        .codec(VideoCodec::Mpeg4)
+        .build()
+        .unwrap();
+
+    // Solid red
+    let data: Vec<u8> = [255, 0, 0, 255].repeat(64 * 48);
+    for _ in 0..10 {
+        encoder.encode_data(&data, PixelFormat::Rgba).unwrap();
+    }
+    assert!(matches!(
+        encoder.encode_data(&data[..100], PixelFormat::Rgba),
+        Err(EncodeError::InvalidFrame)
+    ));
+    encoder.finish().unwrap();
+
+    let mut decoder = VideoDecoder::new(path, false).unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+    let pixel = &frame.data()[..3];
+    assert!(pixel[0] > 200 && pixel[1] < 50 && pixel[2] < 50);
+}
+
+#[test]
+fn raw_stream() {
+    // `m4v` is a raw MPEG-4 part 2 stream with no container
+    let path = solid_video("raw_stream.m4v", &[([0, 0, 255, 255], 10)]);
+
+    let data = std::fs::read(path).unwrap();
+    let mut decoder = VideoDecoder::builder(data)
+        .format_hint("m4v")
+        .build()
+        .unwrap();
+    assert_eq!(decoder.container_format(), "m4v");
+    assert_eq!(decoder.codec_name(), "mpeg4");
+
+    let mut count = 0;
+    while decoder.next_frame().unwrap().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 10);
+}
+
+#[test]
+fn bitstream() {
+    let path = solid_video("bitstream.m4v", &[([0, 255, 0, 255], 10)]);
+
+    let mut decoder = BitstreamDecoder::new(VideoCodec::Mpeg4, PixelFormat::Rgb24).unwrap();
+    assert_eq!(decoder.dimensions(), None);
+
+    // The chunks do not line up with the frames
+    let mut frames = Vec::new();
+    for chunk in std::fs::read(path).unwrap().chunks(100) {
+        decoder.push(chunk).unwrap();
+        frames.extend(std::iter::from_fn(|| decoder.next_frame()));
+    }
+    decoder.finish().unwrap();
+    frames.extend(std::iter::from_fn(|| decoder.next_frame()));
+
+    assert_eq!(frames.len(), 10);
+    assert_eq!(decoder.dimensions().unwrap().width(), 64);
+    for (i, frame) in frames.iter().enumerate() {
+        assert_eq!(frame.index(), i + 1);
+        assert_eq!(frame.timestamp(), None);
+        let pixel = &frame.data()[..3];
+        assert!(pixel[0] < 50 && pixel[1] > 200 && pixel[2] < 50);
+    }
+}
+
+#[test]
+fn mpegts_program() {
+    let path = solid_video("program.ts", &[([255, 0, 0, 255], 10)]);
+
+    let decoder = VideoDecoder::new(path.clone(), false).unwrap();
+    let programs = decoder.programs();
+    assert_eq!(programs.len(), 1);
+    assert_eq!(programs[0].index(), 0);
+    assert!(programs[0].id() > 0);
+    assert!(programs[0].name().is_some());
+    assert_eq!(programs[0].streams(), &[0]);
+
+    // Pushed in chunks, as if it was received live
+    let source = PushSource::new();
+    for chunk in std::fs::read(&path).unwrap().chunks(188 * 7) {
+        source.push(chunk);
+    }
+    source.finish();
+    let mut decoder = VideoDecoder::builder(source).program(0).build().unwrap();
+    let mut count = 0;
+    while decoder.next_frame().unwrap().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 10);
+
+    assert!(matches!(
+        VideoDecoder::builder(path).program(1).build(),
+        Err(DecodeError::InvalidProgram)
+    ));
+}
+
+#[test]
+fn hls() {
+    let path = solid_video("hls.m3u8", &[([255, 0, 0, 255], 10)]);
+
+    for variant in [Variant::Highest, Variant::Lowest, Variant::MaxHeight(10)] {
+        let mut decoder = VideoDecoder::builder(path.clone())
+            .variant(variant)
+            .live_edge(true)
+            .build()
+            .unwrap();
+        assert_eq!(decoder.container_format(), "hls");
+        let mut count = 0;
+        while decoder.next_frame().unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 10);
+    }
+}
+
+#[test]
+fn hls_variants() {
+    use ffmpeg_video_decoder::ffmpeg;
+
+    // Each variant has its own video and audio, the audio of the higher variant is in stereo
+    let dir = output("hls_variants");
+    std::fs::create_dir_all(&dir).unwrap();
+    for (name, width, height, channels) in [("low", 64, 48, 1), ("high", 128, 96, 2)] {
+        common::Fixture::default()
+            .format("mpegts")
+            .size(width, height)
+            .audio(ffmpeg::AVCodecID::AV_CODEC_ID_AAC)
+            .channels(channels)
+            .write(&dir.join(format!("{name}.ts")));
+        let playlist = format!(
+            "#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-TARGETDURATION:1\n#EXT-X-PLAYLIST-TYPE:VOD\n#EXTINF:1.0,\n{name}.ts\n#EXT-X-ENDLIST\n"
+        );
+        std::fs::write(dir.join(format!("{name}.m3u8")), playlist).unwrap();
+    }
+    let path = dir.join("master.m3u8");
+    std::fs::write(
+        &path,
+        "#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=200000,RESOLUTION=64x48\nlow.m3u8\n#EXT-X-STREAM-INF:BANDWIDTH=800000,RESOLUTION=128x96\nhigh.m3u8\n",
+    )
+    .unwrap();
+
+    for (variant, height, channels) in [
+        (Variant::Lowest, 48, 1),
+        (Variant::Highest, 96, 2),
+        (Variant::MaxHeight(64), 48, 1),
+        (Variant::MaxBandwidth(500_000), 48, 1),
+    ] {
+        let mut decoder = VideoDecoder::builder(path.clone())
+            .variant(variant)
+            .audio(true)
+            .build()
+            .unwrap();
+        assert_eq!(decoder.dimensions().height(), height);
+
+        // The audio is from the same variant as the video
+        let mut count = 0;
+        let mut audio = Vec::new();
+        while decoder.next_frame().unwrap().is_some() {
+            count += 1;
+            audio.extend(decoder.take_audio_frames());
+        }
+        audio.extend(decoder.take_audio_frames());
+        assert_eq!(count, 30);
+        assert!(!audio.is_empty());
+        assert!(audio.iter().all(|frame| frame.channels() == channels));
+    }
+}
+
+#[test]
+fn playlist() {
+    let mut paths = Vec::new();
+    for (i, (width, height, frames)) in [(64, 48, 10), (32, 24, 5)].into_iter().enumerate() {
+        let path = output(&format!("playlist{i}.mp4"));
+        let mut encoder = VideoEncoder::builder(&path, width, height)
+            .codec(VideoCodec::Mpeg4)
+            .build()
+            .unwrap();
+        let data: Vec<u8> = [0, 0, 255, 255].repeat((width * height) as usize);
+        for _ in 0..frames {
+            encoder.encode_data(&data, PixelFormat::Rgba).unwrap();
+        }
+        encoder.finish().unwrap();
+        paths.push(path.into());
+    }
+
+    let decoder = PlaylistDecoder::new(paths.clone()).unwrap().rescale(true);
+    assert_eq!(decoder.len(), 2);
+    let frames = decoder.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(frames.len(), 15);
+    for (i, frame) in frames.iter().enumerate() {
+        assert_eq!(frame.index(), i + 1);
+        assert_eq!(frame.dimensions().width(), 64);
+        assert_eq!(frame.dimensions().height(), 48);
+    }
+    assert!(frames
+        .windows(2)
+        .all(|pair| pair[0].timestamp().unwrap() < pair[1].timestamp().unwrap()));
+
+    // A source which fails to open is skipped once its error has been returned
+    let sources = vec![
+        paths[0].clone(),
+        output("playlist_missing.mp4").into(),
+        paths[1].clone(),
+    ];
+    let mut decoder = PlaylistDecoder::new(sources).unwrap();
+    for i in 1..=10 {
+        assert_eq!(decoder.next_frame().unwrap().unwrap().index(), i);
+    }
+    assert!(decoder.next_frame().is_err());
+    assert_eq!(decoder.current(), 1);
+    for i in 11..=15 {
+        assert_eq!(decoder.next_frame().unwrap().unwrap().index(), i);
+    }
+    assert_eq!(decoder.current(), 2);
+    assert!(decoder.next_frame().unwrap().is_none());
+
+    assert!(matches!(
+        PlaylistDecoder::new(Vec::new()),
+        Err(DecodeError::UnableToOpenInput)
+    ));
+}
+
+#[test]
+fn scene_change() {
+    let path = solid_video(
+        "scene_change.mp4",
+        &[([255, 0, 0, 255], 10), ([0, 0, 255, 255], 10)],
+    );
+
+    let changes = scene_changes(path.clone(), 0.3).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].index(), 11);
+
+    // Nothing is different enough
+    assert!(scene_changes(path, 1.0).unwrap().is_empty());
+}
+
+#[test]
+fn black_frames() {
+    let path = solid_video(
+        "black_frames.mp4",
+        &[
+            ([255, 0, 0, 255], 10),
+            ([0, 0, 0, 255], 15),
+            ([255, 0, 0, 255], 10),
+        ],
+    );
+
+    let segments = detect_black_frames(path.clone(), 0.2, Duration::from_millis(300)).unwrap();
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].first_frame(), 11);
+    assert_eq!(segments[0].last_frame(), 25);
+    assert!(segments[0].duration() >= Duration::from_millis(490));
+
+    // Too short
+    assert!(detect_black_frames(path, 0.1, Duration::from_secs(1))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn dominant_colors() {
+    let path = output("dominant_colors.mp4");
+    let mut encoder = VideoEncoder::builder(&path, 64, 48)
+        .codec(VideoCodec::Mpeg4)
+        .build()
+        .unwrap();
+    // The top three quarters are blue, the rest is yellow
+    let data: Vec<u8> = (0..48)
+        .flat_map(|y| {
+            [if y < 36 {
+                [0, 0, 255, 255]
+            } else {
+                [255, 255, 0, 255]
+            }; 64]
+        })
+        .flatten()
+        .collect();
+    encoder.encode_data(&data, PixelFormat::Rgba).unwrap();
+    encoder.finish().unwrap();
+
+    let mut decoder = VideoDecoder::new(path, false).unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+
+    let histogram = frame.histogram();
+    assert_eq!(histogram.len(), 3);
+    assert!(histogram
+        .iter()
+        .all(|channel| channel.iter().sum::<u32>() == 64 * 48));
+    assert!(histogram[2][200..].iter().sum::<u32>() > 64 * 30);
+
+    let [r, g, b] = frame.average_color();
+    assert!(b > r && b > g);
+
+    let colors = frame.dominant_colors(2);
+    assert_eq!(colors.len(), 2);
+    let ([r, g, b], share) = colors[0];
+    assert!(b > 200 && r < 50 && g < 50);
+    assert!((share - 0.75).abs() < 0.05);
+    let ([r, g, b], _) = colors[1];
+    assert!(r > 200 && g > 200 && b < 50);
+}
+
+#[test]
+fn compare_videos() {
+    let gradient: Vec<u8> = (0..48)
+        .flat_map(|y| (0..64).flat_map(move |x| [x * 4, y * 5, 128, 255]))
+        .collect();
+    let encode = |name: &str, width: u32, height: u32, data: &[u8]| {
+        let path = output(name);
+        let mut encoder = VideoEncoder::builder(&path, width, height)
+            .codec(VideoCodec::Mpeg4)
+            .build()
+            .unwrap();
+        for _ in 0..5 {
+            encoder.encode_data(data, PixelFormat::Rgba).unwrap();
+        }
+        encoder.finish().unwrap();
+        path
+    };
+    let reference = encode("compare_reference.mp4", 64, 48, &gradient);
+    let inverted: Vec<u8> = gradient
+        .chunks(4)
+        .flat_map(|pixel| [255 - pixel[0], 255 - pixel[1], pixel[2], 255])
+        .collect();
+    let different = encode("compare_different.mp4", 64, 48, &inverted);
+    // The top left quarter of the gradient
+    let cropped: Vec<u8> = gradient
+        .chunks(64 * 4)
+        .take(24)
+        .flat_map(|row| &row[..32 * 4])
+        .copied()
+        .collect();
+    let smaller = encode("compare_smaller.mp4", 32, 24, &cropped);
+
+    let same = compare(reference.clone(), reference.clone()).unwrap();
+    assert_eq!(same.frames().len(), 5);
+    assert_eq!(same.psnr(), f64::INFINITY);
+    assert_eq!(same.ssim(), 1.0);
+
+    let different = compare(reference.clone(), different).unwrap();
+    assert_eq!(different.frames().len(), 5);
+    assert!(different.psnr() < 20.0);
+    assert!(different.ssim() < same.ssim());
+    assert_eq!(different.frames()[2].index(), 3);
+
+    // Scaled to match the reference
+    let smaller = compare(reference, smaller).unwrap();
+    assert_eq!(smaller.frames().len(), 5);
+    assert!(smaller.psnr().is_finite());
+    assert!(smaller.ssim() < same.ssim());
+}
+
+#[test]
+fn unknown_format() {
+    assert!(matches!(
+        VideoEncoder::new(output("unknown.notaformat"), 64, 48, 30.0),
+        Err(EncodeError::UnknownFormat)
+    ));
+}
+
+#[test]
+fn transcode_frames() {
+    let path = output("transcode.mkv");
+    let options = TranscodeOptions::new()
+        .codec(VideoCodec::Mpeg4)
+        .size(320, 180);
+
+    let mut seen = 0;
+    let frames = transcode("test.mp4", &path, options, |frame| {
+        seen += 1;
+        frame.data_mut().fill(0);
+    })
+    .unwrap();
+    assert_eq!(frames, 901);
+    assert_eq!(seen, 901);
+
+    let mut decoder = VideoDecoder::new(path, false).unwrap();
+    assert_eq!(decoder.dimensions().width(), 320);
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert!(frame.data().iter().all(|&value| value < 20));
+}
+
+#[test]
+fn remux_container() {
+    let mkv = output("remux.mkv");
+    let packets = remux("test.mp4", &mkv, None).unwrap();
+    assert!(packets >= 901);
+
+    // Back into mp4, with the container given explicitly
+    let mp4 = output("remux.out");
+    assert_eq!(remux(mkv.clone(), &mp4, Some("mp4")).unwrap(), packets);
+
+    for path in [mkv, mp4] {
+        let mut decoder = VideoDecoder::new(path, false).unwrap();
+        assert_eq!(decoder.dimensions().width(), 1920);
+        assert_eq!(decoder.next_frame().unwrap().unwrap().index(), 1);
+        assert_eq!(decoder.skip(1000).unwrap(), 900);
+    }
+
+    assert!(matches!(
+        remux("test.mp4", output("remux.mkv"), Some("not a format")),
+        Err(TranscodeError::Encode(EncodeError::UnknownFormat))
+    ));
+}
+
+#[test]
+fn cut_clip() {
+    let path = output("cut.mp4");
+    let start = Duration::from_secs(10);
+    let end = Duration::from_secs(20);
+    let clip = cut("test.mp4", start, end, &path).unwrap();
+    assert!(clip.start() <= start);
+    assert!(clip.end() >= end - Duration::from_millis(50));
+    assert!(clip.packets() > 0);
+
+    // The clip starts at zero, at the keyframe it was cut from
+    let mut source = VideoDecoder::new("test.mp4", false).unwrap();
+    let first = source.get_frame_at(clip.start()).unwrap().unwrap();
+    let mut decoder = VideoDecoder::new(path, false).unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(frame.timestamp(), Some(Duration::ZERO));
+    assert_eq!(frame.data(), first.data());
+
+    let expected = ((clip.duration().as_secs_f64() * 30.0).round() as usize) + 1;
+    assert_eq!(decoder.skip(10_000).unwrap() + 1, expected);
+}
+
+#[test]
+fn gif() {
+    let path = output("animation.gif");
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=160:90")
+        .build()
+        .unwrap();
+
+    let mut gif = GifWriter::new(&path, 160, 90).unwrap();
+    for _ in 0..10 {
+        let frame = decoder.next_frame().unwrap().unwrap();
+        gif.write(&frame, Duration::from_millis(100)).unwrap();
+    }
+    gif.finish().unwrap();
+
+    let mut decoder = VideoDecoder::new(path, false).unwrap();
+    assert_eq!(decoder.codec_name(), "gif");
+    let mut count = 0;
+    while let Some(frame) = decoder.next_frame().unwrap() {
+        count += 1;
+        let expected = Duration::from_millis((count - 1) * 100);
+        assert_eq!(frame.timestamp(), Some(expected));
+    }
+    assert_eq!(count, 10);
+}
+
+#[test]
+fn apng() {
+    let path = output("animation.apng");
+    let mut apng = ApngWriter::new(&path, 32, 32).unwrap();
+    for i in 0..5u8 {
+        let data = [i * 50, 0, 0, 128].repeat(32 * 32);
+        apng.write_data(&data, PixelFormat::Rgba, Duration::from_millis(40))
+            .unwrap();
+    }
+    apng.finish().unwrap();
+
+    // The animation control chunk holds the number of frames and then the number of plays, where 0 loops forever
+    let data = std::fs::read(&path).unwrap();
+    let actl = data.windows(4).position(|chunk| chunk == b"acTL").unwrap();
+    assert_eq!(&data[actl + 4..actl + 8], &5u32.to_be_bytes());
+    assert_eq!(&data[actl + 8..actl + 12], &0u32.to_be_bytes());
+
+    let mut decoder = VideoDecoder::new(path, false).unwrap();
+    assert_eq!(decoder.codec_name(), "apng");
+    let mut count = 0;
+    while decoder.next_frame().unwrap().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 5);
+}
+
+#[test]
+fn alpha() {
+    let path = output("alpha.apng");
+    let mut apng = ApngWriter::new(&path, 32, 32).unwrap();
+    apng.write_data(
+        &[255, 0, 0, 128].repeat(32 * 32),
+        PixelFormat::Rgba,
+        Duration::from_millis(40),
+    )
+    .unwrap();
+    apng.finish().unwrap();
+
+    let mut decoder = VideoDecoder::builder(path.clone())
+        .keep_alpha(true)
+        .build()
+        .unwrap();
+    assert!(decoder.has_alpha());
+    assert_eq!(decoder.output_format(), PixelFormat::Rgba);
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(&frame.data()[..4], &[255, 0, 0, 128]);
+
+    // The alpha channel is dropped by default
+    let decoder = VideoDecoder::new(path, false).unwrap();
+    assert_eq!(decoder.output_format(), PixelFormat::Rgb24);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn save_frame() {
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=64:36")
+        .build()
+        .unwrap();
+    let frame = decoder.next_frame().unwrap().unwrap();
+
+    let path = output("save_frame.png");
+    frame.save(&path).unwrap();
+    let image = ffmpeg_video_decoder::image::open(&path)
+        .unwrap()
+        .into_rgb8();
+    assert_eq!(image.dimensions(), (64, 36));
+    assert_eq!(image.as_raw().as_slice(), frame.data());
+
+    // Lower quality JPEGs are smaller
+    let best = output("save_frame_best.jpg");
+    let worst = output("save_frame_worst.jpg");
+    frame.save_with_quality(&best, 100).unwrap();
+    frame.save_with_quality(&worst, 10).unwrap();
+    assert!(std::fs::metadata(&worst).unwrap().len() < std::fs::metadata(&best).unwrap().len());
+
+    assert!(matches!(
+        frame.save(output("save_frame.unknown")),
+        Err(TranscodeError::Encode(EncodeError::UnknownFormat))
+    ));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn frame_exporter() {
+    use ffmpeg_video_decoder::{FrameExporter, Sampling};
+
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=64:36")
+        .sample(Sampling::EveryNth(100))
+        .build()
+        .unwrap();
+
+    let dir = output("frame_exporter");
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut exporter = FrameExporter::new(dir.join("frame_%03d.png"))
+        .unwrap()
+        .start_number(0);
+    assert_eq!(exporter.next_path(), dir.join("frame_000.png"));
+    assert_eq!(exporter.export_all(&mut decoder).unwrap(), 10);
+    assert!(dir.join("frame_009.png").exists());
+    assert_eq!(exporter.next_path(), dir.join("frame_010.png"));
+
+    let exporter = FrameExporter::new(dir.join("frame_%d.png")).unwrap();
+    assert_eq!(exporter.next_path(), dir.join("frame_1.png"));
+    assert!(matches!(
+        FrameExporter::new(dir.join("frame.png")),
+        Err(EncodeError::InvalidPath)
+    ));
+
+    // Only the file name is numbered
+    let exporter = FrameExporter::new(dir.join("100%d").join("frame_%02d_of_%04d.png")).unwrap();
+    assert_eq!(
+        exporter.next_path(),
+        dir.join("100%d").join("frame_%02d_of_0001.png")
+    );
+    assert!(matches!(
+        FrameExporter::new(dir.join("%d").join("frame.png")),
+        Err(EncodeError::InvalidPath)
+    ));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn image_sequence() {
+    use ffmpeg_video_decoder::{FrameExporter, ImageSequence, Sampling};
+
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=64:36")
+        .sample(Sampling::EveryNth(90))
+        .build()
+        .unwrap();
+    let frames = decoder.decode_all().unwrap();
+    assert_eq!(frames.len(), 11);
+
+    let dir = output("image_sequence");
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut exporter = FrameExporter::new(dir.join("frame_%04d.png")).unwrap();
+    let paths: Vec<_> = frames
+        .iter()
+        .map(|frame| exporter.export(frame).unwrap())
+        .collect();
+
+    let sequence = ImageSequence::pattern(dir.join("frame_%04d.png"), 24.0);
+    let mut decoder = VideoDecoder::new(sequence, false).unwrap();
+    assert_eq!(decoder.framerate(), 24.0);
+    let decoded = decoder.decode_all().unwrap();
+    assert_eq!(decoded.len(), frames.len());
+    for (i, (decoded, frame)) in decoded.iter().zip(&frames).enumerate() {
+        assert_eq!(decoded.index(), i + 1);
+        assert_eq!(
+            decoded.timestamp(),
+            Some(Duration::from_secs(i as u64) / 24)
+        );
+        assert_eq!(decoded.data(), frame.data());
+    }
+
+    // Starting part way through
+    let sequence = ImageSequence::pattern(dir.join("frame_%04d.png"), 24.0).start_number(5);
+    let mut decoder = VideoDecoder::new(sequence, false).unwrap();
+    assert_eq!(decoder.decode_all().unwrap().len(), frames.len() - 4);
+
+    // A list of files is shown in the order given
+    let sequence = ImageSequence::files(paths.iter().rev(), 10.0).unwrap();
+    let mut decoder = VideoDecoder::new(sequence, false).unwrap();
+    let decoded = decoder.decode_all().unwrap();
+    assert_eq!(decoded.len(), frames.len());
+    assert_eq!(decoded[0].data(), frames.last().unwrap().data());
+    assert_eq!(decoded[1].timestamp(), Some(Duration::from_millis(100)));
+
+    for framerate in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        assert!(matches!(
+            ImageSequence::files(&paths, framerate),
+            Err(DecodeError::InvalidFramerate)
+        ));
+    }
+}
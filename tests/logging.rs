@@ -0,0 +1,73 @@
+use ffmpeg_video_decoder::{set_ffmpeg_log_level, LogLevel, VideoDecoder};
+use std::sync::Mutex;
+
+/// The log level is global, so tests which change it must not run at the same time
+static LEVEL: Mutex<()> = Mutex::new(());
+
+#[test]
+fn quiet() {
+    let _level = LEVEL.lock().unwrap_or_else(|e| e.into_inner());
+    set_ffmpeg_log_level(LogLevel::Quiet);
+
+    // Logging has no effect on decoding
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    assert!(decoder.next_frame().unwrap().is_some());
+}
+
+#[cfg(feature = "log")]
+#[test]
+fn capture() {
+    use ffmpeg_video_decoder::ffmpeg;
+
+    /// Keeps the messages logged with the `ffmpeg` target
+    struct Logger(Mutex<Vec<(log::Level, String)>>);
+
+    impl log::Log for Logger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.target() == "ffmpeg"
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                let mut messages = self.0.lock().unwrap_or_else(|e| e.into_inner());
+                messages.push((record.level(), record.args().to_string()));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: Logger = Logger(Mutex::new(Vec::new()));
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let _level = LEVEL.lock().unwrap_or_else(|e| e.into_inner());
+    ffmpeg_video_decoder::capture_ffmpeg_logs();
+    set_ffmpeg_log_level(LogLevel::Warning);
+
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    assert!(decoder.next_frame().unwrap().is_some());
+
+    // A message in two parts is sent as a single line
+    unsafe {
+        ffmpeg::av_log(
+            std::ptr::null_mut(),
+            ffmpeg::AV_LOG_WARNING,
+            c"captured %s".as_ptr(),
+            c"warning".as_ptr(),
+        );
+        ffmpeg::av_log(std::ptr::null_mut(), ffmpeg::AV_LOG_WARNING, c"\n".as_ptr());
+        // Filtered out by the log level
+        ffmpeg::av_log(
+            std::ptr::null_mut(),
+            ffmpeg::AV_LOG_INFO,
+            c"captured info\n".as_ptr(),
+        );
+    }
+
+    let messages = LOGGER.0.lock().unwrap();
+    assert!(messages.contains(&(log::Level::Warn, "captured warning".to_string())));
+    assert!(!messages
+        .iter()
+        .any(|(_, message)| message == "captured info"));
+}
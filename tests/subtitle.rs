@@ -0,0 +1,18 @@
+use ffmpeg_video_decoder::{DecodeError, SubtitleDecoder, VideoDecoder};
+
+#[test]
+fn no_subtitle_stream() {
+    // test video only has a video and an audio stream
+    let result = SubtitleDecoder::new("test.mp4");
+    assert!(matches!(
+        result,
+        Err(DecodeError::UnableToFindSubtitleStream)
+    ));
+}
+
+#[test]
+fn invalid_subtitle_stream() {
+    let video_stream = VideoDecoder::new("test.mp4", false).unwrap().stream_index();
+    let result = SubtitleDecoder::with_stream("test.mp4", video_stream);
+    assert!(matches!(result, Err(DecodeError::InvalidSubtitleStream)));
+}
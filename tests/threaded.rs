@@ -0,0 +1,78 @@
+use ffmpeg_video_decoder::VideoDecoder;
+
+#[test]
+fn spawn() {
+    let frames = VideoDecoder::new("test.mp4", false)
+        .unwrap()
+        .spawn(4)
+        .unwrap();
+
+    let mut max = 0;
+    while let Some(frame) = frames.next_frame().unwrap() {
+        assert_eq!(frame.index(), max + 1);
+        max = frame.index();
+    }
+
+    assert_eq!(max, 901);
+    assert!(frames.next_frame().unwrap().is_none());
+}
+
+#[test]
+fn spawn_drop_looping() {
+    // The decoding thread must exit once the receiver is dropped, even if the decoder never runs out of frames
+    let mut frames = VideoDecoder::new("test.mp4", true)
+        .unwrap()
+        .spawn(2)
+        .unwrap();
+    assert_eq!(frames.next().unwrap().unwrap().index(), 1);
+    drop(frames);
+}
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn send_sync() {
+    assert_send::<VideoDecoder>();
+    assert_send::<ffmpeg_video_decoder::Frame>();
+    assert_sync::<ffmpeg_video_decoder::Frame>();
+}
+
+#[test]
+fn move_between_threads() {
+    let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    let first = decoder.next_frame().unwrap().unwrap();
+
+    // Continue decoding on another thread, then hand the decoder back
+    let (mut decoder, second) = std::thread::spawn(move || {
+        let frame = decoder.next_frame().unwrap().unwrap();
+        (decoder, frame)
+    })
+    .join()
+    .unwrap();
+
+    let third = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(first.index(), 1);
+    assert_eq!(second.index(), 2);
+    assert_eq!(third.index(), 3);
+}
+
+#[test]
+fn decode_concurrently() {
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let mut decoder = VideoDecoder::new("test.mp4", false).unwrap();
+            std::thread::spawn(move || {
+                let mut count = 0;
+                while decoder.next_frame().unwrap().is_some() {
+                    count += 1;
+                }
+                count
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 901);
+    }
+}
@@ -0,0 +1,57 @@
+use ffmpeg_video_decoder::{thumbnails, thumbnails_with, ThumbnailOptions, VideoDecoder};
+use std::time::Duration;
+
+#[test]
+fn duration() {
+    let decoder = VideoDecoder::new("test.mp4", false).unwrap();
+    let duration = decoder.duration().unwrap();
+    assert!(duration.abs_diff(Duration::from_secs_f64(901.0 / 30.0)) < Duration::from_millis(100));
+}
+
+#[test]
+fn keyframe_thumbnails() {
+    let frames = thumbnails("test.mp4", 5).unwrap();
+    assert_eq!(frames.len(), 5);
+    assert!(frames.windows(2).all(|w| w[0].index() <= w[1].index()));
+    assert_eq!(frames[0].dimensions().width(), 1920);
+}
+
+#[test]
+fn exact_thumbnails() {
+    let options = ThumbnailOptions::new().exact(true).max_size(320, 320);
+    let frames = thumbnails_with("test.mp4", 4, options).unwrap();
+    assert_eq!(frames.len(), 4);
+
+    // Positions are at the middle of each quarter of the video
+    let duration = 901.0 / 30.0;
+    for (i, frame) in frames.iter().enumerate() {
+        let expected = Duration::from_secs_f64(duration * (i as f64 + 0.5) / 4.0);
+        assert!(frame.timestamp().unwrap().abs_diff(expected) <= Duration::from_millis(34));
+        assert_eq!(frame.dimensions().width(), 320);
+        assert_eq!(frame.dimensions().height(), 180);
+    }
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn contact_sheet() {
+    let options = ThumbnailOptions::new().max_size(160, 90);
+    let frames = thumbnails_with("test.mp4", 5, options).unwrap();
+    let sheet = ffmpeg_video_decoder::contact_sheet(&frames, 2).unwrap();
+    assert_eq!(sheet.dimensions(), (320, 270));
+    assert_eq!(sheet.get_pixel(0, 0).0, frames[0].data()[..3]);
+
+    // Other formats are converted rather than left blank
+    let mut decoder = VideoDecoder::builder("test.mp4")
+        .filter("scale=64:36")
+        .output_format(ffmpeg_video_decoder::PixelFormat::Gray8)
+        .build()
+        .unwrap();
+    let gray = vec![decoder.next_frame().unwrap().unwrap()];
+    let sheet = ffmpeg_video_decoder::contact_sheet(&gray, 1).unwrap();
+    assert_eq!(sheet.dimensions(), (64, 36));
+    let plane = gray[0].planes()[0];
+    for (x, y, pixel) in sheet.enumerate_pixels() {
+        assert_eq!(pixel.0, [plane.row(y as usize)[x as usize]; 3]);
+    }
+}